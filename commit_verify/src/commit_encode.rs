@@ -35,9 +35,10 @@
 //!
 //! [LNPBP-9]: https://github.com/LNP-BP/LNPBPs/blob/master/lnpbp-0009.md
 
-use std::io;
+use alloc::vec::Vec;
 
 use bitcoin_hashes::HashEngine;
+use strict_encoding::io;
 
 use crate::{CommitVerify, PrehashedProtocol};
 
@@ -86,6 +87,8 @@ pub trait Strategy {
 ///
 /// Implemented after concept by Martin Habovštiak <martin.habovstiak@gmail.com>
 pub mod strategies {
+    use alloc::string::String;
+
     use bitcoin_hashes::Hash;
 
     use super::*;
@@ -106,7 +109,7 @@ pub mod strategies {
 
     /// Encodes data by first hashing them with the provided hash function `H`
     /// and then returning its result serialized with strict encoding rules.
-    pub struct UsingHash<H>(std::marker::PhantomData<H>)
+    pub struct UsingHash<H>(core::marker::PhantomData<H>)
     where
         H: Hash + strict_encoding::StrictEncode;
 
@@ -273,16 +276,16 @@ pub trait ConsensusCommit: Sized + CommitEncode {
     /// Performs commitment to client-side-validated data
     #[inline]
     fn consensus_commit(&self) -> Self::Commitment {
-        let mut encoder = io::Cursor::new(vec![]);
+        let mut encoder = vec![];
         self.commit_encode(&mut encoder);
-        Self::Commitment::commit(&encoder.into_inner())
+        Self::Commitment::commit(&encoder)
     }
 
     /// Verifies commitment to client-side-validated data
     #[inline]
     fn consensus_verify(&self, commitment: &Self::Commitment) -> bool {
-        let mut encoder = io::Cursor::new(vec![]);
+        let mut encoder = vec![];
         self.commit_encode(&mut encoder);
-        commitment.verify(&encoder.into_inner())
+        commitment.verify(&encoder)
     }
 }
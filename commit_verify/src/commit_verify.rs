@@ -47,7 +47,7 @@ where
 {
     /// Error type that may be reported during [`TryCommitVerify::try_commit`]
     /// and [`TryCommitVerify::try_verify`] procedures
-    type Error: std::error::Error;
+    type Error: crate::ErrorTrait;
 
     /// Tries to create commitment to a byte representation of a given message
     fn try_commit(msg: &Msg) -> Result<Self, Self::Error>;
@@ -67,9 +67,9 @@ where
     Self: Eq + Sized,
     Protocol: CommitmentProtocol,
 {
-    /// Error type that may be reported during [`TryCommitVerify::try_commit_static`]
-    /// procedure
-    type Error: std::error::Error;
+    /// Error type that may be reported during
+    /// [`TryCommitVerify::try_commit_static`] procedure
+    type Error: crate::ErrorTrait;
 
     /// Static entropy version of the try_commit method
     fn try_commit_static(msg: &Msg) -> Result<Self, Self::Error>;
@@ -86,6 +86,7 @@ where
 /// commitment to a *message* and a *proof*:
 /// - `self + supplement -> internal_repr`;
 /// - `internal_repr + msg -> (commitment, proof)`.
+///
 /// Later on, a verifier presented with a message and the proof may do the
 /// commitment verification in the following way:
 /// `msg, proof, commitment -> bool`.
@@ -168,7 +169,7 @@ where
     /// procedure. It may also be returned from [`ConvolveCommitProof::verify`]
     /// in case the proof data are invalid and the commitment can't be
     /// re-created.
-    type CommitError: std::error::Error;
+    type CommitError: crate::ErrorTrait;
 
     /// Takes the `supplement` to unparse the content of this container (`self`)
     /// ("convolves" these two data together) and uses them to produce a final
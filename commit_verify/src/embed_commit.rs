@@ -47,7 +47,8 @@ where
     ) -> Result<Container, Container::VerifyError>;
 }
 
-/// Proofs produced by [`EmbedCommitVerifyStatic::embed_commit_static`] procedure.
+/// Proofs produced by [`EmbedCommitVerifyStatic::embed_commit_static`]
+/// procedure.
 pub trait EmbedCommitProofStatic<Msg, Container, Protocol>
 where
     Self: Sized + VerifyEq,
@@ -129,11 +130,11 @@ where
     /// Error type that may be reported during [`Self::embed_commit`] procedure.
     /// It may also be returned from [`Self::verify`] in case the proof data are
     /// invalid and the commitment can't be re-created.
-    type CommitError: std::error::Error;
+    type CommitError: crate::ErrorTrait;
 
     /// Error type that may be reported during [`Self::verify`] procedure.
     /// It must be a subset of [`Self::CommitError`].
-    type VerifyError: std::error::Error + From<Self::CommitError>;
+    type VerifyError: crate::ErrorTrait + From<Self::CommitError>;
 
     /// Creates a commitment to a message and embeds it into the provided
     /// container (`self`) by mutating it and returning commitment proof.
@@ -209,14 +210,14 @@ where
     /// for verification.
     type Proof: EmbedCommitProofStatic<Msg, Self, Protocol>;
 
-    /// Error type that may be reported during [`Self::embed_commit_static`] procedure.
-    /// It may also be returned from [`Self::verify`] in case the proof data are
-    /// invalid and the commitment can't be re-created.
-    type CommitError: std::error::Error;
+    /// Error type that may be reported during [`Self::embed_commit_static`]
+    /// procedure. It may also be returned from [`Self::verify`] in case the
+    /// proof data are invalid and the commitment can't be re-created.
+    type CommitError: crate::ErrorTrait;
 
     /// Error type that may be reported during [`Self::verify`] procedure.
     /// It must be a subset of [`Self::CommitError`].
-    type VerifyError: std::error::Error + From<Self::CommitError>;
+    type VerifyError: crate::ErrorTrait + From<Self::CommitError>;
 
     /// Static entropy version of the embed_commit method
     fn embed_commit_static(
@@ -237,11 +238,13 @@ pub mod test_helpers {
     use super::*;
     use crate::convolve_commit::{ConvolveCommitProof, ConvolveCommitVerify};
 
+    /// Commitment protocol used in tests
     pub enum TestProtocol {}
     impl CommitmentProtocol for TestProtocol {
         const HASH_TAG_MIDSTATE: Option<Midstate> = Some(Midstate([0u8; 32]));
     }
 
+    /// Supplement data used in tests
     pub const SUPPLEMENT: [u8; 32] = [0xFFu8; 32];
 
     /// Runs round-trip of commitment-embed-verify for a given set of messages
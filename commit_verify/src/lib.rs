@@ -15,6 +15,7 @@
 // Coding conventions
 #![recursion_limit = "256"]
 #![deny(dead_code, missing_docs, warnings)]
+#![cfg_attr(not(feature = "std"), no_std)]
 
 //! Library providing primitives for cryptographic commit-verify schemes used in
 //! client-side-validation
@@ -23,7 +24,17 @@
 //!
 //! [LNPBP-9]: https://github.com/LNP-BP/LNPBPs/blob/master/lnpbp-0009.md
 //! [LNPBP-81]: https://github.com/LNP-BP/LNPBPs/blob/master/lnpbp-0081.md
+//!
+//! The crate supports `no_std` environments with `alloc` when the default
+//! `std` feature is disabled; in this mode commit-encoding uses
+//! [`strict_encoding::io`] traits. Features `rand`, `serde` and
+//! `bulletproofs` require `std`.
+
+#[cfg(all(not(feature = "std"), feature = "rand"))]
+compile_error!("feature `rand` requires `std` feature");
 
+#[macro_use]
+extern crate alloc;
 #[macro_use]
 extern crate amplify;
 #[macro_use]
@@ -44,6 +55,11 @@ pub mod lnpbp4;
 pub mod merkle;
 pub mod tagged_hash;
 
+/// Bound for error types used by the commitment traits. Matches
+/// [`std::error::Error`] when `std` feature is enabled.
+#[cfg(feature = "std")]
+pub use std::error::Error as ErrorTrait;
+
 pub use commit_encode::{CommitConceal, CommitEncode, ConsensusCommit};
 pub use embed_commit::{
     EmbedCommitProof, EmbedCommitProofStatic, EmbedCommitVerify,
@@ -58,6 +74,14 @@ pub use crate::commit_verify::{
     CommitVerify, TryCommitVerify, TryCommitVerifyStatic,
 };
 
+/// Bound for error types used by the commitment traits. Without `std` feature
+/// it requires only [`core::fmt::Debug`] and [`core::fmt::Display`].
+#[cfg(not(feature = "std"))]
+pub trait ErrorTrait: core::fmt::Debug + core::fmt::Display {}
+
+#[cfg(not(feature = "std"))]
+impl<T> ErrorTrait for T where T: core::fmt::Debug + core::fmt::Display {}
+
 // TODO: Improve support of creating tagged hashes of the messages at the
 //       commitment protocol level.
 
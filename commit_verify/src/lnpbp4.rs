@@ -44,15 +44,15 @@
 //!
 //! [LNPBP-4]: https://github.com/LNP-BP/LNPBPs/blob/master/lnpbp-0004.md
 
-use std::cmp::Ordering;
-use std::collections::{BTreeMap, BTreeSet};
-use std::fmt::Debug;
-use std::io::Write;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt::Debug;
 
 use amplify::num::u256;
 use amplify::{Slice32, Wrapper};
 use bitcoin_hashes::{sha256, sha256t, Hash, HashEngine};
-use strict_encoding::{StrictDecode, StrictEncode};
+use strict_encoding::{io, StrictArmor, StrictDecode, StrictEncode};
 
 use crate::merkle::MerkleNode;
 use crate::tagged_hash::TaggedHash;
@@ -177,9 +177,8 @@ pub type MessageMap = BTreeMap<ProtocolId, Message>;
 
 /// Errors generated during multi-message commitment process by
 /// [`MerkleTree::try_commit`]
-#[derive(
-    Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Error, Debug, Display
-)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
+#[cfg_attr(feature = "std", derive(Error))]
 #[display(doc_comments)]
 pub enum Error {
     /// can't create commitment for an empty message list and zero tree depth.
@@ -195,7 +194,7 @@ pub enum Error {
 }
 
 /// Iterator over messages in [`MerkleTree`] and [`MerkleBlock`].
-pub struct MessageIter(std::vec::IntoIter<Message>);
+pub struct MessageIter(alloc::vec::IntoIter<Message>);
 
 impl Iterator for MessageIter {
     type Item = Message;
@@ -236,7 +235,6 @@ impl CommitConceal for MerkleTree {
             .expect("internal MerkleTree inconsistency");
 
         let mut layer = (0..self.width())
-            .into_iter()
             .map(|pos| {
                 map.get(&pos)
                     .map(|(protocol_id, message)| {
@@ -273,7 +271,7 @@ impl CommitConceal for MerkleTree {
 }
 
 impl CommitEncode for MerkleTree {
-    fn commit_encode<E: Write>(&self, e: E) -> usize {
+    fn commit_encode<E: io::Write>(&self, e: E) -> usize {
         let commitment = self.commit_conceal();
         commitment.strict_encode(e).expect("memory encoder failure")
     }
@@ -283,7 +281,7 @@ impl ConsensusCommit for MerkleTree {
     type Commitment = CommitmentHash;
 }
 
-#[cfg(feature = "rand")]
+#[cfg(any(test, feature = "rand"))]
 mod commit {
     use rand::{thread_rng, RngCore};
 
@@ -542,7 +540,6 @@ impl From<&MerkleTree> for MerkleBlock {
             .expect("internal MerkleTree inconsistency");
 
         let cross_section = (0..tree.width())
-            .into_iter()
             .map(|pos| {
                 map.get(&pos)
                     .map(|(protocol_id, message)| TreeNode::CommitmentLeaf {
@@ -586,7 +583,7 @@ impl CommitConceal for MerkleBlock {
 }
 
 impl CommitEncode for MerkleBlock {
-    fn commit_encode<E: Write>(&self, e: E) -> usize {
+    fn commit_encode<E: io::Write>(&self, e: E) -> usize {
         let commitment = self.commit_conceal();
         commitment.strict_encode(e).expect("memory encoder failure")
     }
@@ -596,6 +593,10 @@ impl ConsensusCommit for MerkleBlock {
     type Commitment = CommitmentHash;
 }
 
+impl StrictArmor for MerkleBlock {
+    const ARMOR_TAG: &'static str = "MerkleBlock";
+}
+
 impl IntoIterator for &MerkleBlock {
     type Item = Message;
     type IntoIter = MessageIter;
@@ -616,16 +617,14 @@ impl IntoIterator for &MerkleBlock {
 
 /// commitment under protocol id {_0} is absent from the known part of a given
 /// LNPBP-4 Merkle block.
-#[derive(
-    Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display, Error
-)]
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display)]
+#[cfg_attr(feature = "std", derive(Error))]
 #[display(doc_comments)]
 pub struct LeafNotKnown(ProtocolId);
 
 /// attempt to merge unrelated LNPBP-4 proof.
-#[derive(
-    Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display, Error
-)]
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display)]
+#[cfg_attr(feature = "std", derive(Error))]
 #[display(doc_comments)]
 pub struct UnrelatedProof;
 
@@ -1305,4 +1304,16 @@ mod test {
 
         assert_eq!(block1, expected);
     }
+
+    #[test]
+    fn test_armored() {
+        let block = MerkleBlock::from(MerkleTree {
+            depth: 2,
+            entropy: 0xCAFE,
+            messages: Default::default(),
+        });
+        let armored = block.to_armored().unwrap();
+        assert!(armored.starts_with("-----BEGIN STRICT MerkleBlock-----\n"));
+        assert_eq!(MerkleBlock::from_armored(&armored).unwrap(), block);
+    }
 }
@@ -17,9 +17,11 @@
 //!
 //! [LNPBP-81]: https://github.com/LNP-BP/LNPBPs/blob/master/lnpbp-0081.md
 
-use std::io;
+use alloc::string::ToString;
+use alloc::vec::Vec;
 
 use bitcoin_hashes::{sha256, Hash, HashEngine};
+use strict_encoding::io;
 
 use crate::{
     commit_encode, CommitEncode, CommitVerify, ConsensusCommit,
@@ -301,6 +303,7 @@ mod test {
     use crate::CommitConceal;
 
     #[test]
+    #[allow(non_local_definitions)]
     fn collections() {
         // First, we define a data type
         #[derive(
@@ -16,6 +16,8 @@
 
 #![allow(clippy::needless_borrow)] // Due to amplify_derive::Display bug
 
+use alloc::vec::Vec;
+
 use amplify::{Slice32, Wrapper};
 use bitcoin_hashes::hex::FromHex;
 use bitcoin_hashes::{hex, sha256, sha256t, Error, Hash, HashEngine};
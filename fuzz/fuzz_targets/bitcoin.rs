@@ -0,0 +1,59 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Decoders of bitcoin transactions, scripts, keys, signatures, addresses,
+//! PSBTs and taproot data structures.
+
+#![no_main]
+
+use bitcoin::psbt::{PartiallySignedTransaction, TapTree};
+use bitcoin::secp256k1::{ecdsa, schnorr};
+use bitcoin::util::address::Address;
+use bitcoin::util::bip32::{DerivationPath, ExtendedPubKey};
+use bitcoin::util::taproot::{ControlBlock, LeafVersion, TaprootMerkleBranch};
+use bitcoin::{
+    secp256k1, Amount, EcdsaSig, Network, OutPoint, PublicKey, SchnorrSig,
+    Script, Transaction, TxIn, TxOut, Txid, Witness, XOnlyPublicKey,
+};
+use libfuzzer_sys::fuzz_target;
+use strict_encoding_fuzz::fuzz_types;
+
+fuzz_target!(|data: &[u8]| {
+    fuzz_types!(data;
+        Transaction,                // 0
+        TxIn,                       // 1
+        TxOut,                      // 2
+        OutPoint,                   // 3
+        Script,                     // 4
+        Witness,                    // 5
+        Txid,                       // 6
+        Amount,                     // 7
+        Network,                    // 8
+        Address,                    // 9
+        PublicKey,                  // 10
+        secp256k1::PublicKey,       // 11
+        XOnlyPublicKey,             // 12
+        ecdsa::Signature,           // 13
+        schnorr::Signature,         // 14
+        EcdsaSig,                   // 15
+        SchnorrSig,                 // 16
+        ExtendedPubKey,             // 17
+        DerivationPath,             // 18
+        PartiallySignedTransaction, // 19
+        TapTree,                    // 20
+        ControlBlock,               // 21
+        LeafVersion,                // 22
+        TaprootMerkleBranch,        // 23
+    );
+});
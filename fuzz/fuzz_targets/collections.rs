@@ -0,0 +1,49 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Decoders of collections and generic wrapper types.
+
+#![no_main]
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::ops::{Range, RangeInclusive};
+use std::rc::Rc;
+
+use libfuzzer_sys::fuzz_target;
+use strict_encoding::{LargeVec, MediumVec};
+use strict_encoding_fuzz::fuzz_types;
+
+fuzz_target!(|data: &[u8]| {
+    fuzz_types!(data;
+        Vec<u8>,                        // 0
+        Vec<u64>,                       // 1
+        Vec<String>,                    // 2
+        Vec<Vec<u8>>,                   // 3
+        Option<u8>,                     // 4
+        Option<Vec<u16>>,               // 5
+        BTreeSet<u8>,                   // 6
+        BTreeSet<String>,               // 7
+        BTreeMap<u8, String>,           // 8
+        BTreeMap<String, Vec<u8>>,      // 9
+        HashSet<u16>,                   // 10
+        HashMap<usize, u8>,             // 11
+        LargeVec<u8>,                   // 12
+        MediumVec<u16>,                 // 13
+        Range<u16>,                     // 14
+        RangeInclusive<u8>,             // 15
+        (u8, String),                   // 16
+        Rc<String>,                     // 17
+        Vec<Option<BTreeMap<u8, u8>>>,  // 18
+    );
+});
@@ -0,0 +1,36 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Decoders of LNPBP-4 multi-protocol commitment structures.
+
+#![no_main]
+
+use commit_verify::lnpbp4::{
+    CommitmentHash, MerkleBlock, MerkleProof, MerkleTree, Message, ProtocolId,
+};
+use commit_verify::merkle::MerkleNode;
+use libfuzzer_sys::fuzz_target;
+use strict_encoding_fuzz::fuzz_types;
+
+fuzz_target!(|data: &[u8]| {
+    fuzz_types!(data;
+        MerkleBlock,    // 0
+        MerkleProof,    // 1
+        MerkleTree,     // 2
+        CommitmentHash, // 3
+        MerkleNode,     // 4
+        ProtocolId,     // 5
+        Message,        // 6
+    );
+});
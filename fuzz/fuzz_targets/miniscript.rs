@@ -0,0 +1,38 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Decoders of recursive miniscript, policy, taproot tree and descriptor data
+//! structures.
+
+#![no_main]
+
+use bitcoin::{PublicKey, XOnlyPublicKey};
+use libfuzzer_sys::fuzz_target;
+use miniscript::descriptor::{Descriptor, DescriptorPublicKey, TapTree};
+use miniscript::policy::concrete::Policy;
+use miniscript::{Legacy, Miniscript, Segwitv0, Tap};
+use strict_encoding_fuzz::fuzz_types;
+
+fuzz_target!(|data: &[u8]| {
+    fuzz_types!(data;
+        Miniscript<PublicKey, Legacy>,          // 0
+        Miniscript<PublicKey, Segwitv0>,        // 1
+        Miniscript<XOnlyPublicKey, Tap>,        // 2
+        Policy<PublicKey>,                      // 3
+        TapTree<XOnlyPublicKey>,                // 4
+        Descriptor<PublicKey>,                  // 5
+        Descriptor<DescriptorPublicKey>,        // 6
+        DescriptorPublicKey,                    // 7
+    );
+});
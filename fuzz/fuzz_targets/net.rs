@@ -0,0 +1,43 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Decoders of network addresses using uniform address encoding.
+
+#![no_main]
+
+use std::net::{
+    IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6,
+};
+
+use libfuzzer_sys::fuzz_target;
+use strict_encoding::net::{
+    DnsName, I2pAddr, OnionAddrV3, Transport, UniformAddr,
+};
+use strict_encoding_fuzz::fuzz_types;
+
+fuzz_target!(|data: &[u8]| {
+    fuzz_types!(data;
+        UniformAddr,    // 0
+        IpAddr,         // 1
+        Ipv4Addr,       // 2
+        Ipv6Addr,       // 3
+        SocketAddr,     // 4
+        SocketAddrV4,   // 5
+        SocketAddrV6,   // 6
+        OnionAddrV3,    // 7
+        I2pAddr,        // 8
+        DnsName,        // 9
+        Transport,      // 10
+    );
+});
@@ -0,0 +1,56 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Decoders of primitive types, fixed-size byte strings and strings.
+
+#![no_main]
+
+use std::time::Duration;
+
+use amplify::num::{i1024, i256, i512, u1024, u24, u256, u512};
+use amplify::Slice32;
+use libfuzzer_sys::fuzz_target;
+use strict_encoding_fuzz::fuzz_types;
+
+fuzz_target!(|data: &[u8]| {
+    fuzz_types!(data;
+        (),             // 0
+        bool,           // 1
+        u8,             // 2
+        i8,             // 3
+        u16,            // 4
+        i16,            // 5
+        u24,            // 6
+        u32,            // 7
+        i32,            // 8
+        u64,            // 9
+        i64,            // 10
+        u128,           // 11
+        i128,           // 12
+        usize,          // 13
+        f32,            // 14
+        f64,            // 15
+        Duration,       // 16
+        u256,           // 17
+        u512,           // 18
+        u1024,          // 19
+        i256,           // 20
+        i512,           // 21
+        i1024,          // 22
+        [u8; 32],       // 23
+        Slice32,        // 24
+        Box<[u8]>,      // 25
+        String,         // 26
+    );
+});
@@ -0,0 +1,48 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Decoders of type descriptions and dynamically-typed values.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use strict_encoding::schema::{LenPrefix, Primitive, TypeDescr};
+use strict_encoding::{io, Error, StrictDecode, StrictEncode, StrictValue};
+use strict_encoding_fuzz::fuzz_types;
+
+/// Type description followed by a value of the described type
+struct TypedValue(TypeDescr, StrictValue);
+
+impl StrictEncode for TypedValue {
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        Ok(self.0.strict_encode(&mut e)? + self.1.strict_encode(&mut e)?)
+    }
+}
+
+impl StrictDecode for TypedValue {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        let ty = TypeDescr::strict_decode(&mut d)?;
+        let value = StrictValue::decode(&ty, &mut d)?;
+        Ok(TypedValue(ty, value))
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    fuzz_types!(data;
+        Primitive,      // 0
+        LenPrefix,      // 1
+        TypeDescr,      // 2
+        TypedValue,     // 3
+    );
+});
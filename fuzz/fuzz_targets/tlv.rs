@@ -0,0 +1,46 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Decoders of TLV streams in both strict and BOLT-1 encodings.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use strict_encoding::{
+    io, BigSize, Error, StrictDecode, StrictEncode, TlvStream,
+};
+use strict_encoding_fuzz::fuzz_types;
+
+/// TLV stream in BOLT-1 encoding
+struct BoltTlvStream(TlvStream);
+
+impl StrictEncode for BoltTlvStream {
+    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
+        self.0.bolt_encode(e)
+    }
+}
+
+impl StrictDecode for BoltTlvStream {
+    fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
+        TlvStream::bolt_decode(d).map(BoltTlvStream)
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    fuzz_types!(data;
+        BigSize,        // 0
+        TlvStream,      // 1
+        BoltTlvStream,  // 2
+    );
+});
@@ -0,0 +1,37 @@
+#!/usr/bin/env bash
+#
+# Builds seed corpus of the fuzz targets out of the test vectors used by the
+# workspace unit tests. Encodings of all objects passing strict encoding
+# roundtrip tests are collected by `strict_encoding_test` helpers and copied
+# into `corpus/<target>/`, prefixed with the byte selecting the type of the
+# object in the target.
+
+set -euo pipefail
+
+FUZZ_DIR="$(cd "$(dirname "$0")" && pwd)"
+VECTORS="$(mktemp -d)"
+trap 'rm -rf "$VECTORS"' EXIT
+
+(cd "$FUZZ_DIR/.." && STRICT_ENCODING_CORPUS="$VECTORS" \
+    cargo test --workspace --features bitcoin,miniscript >/dev/null)
+
+# Strips module paths and whitespaces from the type name
+normalize() {
+    sed -E 's/[A-Za-z0-9_]+:://g; s/[[:space:]]//g'
+}
+
+for target in "$FUZZ_DIR"/fuzz_targets/*.rs; do
+    name="$(basename "$target" .rs)"
+    mkdir -p "$FUZZ_DIR/corpus/$name"
+    grep -E '^[[:space:]]+.+, +// [0-9]+$' "$target" | while read -r line; do
+        ty="$(echo "$line" | sed -E 's/,[[:space:]]*\/\/ [0-9]+$//' | normalize)"
+        idx="${line##*// }"
+        for dir in "$VECTORS"/*; do
+            [ "$(basename "$dir" | normalize)" = "$ty" ] || continue
+            for vector in "$dir"/*; do
+                out="$FUZZ_DIR/corpus/$name/$(printf '%02x' "$idx")-$(basename "$vector")"
+                { printf "\\x$(printf '%02x' "$idx")"; cat "$vector"; } > "$out"
+            done
+        done
+    done
+done
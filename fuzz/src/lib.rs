@@ -0,0 +1,153 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+// Coding conventions
+#![deny(dead_code, missing_docs, warnings)]
+
+//! Shared harness of the strict decoding fuzz targets.
+//!
+//! Each fuzz target covers a family of decoders; the first byte of the fuzz
+//! input selects the decoded type (modulo the number of types in the family),
+//! and the rest of the input is passed to [`check`] for that type.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use strict_encoding::{
+    DecodeLimits, LimitedReader, StrictDecode, StrictEncode,
+};
+
+/// Maximal nesting depth of the decoded data. Fuzz targets decode data with
+/// the limits and in canonical mode, like any code decoding untrusted input
+/// must do.
+pub const MAX_DEPTH: usize = 128;
+
+/// Number of bytes each decoder may allocate regardless of the input length
+pub const ALLOC_BASE: usize = 1 << 24;
+
+/// Number of bytes each decoder may allocate per byte of the input
+pub const ALLOC_PER_BYTE: usize = 256;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// System allocator tracking peak amount of the allocated memory
+pub struct TrackingAlloc;
+
+unsafe impl GlobalAlloc for TrackingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            allocated(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            allocated(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn realloc(
+        &self,
+        ptr: *mut u8,
+        layout: Layout,
+        new_size: usize,
+    ) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+            allocated(new_size);
+        }
+        new_ptr
+    }
+}
+
+fn allocated(size: usize) {
+    let total = ALLOCATED.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(total, Ordering::Relaxed);
+}
+
+#[global_allocator]
+static GLOBAL: TrackingAlloc = TrackingAlloc;
+
+/// Decodes `data` as a value of type `T`, checking that
+/// - the decoder does not panic (a panic aborts the fuzzing process);
+/// - the decoder does not allocate more than [`ALLOC_BASE`] plus
+///   [`ALLOC_PER_BYTE`] bytes per byte of the input;
+/// - successfully decoded value is encoded back exactly into the consumed part
+///   of the input, i.e. in canonical mode the decoder accepts only canonical
+///   encodings.
+///
+/// # Panics
+///
+/// If any of the checks fails.
+pub fn check<T>(data: &[u8])
+where
+    T: StrictEncode + StrictDecode,
+{
+    let limits = DecodeLimits {
+        max_depth: MAX_DEPTH,
+        canonical: true,
+        ..DecodeLimits::UNLIMITED
+    };
+    let mut reader = LimitedReader::new(data, limits);
+
+    let baseline = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    let res = reader.decode::<T>();
+    let peak = PEAK.load(Ordering::Relaxed).saturating_sub(baseline);
+    let budget = ALLOC_BASE + ALLOC_PER_BYTE * data.len();
+    assert!(
+        peak <= budget,
+        "decoder of `{}` allocated {} bytes for {} bytes of input",
+        std::any::type_name::<T>(),
+        peak,
+        data.len()
+    );
+
+    if let Ok(value) = res {
+        let consumed = &data[..reader.consumed()];
+        let encoded = value
+            .strict_serialize()
+            .expect("decoded value can't be encoded");
+        assert_eq!(
+            encoded,
+            consumed,
+            "non-canonical encoding of `{}` is accepted by the decoder",
+            std::any::type_name::<T>()
+        );
+    }
+}
+
+/// Runs [`check`] for one of the listed types, selected by the first byte of
+/// the fuzz input. The types are indexed from zero in the order they are
+/// listed.
+#[macro_export]
+macro_rules! fuzz_types {
+    ($data:expr; $($ty:ty),+ $(,)?) => {{
+        let checks: &[fn(&[u8])] = &[$($crate::check::<$ty>),+];
+        if let Some((selector, data)) = $data.split_first() {
+            checks[*selector as usize % checks.len()](data);
+        }
+    }};
+}
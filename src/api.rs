@@ -280,6 +280,7 @@ where
     ///   [`ClientData::validate_internal_consistency`] method,
     /// - validates single-use-seal for the item using the provided `resolver`
     ///   object,
+    ///
     /// adding reported issues to the [`Status`] log returned by the function.
     ///
     /// The function should not fail on any validation failures and run the
@@ -404,6 +405,7 @@ mod test {
         )]
         struct Seal {}
 
+        #[allow(dead_code)]
         struct Protocol {}
 
         impl SealProtocol<Seal> for Protocol {
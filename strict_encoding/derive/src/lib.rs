@@ -34,9 +34,18 @@
 //! TLV (type-length-value) extensions: strict encoding, used for pure
 //! client-side-validation, does not allow use of TLVs, while in network
 //! protocol context this requirement is relaxed and specially-designed TLV
-//! encoding is allowed (see sections below on how to use TLV encoding). Network
-//! encoding TLV is not strictly BOLT-1 compatible; if you are looking for
-//! BOLT-1 TLV implementation, please check `lightning_encoding_derive` crate.
+//! encoding is allowed (see sections below on how to use TLV encoding). By
+//! default network encoding TLV is not BOLT-1 compatible; BOLT-1 TLV streams
+//! can be used with the `bolt` argument.
+//!
+//! Layout of the encoded data may be described with
+//! `#[derive(`[`StrictType`]`)]` and `#[derive(`[`NetworkType`]`)]` macros,
+//! which produce implementation of `strict_encoding::StrictType` trait
+//! according to the same attributes as the encoding macros.
+//!
+//! Zero-copy decoding of types borrowing byte slices and strings from the
+//! source data is provided by `#[derive(`[`StrictDecodeBorrowed`]`)]`, which
+//! accepts the same attributes as [`StrictDecode`] derivation except TLV ones.
 //!
 //! # Attribute
 //!
@@ -56,10 +65,58 @@
 //! ### `use_tlv`
 //!
 //! Applies TLV extension to the data type and allows use of `tlv` and
-//! `unknown_tlvs` arguments on struct fields.
+//! `unknown_tlvs` arguments on struct fields. TLV fields are encoded after all
+//! other fields as a `strict_encoding::TlvStream`; the derived types also
+//! implement `ToTlvStream` and `ApplyTlvStream` traits for the conversion
+//! to and from the stream.
 //!
 //! NB: TLVs work only with structures and not enums.
 //!
+//! ### `bolt`
+//!
+//! Can be used only together with `use_tlv`. Encodes TLV stream according to
+//! BOLT-1 rules: TLV type ids and value lengths are encoded as
+//! `strict_encoding::BigSize`, and the records are read up to the end of the
+//! data, so the TLV stream must be the last part of the encoded message. The
+//! values of the TLV fields still use their network encoding. Such types can't
+//! be described with `NetworkType`.
+//!
+//! ### `version = <8-bit unsigned integer>`
+//!
+//! Encodes the data structure prefixed with a byte holding the provided
+//! version number. Decoding of the data with a greater version fails with
+//! `strict_encoding::Error::UnsupportedDataStructure`; the same happens to
+//! the data of older versions, unless `migrate` argument is present. Can't be
+//! used with `StrictDecodeBorrowed` derivation.
+//!
+//! ### `migrate`
+//!
+//! Can be used only together with `version`. Decodes data of older versions
+//! with `strict_encoding::StrictMigrate` implementation, which must be
+//! provided for the type; asynchronous derivations use
+//! `strict_encoding::StrictMigrateAsync` implementation instead.
+//!
+//! ### `bound = "<where predicates>"`
+//!
+//! Replaces trait bounds on the type parameters, which are added to the
+//! derived implementations, with the provided comma-separated list of where
+//! predicates. By default each type parameter and associated type projection
+//! (like `R::Item` or `<R as Trait>::Item`) used by non-skipped fields is
+//! bounded by the derived trait; an explicit bound is required when the field
+//! types need more than that (for instance `Ord` for `BTreeSet<T>` decoding)
+//! or when they don't need the bound at all. The same predicates are used by
+//! all derivations reading the attribute, so for the types deriving both
+//! encoding and decoding they must cover both traits.
+//!
+//! ### `validate = "path::to::fn"`
+//!
+//! Checks the decoded data with the provided function, which must have
+//! `fn(&Self) -> Result<(), E>` signature, where `E: Display`. The function is
+//! called after all the data, including TLVs, are decoded (or migrated from an
+//! older version); its error is returned as
+//! `strict_encoding::Error::DataIntegrityError` with the error message.
+//! Encoding does not call the function.
+//!
 //! ### `crate = ::path::to::strict_encoding_crate`
 //!
 //! Allows to specify custom path to `strict_encoding` crate
@@ -104,6 +161,51 @@
 //! Allowed only for named and unnamed (tuple) structure fields and enum variant
 //! associated value fields.
 //!
+//! ### `bound = "<where predicates>"`
+//!
+//! Replaces trait bounds inferred from the field type (or from the types of
+//! all enum variant fields) with the provided where predicates; see the
+//! type-level argument with the same name above.
+//!
+//! ### `validate = "path::to::fn"`
+//!
+//! Allowed only for fields, which are not skipped and are not TLVs.
+//!
+//! Checks the decoded field value with the provided function, which must have
+//! `fn(&FieldType) -> Result<(), E>` signature, where `E: Display`. The
+//! function is called right after the field is decoded; its error is returned
+//! as `strict_encoding::Error::DataIntegrityError` within the error context
+//! with the field and type names.
+//!
+//! ### `with = "path::to::module"`
+//!
+//! Allowed only for fields, which are not skipped and are not TLVs.
+//!
+//! Encodes and decodes the field with `strict_encode` and `strict_decode`
+//! functions from the provided module instead of the strict encoding traits
+//! of the field type, which therefore doesn't have to implement them.
+//! [`StrictType`] and [`NetworkType`] derivations additionally require
+//! `strict_type` function. Function signatures are described in
+//! `strict_encoding::adapters` module. Fields encoded with adapters make the
+//! type size variable and are not bounded by the derived trait, so a
+//! `bound = "..."` argument may be required for the generic field types.
+//! Asynchronous derivations use `strict_encode_async` and `strict_decode_async`
+//! functions from the same module. Adapters are not supported by borrowed
+//! derivation.
+//!
+//! ### `as_u32_len`, `fixed_bytes`, `uniform`
+//!
+//! Shorthands for `with` argument using adapters from
+//! `strict_encoding::adapters` module with the same name:
+//! - `as_u32_len` encodes `Vec`, `BTreeSet` and `BTreeMap` fields with `u32`
+//!   length prefix;
+//! - `fixed_bytes` encodes `bitcoin_hashes::Hash` fields as fixed-size byte
+//!   strings;
+//! - `uniform` encodes network addresses implementing
+//!   `strict_encoding::net::Uniform` with LNPBP-42 uniform encoding.
+//!
+//! Can't be combined with each other or with `with` argument.
+//!
 //! ### `value = <unsigned integer>`
 //!
 //! Allowed only for enum variants.
@@ -126,7 +228,7 @@
 //!
 //! Specifies structure field which will be "capture all" for unknown odd TLV
 //! ids. The argument can be used only for a single field within a structure and
-//! the field type must be `BTreeMap<usize, Box<[u8]>]`.
+//! the field type must be `strict_encoding::TlvStream`.
 //!
 //! NB: if an unknown even TLV type id is met, error is raised and the value
 //! does not get into the field.
@@ -213,7 +315,10 @@ extern crate syn;
 #[macro_use]
 extern crate amplify_syn;
 
-use encoding_derive_helpers::{decode_derive, encode_derive};
+use encoding_derive_helpers::{
+    decode_async_derive, decode_borrowed_derive, decode_derive,
+    encode_async_derive, encode_derive, schema_derive,
+};
 use proc_macro::TokenStream;
 use syn::DeriveInput;
 
@@ -227,6 +332,7 @@ pub fn derive_strict_encode(input: TokenStream) -> TokenStream {
         ident!(StrictEncode),
         ident!(strict_encode),
         ident!(strict_serialize),
+        ident!(strict_encoded_len),
         derive_input,
         false,
     )
@@ -262,6 +368,7 @@ pub fn derive_network_encode(input: TokenStream) -> TokenStream {
         ident!(StrictEncode),
         ident!(strict_encode),
         ident!(strict_serialize),
+        ident!(strict_encoded_len),
         derive_input,
         true,
     )
@@ -286,3 +393,129 @@ pub fn derive_network_decode(input: TokenStream) -> TokenStream {
     .unwrap_or_else(|e| e.to_compile_error())
     .into()
 }
+
+/// Derives [`StrictEncodeAsync`] implementation for the type, producing the
+/// same data as [`StrictEncode`] derivation does. Requires `async` feature of
+/// `strict_encoding` crate.
+#[proc_macro_derive(StrictEncodeAsync, attributes(strict_encoding))]
+pub fn derive_strict_encode_async(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    encode_async_derive(
+        "strict_encoding",
+        ident!(strict_encoding),
+        ident!(StrictEncodeAsync),
+        ident!(strict_encode_async),
+        derive_input,
+        false,
+    )
+    .unwrap_or_else(|e| e.to_compile_error())
+    .into()
+}
+
+/// Derives [`StrictDecodeAsync`] implementation for the type, consuming the
+/// same data as [`StrictDecode`] derivation does. Requires `async` feature of
+/// `strict_encoding` crate.
+#[proc_macro_derive(StrictDecodeAsync, attributes(strict_encoding))]
+pub fn derive_strict_decode_async(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    decode_async_derive(
+        "strict_encoding",
+        ident!(strict_encoding),
+        ident!(StrictDecodeAsync),
+        ident!(strict_decode_async),
+        derive_input,
+        false,
+    )
+    .unwrap_or_else(|e| e.to_compile_error())
+    .into()
+}
+
+/// Derives [`StrictEncodeAsync`] implementation for the type, producing the
+/// same data as [`NetworkEncode`] derivation does. Types using TLV extensions
+/// must also derive [`NetworkEncode`], which provides conversion of their TLV
+/// fields into the stream. Requires `async` feature of `strict_encoding`
+/// crate.
+#[proc_macro_derive(NetworkEncodeAsync, attributes(network_encoding))]
+pub fn derive_network_encode_async(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    encode_async_derive(
+        "network_encoding",
+        ident!(strict_encoding),
+        ident!(StrictEncodeAsync),
+        ident!(strict_encode_async),
+        derive_input,
+        true,
+    )
+    .unwrap_or_else(|e| e.to_compile_error())
+    .into()
+}
+
+/// Derives [`StrictDecodeAsync`] implementation for the type, consuming the
+/// same data as [`NetworkDecode`] derivation does. Types using TLV extensions
+/// must also derive [`NetworkDecode`], which provides application of the TLV
+/// stream to their fields. Requires `async` feature of `strict_encoding`
+/// crate.
+#[proc_macro_derive(NetworkDecodeAsync, attributes(network_encoding))]
+pub fn derive_network_decode_async(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    decode_async_derive(
+        "network_encoding",
+        ident!(strict_encoding),
+        ident!(StrictDecodeAsync),
+        ident!(strict_decode_async),
+        derive_input,
+        true,
+    )
+    .unwrap_or_else(|e| e.to_compile_error())
+    .into()
+}
+
+/// Derives [`StrictDecodeBorrowed`] implementation for the type, decoding the
+/// same data as [`StrictDecode`] derivation does while borrowing byte slices
+/// and strings from the source data. The type may have at most one lifetime
+/// parameter, which is used as the lifetime of the borrowed data.
+#[proc_macro_derive(StrictDecodeBorrowed, attributes(strict_encoding))]
+pub fn derive_strict_decode_borrowed(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    decode_borrowed_derive(
+        "strict_encoding",
+        ident!(strict_encoding),
+        ident!(StrictDecodeBorrowed),
+        ident!(strict_decode_borrowed),
+        derive_input,
+    )
+    .unwrap_or_else(|e| e.to_compile_error())
+    .into()
+}
+
+/// Derives [`StrictType`] implementation for the type, describing its strict
+/// encoding layout.
+#[proc_macro_derive(StrictType, attributes(strict_encoding))]
+pub fn derive_strict_type(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    schema_derive(
+        "strict_encoding",
+        ident!(strict_encoding),
+        ident!(StrictType),
+        derive_input,
+        false,
+    )
+    .unwrap_or_else(|e| e.to_compile_error())
+    .into()
+}
+
+/// Derives [`StrictType`] implementation for the type, also describing TLV
+/// extension fields.
+#[proc_macro_derive(NetworkType, attributes(network_encoding))]
+pub fn derive_network_type(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    schema_derive(
+        "network_encoding",
+        ident!(strict_encoding),
+        ident!(StrictType),
+        derive_input,
+        true,
+    )
+    .unwrap_or_else(|e| e.to_compile_error())
+    .into()
+}
@@ -0,0 +1,26 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+// error-pattern: `with`, `as_u32_len`, `fixed_bytes` and `uniform` attributes are mutually exclusive
+
+#[macro_use]
+extern crate strict_encoding_derive;
+
+#[derive(StrictEncode, StrictDecode)]
+struct Data {
+    #[strict_encoding(as_u32_len, with = "codec")]
+    items: Vec<u8>,
+}
+
+fn main() {}
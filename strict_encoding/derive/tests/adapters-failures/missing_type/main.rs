@@ -0,0 +1,43 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+// error-pattern: cannot find function `strict_type` in module `codec`
+
+#[macro_use]
+extern crate strict_encoding_derive;
+
+use strict_encoding::{io, Error, StrictDecode, StrictEncode};
+
+mod codec {
+    use super::*;
+
+    pub fn strict_encode<E: io::Write>(
+        value: &u8,
+        e: E,
+    ) -> Result<usize, Error> {
+        value.strict_encode(e)
+    }
+
+    pub fn strict_decode<D: io::Read>(d: D) -> Result<u8, Error> {
+        u8::strict_decode(d)
+    }
+}
+
+#[derive(StrictEncode, StrictDecode, StrictType)]
+struct Data {
+    #[strict_encoding(with = "codec")]
+    value: u8,
+}
+
+fn main() {}
@@ -0,0 +1,381 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+#[macro_use]
+extern crate amplify;
+#[macro_use]
+extern crate strict_encoding_derive;
+extern crate strict_encoding_test;
+
+mod common;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::SocketAddr;
+
+use bitcoin_hashes::{sha256, Hash};
+use common::{compile_test, Result};
+use strict_encoding::net::UNIFORM_LEN;
+use strict_encoding::schema::{
+    FieldDescr, LenPrefix, Primitive, StructDescr, TypeDescr,
+};
+use strict_encoding::{
+    Error, PathSegment, StrictDecode, StrictEncode, StrictType,
+};
+use strict_encoding_test::test_encoding_roundtrip;
+
+#[test]
+fn adapters_skipped() { compile_test("adapters-failures/skipped"); }
+
+#[test]
+fn adapters_tlv() { compile_test("adapters-failures/tlv"); }
+
+#[test]
+fn adapters_variant() { compile_test("adapters-failures/variant"); }
+
+#[test]
+fn adapters_exclusive() { compile_test("adapters-failures/exclusive"); }
+
+#[test]
+fn adapters_borrowed() { compile_test("adapters-failures/borrowed"); }
+
+#[test]
+fn adapters_invalid_path() { compile_test("adapters-failures/invalid_path"); }
+
+#[test]
+fn adapters_missing_type() { compile_test("adapters-failures/missing_type"); }
+
+/// Foreign type which does not implement any of the strict encoding traits
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Celsius(i16);
+
+mod celsius {
+    use strict_encoding::schema::{Primitive, TypeDescr};
+    use strict_encoding::{io, Error, StrictDecode, StrictEncode};
+
+    use super::Celsius;
+
+    pub fn strict_encode<E: io::Write>(
+        value: &Celsius,
+        e: E,
+    ) -> Result<usize, Error> {
+        value.0.strict_encode(e)
+    }
+
+    pub fn strict_decode<D: io::Read>(d: D) -> Result<Celsius, Error> {
+        let value = i16::strict_decode(d)?;
+        if value < -273 {
+            return Err(Error::DataIntegrityError(format!(
+                "{} is below absolute zero",
+                value
+            )));
+        }
+        Ok(Celsius(value))
+    }
+
+    pub fn strict_type() -> TypeDescr { TypeDescr::Primitive(Primitive::I16) }
+}
+
+#[test]
+fn adapters_custom() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode, StrictType)]
+    struct Reading {
+        id: u8,
+        #[strict_encoding(with = "celsius")]
+        temperature: Celsius,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct Pair(
+        #[strict_encoding(with = "celsius")] Celsius,
+        #[strict_encoding(with = "self::celsius")] Celsius,
+    );
+
+    let reading = Reading {
+        id: 1,
+        temperature: Celsius(-5),
+    };
+    test_encoding_roundtrip(&reading, [0x01, 0xFB, 0xFF])?;
+    assert_eq!(reading.strict_encoded_len()?, 3);
+    assert_eq!(Reading::FIXED_SIZE, None);
+    test_encoding_roundtrip(&Pair(Celsius(1), Celsius(2)), [
+        0x01, 0x00, 0x02, 0x00,
+    ])?;
+
+    let err = Reading::strict_deserialize([0x01, 0x00, 0xFE]).unwrap_err();
+    assert_eq!(
+        err.context().map(|context| context.path.clone()),
+        Some(vec![PathSegment::Field("temperature")])
+    );
+    assert_eq!(
+        err.root_cause(),
+        &Error::DataIntegrityError(s!("-512 is below absolute zero"))
+    );
+
+    assert_eq!(
+        Reading::strict_type(),
+        TypeDescr::Struct(StructDescr {
+            name: s!("Reading"),
+            fields: vec![
+                FieldDescr {
+                    name: Some(s!("id")),
+                    ty: TypeDescr::Primitive(Primitive::U8),
+                },
+                FieldDescr {
+                    name: Some(s!("temperature")),
+                    ty: TypeDescr::Primitive(Primitive::I16),
+                },
+            ],
+            tlvs: vec![],
+            unknown_tlvs: false,
+        })
+    );
+    Ok(())
+}
+
+#[test]
+fn adapters_u32_len() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug, Default)]
+    #[derive(StrictEncode, StrictDecode, StrictType)]
+    struct Index {
+        #[strict_encoding(as_u32_len)]
+        list: Vec<u8>,
+        #[strict_encoding(as_u32_len)]
+        set: BTreeSet<u16>,
+        #[strict_encoding(as_u32_len)]
+        map: BTreeMap<u8, String>,
+    }
+
+    let index = Index {
+        list: vec![1, 2],
+        set: bset! { 3 },
+        map: bmap! { 4 => s!("a") },
+    };
+    test_encoding_roundtrip(&index, [
+        0x02, 0x00, 0x00, 0x00, 0x01, 0x02, 0x01, 0x00, 0x00, 0x00, 0x03, 0x00,
+        0x01, 0x00, 0x00, 0x00, 0x04, 0x01, 0x00, b'a',
+    ])?;
+    assert_eq!(index.strict_encoded_len()?, 20);
+
+    let large = Index {
+        list: vec![0xAA; u16::MAX as usize + 1],
+        ..default!()
+    };
+    let data = large.strict_serialize()?;
+    assert_eq!(&data[..4], &[0x00, 0x00, 0x01, 0x00]);
+    assert_eq!(Index::strict_deserialize(data)?, large);
+
+    // Set items must be ordered
+    let err = Index::strict_deserialize([
+        0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x02, 0x00, 0x01, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+    ])
+    .unwrap_err();
+    assert_eq!(
+        err.context().map(|context| context.path.clone()),
+        Some(vec![PathSegment::Field("set")])
+    );
+    assert!(matches!(err.root_cause(), Error::DataIntegrityError(_)));
+
+    let fields = match Index::strict_type() {
+        TypeDescr::Struct(descr) => descr.fields,
+        _ => unreachable!(),
+    };
+    let u8 = TypeDescr::Primitive(Primitive::U8);
+    assert_eq!(fields[0].ty, TypeDescr::list(LenPrefix::U32, u8.clone()));
+    assert_eq!(
+        fields[1].ty,
+        TypeDescr::set(LenPrefix::U32, TypeDescr::Primitive(Primitive::U16))
+    );
+    assert_eq!(
+        fields[2].ty,
+        TypeDescr::map(LenPrefix::U32, u8, TypeDescr::Unicode(LenPrefix::U16))
+    );
+    Ok(())
+}
+
+#[test]
+fn adapters_fixed_bytes() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode, StrictType)]
+    struct Commitment {
+        #[strict_encoding(fixed_bytes)]
+        hash: sha256::Hash,
+        nonce: u8,
+    }
+
+    let hash = sha256::Hash::hash(b"commitment");
+    let commitment = Commitment { hash, nonce: 7 };
+    let mut data = hash.to_vec();
+    data.push(7);
+    test_encoding_roundtrip(&commitment, data)?;
+    assert_eq!(commitment.strict_encoded_len()?, 33);
+    assert!(matches!(
+        Commitment::strict_type(),
+        TypeDescr::Struct(descr) if descr.fields[0].ty == TypeDescr::Bytes(32)
+    ));
+    Ok(())
+}
+
+#[test]
+fn adapters_uniform() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode, StrictType)]
+    struct Peer {
+        #[strict_encoding(uniform)]
+        addr: SocketAddr,
+    }
+
+    let peer = Peer {
+        addr: "127.0.0.1:9735".parse().unwrap(),
+    };
+    let data = peer.strict_serialize()?;
+    assert_eq!(data.len(), UNIFORM_LEN);
+    assert_eq!(Peer::strict_deserialize(&data)?, peer);
+    assert!(matches!(
+        Peer::strict_type(),
+        TypeDescr::Struct(descr)
+            if descr.fields[0].ty == TypeDescr::Bytes(UNIFORM_LEN as u16)
+    ));
+    Ok(())
+}
+
+#[test]
+fn adapters_enum() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    enum Event {
+        Measured(#[strict_encoding(with = "celsius")] Celsius),
+        Batch {
+            #[strict_encoding(as_u32_len)]
+            readings: Vec<u8>,
+            #[strict_encoding(fixed_bytes)]
+            digest: sha256::Hash,
+        },
+    }
+
+    test_encoding_roundtrip(&Event::Measured(Celsius(20)), [0x00, 0x14, 0x00])?;
+    let batch = Event::Batch {
+        readings: vec![1],
+        digest: sha256::Hash::hash(b"batch"),
+    };
+    assert_eq!(batch.strict_encoded_len()?, 1 + 5 + 32);
+    test_encoding_roundtrip(&batch, batch.strict_serialize()?)?;
+
+    let err = Event::strict_deserialize([0x00, 0x00, 0xFE]).unwrap_err();
+    assert_eq!(
+        err.context().map(|context| context.path.clone()),
+        Some(vec![
+            PathSegment::Variant("Measured"),
+            PathSegment::Field("0")
+        ])
+    );
+    Ok(())
+}
+
+#[test]
+fn adapters_network() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug, Default)]
+    #[derive(NetworkEncode, NetworkDecode)]
+    #[network_encoding(use_tlv)]
+    struct Announcement {
+        #[network_encoding(as_u32_len)]
+        features: Vec<u8>,
+        #[network_encoding(tlv = 1)]
+        alias: Option<u8>,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(NetworkEncode, NetworkDecode, NetworkType)]
+    struct Reading {
+        #[network_encoding(with = "celsius")]
+        temperature: Celsius,
+        #[network_encoding(uniform)]
+        source: SocketAddr,
+    }
+
+    test_encoding_roundtrip(
+        &Announcement {
+            features: vec![0xFF],
+            alias: None,
+        },
+        [0x01, 0x00, 0x00, 0x00, 0xFF],
+    )?;
+
+    let reading = Reading {
+        temperature: Celsius(1),
+        source: "10.0.0.1:80".parse().unwrap(),
+    };
+    let data = reading.strict_serialize()?;
+    assert_eq!(data.len(), 2 + UNIFORM_LEN);
+    assert_eq!(Reading::strict_deserialize(&data)?, reading);
+    Ok(())
+}
+
+#[test]
+fn adapters_validated() -> Result {
+    fn warm(value: &Celsius) -> std::result::Result<(), &'static str> {
+        if value.0 < 0 {
+            return Err("freezing");
+        }
+        Ok(())
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct Room {
+        #[strict_encoding(with = "celsius", validate = "warm")]
+        temperature: Celsius,
+    }
+
+    test_encoding_roundtrip(
+        &Room {
+            temperature: Celsius(21),
+        },
+        [0x15, 0x00],
+    )?;
+    assert_eq!(
+        Room::strict_deserialize([0xFF, 0xFF])
+            .unwrap_err()
+            .root_cause(),
+        &Error::DataIntegrityError(s!("freezing"))
+    );
+    Ok(())
+}
+
+#[test]
+fn adapters_generic() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct Log<T, H: Hash> {
+        #[strict_encoding(
+            as_u32_len,
+            bound = "T: StrictEncode + StrictDecode"
+        )]
+        entries: Vec<T>,
+        #[strict_encoding(fixed_bytes)]
+        head: H,
+    }
+
+    let log = Log {
+        entries: vec![1u16],
+        head: sha256::Hash::hash(b"head"),
+    };
+    test_encoding_roundtrip(&log, log.strict_serialize()?)?;
+    assert_eq!(&log.strict_serialize()?[..6], &[
+        0x01, 0x00, 0x00, 0x00, 0x01, 0x00
+    ]);
+    Ok(())
+}
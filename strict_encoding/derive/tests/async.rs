@@ -0,0 +1,426 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+#[macro_use]
+extern crate amplify;
+#[macro_use]
+extern crate strict_encoding_derive;
+extern crate strict_encoding_test;
+
+mod common;
+
+use std::net::SocketAddr;
+
+use bitcoin_hashes::{sha256, Hash};
+use common::Result;
+use strict_encoding::{
+    async_trait, io, AsyncRead, Error, StrictDecode, StrictDecodeAsync,
+    StrictMigrate, StrictMigrateAsync, TlvError, TlvStream,
+};
+use strict_encoding_test::{
+    test_async_encoding_roundtrip, test_async_object_encoding_roundtrip,
+};
+
+#[test]
+fn async_structs() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug, Default)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[derive(StrictEncodeAsync, StrictDecodeAsync)]
+    struct Named {
+        pub data: String,
+        pub value: u16,
+        #[strict_encoding(skip)]
+        pub ephemeral: bool,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[derive(StrictEncodeAsync, StrictDecodeAsync)]
+    struct Tuple(u8, Vec<u8>);
+
+    test_async_encoding_roundtrip(
+        &Named {
+            data: s!("abc"),
+            value: 0x0201,
+            ephemeral: false,
+        },
+        [0x03, 0x00, b'a', b'b', b'c', 0x01, 0x02],
+    )?;
+    test_async_encoding_roundtrip(&Tuple(0xFF, vec![0xAA]), [
+        0xFF, 0x01, 0x00, 0xAA,
+    ])?;
+
+    Ok(())
+}
+
+#[test]
+fn async_enums() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[derive(StrictEncodeAsync, StrictDecodeAsync)]
+    enum WithFields {
+        First(u8),
+        Second(Vec<u8>),
+        Third,
+        Fourth {
+            data: String,
+            #[strict_encoding(skip)]
+            ephemeral: bool,
+        },
+        #[strict_encoding(value = 7)]
+        Seventh,
+    }
+
+    test_async_encoding_roundtrip(&WithFields::First(0xC8), [0x00, 0xC8])?;
+    test_async_encoding_roundtrip(&WithFields::Second(vec![0xA1, 0xA2]), [
+        0x01, 0x02, 0x00, 0xA1, 0xA2,
+    ])?;
+    test_async_encoding_roundtrip(&WithFields::Third, [0x02])?;
+    test_async_encoding_roundtrip(
+        &WithFields::Fourth {
+            data: s!("a"),
+            ephemeral: false,
+        },
+        [0x03, 0x01, 0x00, b'a'],
+    )?;
+    test_async_encoding_roundtrip(&WithFields::Seventh, [0x07])?;
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[derive(StrictEncodeAsync, StrictDecodeAsync)]
+    #[strict_encoding(by_order, repr = u16)]
+    #[repr(u16)]
+    enum ByOrder {
+        Bit8 = 1,
+        Bit16 = 2,
+    }
+
+    test_async_encoding_roundtrip(&ByOrder::Bit8, [0x00, 0x00])?;
+    test_async_encoding_roundtrip(&ByOrder::Bit16, [0x01, 0x00])?;
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[derive(StrictEncodeAsync, StrictDecodeAsync)]
+    #[strict_encoding(by_value)]
+    #[repr(u8)]
+    enum ByValue {
+        Bit8 = 1,
+        Bit16 = 2,
+        #[strict_encoding(value = 0x10)]
+        Bit32 = 4,
+    }
+
+    test_async_encoding_roundtrip(&ByValue::Bit8, [0x01])?;
+    test_async_encoding_roundtrip(&ByValue::Bit16, [0x02])?;
+    test_async_encoding_roundtrip(&ByValue::Bit32, [0x10])?;
+
+    Ok(())
+}
+
+#[test]
+fn async_unknown_variant() {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictDecodeAsync)]
+    enum Simple {
+        A,
+        B,
+    }
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    assert_eq!(
+        rt.block_on(Simple::strict_deserialize_async([0x01])),
+        Ok(Simple::B)
+    );
+    assert_eq!(
+        rt.block_on(Simple::strict_deserialize_async([0x02])),
+        Err(strict_encoding::Error::EnumValueNotKnown("Simple", 2))
+    );
+}
+
+#[test]
+fn async_tlv() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug, Default)]
+    #[derive(NetworkEncode, NetworkDecode)]
+    #[derive(NetworkEncodeAsync, NetworkDecodeAsync)]
+    #[network_encoding(use_tlv)]
+    struct Tlv {
+        fixed: u8,
+
+        #[network_encoding(tlv = 0xBEEF)]
+        tlv: Option<u32>,
+
+        #[network_encoding(unknown_tlvs)]
+        rest: TlvStream,
+    }
+
+    let mut rest = TlvStream::new();
+    rest.insert(0xBEF1, [0x07]);
+    test_async_encoding_roundtrip(&Tlv::default(), [0x00])?;
+    test_async_encoding_roundtrip(
+        &Tlv {
+            fixed: 0xDD,
+            tlv: Some(0xDEADCAFE),
+            rest,
+        },
+        [
+            0xDD, // Fixed field
+            0x02, 0x00, // Count of TLV elements
+            0xEF, 0xBE, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Type
+            0x04, 0x00, // Length
+            0xFE, 0xCA, 0xAD, 0xDE, // Value
+            0xF1, 0xBE, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Type
+            0x01, 0x00, // Length
+            0x07, // Value
+        ],
+    )?;
+
+    #[derive(Clone, PartialEq, Eq, Debug, Default)]
+    #[derive(NetworkEncode, NetworkDecode)]
+    #[derive(NetworkEncodeAsync, NetworkDecodeAsync)]
+    #[network_encoding(use_tlv, bolt)]
+    struct Bolt {
+        fixed: u8,
+
+        #[network_encoding(tlv = 1)]
+        tlv: Option<u16>,
+    }
+
+    test_async_encoding_roundtrip(
+        &Bolt {
+            fixed: 0xDD,
+            tlv: Some(0x0201),
+        },
+        [0xDD, 0x01, 0x02, 0x01, 0x02],
+    )?;
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let err = rt
+        .block_on(Bolt::strict_deserialize_async([0xDD, 0x02, 0x00]))
+        .unwrap_err();
+    assert_eq!(err.root_cause(), &TlvError::UnknownEvenType(2).into());
+
+    Ok(())
+}
+
+#[test]
+fn async_version() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[derive(StrictEncodeAsync, StrictDecodeAsync)]
+    #[strict_encoding(version = 1)]
+    struct Record {
+        id: u16,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[derive(StrictEncodeAsync, StrictDecodeAsync)]
+    #[strict_encoding(version = 3)]
+    enum Versioned {
+        First(u8),
+        Second,
+    }
+
+    test_async_encoding_roundtrip(&Record { id: 0x0201 }, [0x01, 0x01, 0x02])?;
+    test_async_encoding_roundtrip(&Versioned::First(7), [0x03, 0x00, 0x07])?;
+    test_async_encoding_roundtrip(&Versioned::Second, [0x03, 0x01])?;
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    assert_eq!(
+        rt.block_on(Record::strict_deserialize_async([0x02, 0x01, 0x02])),
+        Err(Error::UnsupportedDataStructure(
+            "`Record` data have a version newer than the supported version 1"
+        ))
+    );
+    assert_eq!(
+        rt.block_on(Versioned::strict_deserialize_async([0x02, 0x01])),
+        Err(Error::UnsupportedDataStructure(
+            "`Versioned` data have a version older than the supported version \
+             3"
+        ))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn async_version_migrate() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[derive(StrictEncodeAsync, StrictDecodeAsync)]
+    struct RecordV0 {
+        id: u16,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[derive(StrictEncodeAsync, StrictDecodeAsync)]
+    #[strict_encoding(version = 1, migrate)]
+    struct Record {
+        id: u16,
+        name: String,
+    }
+
+    impl StrictMigrate for Record {
+        fn strict_migrate<D: io::Read>(
+            version: u8,
+            d: D,
+        ) -> std::result::Result<Self, Error> {
+            match version {
+                0 => Ok(Record {
+                    id: RecordV0::strict_decode(d)?.id,
+                    name: s!("unnamed"),
+                }),
+                _ => Err(Error::UnsupportedDataStructure("unknown version")),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl StrictMigrateAsync for Record {
+        async fn strict_migrate_async<D>(
+            version: u8,
+            d: &mut D,
+        ) -> std::result::Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            match version {
+                0 => Ok(Record {
+                    id: RecordV0::strict_decode_async(d).await?.id,
+                    name: s!("unnamed"),
+                }),
+                _ => Err(Error::UnsupportedDataStructure("unknown version")),
+            }
+        }
+    }
+
+    test_async_encoding_roundtrip(
+        &Record {
+            id: 0x0201,
+            name: s!("a"),
+        },
+        [0x01, 0x01, 0x02, 0x01, 0x00, b'a'],
+    )?;
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    assert_eq!(
+        rt.block_on(Record::strict_deserialize_async([0x00, 0x01, 0x02])),
+        Ok(Record {
+            id: 0x0201,
+            name: s!("unnamed"),
+        })
+    );
+
+    Ok(())
+}
+
+/// Foreign type which does not implement any of the strict encoding traits
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Celsius(i16);
+
+mod celsius {
+    use strict_encoding::{
+        io, AsyncRead, AsyncWrite, Error, StrictDecode, StrictDecodeAsync,
+        StrictEncode, StrictEncodeAsync,
+    };
+
+    use super::Celsius;
+
+    pub fn strict_encode<E: io::Write>(
+        value: &Celsius,
+        e: E,
+    ) -> Result<usize, Error> {
+        value.0.strict_encode(e)
+    }
+
+    pub fn strict_decode<D: io::Read>(d: D) -> Result<Celsius, Error> {
+        i16::strict_decode(d).map(Celsius)
+    }
+
+    pub async fn strict_encode_async<E>(
+        value: &Celsius,
+        e: &mut E,
+    ) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        value.0.strict_encode_async(e).await
+    }
+
+    pub async fn strict_decode_async<D>(d: &mut D) -> Result<Celsius, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        i16::strict_decode_async(d).await.map(Celsius)
+    }
+}
+
+#[test]
+fn async_adapters() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[derive(StrictEncodeAsync, StrictDecodeAsync)]
+    struct Reading {
+        #[strict_encoding(with = "celsius")]
+        temperature: Celsius,
+        #[strict_encoding(as_u32_len)]
+        samples: Vec<u8>,
+        #[strict_encoding(fixed_bytes)]
+        hash: sha256::Hash,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[derive(StrictEncodeAsync, StrictDecodeAsync)]
+    enum Peer {
+        Addr(#[strict_encoding(uniform)] SocketAddr),
+        Reading {
+            #[strict_encoding(with = "celsius")]
+            temperature: Celsius,
+        },
+    }
+
+    let hash = sha256::Hash::hash(b"test");
+    let mut data = vec![0xFB, 0xFF, 0x02, 0x00, 0x00, 0x00, 0x01, 0x02];
+    data.extend(&hash[..]);
+    test_async_encoding_roundtrip(
+        &Reading {
+            temperature: Celsius(-5),
+            samples: vec![1, 2],
+            hash,
+        },
+        data,
+    )?;
+
+    test_async_object_encoding_roundtrip(&Peer::Addr(
+        "127.0.0.1:9735".parse().unwrap(),
+    ))?;
+    test_async_encoding_roundtrip(
+        &Peer::Reading {
+            temperature: Celsius(20),
+        },
+        [0x01, 0x14, 0x00],
+    )?;
+
+    Ok(())
+}
@@ -12,6 +12,8 @@
 // You should have received a copy of the Apache 2.0 License along with this
 // software. If not, see <https://opensource.org/licenses/Apache-2.0>.
 
+// error-pattern: conflicting implementations of trait `StrictEncode` for type `One`
+
 #[macro_use]
 extern crate strict_encoding_derive;
 
@@ -20,3 +22,5 @@ extern crate strict_encoding_derive;
 struct One {
     a: Vec<u8>,
 }
+
+fn main() {}
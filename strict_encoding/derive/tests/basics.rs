@@ -16,7 +16,6 @@
 extern crate amplify;
 #[macro_use]
 extern crate strict_encoding_derive;
-#[macro_use]
 extern crate strict_encoding_test;
 
 mod common;
@@ -26,28 +25,35 @@ use strict_encoding::{StrictDecode, StrictEncode};
 use strict_encoding_test::test_encoding_roundtrip;
 
 #[test]
-#[should_panic]
-fn no_strict_units() { compile_test("basics-failures/no_strict_units"); }
+fn no_strict_unions() { compile_test("basics-failures/no_strict_unions"); }
 
 #[test]
-#[should_panic]
 fn no_networking_unions() {
     compile_test("basics-failures/no_networking_unions");
 }
 
 #[test]
-#[should_panic]
 fn strict_network_exclusivity() {
     compile_test("basics-failures/strict_network_exclusivity");
 }
 
 #[test]
-#[should_panic]
-fn no_unit_types() { compile_test("basics-failures/no_unit_types"); }
+fn unit_types() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug, Default)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct Unit {
+        a: (),
+    }
+    test_encoding_roundtrip(&Unit::default(), []).map_err(Error::from)
+}
 
 #[test]
-#[should_panic]
-fn no_empty_types() { compile_test("basics-failures/no_empty_types"); }
+fn empty_types() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct Empty;
+    test_encoding_roundtrip(&Empty, []).map_err(Error::from)
+}
 
 #[test]
 fn unit_struct() -> Result {
@@ -130,6 +136,7 @@ fn skipping() -> Result {
 }
 
 #[test]
+#[allow(dead_code)]
 fn custom_crate() {
     use strict_encoding as custom_crate;
 
@@ -147,6 +154,7 @@ fn custom_crate() {
 }
 
 #[test]
+#[allow(dead_code)]
 fn generics() {
     #[derive(StrictEncode, StrictDecode)]
     enum CustomErr1<Err>
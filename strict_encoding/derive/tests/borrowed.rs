@@ -0,0 +1,151 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+#[macro_use]
+extern crate amplify;
+#[macro_use]
+extern crate strict_encoding_derive;
+
+mod common;
+
+use common::{compile_test, Result};
+use strict_encoding::{BorrowedIter, StrictDecodeBorrowed, StrictEncode};
+
+#[test]
+fn borrowed_multiple_lifetimes() {
+    compile_test("borrowed-failures/multiple_lifetimes");
+}
+
+#[test]
+fn borrowed_structs() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug, Default)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct Owned {
+        pub id: u32,
+        pub payload: Vec<u8>,
+        pub name: String,
+        pub hash: [u8; 4],
+        #[strict_encoding(skip)]
+        pub ephemeral: bool,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecodeBorrowed)]
+    struct Borrowed<'a> {
+        pub id: u32,
+        pub payload: &'a [u8],
+        pub name: &'a str,
+        pub hash: &'a [u8; 4],
+        #[strict_encoding(skip)]
+        pub ephemeral: bool,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictDecodeBorrowed)]
+    struct Tuple(u8, String);
+
+    let owned = Owned {
+        id: 0x04030201,
+        payload: vec![0xAA, 0xBB],
+        name: s!("abc"),
+        hash: [1, 2, 3, 4],
+        ephemeral: false,
+    };
+    let data = owned.strict_serialize()?;
+    let borrowed = Borrowed::strict_deserialize_borrowed(&data)?;
+    assert_eq!(borrowed, Borrowed {
+        id: 0x04030201,
+        payload: &[0xAA, 0xBB],
+        name: "abc",
+        hash: &[1, 2, 3, 4],
+        ephemeral: false,
+    });
+    assert_eq!(borrowed.payload.as_ptr(), data[6..].as_ptr());
+    assert_eq!(borrowed.strict_serialize()?, data);
+
+    assert_eq!(
+        Tuple::strict_deserialize_borrowed(&[0xFF, 0x01, 0x00, b'a'])?,
+        Tuple(0xFF, s!("a"))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn borrowed_records() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct Record {
+        pub key: String,
+        pub values: Vec<Vec<u8>>,
+    }
+
+    #[derive(Clone, Debug)]
+    #[derive(StrictDecodeBorrowed)]
+    struct RecordView<'a> {
+        pub key: &'a str,
+        pub values: BorrowedIter<'a, &'a [u8]>,
+    }
+
+    let records = vec![
+        Record {
+            key: s!("first"),
+            values: vec![vec![1, 2], vec![]],
+        },
+        Record {
+            key: s!("second"),
+            values: vec![vec![3]],
+        },
+    ];
+    let data = records.strict_serialize()?;
+
+    let view = BorrowedIter::<RecordView>::strict_deserialize_borrowed(&data)?;
+    assert_eq!(view.len(), 2);
+    let keys = view.clone().map(|record| record.key).collect::<Vec<_>>();
+    assert_eq!(keys, vec!["first", "second"]);
+    let values = view.flat_map(|record| record.values).collect::<Vec<_>>();
+    assert_eq!(values, vec![&[1u8, 2][..], &[], &[3]]);
+
+    Ok(())
+}
+
+#[test]
+fn borrowed_enums() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecodeBorrowed)]
+    #[strict_encoding(by_value, repr = u8)]
+    enum Message<'a> {
+        #[strict_encoding(value = 0x10)]
+        Ping,
+        #[strict_encoding(value = 0x11)]
+        Data(&'a [u8]),
+        #[strict_encoding(value = 0x12)]
+        Named { name: &'a str, value: u16 },
+    }
+
+    for msg in [Message::Ping, Message::Data(&[1, 2, 3]), Message::Named {
+        name: "abc",
+        value: 0xFFFF,
+    }] {
+        let data = msg.strict_serialize()?;
+        assert_eq!(Message::strict_deserialize_borrowed(&data)?, msg);
+    }
+
+    assert_eq!(
+        Message::strict_deserialize_borrowed(&[0x13]).unwrap_err(),
+        strict_encoding::Error::EnumValueNotKnown("Message", 0x13)
+    );
+
+    Ok(())
+}
@@ -12,9 +12,13 @@
 // You should have received a copy of the Apache 2.0 License along with this
 // software. If not, see <https://opensource.org/licenses/Apache-2.0>.
 
+// error-pattern: expected `:`
+
 #[macro_use]
 extern crate strict_encoding_derive;
 
-#[derive(Clone, PartialEq, Eq, Debug)]
 #[derive(StrictEncode, StrictDecode)]
-struct Empty;
+#[strict_encoding(bound = "T StrictEncode")]
+struct Wrapper<T>(T);
+
+fn main() {}
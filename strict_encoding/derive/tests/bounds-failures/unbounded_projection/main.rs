@@ -0,0 +1,44 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+// error-pattern: the trait bound `Log<Plain>: StrictEncode` is not satisfied
+
+#[macro_use]
+extern crate strict_encoding_derive;
+
+trait Scheme {
+    type Item;
+}
+
+struct Plain;
+
+impl Scheme for Plain {
+    type Item = Opaque;
+}
+
+struct Opaque;
+
+#[derive(StrictEncode)]
+struct Log<S: Scheme> {
+    items: Vec<S::Item>,
+}
+
+fn main() {
+    // `Opaque` does not implement `StrictEncode`, so the inferred
+    // `S::Item: StrictEncode` bound is not satisfied
+    strict_encoding::StrictEncode::strict_serialize(&Log::<Plain> {
+        items: vec![],
+    })
+    .unwrap();
+}
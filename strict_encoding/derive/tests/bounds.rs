@@ -0,0 +1,260 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+#[macro_use]
+extern crate amplify;
+#[macro_use]
+extern crate strict_encoding_derive;
+extern crate strict_encoding_test;
+
+mod common;
+
+use std::collections::BTreeSet;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use common::{compile_test, Result};
+use strict_encoding::schema::{Primitive, TypeDescr};
+use strict_encoding::{
+    StrictDecode, StrictDecodeBorrowed, StrictEncode, StrictType,
+};
+use strict_encoding_test::test_encoding_roundtrip;
+
+#[test]
+fn bounds_invalid_predicate() {
+    compile_test("bounds-failures/invalid_predicate");
+}
+
+#[test]
+fn bounds_non_string() { compile_test("bounds-failures/non_string"); }
+
+#[test]
+fn bounds_empty_override() { compile_test("bounds-failures/empty_override"); }
+
+#[test]
+fn bounds_insufficient_inferred() {
+    compile_test("bounds-failures/insufficient_inferred");
+}
+
+#[test]
+fn bounds_unbounded_projection() {
+    compile_test("bounds-failures/unbounded_projection");
+}
+
+/// Type which does not implement any of the strict encoding traits
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+struct Opaque;
+
+trait Scheme {
+    type Item: Clone + Eq + Debug;
+    type Meta: Clone + Eq + Debug;
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Plain;
+
+impl Scheme for Plain {
+    type Item = u16;
+    type Meta = Opaque;
+}
+
+#[test]
+fn bounds_type_params() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode, StrictType)]
+    struct Pair<A, B> {
+        first: A,
+        rest: Vec<Option<B>>,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    enum Either<L, R> {
+        Left(L),
+        Right { value: Option<R> },
+    }
+
+    let pair = Pair {
+        first: 1u8,
+        rest: vec![Some(s!("a"))],
+    };
+    test_encoding_roundtrip(&pair, [0x01, 0x01, 0x00, 0x01, 0x01, 0x00, b'a'])?;
+    assert!(matches!(
+        Pair::<u8, String>::strict_type(),
+        TypeDescr::Struct(descr) if descr.fields[0].ty
+            == TypeDescr::Primitive(Primitive::U8)
+    ));
+
+    test_encoding_roundtrip(&Either::<u8, u16>::Left(5), [0x00, 0x05])?;
+    test_encoding_roundtrip(&Either::<u8, u16>::Right { value: Some(5) }, [
+        0x01, 0x01, 0x05, 0x00,
+    ])?;
+    Ok(())
+}
+
+#[test]
+fn bounds_projections() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode, StrictType)]
+    struct Log<S: Scheme> {
+        items: Vec<S::Item>,
+        last: Option<<S as Scheme>::Item>,
+        #[strict_encoding(skip)]
+        meta: Option<S::Meta>,
+    }
+
+    let log = Log::<Plain> {
+        items: vec![1, 2],
+        last: Some(3),
+        meta: None,
+    };
+    test_encoding_roundtrip(&log, [
+        0x02, 0x00, 0x01, 0x00, 0x02, 0x00, 0x01, 0x03, 0x00,
+    ])?;
+    assert!(matches!(Log::<Plain>::strict_type(), TypeDescr::Struct(_)));
+    Ok(())
+}
+
+#[test]
+fn bounds_skipped() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct Tagged<T, U> {
+        id: u8,
+        #[strict_encoding(skip)]
+        cache: Option<T>,
+        #[strict_encoding(skip)]
+        _phantom: PhantomData<U>,
+    }
+
+    test_encoding_roundtrip(
+        &Tagged::<Opaque, Opaque> {
+            id: 7,
+            cache: None,
+            _phantom: PhantomData,
+        },
+        [0x07],
+    )?;
+    Ok(())
+}
+
+#[test]
+fn bounds_explicit() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[strict_encoding(bound = "T: StrictEncode + StrictDecode + Ord + Debug")]
+    struct Set<T> {
+        items: BTreeSet<T>,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct Mixed<T, K> {
+        id: T,
+        #[strict_encoding(
+            bound = "K: StrictEncode + StrictDecode + Ord + Debug"
+        )]
+        keys: BTreeSet<K>,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    enum Message<K> {
+        Ping,
+        #[strict_encoding(
+            bound = "K: StrictEncode + StrictDecode + Ord + Debug"
+        )]
+        Keys(BTreeSet<K>, u8),
+    }
+
+    let set = Set {
+        items: bset! { 2u8, 1 },
+    };
+    test_encoding_roundtrip(&set, [0x02, 0x00, 0x01, 0x02])?;
+
+    let mixed = Mixed {
+        id: 1u16,
+        keys: bset! { 3u8 },
+    };
+    test_encoding_roundtrip(&mixed, [0x01, 0x00, 0x01, 0x00, 0x03])?;
+
+    test_encoding_roundtrip(&Message::Keys(bset! { 5u8 }, 6), [
+        0x01, 0x01, 0x00, 0x05, 0x06,
+    ])?;
+    test_encoding_roundtrip(&Message::<u8>::Ping, [0x00])?;
+    Ok(())
+}
+
+#[test]
+fn bounds_user_where_clause() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct Ordered<T>
+    where
+        T: Ord + Debug,
+    {
+        items: BTreeSet<T>,
+    }
+
+    test_encoding_roundtrip(
+        &Ordered {
+            items: bset! { 1u8 },
+        },
+        [0x01, 0x00, 0x01],
+    )?;
+    Ok(())
+}
+
+#[test]
+fn bounds_network() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(NetworkEncode, NetworkDecode)]
+    #[network_encoding(use_tlv)]
+    struct Envelope<T> {
+        data: T,
+        #[network_encoding(tlv = 1)]
+        extra: Option<T>,
+    }
+
+    test_encoding_roundtrip(
+        &Envelope {
+            data: 1u8,
+            extra: None,
+        },
+        [0x01],
+    )?;
+    Ok(())
+}
+
+#[test]
+fn bounds_borrowed() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictDecodeBorrowed)]
+    struct View<'a, T> {
+        data: &'a [u8],
+        value: T,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictDecodeBorrowed)]
+    struct Owned<T>(T);
+
+    let data = [0x01, 0x00, 0xAA, 0x02];
+    assert_eq!(View::<u8>::strict_deserialize_borrowed(&data)?, View {
+        data: &[0xAA],
+        value: 2,
+    });
+    assert_eq!(Owned::<u8>::strict_deserialize_borrowed(&[0x05])?, Owned(5));
+    Ok(())
+}
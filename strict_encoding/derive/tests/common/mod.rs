@@ -15,16 +15,38 @@
 extern crate compiletest_rs as compiletest;
 
 use std::fmt::{Debug, Display, Formatter};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
 
 use strict_encoding::{StrictDecode, StrictEncode};
+use strict_encoding_test::proptest::test_runner::TestError;
 use strict_encoding_test::DataEncodingTestFailure;
 
+/// Compiles test case from `tests/<suite>-failures/<case>` directory, which
+/// must fail with an error matching `// error-pattern:` comment of the case.
 #[allow(dead_code)]
-pub fn compile_test(mode: &'static str) {
+pub fn compile_test(case: &'static str) {
+    let deps = env::current_exe()
+        .expect("test executable path")
+        .parent()
+        .expect("test executable is in the dependencies directory")
+        .to_owned();
+    let derive_lib =
+        format!("{}strict_encoding_derive", env::consts::DLL_PREFIX);
+    let flags = format!(
+        "--edition 2021 --extern strict_encoding_derive={} --extern \
+         strict_encoding={}",
+        latest_artifact(&deps, &derive_lib, env::consts::DLL_EXTENSION)
+            .display(),
+        latest_artifact(&deps, "libstrict_encoding", "rlib").display(),
+    );
     let mut config = compiletest::Config {
-        mode: mode.parse().expect("Invalid mode"),
-        src_base: PathBuf::from(format!("tests/{}", mode)),
+        mode: "compile-fail".parse().expect("Invalid mode"),
+        src_base: PathBuf::from(format!("tests/{}", case)),
+        build_base: PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+            .join("compile-fail")
+            .join(case),
+        target_rustcflags: Some(flags),
         ..default!()
     };
     config.link_deps();
@@ -32,6 +54,31 @@ pub fn compile_test(mode: &'static str) {
     compiletest::run_tests(&config);
 }
 
+/// Finds the most recently built artifact of a crate, since the dependencies
+/// directory may contain artifacts for several builds of the same crate,
+/// making linking with `-L` ambiguous.
+fn latest_artifact(deps: &Path, name: &str, ext: &str) -> PathBuf {
+    let prefix = format!("{}-", name);
+    fs::read_dir(deps)
+        .expect("dependencies directory")
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension().map(|e| e == ext).unwrap_or_default()
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .map(|name| name.starts_with(&prefix))
+                    .unwrap_or_default()
+        })
+        .max_by_key(|path| {
+            path.metadata()
+                .and_then(|meta| meta.modified())
+                .expect("artifact modification time")
+        })
+        .unwrap_or_else(|| panic!("no {} artifact in {}", name, deps.display()))
+}
+
 #[derive(Display)]
 #[display(inner)]
 pub struct Error(pub Box<dyn std::error::Error>);
@@ -60,3 +107,10 @@ impl From<strict_encoding::Error> for Error {
 }
 
 pub type Result = std::result::Result<(), Error>;
+
+impl<T> From<TestError<T>> for Error
+where
+    T: Debug + 'static,
+{
+    fn from(err: TestError<T>) -> Self { Self(Box::new(err)) }
+}
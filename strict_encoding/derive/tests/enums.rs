@@ -15,6 +15,7 @@
 #[macro_use]
 extern crate amplify;
 #[macro_use]
+extern crate strict_encoding_derive;
 extern crate strict_encoding_test;
 
 mod common;
@@ -172,3 +173,54 @@ fn enum_custom_values() -> Result {
 
     Ok(())
 }
+
+#[test]
+fn enum_encoded_len() -> Result {
+    use strict_encoding::StrictEncode;
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[strict_encoding(by_order, repr = u16)]
+    enum Unit {
+        A,
+        B,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[strict_encoding(by_order)]
+    enum SameSize {
+        Key([u8; 32]),
+        Index(u16, u16, [u8; 28]),
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[strict_encoding(by_value, repr = u8)]
+    enum Variable {
+        #[strict_encoding(value = 0x10)]
+        Empty,
+        #[strict_encoding(value = 0x20)]
+        Number(u64),
+        #[strict_encoding(value = 0x30)]
+        Named { name: String, unit: Unit },
+    }
+
+    assert_eq!(Unit::FIXED_SIZE, Some(2));
+    assert_eq!(SameSize::FIXED_SIZE, Some(33));
+    assert_eq!(Variable::FIXED_SIZE, None);
+
+    assert_eq!(SameSize::Index(1, 2, [0; 28]).strict_encoded_len()?, 33);
+    assert_eq!(Variable::Empty.strict_encoded_len()?, 1);
+    assert_eq!(Variable::Number(5).strict_encoded_len()?, 9);
+    let named = Variable::Named {
+        name: s!("abc"),
+        unit: Unit::B,
+    };
+    assert_eq!(named.strict_encoded_len()?, 1 + 5 + 2);
+    test_encoding_roundtrip(&named, [
+        0x30, 0x03, 0x00, b'a', b'b', b'c', 0x01, 0x00,
+    ])?;
+
+    Ok(())
+}
@@ -0,0 +1,300 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+#[macro_use]
+extern crate amplify;
+#[macro_use]
+extern crate strict_encoding_test;
+
+mod common;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
+use std::ops::{Range, RangeInclusive, RangeToInclusive};
+use std::time::Duration;
+
+use amplify::flags::FlagVec;
+use amplify::num::{u24, u256};
+use bitcoin::secp256k1::{ecdsa, schnorr, KeyPair, XOnlyPublicKey};
+use bitcoin::util::bip32;
+use bitcoin::util::taproot::{ControlBlock, FutureLeafVersion, LeafVersion};
+use bitcoin::{
+    Address, Amount, EcdsaSig, Network, OutPoint, SchnorrSig, Script,
+    Transaction, TxIn, TxOut, Txid, Witness,
+};
+use bitcoin_hashes::{hash160, sha256, sha256d, sha512};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use common::Result;
+use strict_encoding::net::{DnsName, OnionAddrV3, UniformAddr};
+use strict_encoding::{
+    io, BigSize, ConfinedSet, ConfinedString, ConfinedVec, Error, LargeMap,
+    LargeSet, LargeVec, MediumMap, MediumSet, MediumString, MediumVec,
+    StrictDecode, StrictEncode, TlvStream,
+};
+use strict_encoding_test::arbitrary::{
+    check_strict_roundtrip_with, StrictArbitrary,
+};
+use strict_encoding_test::proptest::prelude::*;
+use strict_encoding_test::proptest::test_runner::{Config, TestError};
+use strict_encoding_test::{test_truncated_decoding, DataEncodingTestFailure};
+
+#[test]
+fn proptest_primitives() -> Result {
+    check_strict_roundtrip!(bool)?;
+    check_strict_roundtrip!(u8)?;
+    check_strict_roundtrip!(i16)?;
+    check_strict_roundtrip!(u24)?;
+    check_strict_roundtrip!(u64)?;
+    check_strict_roundtrip!(i128)?;
+    check_strict_roundtrip!(usize)?;
+    check_strict_roundtrip!(f32)?;
+    check_strict_roundtrip!(f64)?;
+    check_strict_roundtrip!(u256)?;
+    check_strict_roundtrip!(Duration)?;
+    check_strict_roundtrip!([u8; 32])?;
+    check_strict_roundtrip!(FlagVec)?;
+    Ok(())
+}
+
+#[test]
+fn proptest_hashes() -> Result {
+    check_strict_roundtrip!(sha256::Hash)?;
+    check_strict_roundtrip!(sha256d::Hash)?;
+    check_strict_roundtrip!(sha512::Hash)?;
+    check_strict_roundtrip!(hash160::Hash)?;
+    Ok(())
+}
+
+#[test]
+fn proptest_collections() -> Result {
+    check_strict_roundtrip!(String)?;
+    check_strict_roundtrip!(Box<[u8]>)?;
+    check_strict_roundtrip!(Option<Vec<u16>>)?;
+    check_strict_roundtrip!(BTreeSet<String>)?;
+    check_strict_roundtrip!(BTreeMap<u8, Vec<Option<bool>>>)?;
+    check_strict_roundtrip!((u8, String))?;
+    check_strict_roundtrip!(MediumVec<u32>)?;
+    check_strict_roundtrip!(LargeVec<Vec<u8>>, cases = 32)?;
+    check_strict_roundtrip!(BigSize)?;
+    check_strict_roundtrip!(HashSet<u16>)?;
+    check_strict_roundtrip!(HashMap<usize, String>)?;
+    check_strict_roundtrip!(Range<u8>)?;
+    check_strict_roundtrip!(RangeInclusive<i32>)?;
+    check_strict_roundtrip!(RangeToInclusive<u64>)?;
+    Ok(())
+}
+
+#[test]
+fn proptest_confined() -> Result {
+    check_strict_roundtrip!(ConfinedVec<u8, 1, 3>)?;
+    check_strict_roundtrip!(ConfinedString<2, 4>)?;
+    check_strict_roundtrip!(ConfinedSet<u16, 4, 0x100>)?;
+    check_strict_roundtrip!(MediumString)?;
+    check_strict_roundtrip!(MediumSet<u32>)?;
+    check_strict_roundtrip!(LargeSet<Vec<u8>>, cases = 32)?;
+    check_strict_roundtrip!(MediumMap<u8, String>)?;
+    check_strict_roundtrip!(LargeMap<u16, Option<u8>>)?;
+    Ok(())
+}
+
+#[test]
+fn proptest_net() -> Result {
+    check_strict_roundtrip!(IpAddr)?;
+    check_strict_roundtrip!(SocketAddr)?;
+    check_strict_roundtrip!(OnionAddrV3)?;
+    check_strict_roundtrip!(UniformAddr)?;
+    check_strict_roundtrip!(DnsName)?;
+    Ok(())
+}
+
+#[test]
+fn proptest_chrono() -> Result {
+    check_strict_roundtrip!(NaiveDateTime)?;
+    check_strict_roundtrip!(DateTime<Utc>)?;
+    Ok(())
+}
+
+#[test]
+fn proptest_bitcoin() -> Result {
+    check_strict_roundtrip!(Txid)?;
+    check_strict_roundtrip!(Amount)?;
+    check_strict_roundtrip!(Network)?;
+    check_strict_roundtrip!(Script)?;
+    check_strict_roundtrip!(Witness)?;
+    check_strict_roundtrip!(OutPoint)?;
+    check_strict_roundtrip!(TxIn)?;
+    check_strict_roundtrip!(TxOut)?;
+    check_strict_roundtrip!(Transaction, cases = 64)?;
+    check_strict_roundtrip!(Address)?;
+    Ok(())
+}
+
+#[test]
+fn proptest_bitcoin_keys() -> Result {
+    check_strict_roundtrip!(KeyPair, cases = 64)?;
+    check_strict_roundtrip!(bitcoin::PublicKey, cases = 64)?;
+    check_strict_roundtrip!(XOnlyPublicKey, cases = 64)?;
+    check_strict_roundtrip!(ecdsa::Signature, cases = 64)?;
+    check_strict_roundtrip!(schnorr::Signature, cases = 64)?;
+    check_strict_roundtrip!(EcdsaSig, cases = 64)?;
+    check_strict_roundtrip!(SchnorrSig, cases = 64)?;
+    check_strict_roundtrip!(bip32::DerivationPath)?;
+    check_strict_roundtrip!(bip32::ExtendedPrivKey, cases = 32)?;
+    check_strict_roundtrip!(bip32::ExtendedPubKey, cases = 32)?;
+    Ok(())
+}
+
+#[test]
+fn proptest_taproot() -> Result {
+    check_strict_roundtrip!(LeafVersion)?;
+    check_strict_roundtrip!(FutureLeafVersion)?;
+    check_strict_roundtrip!(ControlBlock, cases = 64)?;
+    Ok(())
+}
+
+#[test]
+fn proptest_derived() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+    struct Unit;
+    strict_arbitrary!(struct Unit);
+
+    #[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+    struct Tuple(u8, Vec<u16>);
+    strict_arbitrary!(struct Tuple(first, second));
+
+    #[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+    struct Named {
+        tuple: Tuple,
+        map: BTreeMap<u8, String>,
+        addr: Option<IpAddr>,
+    }
+    strict_arbitrary!(struct Named { tuple, map, addr });
+
+    #[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+    enum Sum {
+        One,
+        Two(Unit, u64),
+        Three { named: Named },
+    }
+    strict_arbitrary!(enum Sum {
+        One,
+        Two(unit, value),
+        Three { named },
+    });
+
+    check_strict_roundtrip!(Unit, cases = 1)?;
+    check_strict_roundtrip!(Tuple)?;
+    check_strict_roundtrip!(Named)?;
+    check_strict_roundtrip!(Sum)?;
+    check_strict_roundtrip!(Vec<Sum>, cases = 32)?;
+    Ok(())
+}
+
+#[test]
+fn proptest_custom_strategy() -> Result {
+    check_strict_roundtrip!(
+        strategy = (1..=1024u16).prop_map(|len| vec![0xA5u8; len as usize]),
+        cases = 16,
+    )?;
+    check_strict_roundtrip!(
+        strategy = Vec::<u8>::strict_strategy()
+            .prop_map(|data| String::from_utf8_lossy(&data).to_string())
+    )?;
+    Ok(())
+}
+
+#[test]
+fn proptest_failures() {
+    // Expected failures should not be persisted as regressions
+    let config = || Config {
+        failure_persistence: None,
+        ..Config::default()
+    };
+
+    /// Type with decoder panicking on truncated data
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    struct Panicking(u16);
+
+    impl StrictEncode for Panicking {
+        fn strict_encode<E: io::Write>(
+            &self,
+            e: E,
+        ) -> std::result::Result<usize, Error> {
+            self.0.strict_encode(e)
+        }
+    }
+
+    impl StrictDecode for Panicking {
+        fn strict_decode<D: io::Read>(
+            d: D,
+        ) -> std::result::Result<Self, Error> {
+            Ok(Panicking(u16::strict_decode(d).expect("truncated data")))
+        }
+    }
+
+    /// Type with decoder accepting truncated data
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    struct Lenient(Vec<u8>);
+
+    impl StrictEncode for Lenient {
+        fn strict_encode<E: io::Write>(
+            &self,
+            mut e: E,
+        ) -> std::result::Result<usize, Error> {
+            e.write_all(&self.0)?;
+            Ok(self.0.len())
+        }
+    }
+
+    impl StrictDecode for Lenient {
+        fn strict_decode<D: io::Read>(
+            mut d: D,
+        ) -> std::result::Result<Self, Error> {
+            let mut data = vec![];
+            d.read_to_end(&mut data)?;
+            Ok(Lenient(data))
+        }
+    }
+
+    let err = test_truncated_decoding(&Panicking(0x0102)).unwrap_err();
+    assert_eq!(err, DataEncodingTestFailure::DecoderPanicked(vec![]));
+    assert!(matches!(
+        check_strict_roundtrip_with(any::<u16>().prop_map(Panicking), config()),
+        Err(TestError::Fail(_, Panicking(_)))
+    ));
+
+    assert_eq!(
+        test_truncated_decoding(&Lenient(vec![1, 2])).unwrap_err(),
+        DataEncodingTestFailure::TruncatedDataDecoded {
+            truncated: vec![],
+            decoded: Lenient(vec![]),
+        }
+    );
+    match check_strict_roundtrip_with(
+        Vec::<u8>::strict_strategy().prop_map(Lenient),
+        config(),
+    ) {
+        // Shrinking finds the minimal failing value
+        Err(TestError::Fail(_, value)) => assert_eq!(value, Lenient(vec![0])),
+        res => panic!("unexpected test result {:?}", res),
+    }
+
+    // TLV streams are decoded from truncated data by design
+    let stream = TlvStream::strict_strategy()
+        .prop_filter("non-empty stream", |stream| !stream.is_empty());
+    assert!(matches!(
+        check_strict_roundtrip_with(stream, config()),
+        Err(TestError::Fail(_, _))
+    ));
+}
@@ -0,0 +1,238 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+#[macro_use]
+extern crate amplify;
+#[macro_use]
+extern crate strict_encoding_derive;
+
+use std::collections::BTreeMap;
+
+use strict_encoding::schema::{
+    EnumDescr, FieldDescr, LenPrefix, Primitive, StructDescr, TlvDescr,
+    TypeDescr, VariantDescr,
+};
+use strict_encoding::{LargeVec, MediumVec, StrictType, TlvStream};
+
+fn prim(primitive: Primitive) -> TypeDescr { TypeDescr::Primitive(primitive) }
+
+#[test]
+fn struct_schema() {
+    #[allow(dead_code)]
+    #[derive(StrictType)]
+    struct Data {
+        id: u16,
+        #[strict_encoding(skip)]
+        cache: Option<Vec<u8>>,
+        name: String,
+        items: MediumVec<u32>,
+        blob: LargeVec<u8>,
+        digest: [u8; 32],
+    }
+
+    assert_eq!(
+        Data::strict_type(),
+        TypeDescr::Struct(StructDescr {
+            name: s!("Data"),
+            fields: vec![
+                FieldDescr::named("id", prim(Primitive::U16)),
+                FieldDescr::named("name", TypeDescr::Unicode(LenPrefix::U16)),
+                FieldDescr::named(
+                    "items",
+                    TypeDescr::list(LenPrefix::U24, prim(Primitive::U32))
+                ),
+                FieldDescr::named(
+                    "blob",
+                    TypeDescr::list(LenPrefix::U32, prim(Primitive::U8))
+                ),
+                FieldDescr::named("digest", TypeDescr::Bytes(32)),
+            ],
+            tlvs: vec![],
+            unknown_tlvs: false,
+        })
+    );
+}
+
+#[test]
+fn tuple_struct_schema() {
+    #[allow(dead_code)]
+    #[derive(StrictType)]
+    struct Pair(u8, Option<i64>);
+
+    #[derive(StrictType)]
+    struct Unit;
+
+    assert_eq!(
+        Pair::strict_type(),
+        TypeDescr::Struct(StructDescr {
+            name: s!("Pair"),
+            fields: vec![
+                FieldDescr::unnamed(prim(Primitive::U8)),
+                FieldDescr::unnamed(TypeDescr::option(prim(Primitive::I64))),
+            ],
+            tlvs: vec![],
+            unknown_tlvs: false,
+        })
+    );
+    assert_eq!(
+        Unit::strict_type(),
+        TypeDescr::named_struct("Unit", None::<(&'static str, TypeDescr)>)
+    );
+}
+
+#[test]
+fn enum_schema() {
+    #[allow(dead_code)]
+    #[derive(StrictType)]
+    #[strict_encoding(repr = u16)]
+    enum Message {
+        Ping,
+        Data(Vec<u8>),
+        #[strict_encoding(value = 0x10)]
+        Named {
+            key: u8,
+            value: BTreeMap<u8, bool>,
+        },
+    }
+
+    assert_eq!(
+        Message::strict_type(),
+        TypeDescr::Enum(EnumDescr {
+            name: s!("Message"),
+            repr: Primitive::U16,
+            by_order: true,
+            variants: vec![
+                VariantDescr {
+                    name: s!("Ping"),
+                    value: 0,
+                    fields: vec![],
+                },
+                VariantDescr {
+                    name: s!("Data"),
+                    value: 1,
+                    fields: vec![FieldDescr::unnamed(TypeDescr::list(
+                        LenPrefix::U16,
+                        prim(Primitive::U8)
+                    ))],
+                },
+                VariantDescr {
+                    name: s!("Named"),
+                    value: 0x10,
+                    fields: vec![
+                        FieldDescr::named("key", prim(Primitive::U8)),
+                        FieldDescr::named(
+                            "value",
+                            TypeDescr::map(
+                                LenPrefix::U16,
+                                prim(Primitive::U8),
+                                prim(Primitive::Bool)
+                            )
+                        ),
+                    ],
+                },
+            ],
+        })
+    );
+}
+
+#[test]
+fn enum_by_value_schema() {
+    #[allow(dead_code)]
+    #[derive(Clone, Copy, StrictType)]
+    #[strict_encoding(by_value, repr = u8)]
+    #[repr(u8)]
+    enum Flags {
+        Bit8 = 1,
+        Bit16 = 2,
+        #[strict_encoding(skip)]
+        Bit32 = 4,
+        Bit64 = 8,
+    }
+
+    let descr = match Flags::strict_type() {
+        TypeDescr::Enum(descr) => descr,
+        _ => panic!("enum must be described as enum"),
+    };
+    assert!(!descr.by_order);
+    assert_eq!(descr.repr, Primitive::U8);
+    assert_eq!(
+        descr
+            .variants
+            .iter()
+            .map(|variant| (variant.name.as_str(), variant.value))
+            .collect::<Vec<_>>(),
+        vec![("Bit8", 1), ("Bit16", 2), ("Bit64", 8)]
+    );
+}
+
+#[test]
+fn nested_schema() {
+    #[allow(dead_code)]
+    #[derive(StrictType)]
+    struct Inner(u8);
+
+    #[allow(dead_code)]
+    #[derive(StrictType)]
+    struct Outer {
+        inner: Vec<Inner>,
+    }
+
+    assert_eq!(
+        Outer::strict_type(),
+        TypeDescr::named_struct("Outer", [(
+            "inner",
+            TypeDescr::list(LenPrefix::U16, Inner::strict_type())
+        )])
+    );
+}
+
+#[test]
+fn tlv_schema() {
+    #[allow(dead_code)]
+    #[derive(NetworkType)]
+    #[network_encoding(use_tlv)]
+    struct Tlv {
+        fixed: u8,
+
+        #[network_encoding(tlv = 0xCAFE)]
+        second: Option<u32>,
+
+        #[network_encoding(tlv = 0xBAD)]
+        first: Vec<u8>,
+
+        #[network_encoding(unknown_tlvs)]
+        rest: TlvStream,
+    }
+
+    assert_eq!(
+        Tlv::strict_type(),
+        TypeDescr::Struct(StructDescr {
+            name: s!("Tlv"),
+            fields: vec![FieldDescr::named("fixed", prim(Primitive::U8))],
+            tlvs: vec![
+                TlvDescr {
+                    id: 0xBAD,
+                    name: Some(s!("first")),
+                    ty: TypeDescr::list(LenPrefix::U16, prim(Primitive::U8)),
+                },
+                TlvDescr {
+                    id: 0xCAFE,
+                    name: Some(s!("second")),
+                    ty: prim(Primitive::U32),
+                },
+            ],
+            unknown_tlvs: true,
+        })
+    );
+}
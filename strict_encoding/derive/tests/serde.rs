@@ -0,0 +1,267 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Parity of strict encoding derivation with `strict_encoding::serde`
+//! bridge applied to the same types.
+
+#[macro_use]
+extern crate amplify;
+
+mod common;
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use common::Result;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use strict_encoding::{PathSegment, StrictDecode, StrictEncode};
+use strict_encoding_test::test_encoding_roundtrip;
+
+fn test_serde_parity<T>(value: &T, data: impl AsRef<[u8]>) -> Result
+where
+    T: StrictEncode
+        + StrictDecode
+        + Serialize
+        + DeserializeOwned
+        + Clone
+        + PartialEq
+        + Debug
+        + 'static,
+{
+    test_encoding_roundtrip(value, &data)?;
+    assert_eq!(strict_encoding::serde::to_vec(value)?, data.as_ref());
+    assert_eq!(&strict_encoding::serde::from_slice::<T>(&data)?, value);
+    Ok(())
+}
+
+#[test]
+fn serde_struct_numbered_fields() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct NumberedFields(u8, String);
+
+    let fields = NumberedFields(7, s!("some"));
+    test_serde_parity(&fields, [0x07, 0x04, 0x00, b's', b'o', b'm', b'e'])
+}
+
+#[test]
+fn serde_struct_named_fields() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct Fixed {
+        id: u32,
+        hash: [u8; 32],
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct Variable(Fixed, Vec<Fixed>, String);
+
+    #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct Complex {
+        flag: bool,
+        amount: Option<u64>,
+        missing: Option<i16>,
+        map: BTreeMap<u8, String>,
+        heap: Box<[u8]>,
+        nested: Variable,
+    }
+
+    let fixed = Fixed {
+        id: 1,
+        hash: [0xAA; 32],
+    };
+    let variable = Variable(fixed.clone(), vec![fixed], s!("v"));
+    let complex = Complex {
+        flag: true,
+        amount: Some(0x0102),
+        missing: None,
+        map: bmap! { 1 => s!("a"), 2 => s!("bc") },
+        heap: Box::from([0xA1, 0xA2]),
+        nested: variable,
+    };
+    let data = strict_encoding::strict_serialize(&complex)?;
+    assert_eq!(&data[..14], [
+        0x01, 0x01, 0x02, 0x01, 0, 0, 0, 0, 0, 0, 0x00, 0x02, 0x00, 0x01,
+    ]);
+    test_serde_parity(&complex, data)
+}
+
+#[test]
+fn serde_enum_associated_types() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct Heap(Box<[u8]>);
+
+    #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+    #[derive(StrictEncode, StrictDecode)]
+    enum Hi {
+        First(u8),
+        Second(Heap),
+        Third,
+        Fourth { heap: Heap },
+        Fifth(u16, bool),
+    }
+
+    let heap = Heap(Box::from([0xA1, 0xA2]));
+    test_serde_parity(&Hi::First(0xC8), [0x00, 0xC8])?;
+    test_serde_parity(&Hi::Second(heap.clone()), [
+        0x01, 0x02, 0x00, 0xA1, 0xA2,
+    ])?;
+    test_serde_parity(&Hi::Third, [0x02])?;
+    test_serde_parity(&Hi::Fourth { heap }, [0x03, 0x02, 0x00, 0xA1, 0xA2])?;
+    test_serde_parity(&Hi::Fifth(0x0201, true), [0x04, 0x01, 0x02, 0x01])?;
+
+    assert_eq!(
+        strict_encoding::serde::from_slice::<Hi>([0x09]).unwrap_err(),
+        Hi::strict_deserialize([0x09]).unwrap_err()
+    );
+    Ok(())
+}
+
+#[test]
+fn serde_enum_default_values() -> Result {
+    #[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[repr(u16)]
+    enum ContractType {
+        Bit8 = 1,
+        Bit16 = 2,
+        Bit32 = 4,
+        Bit64 = 8,
+    }
+
+    test_serde_parity(&ContractType::Bit8, [0x00])?;
+    test_serde_parity(&ContractType::Bit16, [0x01])?;
+    test_serde_parity(&ContractType::Bit32, [0x02])?;
+    test_serde_parity(&ContractType::Bit64, [0x03])
+}
+
+#[test]
+fn serde_error_context() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+    #[derive(StrictEncode, StrictDecode)]
+    enum Assignment {
+        Void,
+        Confidential { revealed: bool },
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct Transition {
+        id: u16,
+        assignments: Vec<Assignment>,
+    }
+
+    let transition = Transition {
+        id: 1,
+        assignments: vec![Assignment::Void, Assignment::Confidential {
+            revealed: true,
+        }],
+    };
+    test_serde_parity(&transition, [0x01, 0x00, 0x02, 0x00, 0x00, 0x01, 0x01])?;
+
+    // Errors produced by the serde bridge have the same context as the ones
+    // from derived decoders
+    let data = [0x01, 0x00, 0x02, 0x00, 0x00, 0x01, 0x05];
+    let err =
+        strict_encoding::serde::from_slice::<Transition>(data).unwrap_err();
+    assert_eq!(err, Transition::strict_deserialize(data).unwrap_err());
+    let context = err.context().expect("error context");
+    assert_eq!(context.type_name, Some("Transition"));
+    assert_eq!(context.path, vec![
+        PathSegment::Field("assignments"),
+        PathSegment::Index(1),
+        PathSegment::Variant("Confidential"),
+        PathSegment::Field("revealed"),
+    ]);
+    assert_eq!(context.offset, Some(7));
+    assert_eq!(
+        err.root_cause(),
+        &strict_encoding::Error::ValueOutOfRange("boolean", 0..1, 5)
+    );
+
+    let data = [0x01, 0x00, 0x01, 0x00, 0x02];
+    assert_eq!(
+        strict_encoding::serde::from_slice::<Transition>(data)
+            .unwrap_err()
+            .root_cause(),
+        Transition::strict_deserialize(data)
+            .unwrap_err()
+            .root_cause()
+    );
+    Ok(())
+}
+
+#[test]
+fn serde_with_strict_adapter() -> Result {
+    /// Type which is strict-encodable, but does not implement serde traits
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct StrictOnly {
+        value: u32,
+        tail: Vec<u8>,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct Mixed {
+        prefix: u8,
+        #[serde(with = "strict_encoding::serde::as_strict")]
+        inner: StrictOnly,
+        suffix: String,
+    }
+
+    let mixed = Mixed {
+        prefix: 0xFF,
+        inner: StrictOnly {
+            value: 0x04030201,
+            tail: vec![0xA1],
+        },
+        suffix: s!("s"),
+    };
+    test_serde_parity(&mixed, [
+        0xFF, 0x01, 0x02, 0x03, 0x04, 0x01, 0x00, 0xA1, 0x01, 0x00, b's',
+    ])
+}
+
+#[test]
+fn serde_usize_divergence() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct WithUsize {
+        len: usize,
+        #[serde(with = "strict_encoding::serde::as_strict")]
+        strict_len: usize,
+    }
+
+    let value = WithUsize {
+        len: 0x0201,
+        strict_len: 0x0201,
+    };
+    let strict = [0x01, 0x02, 0x01, 0x02];
+    test_encoding_roundtrip(&value, strict)?;
+
+    // Serde encodes `usize` as `u64`, unless strict adapter is used
+    let serde = [0x01, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x02];
+    assert_eq!(strict_encoding::serde::to_vec(&value)?, serde);
+    assert_eq!(
+        strict_encoding::serde::from_slice::<WithUsize>(&serde)?,
+        value
+    );
+    assert!(strict_encoding::serde::from_slice::<WithUsize>(&strict).is_err());
+    Ok(())
+}
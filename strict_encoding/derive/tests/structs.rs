@@ -15,7 +15,7 @@
 #[macro_use]
 extern crate amplify;
 #[macro_use]
-extern crate strict_encoding_test;
+extern crate strict_encoding_derive;
 
 mod common;
 
@@ -35,3 +35,100 @@ fn struct_numbered_fields() -> Result {
 
     Ok(())
 }
+
+#[test]
+fn struct_encoded_len() -> Result {
+    use strict_encoding::StrictEncode;
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct Fixed {
+        id: u32,
+        hash: [u8; 32],
+        #[strict_encoding(skip)]
+        cache: Vec<u8>,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct Variable(Fixed, Vec<Fixed>, String);
+
+    assert_eq!(Fixed::FIXED_SIZE, Some(36));
+    assert_eq!(Variable::FIXED_SIZE, None);
+
+    let fixed = Fixed {
+        id: 1,
+        hash: [0xAA; 32],
+        cache: vec![],
+    };
+    assert_eq!(fixed.strict_encoded_len()?, 36);
+    let variable = Variable(fixed.clone(), vec![fixed; 3], s!("data"));
+    assert_eq!(variable.strict_encoded_len()?, 36 + 2 + 3 * 36 + 2 + 4);
+    assert_eq!(
+        variable.strict_encoded_len()?,
+        variable.strict_serialize()?.len()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn struct_error_context() -> Result {
+    use strict_encoding::{Error, PathSegment, StrictDecode};
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    enum Assignment {
+        Void,
+        Confidential { revealed: bool },
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct Transition {
+        id: u16,
+        assignments: Vec<Assignment>,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct Consignment {
+        version: u8,
+        transitions: Vec<Transition>,
+    }
+
+    let data = [
+        0x01, 0x02, 0x00, // version and number of transitions
+        0x01, 0x00, 0x01, 0x00, 0x00, // first transition
+        0x02, 0x00, 0x02, 0x00, 0x00, 0x01, 0x05, // second transition
+    ];
+    let err = Consignment::strict_deserialize(data).unwrap_err();
+    let context = err.context().expect("derived decoders provide context");
+    assert_eq!(context.type_name, Some("Consignment"));
+    assert_eq!(context.path, vec![
+        PathSegment::Field("transitions"),
+        PathSegment::Index(1),
+        PathSegment::Field("assignments"),
+        PathSegment::Index(1),
+        PathSegment::Variant("Confidential"),
+        PathSegment::Field("revealed"),
+    ]);
+    assert_eq!(context.offset, Some(15));
+    assert_eq!(
+        err.root_cause(),
+        &Error::ValueOutOfRange("boolean", 0..1, 5)
+    );
+    assert!(err.to_string().starts_with(
+        "decoding of \
+         Consignment.transitions[1].assignments[1]::Confidential.revealed at \
+         byte offset 15 failed: "
+    ));
+
+    // Errors outside of derived types are left intact
+    assert_eq!(
+        Vec::<bool>::strict_deserialize([0x01, 0x00, 0x02]).unwrap_err(),
+        Error::ValueOutOfRange("boolean", 0..1, 2)
+    );
+
+    Ok(())
+}
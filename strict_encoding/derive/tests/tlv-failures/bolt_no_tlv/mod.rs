@@ -12,12 +12,16 @@
 // You should have received a copy of the Apache 2.0 License along with this
 // software. If not, see <https://opensource.org/licenses/Apache-2.0>.
 
+// error-pattern: `bolt` attribute requires `use_tlv` to be set
+
 #[macro_use]
 extern crate strict_encoding_derive;
 
 #[derive(NetworkEncode, NetworkDecode)]
-#[network_encoding(use_tlv)]
+#[network_encoding(bolt)]
 struct Tlv {
-    #[network_encoding(tlv = 0xCAFEBAD)]
+    #[network_encoding(tlv = 1)]
     tlv: Option<u8>,
 }
+
+fn main() {}
@@ -12,6 +12,8 @@
 // You should have received a copy of the Apache 2.0 License along with this
 // software. If not, see <https://opensource.org/licenses/Apache-2.0>.
 
+// error-pattern: TLV extensions are not allowed in `strict_encoding`
+
 #[macro_use]
 extern crate strict_encoding_derive;
 
@@ -21,3 +23,5 @@ struct Tlv {
     #[strict_encoding(tlv = 0xCAFE)]
     tlv: Option<u8>,
 }
+
+fn main() {}
@@ -12,6 +12,8 @@
 // You should have received a copy of the Apache 2.0 License along with this
 // software. If not, see <https://opensource.org/licenses/Apache-2.0>.
 
+// error-pattern: Deriving `StrictEncode` is not supported in unions
+
 #[macro_use]
 extern crate strict_encoding_derive;
 
@@ -21,3 +23,5 @@ union Unit {
     variant1: u16,
     variant2: i16,
 }
+
+fn main() {}
@@ -12,6 +12,8 @@
 // You should have received a copy of the Apache 2.0 License along with this
 // software. If not, see <https://opensource.org/licenses/Apache-2.0>.
 
+// error-pattern: TLV-related attributes are allowed only when global `use_tlv` attribute is set
+
 #[macro_use]
 extern crate strict_encoding_derive;
 
@@ -20,3 +22,5 @@ struct Tlv {
     #[network_encoding(tlv = 0xCAFE)]
     tlv: Option<u8>,
 }
+
+fn main() {}
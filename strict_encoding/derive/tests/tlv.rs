@@ -16,143 +16,32 @@
 extern crate amplify;
 #[macro_use]
 extern crate strict_encoding_derive;
-#[macro_use]
 extern crate strict_encoding_test;
 
 mod common;
 use std::collections::BTreeMap;
 
 use common::{compile_test, Error, Result};
-use strict_encoding::{strict_deserialize, StrictDecode};
+use strict_encoding::{
+    strict_deserialize, strict_serialize, ApplyTlvStream, StrictDecode,
+    TlvError, TlvStream, ToTlvStream,
+};
 use strict_encoding_test::test_encoding_roundtrip;
 
-pub mod internet2 {
-    pub mod tlv {
-        use std::collections::BTreeMap;
-        use std::io::{Read, Write};
-
-        use amplify::Wrapper;
-        use strict_encoding::Error;
-
-        /// TLV type field value
-        #[derive(
-            Wrapper, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
-            Default, Debug, Display, From
-        )]
-        #[derive(StrictEncode, StrictDecode)]
-        #[display(inner)]
-        #[wrapper(LowerHex, UpperHex, Octal, FromStr)]
-        pub struct Type(u64);
-
-        impl From<usize> for Type {
-            fn from(val: usize) -> Self { Type(val as u64) }
-        }
-
-        /// Unknown TLV record represented by raw bytes
-        #[derive(
-            Wrapper, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default,
-            Debug, From
-        )]
-        #[derive(StrictEncode, StrictDecode)]
-        pub struct RawValue(Box<[u8]>);
-
-        impl AsRef<[u8]> for RawValue {
-            #[inline]
-            fn as_ref(&self) -> &[u8] { self.0.as_ref() }
-        }
-
-        #[derive(
-            Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default, From
-        )]
-        pub struct Stream(#[from] BTreeMap<Type, RawValue>);
-
-        impl strict_encoding::StrictEncode for Stream {
-            fn strict_encode<E: Write>(&self, e: E) -> Result<usize, Error> {
-                if self.0.is_empty() {
-                    return Ok(0);
-                }
-                self.0.strict_encode(e)
-            }
-        }
-
-        impl strict_encoding::StrictDecode for Stream {
-            fn strict_decode<D: Read>(d: D) -> Result<Self, Error> {
-                match BTreeMap::strict_decode(d) {
-                    Ok(data) => Ok(Self(data)),
-                    Err(strict_encoding::Error::Io(_)) => Ok(Self::default()),
-                    Err(err) => Err(err),
-                }
-            }
-        }
-
-        impl<'a> IntoIterator for &'a Stream {
-            type Item = (&'a Type, &'a RawValue);
-            type IntoIter =
-                std::collections::btree_map::Iter<'a, Type, RawValue>;
-            #[inline]
-            fn into_iter(self) -> Self::IntoIter { self.0.iter() }
-        }
-        impl IntoIterator for Stream {
-            type Item = (Type, RawValue);
-            type IntoIter =
-                std::collections::btree_map::IntoIter<Type, RawValue>;
-            #[inline]
-            fn into_iter(self) -> Self::IntoIter { self.0.into_iter() }
-        }
-
-        impl Stream {
-            #[inline]
-            pub fn new() -> Self { Self::default() }
-
-            #[inline]
-            pub fn get(&self, type_id: &Type) -> Option<&RawValue> {
-                self.0.get(type_id)
-            }
-
-            #[inline]
-            pub fn insert(
-                &mut self,
-                type_id: Type,
-                value: impl AsRef<[u8]>,
-            ) -> bool {
-                self.0
-                    .insert(type_id, RawValue::from(Box::from(value.as_ref())))
-                    .is_none()
-            }
-
-            #[inline]
-            pub fn contains_key(&self, type_id: &Type) -> bool {
-                self.0.contains_key(type_id)
-            }
-
-            #[inline]
-            pub fn len(&self) -> usize { self.0.len() }
-
-            #[inline]
-            pub fn is_empty(&self) -> bool { self.0.is_empty() }
-        }
-    }
-}
-
 #[test]
-#[should_panic]
 fn tlv_no_strict() { compile_test("tlv-failures/no_strict"); }
 
 #[test]
-#[should_panic]
 fn tlv_no_enums() { compile_test("tlv-failures/no_enums"); }
 
 #[test]
-#[should_panic]
 fn tlv_no_unions() { compile_test("tlv-failures/no_unions"); }
 
 #[test]
-#[should_panic]
-fn tlv_non_u16_type() { compile_test("tlv-failures/tlv_non_u16_type"); }
+fn tlv_undeclared() { compile_test("tlv-failures/tlv_undeclared"); }
 
 #[test]
-#[should_panic]
-fn tlv_undeclared() { compile_test("tlv-failures/tlv_undeclared"); }
+fn tlv_bolt_no_tlv() { compile_test("tlv-failures/bolt_no_tlv"); }
 
 const TLV_U32: u32 = 0xDEADCAFE;
 macro_rules! tlv_u32 {
@@ -194,7 +83,7 @@ fn tlv_optional() -> Result {
             fixed: 0xDD,
             tlv: Some(TLV_U32),
         },
-        vec![0xDD]
+        [0xDD]
             .iter()
             .chain(&tlv_u32!()[..])
             .cloned()
@@ -203,6 +92,29 @@ fn tlv_optional() -> Result {
     .map_err(Error::from)
 }
 
+#[test]
+fn tlv_non_u16_type() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug, Default)]
+    #[derive(NetworkEncode, NetworkDecode)]
+    #[network_encoding(use_tlv)]
+    struct Tlv {
+        #[network_encoding(tlv = 0xCAFEBAD)]
+        tlv: Option<u8>,
+    }
+
+    test_encoding_roundtrip(&Tlv { tlv: Some(7) }, [
+        // Count of TLV elements:
+        0x01, 0x00, //
+        // Type field:
+        0xAD, 0xEB, 0xAF, 0x0C, 0x00, 0x00, 0x00, 0x00, //
+        // Length:
+        0x01, 0x00, //
+        // Value field:
+        0x07,
+    ])
+    .map_err(Error::from)
+}
+
 #[test]
 fn tlv_newtype() -> Result {
     #[derive(Clone, PartialEq, Eq, Debug, Default)]
@@ -371,12 +283,10 @@ fn tlv_collection() -> Result {
 
 #[test]
 fn tlv_unknown() -> Result {
-    use internet2::tlv;
-
     #[derive(Clone, PartialEq, Eq, Debug, Default)]
     #[derive(NetworkEncode, NetworkDecode)]
     #[network_encoding(use_tlv)]
-    struct TlvStream {
+    struct Message {
         field: Vec<u8>,
 
         #[network_encoding(tlv = 1)]
@@ -386,13 +296,161 @@ fn tlv_unknown() -> Result {
         tlv_int2: Option<String>,
 
         #[network_encoding(unknown_tlvs)]
-        rest_of_tlvs: tlv::Stream,
+        rest_of_tlvs: TlvStream,
     }
 
-    test_encoding_roundtrip(&TlvStream::default(), [0x00; 2])
-        .map_err(Error::from)
+    test_encoding_roundtrip(&Message::default(), [0x00; 2])?;
+
+    let data = [
+        0x00, 0x00, // empty field
+        0x02, 0x00, // Count of TLV fields
+        0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // type
+        0x02, 0x00, // length
+        0xA1, 0xA2, // value
+        0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // type
+        0x01, 0x00, // length
+        0xB1, // value
+    ];
+    let mut rest_of_tlvs = TlvStream::new();
+    rest_of_tlvs.insert(5, [0xB1]);
+    let message = Message {
+        tlv_int: Some(0xA2A1),
+        rest_of_tlvs,
+        ..default!()
+    };
+    test_encoding_roundtrip(&message, data)?;
+
+    // Unknown even TLV type
+    let mut data = data;
+    data[16] = 0x06;
+    let err = Message::strict_deserialize(data).unwrap_err();
+    assert_eq!(err.root_cause(), &TlvError::UnknownEvenType(6).into());
+    assert_eq!(
+        err.to_string(),
+        "decoding of Message.<tlv 6> at byte offset 27 failed: TLV extension \
+         error: an unknown even TLV type 6"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tlv_stream_conversion() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug, Default)]
+    #[derive(NetworkEncode, NetworkDecode)]
+    #[network_encoding(use_tlv)]
+    struct Message {
+        fixed: u8,
+
+        #[network_encoding(tlv = 0xBAD)]
+        first: Option<u8>,
+
+        #[network_encoding(tlv = 0xCAFE)]
+        second: Vec<u8>,
+
+        #[network_encoding(unknown_tlvs)]
+        unknown: TlvStream,
+    }
+
+    let mut unknown = TlvStream::new();
+    unknown.insert(0xBEEF, [0xC1]);
+    let message = Message {
+        fixed: 0xDD,
+        first: Some(0xA1),
+        second: vec![],
+        unknown,
+    };
+
+    let stream = message.to_tlv_stream()?;
+    assert_eq!(stream.len(), 2);
+    assert_eq!(stream.get(0xBAD), Some([0xA1].as_ref()));
+    assert_eq!(stream.get(0xBEEF), Some([0xC1].as_ref()));
+    assert_eq!(
+        strict_serialize(&message)?,
+        [&[0xDD][..], &strict_serialize(&stream)?].concat()
+    );
+
+    let mut forwarded = Message::default();
+    forwarded.apply_tlv_stream(stream.clone())?;
+    assert_eq!(forwarded, Message {
+        fixed: 0,
+        ..message.clone()
+    });
+
+    let mut stream = stream;
+    stream.insert(0xCAFE, strict_serialize(&vec![0xB1u8])?);
+    forwarded.apply_tlv_stream(stream)?;
+    assert_eq!(forwarded.first, Some(0xA1));
+    assert_eq!(forwarded.second, vec![0xB1]);
+
+    let mut stream = TlvStream::new();
+    stream.insert(0xBAD, []);
+    let err = forwarded.apply_tlv_stream(stream).unwrap_err();
+    assert_eq!(
+        err.context().map(|context| context.path.clone()),
+        Some(vec![strict_encoding::PathSegment::Tlv(0xBAD)])
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tlv_bolt() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug, Default)]
+    #[derive(NetworkEncode, NetworkDecode)]
+    #[network_encoding(use_tlv, bolt)]
+    struct Message {
+        fixed: u8,
+
+        #[network_encoding(tlv = 2)]
+        scid: Option<[u8; 8]>,
+
+        #[network_encoding(tlv = 254)]
+        cltv_delta: Option<[u8; 2]>,
+
+        #[network_encoding(unknown_tlvs)]
+        unknown: TlvStream,
+    }
+
+    test_encoding_roundtrip(&Message::default(), [0x00])?;
+
+    let data = [
+        0xDD, // fixed
+        0x02, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02,
+        0x26, // scid
+        0x21, 0x00, // unknown odd type
+        0xfd, 0x00, 0xfe, 0x02, 0x02, 0x26, // cltv_delta
+    ];
+    let mut unknown = TlvStream::new();
+    unknown.insert(0x21, []);
+    test_encoding_roundtrip(
+        &Message {
+            fixed: 0xDD,
+            scid: Some([0, 0, 0, 0, 0, 0, 0x02, 0x26]),
+            cltv_delta: Some([0x02, 0x26]),
+            unknown,
+        },
+        data,
+    )?;
+
+    // It's OK to be odd, but not even
+    let err = Message::strict_deserialize([0xDD, 0x12, 0x00]).unwrap_err();
+    assert_eq!(err.root_cause(), &TlvError::UnknownEvenType(0x12).into());
+
+    // Records must be ordered
+    let err = Message::strict_deserialize([0xDD, 0x21, 0x00, 0x02, 0x00])
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause(),
+        &TlvError::Order {
+            read: 0x02,
+            max: 0x21
+        }
+        .into()
+    );
+
+    Ok(())
 }
 
 // TODO: Complete TLV encoding derivation test cases:
-//       - Failing on unknown even fields
 //       - Failed lengths etc
@@ -12,11 +12,15 @@
 // You should have received a copy of the Apache 2.0 License along with this
 // software. If not, see <https://opensource.org/licenses/Apache-2.0>.
 
+// error-pattern: unexpected token
+
 #[macro_use]
 extern crate strict_encoding_derive;
 
-#[derive(Clone, PartialEq, Eq, Debug, Default)]
 #[derive(StrictEncode, StrictDecode)]
-struct Unit {
-    a: (),
+struct Data {
+    #[strict_encoding(validate = "not a path")]
+    id: u8,
 }
+
+fn main() {}
@@ -0,0 +1,35 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+// error-pattern: `validate` attribute can't be used with TLV fields
+
+#[macro_use]
+extern crate strict_encoding_derive;
+
+fn positive(value: &Option<u8>) -> Result<(), &'static str> {
+    if *value == Some(0) {
+        return Err("zero");
+    }
+    Ok(())
+}
+
+#[derive(NetworkEncode, NetworkDecode)]
+#[network_encoding(use_tlv)]
+struct Data {
+    id: u8,
+    #[network_encoding(tlv = 1, validate = "positive")]
+    extra: Option<u8>,
+}
+
+fn main() {}
@@ -0,0 +1,372 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+#[macro_use]
+extern crate amplify;
+#[macro_use]
+extern crate strict_encoding_derive;
+extern crate strict_encoding_test;
+
+mod common;
+
+use std::collections::BTreeMap;
+
+use common::{compile_test, Result};
+use strict_encoding::{
+    io, Error, PathSegment, StrictDecode, StrictDecodeBorrowed, StrictEncode,
+    StrictMigrate,
+};
+use strict_encoding_test::{
+    test_async_encoding_roundtrip, test_encoding_roundtrip,
+};
+
+#[test]
+fn validate_skipped() { compile_test("validate-failures/skipped"); }
+
+#[test]
+fn validate_tlv() { compile_test("validate-failures/tlv"); }
+
+#[test]
+fn validate_variant() { compile_test("validate-failures/variant"); }
+
+#[test]
+fn validate_invalid_path() { compile_test("validate-failures/invalid_path"); }
+
+#[test]
+fn validate_wrong_signature() {
+    compile_test("validate-failures/wrong_signature");
+}
+
+fn non_empty<T>(items: &[T]) -> std::result::Result<(), &'static str> {
+    if items.is_empty() {
+        return Err("empty list");
+    }
+    Ok(())
+}
+
+mod checks {
+    pub fn percent(value: &u8) -> Result<(), String> {
+        if *value > 100 {
+            return Err(format!("{} exceeds 100%", value));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(StrictEncode, StrictDecode)]
+#[strict_encoding(validate = "Range::check")]
+struct Range {
+    start: u16,
+    end: u16,
+}
+
+impl Range {
+    fn check(&self) -> std::result::Result<(), Error> {
+        if self.start > self.end {
+            return Err(Error::ValueOutOfRange(
+                "range start",
+                0..self.end as u128 + 1,
+                self.start as u128,
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn validate_fields() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct Allocation {
+        #[strict_encoding(validate = "non_empty")]
+        owners: Vec<u8>,
+        #[strict_encoding(validate = "checks::percent")]
+        share: u8,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct Shares(#[strict_encoding(validate = "checks::percent")] u8, u8);
+
+    test_encoding_roundtrip(
+        &Allocation {
+            owners: vec![1],
+            share: 100,
+        },
+        [0x01, 0x00, 0x01, 100],
+    )?;
+    test_encoding_roundtrip(&Shares(5, 200), [5, 200])?;
+
+    let err = Allocation::strict_deserialize([0x00, 0x00, 50]).unwrap_err();
+    let context = err.context().expect("validation errors have context");
+    assert_eq!(context.type_name, Some("Allocation"));
+    assert_eq!(context.path, vec![PathSegment::Field("owners")]);
+    assert_eq!(
+        err.root_cause(),
+        &Error::DataIntegrityError(s!("empty list"))
+    );
+
+    let err =
+        Allocation::strict_deserialize([0x01, 0x00, 0x01, 101]).unwrap_err();
+    assert_eq!(
+        err.root_cause(),
+        &Error::DataIntegrityError(s!("101 exceeds 100%"))
+    );
+    assert_eq!(
+        Shares::strict_deserialize([101, 0])
+            .unwrap_err()
+            .context()
+            .map(|context| context.path.clone()),
+        Some(vec![PathSegment::Field("0")])
+    );
+    Ok(())
+}
+
+#[test]
+fn validate_type() -> Result {
+    test_encoding_roundtrip(&Range { start: 1, end: 2 }, [
+        0x01, 0x00, 0x02, 0x00,
+    ])?;
+    assert_eq!(
+        Range::strict_deserialize([0x03, 0x00, 0x02, 0x00]),
+        Err(Error::DataIntegrityError(s!("Decoding resulted in value \
+                                          `3` for type `range start` \
+                                          that exceeds the supported \
+                                          range 0..3")))
+    );
+
+    // Validation is not performed during encoding
+    assert_eq!(Range { start: 3, end: 2 }.strict_serialize()?, [
+        0x03, 0x00, 0x02, 0x00
+    ]);
+    Ok(())
+}
+
+#[test]
+fn validate_nested() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    struct Schedule {
+        id: u8,
+        ranges: Vec<Range>,
+    }
+
+    let err = Schedule::strict_deserialize([
+        0x01, 0x02, 0x00, 0x01, 0x00, 0x02, 0x00, 0x03, 0x00, 0x02, 0x00,
+    ])
+    .unwrap_err();
+    let context = err.context().expect("nested errors have context");
+    assert_eq!(context.path, vec![PathSegment::Field("ranges")]);
+    assert!(matches!(err.root_cause(), Error::DataIntegrityError(_)));
+    Ok(())
+}
+
+#[test]
+fn validate_enum() -> Result {
+    fn known(key: &u8) -> std::result::Result<(), &'static str> {
+        if *key == 0 {
+            return Err("zero key");
+        }
+        Ok(())
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[strict_encoding(validate = "Command::check")]
+    enum Command {
+        Stop,
+        Set {
+            #[strict_encoding(validate = "known")]
+            key: u8,
+            values: BTreeMap<u8, u8>,
+        },
+    }
+
+    impl Command {
+        fn check(&self) -> std::result::Result<(), &'static str> {
+            match self {
+                Command::Set { values, .. } if values.is_empty() => {
+                    Err("no values")
+                }
+                _ => Ok(()),
+            }
+        }
+    }
+
+    test_encoding_roundtrip(&Command::Stop, [0x00])?;
+    test_encoding_roundtrip(
+        &Command::Set {
+            key: 1,
+            values: bmap! { 2 => 3 },
+        },
+        [0x01, 0x01, 0x01, 0x00, 0x02, 0x03],
+    )?;
+    assert_eq!(
+        Command::strict_deserialize([0x01, 0x01, 0x00, 0x00]),
+        Err(Error::DataIntegrityError(s!("no values")))
+    );
+    let err = Command::strict_deserialize([0x01, 0x00, 0x01, 0x00, 0x02, 0x03])
+        .unwrap_err();
+    assert_eq!(
+        err.context().map(|context| context.path.clone()),
+        Some(vec![PathSegment::Variant("Set"), PathSegment::Field("key")])
+    );
+    Ok(())
+}
+
+#[test]
+fn validate_network_tlv() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug, Default)]
+    #[derive(NetworkEncode, NetworkDecode)]
+    #[network_encoding(use_tlv, validate = "Channel::check")]
+    struct Channel {
+        #[network_encoding(validate = "checks::percent")]
+        fee: u8,
+        #[network_encoding(tlv = 1)]
+        max_fee: Option<u8>,
+    }
+
+    impl Channel {
+        fn check(&self) -> std::result::Result<(), &'static str> {
+            match self.max_fee {
+                Some(max_fee) if max_fee < self.fee => Err("fee above maximum"),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    test_encoding_roundtrip(
+        &Channel {
+            fee: 5,
+            max_fee: Some(10),
+        },
+        [
+            0x05, 0x01, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x01, 0x00, 0x0A,
+        ],
+    )?;
+    assert_eq!(
+        Channel::strict_deserialize([
+            0x05, 0x01, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x01, 0x00, 0x01,
+        ]),
+        Err(Error::DataIntegrityError(s!("fee above maximum")))
+    );
+    Ok(())
+}
+
+#[test]
+fn validate_migrated() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[strict_encoding(version = 1, migrate, validate = "Limits::check")]
+    struct Limits {
+        min: u8,
+        max: u8,
+    }
+
+    impl Limits {
+        fn check(&self) -> std::result::Result<(), &'static str> {
+            if self.min > self.max {
+                return Err("minimum exceeds maximum");
+            }
+            Ok(())
+        }
+    }
+
+    impl StrictMigrate for Limits {
+        fn strict_migrate<D: io::Read>(
+            _: u8,
+            d: D,
+        ) -> std::result::Result<Self, Error> {
+            // Version 0 had only the minimum, with the maximum fixed at 10
+            Ok(Limits {
+                min: u8::strict_decode(d)?,
+                max: 10,
+            })
+        }
+    }
+
+    assert_eq!(Limits::strict_deserialize([0x00, 0x05])?, Limits {
+        min: 5,
+        max: 10
+    });
+    assert_eq!(
+        Limits::strict_deserialize([0x00, 0x0B]),
+        Err(Error::DataIntegrityError(s!("minimum exceeds maximum")))
+    );
+    Ok(())
+}
+
+#[test]
+fn validate_borrowed() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictDecodeBorrowed)]
+    #[strict_encoding(validate = "View::check")]
+    struct View<'a> {
+        #[strict_encoding(validate = "non_empty")]
+        data: &'a [u8],
+        len: u8,
+    }
+
+    impl<'a> View<'a> {
+        fn check(&self) -> std::result::Result<(), &'static str> {
+            if self.data.len() != self.len as usize {
+                return Err("length mismatch");
+            }
+            Ok(())
+        }
+    }
+
+    assert_eq!(
+        View::strict_deserialize_borrowed(&[0x01, 0x00, 0xAA, 0x01])?,
+        View {
+            data: &[0xAA],
+            len: 1
+        }
+    );
+    assert_eq!(
+        View::strict_deserialize_borrowed(&[0x01, 0x00, 0xAA, 0x02]),
+        Err(Error::DataIntegrityError(s!("length mismatch")))
+    );
+    assert_eq!(
+        View::strict_deserialize_borrowed(&[0x00, 0x00, 0x00])
+            .unwrap_err()
+            .root_cause(),
+        &Error::DataIntegrityError(s!("empty list"))
+    );
+    Ok(())
+}
+
+#[test]
+fn validate_async() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[derive(StrictEncodeAsync, StrictDecodeAsync)]
+    #[strict_encoding(validate = "Pair::check")]
+    struct Pair(#[strict_encoding(validate = "checks::percent")] u8, u8);
+
+    impl Pair {
+        fn check(&self) -> std::result::Result<(), &'static str> {
+            if self.0 > self.1 {
+                return Err("unordered pair");
+            }
+            Ok(())
+        }
+    }
+
+    test_async_encoding_roundtrip(&Pair(1, 2), [0x01, 0x02])?;
+    Ok(())
+}
@@ -0,0 +1,258 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+#[macro_use]
+extern crate amplify;
+#[macro_use]
+extern crate strict_encoding_derive;
+extern crate strict_encoding_test;
+
+mod common;
+
+use common::{compile_test, Result};
+use strict_encoding::schema::{Primitive, TypeDescr};
+use strict_encoding::{
+    io, Error, StrictDecode, StrictEncode, StrictMigrate, StrictType,
+    StrictValue, TlvStream,
+};
+use strict_encoding_test::test_encoding_roundtrip;
+
+#[test]
+fn version_migrate_no_version() {
+    compile_test("version-failures/migrate_no_version");
+}
+
+#[test]
+fn version_overflow() { compile_test("version-failures/version_overflow"); }
+
+#[test]
+fn version_field() { compile_test("version-failures/field_version"); }
+
+#[test]
+fn version_no_migrate_impl() {
+    compile_test("version-failures/no_migrate_impl");
+}
+
+#[test]
+fn version_no_borrowed() { compile_test("version-failures/no_borrowed"); }
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(StrictEncode, StrictDecode, StrictType)]
+#[strict_encoding(version = 1)]
+struct Record {
+    id: u16,
+    name: String,
+}
+
+#[test]
+fn version_struct() -> Result {
+    let record = Record {
+        id: 0xCAFE,
+        name: s!("a"),
+    };
+    test_encoding_roundtrip(&record, [0x01, 0xFE, 0xCA, 0x01, 0x00, b'a'])?;
+    assert_eq!(record.strict_encoded_len()?, 6);
+
+    assert_eq!(
+        Record::strict_deserialize([0x02, 0xFE, 0xCA, 0x01, 0x00, b'a']),
+        Err(Error::UnsupportedDataStructure(
+            "`Record` data have a version newer than the supported version 1"
+        ))
+    );
+    assert_eq!(
+        Record::strict_deserialize([0x00, 0xFE, 0xCA, 0x01, 0x00, b'a']),
+        Err(Error::UnsupportedDataStructure(
+            "`Record` data have a version older than the supported version 1"
+        ))
+    );
+    Ok(())
+}
+
+#[test]
+fn version_fixed_size() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[strict_encoding(version = 0)]
+    struct Point(u16, u16);
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[strict_encoding(version = 255, repr = u16)]
+    enum Flag {
+        Off,
+        On,
+    }
+
+    assert_eq!(Point::FIXED_SIZE, Some(5));
+    assert_eq!(Flag::FIXED_SIZE, Some(3));
+    test_encoding_roundtrip(&Point(1, 2), [0x00, 0x01, 0x00, 0x02, 0x00])?;
+    test_encoding_roundtrip(&Flag::On, [0xFF, 0x01, 0x00])?;
+    assert_eq!(
+        Point::strict_deserialize([0x01, 0x01, 0x00, 0x02, 0x00]),
+        Err(Error::UnsupportedDataStructure(
+            "`Point` data have a version newer than the supported version 0"
+        ))
+    );
+    assert_eq!(
+        Flag::strict_deserialize([0xFE, 0x01, 0x00]),
+        Err(Error::UnsupportedDataStructure(
+            "`Flag` data have a version older than the supported version 255"
+        ))
+    );
+    Ok(())
+}
+
+#[test]
+fn version_enum() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[strict_encoding(version = 3)]
+    enum Message {
+        Ping,
+        Data(Vec<u8>),
+    }
+
+    test_encoding_roundtrip(&Message::Ping, [0x03, 0x00])?;
+    test_encoding_roundtrip(&Message::Data(vec![0xAA]), [
+        0x03, 0x01, 0x01, 0x00, 0xAA,
+    ])?;
+    assert_eq!(Message::Data(vec![0xAA]).strict_encoded_len()?, 5);
+    assert!(matches!(
+        Message::strict_deserialize([0x04, 0x00]),
+        Err(Error::UnsupportedDataStructure(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn version_migrate() -> Result {
+    // Version 0 of the record, which had no `name` field
+    #[derive(StrictEncode, StrictDecode)]
+    struct RecordV0 {
+        id: u16,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[strict_encoding(version = 2, migrate)]
+    struct Record {
+        id: u16,
+        name: String,
+    }
+
+    impl StrictMigrate for Record {
+        fn strict_migrate<D: io::Read>(
+            version: u8,
+            d: D,
+        ) -> std::result::Result<Self, Error> {
+            match version {
+                0 => {
+                    let old = RecordV0::strict_decode(d)?;
+                    Ok(Record {
+                        id: old.id,
+                        name: s!("unnamed"),
+                    })
+                }
+                _ => Err(Error::UnsupportedDataStructure(
+                    "record version 1 was never released",
+                )),
+            }
+        }
+    }
+
+    let record = Record {
+        id: 7,
+        name: s!("unnamed"),
+    };
+    let mut data = vec![0x00];
+    data.extend(RecordV0 { id: 7 }.strict_serialize()?);
+    assert_eq!(Record::strict_deserialize(&data)?, record);
+    test_encoding_roundtrip(&record, [
+        0x02, 0x07, 0x00, 0x07, 0x00, b'u', b'n', b'n', b'a', b'm', b'e', b'd',
+    ])?;
+
+    assert_eq!(
+        Record::strict_deserialize([0x01, 0x07, 0x00]),
+        Err(Error::UnsupportedDataStructure(
+            "record version 1 was never released"
+        ))
+    );
+    assert!(matches!(
+        Record::strict_deserialize([0x03, 0x07, 0x00]),
+        Err(Error::UnsupportedDataStructure(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn version_network() -> Result {
+    #[derive(Clone, PartialEq, Eq, Debug, Default)]
+    #[derive(NetworkEncode, NetworkDecode)]
+    #[network_encoding(version = 1, use_tlv)]
+    struct Announcement {
+        id: u8,
+        #[network_encoding(tlv = 1)]
+        alias: Option<String>,
+        #[network_encoding(unknown_tlvs)]
+        unknown: TlvStream,
+    }
+
+    test_encoding_roundtrip(&Announcement::default(), [0x01, 0x00])?;
+    assert!(matches!(
+        Announcement::strict_deserialize([0x02, 0x00]),
+        Err(Error::UnsupportedDataStructure(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn version_schema() -> Result {
+    let ty = Record::strict_type();
+    match ty {
+        TypeDescr::Versioned(1, ref inner) => assert!(matches!(
+            inner.as_ref(),
+            TypeDescr::Struct(descr) if descr.name == "Record"
+        )),
+        _ => panic!("versioned type must be described as versioned"),
+    }
+    assert_eq!(TypeDescr::strict_deserialize(ty.strict_serialize()?)?, ty);
+
+    let data = Record {
+        id: 1,
+        name: s!("a"),
+    }
+    .strict_serialize()?;
+    let value = StrictValue::deserialize(&ty, &data)?;
+    match value {
+        StrictValue::Versioned(1, ref inner) => {
+            assert!(matches!(inner.as_ref(), StrictValue::Struct(_)))
+        }
+        _ => panic!("versioned type must be decoded as versioned value"),
+    }
+    assert_eq!(value.strict_serialize()?, data);
+    assert_eq!(value.to_string(), r#"v1 Record { id: 1, name: "a" }"#);
+
+    let mut newer = data.clone();
+    newer[0] = 2;
+    assert!(matches!(
+        StrictValue::deserialize(&ty, &newer),
+        Err(Error::UnsupportedDataStructure(_))
+    ));
+
+    assert_eq!(
+        TypeDescr::versioned(1, TypeDescr::Primitive(Primitive::U8)),
+        TypeDescr::Versioned(1, Box::new(TypeDescr::Primitive(Primitive::U8)))
+    );
+    Ok(())
+}
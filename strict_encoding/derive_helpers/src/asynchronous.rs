@@ -0,0 +1,417 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+use amplify::proc_attr::ParametrizedAttr;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{ToTokens, TokenStreamExt};
+use syn::spanned::Spanned;
+use syn::{
+    Data, DeriveInput, Error, Fields, Ident, Index, LitStr, Path, Result,
+};
+
+use crate::bound::bounded_generics;
+use crate::decode::version_impl;
+use crate::param::{
+    encoded_fields, error_context, field_lit, validated_field, validated_type,
+    variant_param, EncodingDerive, TlvDerive,
+};
+
+/// Performs actual derivation of the asynchronous encode trait using the
+/// provided information about trait parameters and requirements for TLV
+/// support.
+///
+/// The derived trait must have a single async `#encode_name(&self, e: &mut E)`
+/// method, where `E: AsyncWrite + Unpin + Send`. The crate `crate_name` must
+/// export `async_trait` macro as well as `AsyncWrite` trait from its root.
+/// Fields encoded with adapters use `#encode_name(&value, e)` async function
+/// from the adapter module. TLV fields are converted into a stream with
+/// `ToTlvStream` implementation, which must be provided by the blocking
+/// derivation, and the stream is written with `#encode_name` (or
+/// `bolt_encode_async` for BOLT-1 streams) async method of `TlvStream`.
+pub fn encode_async_derive(
+    attr_name: &'static str,
+    crate_name: Ident,
+    trait_name: Ident,
+    encode_name: Ident,
+    input: DeriveInput,
+    tlv_encoding: bool,
+) -> Result<TokenStream2> {
+    let generics =
+        bounded_generics(attr_name, &crate_name, &input, |import| {
+            quote! { #import::#trait_name }
+        })?;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let ident_name = &input.ident;
+
+    let mut global_param = ParametrizedAttr::with(attr_name, &input.attrs)?;
+    let is_enum = matches!(input.data, Data::Enum(_));
+    let encoding = EncodingDerive::with(
+        &mut global_param,
+        &crate_name,
+        true,
+        is_enum,
+        false,
+    )?;
+    check_tlv_encoding(attr_name, tlv_encoding, &encoding, ident_name)?;
+    let import = &encoding.use_crate;
+
+    let mut inner_impl = match encoding.version {
+        Some(version) => quote! {
+            len += #version.#encode_name(e).await?;
+        },
+        None => TokenStream2::new(),
+    };
+
+    inner_impl.append_all(match input.data {
+        Data::Struct(ref data) => {
+            let mut stream = TokenStream2::new();
+            for (name, encoding) in encoded_fields(
+                attr_name,
+                &crate_name,
+                &data.fields,
+                global_param,
+                false,
+            )? {
+                if is_strict_field(&encoding) {
+                    stream.append_all(encode_field(
+                        &encoding,
+                        import,
+                        &encode_name,
+                        quote! { self.#name },
+                    ));
+                }
+            }
+            if encoding.tlv.is_some() {
+                let write_tlvs = if encoding.bolt {
+                    ident!(bolt_encode_async)
+                } else {
+                    encode_name.clone()
+                };
+                stream.append_all(quote! {
+                    len += #import::ToTlvStream::to_tlv_stream(self)?.#write_tlvs(e).await?;
+                });
+            }
+            stream
+        }
+        Data::Enum(ref data) => {
+            let repr = &encoding.repr;
+            let mut variants = TokenStream2::new();
+            for (order, variant) in data.variants.iter().enumerate() {
+                let (local_param, encoding) = variant_param(
+                    attr_name,
+                    &crate_name,
+                    &global_param,
+                    &variant.attrs,
+                )?;
+                if encoding.skip {
+                    continue;
+                }
+
+                let fields = encoded_fields(
+                    attr_name,
+                    &crate_name,
+                    &variant.fields,
+                    local_param,
+                    true,
+                )?;
+                let mut captures = vec![];
+                let mut field_impl = TokenStream2::new();
+                for (index, (field, (_, encoding))) in
+                    variant.fields.iter().zip(fields).enumerate()
+                {
+                    let binding = field.ident.clone().unwrap_or_else(|| {
+                        Ident::new(&format!("_{}", index), Span::call_site())
+                    });
+                    captures.push(match (&field.ident, encoding.skip) {
+                        (Some(ident), true) => quote! { #ident: _ },
+                        (None, true) => quote! { _ },
+                        _ => binding.to_token_stream(),
+                    });
+                    if !encoding.skip {
+                        field_impl.append_all(encode_field(
+                            &encoding,
+                            import,
+                            &encode_name,
+                            quote! { (*#binding) },
+                        ));
+                    }
+                }
+                let bra_captures_ket = match variant.fields {
+                    Fields::Named(_) => quote! { { #( #captures ),* } },
+                    Fields::Unnamed(_) => quote! { ( #( #captures ),* ) },
+                    Fields::Unit => quote! {},
+                };
+
+                let ident = &variant.ident;
+                let value = match (encoding.value, encoding.by_order) {
+                    (Some(val), _) => val.to_token_stream(),
+                    (None, true) => Index::from(order).to_token_stream(),
+                    (None, false) => quote! { Self::#ident },
+                };
+
+                variants.append_all(quote_spanned! { variant.span() =>
+                    Self::#ident #bra_captures_ket => {
+                        len += (#value as #repr).#encode_name(e).await?;
+                        #field_impl
+                    }
+                });
+            }
+            quote! {
+                match self {
+                    #variants
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input,
+                format!("Deriving `{}` is not supported in unions", trait_name),
+            ))
+        }
+    });
+
+    Ok(quote! {
+        #[#import::async_trait]
+        impl #impl_generics #import::#trait_name for #ident_name #ty_generics #where_clause {
+            #[allow(clippy::unnecessary_cast)]
+            async fn #encode_name<E>(&self, e: &mut E) -> ::core::result::Result<usize, #import::Error>
+            where
+                E: #import::AsyncWrite + ::core::marker::Unpin + ::core::marker::Send,
+            {
+                use #import::#trait_name;
+                let mut len = 0;
+                #inner_impl
+                Ok(len)
+            }
+        }
+    })
+}
+
+/// Performs actual derivation of the asynchronous decode trait using the
+/// provided information about trait parameters and requirements for TLV
+/// support.
+///
+/// The derived trait must have a single async `#decode_name(d: &mut D)`
+/// method, where `D: AsyncRead + Unpin + Send`. The crate `crate_name` must
+/// export `async_trait` macro as well as `AsyncRead` trait from its root.
+/// Fields encoded with adapters use `#decode_name(d)` async function from the
+/// adapter module, and older versions of the types with `migrate` attribute
+/// are decoded with `StrictMigrateAsync` trait exported by the crate. TLV
+/// stream is read with `#decode_name` (or `bolt_decode_async` for BOLT-1
+/// streams) async method of `TlvStream` and applied with `ApplyTlvStream`
+/// implementation, which must be provided by the blocking derivation.
+pub fn decode_async_derive(
+    attr_name: &'static str,
+    crate_name: Ident,
+    trait_name: Ident,
+    decode_name: Ident,
+    input: DeriveInput,
+    tlv_encoding: bool,
+) -> Result<TokenStream2> {
+    let generics =
+        bounded_generics(attr_name, &crate_name, &input, |import| {
+            quote! { #import::#trait_name }
+        })?;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let ident_name = &input.ident;
+
+    let mut global_param = ParametrizedAttr::with(attr_name, &input.attrs)?;
+    let is_enum = matches!(input.data, Data::Enum(_));
+    let encoding = EncodingDerive::with(
+        &mut global_param,
+        &crate_name,
+        true,
+        is_enum,
+        false,
+    )?;
+    check_tlv_encoding(attr_name, tlv_encoding, &encoding, ident_name)?;
+    let import = &encoding.use_crate;
+
+    let decode_fields =
+        |fields: &Fields, param: ParametrizedAttr, variant: Option<&Ident>| {
+            let context = error_context(ident_name, variant);
+            let mut stream = TokenStream2::new();
+            for (field, (name, encoding)) in fields.iter().zip(encoded_fields(
+                attr_name,
+                &crate_name,
+                fields,
+                param,
+                variant.is_some(),
+            )?) {
+                stream.append_all(if !is_strict_field(&encoding) {
+                quote! { #name: Default::default(), }
+            } else {
+                let decode = match encoding.adapter {
+                    Some(ref adapter) => {
+                        let module = adapter.module(import);
+                        quote! { #module::#decode_name(d).await }
+                    }
+                    None => {
+                        quote! { #import::#trait_name::#decode_name(d).await }
+                    }
+                };
+                let value = validated_field(
+                    decode,
+                    encoding
+                        .validate
+                        .as_ref()
+                        .map(|validate| (validate, &field.ty)),
+                    import,
+                    &field_lit(&name),
+                    &context,
+                );
+                quote! { #name: #value, }
+            });
+            }
+            Ok::<_, Error>(stream)
+        };
+
+    let inner_impl = match input.data {
+        Data::Struct(ref data) => {
+            let fields =
+                decode_fields(&data.fields, global_param.clone(), None)?;
+            if encoding.tlv.is_some() {
+                let read_tlvs = if encoding.bolt {
+                    quote! { #import::TlvStream::bolt_decode_async(d).await? }
+                } else {
+                    quote! { <#import::TlvStream as #import::#trait_name>::#decode_name(d).await? }
+                };
+                quote! {
+                    let mut s = Self { #fields };
+                    let tlvs = #read_tlvs;
+                    #import::ApplyTlvStream::apply_tlv_stream(&mut s, tlvs)?;
+                    Ok(s)
+                }
+            } else {
+                quote! { Ok(Self { #fields }) }
+            }
+        }
+        Data::Enum(ref data) => {
+            let repr = &encoding.repr;
+            let mut variants = TokenStream2::new();
+            for (order, variant) in data.variants.iter().enumerate() {
+                let (local_param, encoding) = variant_param(
+                    attr_name,
+                    &crate_name,
+                    &global_param,
+                    &variant.attrs,
+                )?;
+                if encoding.skip {
+                    continue;
+                }
+
+                let fields = decode_fields(
+                    &variant.fields,
+                    local_param,
+                    Some(&variant.ident),
+                )?;
+
+                let ident = &variant.ident;
+                let value = match (encoding.value, encoding.by_order) {
+                    (Some(val), _) => val.to_token_stream(),
+                    (None, true) => Index::from(order).to_token_stream(),
+                    (None, false) => quote! { Self::#ident as #repr },
+                };
+
+                variants.append_all(quote_spanned! { variant.span() =>
+                    x if x == #value => Self::#ident { #fields },
+                });
+            }
+            let enum_name =
+                LitStr::new(&ident_name.to_string(), Span::call_site());
+            quote! {
+                Ok(match <#repr as #import::#trait_name>::#decode_name(d).await? {
+                    #variants
+                    unknown => Err(#import::Error::EnumValueNotKnown(#enum_name, unknown as usize))?
+                })
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input,
+                format!("Deriving `{}` is not supported in unions", trait_name),
+            ))
+        }
+    };
+
+    let version = version_impl(
+        &encoding,
+        ident_name,
+        quote! { <u8 as #import::#trait_name>::#decode_name(d).await? },
+        quote! {
+            <Self as #import::StrictMigrateAsync>::strict_migrate_async(version, d).await
+        },
+    );
+    let inner_impl =
+        validated_type(inner_impl, encoding.validate.as_ref(), import);
+
+    Ok(quote! {
+        #[#import::async_trait]
+        impl #impl_generics #import::#trait_name for #ident_name #ty_generics #where_clause {
+            #[allow(clippy::init_numbered_fields)]
+            async fn #decode_name<D>(d: &mut D) -> ::core::result::Result<Self, #import::Error>
+            where
+                D: #import::AsyncRead + ::core::marker::Unpin + ::core::marker::Send,
+            {
+                use #import::#trait_name;
+                #version
+                #inner_impl
+            }
+        }
+    })
+}
+
+/// Fails if TLV extensions are used by a derivation which does not support
+/// them.
+fn check_tlv_encoding(
+    attr_name: &'static str,
+    tlv_encoding: bool,
+    encoding: &EncodingDerive,
+    ident_name: &Ident,
+) -> Result<()> {
+    if !tlv_encoding && encoding.tlv.is_some() {
+        return Err(Error::new(
+            ident_name.span(),
+            format!("TLV extensions are not allowed in `{}`", attr_name),
+        ));
+    }
+    Ok(())
+}
+
+/// Detects fields which are neither skipped nor encoded as a part of the TLV
+/// stream.
+fn is_strict_field(encoding: &EncodingDerive) -> bool {
+    !encoding.skip && matches!(encoding.tlv, None | Some(TlvDerive::None))
+}
+
+/// Generates code encoding a field, accessed with `value` expression, either
+/// with its adapter or with the trait implementation.
+fn encode_field(
+    encoding: &EncodingDerive,
+    import: &Path,
+    encode_name: &Ident,
+    value: TokenStream2,
+) -> TokenStream2 {
+    match encoding.adapter {
+        Some(ref adapter) => {
+            let module = adapter.module(import);
+            quote! {
+                len += #module::#encode_name(&#value, e).await?;
+            }
+        }
+        None => quote! {
+            len += #value.#encode_name(e).await?;
+        },
+    }
+}
@@ -0,0 +1,182 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+use amplify::proc_attr::ParametrizedAttr;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{ToTokens, TokenStreamExt};
+use syn::spanned::Spanned;
+use syn::{
+    Data, DeriveInput, Error, Fields, GenericParam, Ident, Index, Lifetime,
+    LifetimeDef, LitStr, Result,
+};
+
+use crate::bound::bounded_generics;
+use crate::param::{
+    check_no_tlv, check_unversioned, error_context, field_lit, plain_fields,
+    validated_field, validated_type, variant_param, EncodingDerive,
+};
+
+/// Performs actual derivation of the borrowed decode trait using the provided
+/// information about trait parameters.
+///
+/// The derived trait must be generic over a single lifetime `'a` and have a
+/// single `#decode_name(d: &mut &'a [u8])` method. If the type has a lifetime
+/// parameter it is used as the trait lifetime, so the decoded fields may
+/// borrow from the source data; types with more than one lifetime parameter
+/// are not supported. TLV extensions are not supported by borrowed decoding.
+pub fn decode_borrowed_derive(
+    attr_name: &'static str,
+    crate_name: Ident,
+    trait_name: Ident,
+    decode_name: Ident,
+    input: DeriveInput,
+) -> Result<TokenStream2> {
+    let mut lifetimes = input.generics.lifetimes();
+    let (lifetime, is_own) = match (lifetimes.next(), lifetimes.next()) {
+        (Some(def), None) => (def.lifetime.clone(), false),
+        (None, _) => (Lifetime::new("'__strict_de", Span::call_site()), true),
+        (Some(_), Some(second)) => {
+            return Err(Error::new_spanned(
+                second,
+                format!(
+                    "Deriving `{}` is supported only for types with at most \
+                     one lifetime parameter",
+                    trait_name
+                ),
+            ))
+        }
+    };
+    let generics =
+        bounded_generics(attr_name, &crate_name, &input, |import| {
+            quote! { #import::#trait_name<#lifetime> }
+        })?;
+    let mut impl_generics = generics.clone();
+    if is_own {
+        impl_generics.params.insert(
+            0,
+            GenericParam::Lifetime(LifetimeDef::new(lifetime.clone())),
+        );
+    }
+    let (impl_generics, _, _) = impl_generics.split_for_impl();
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
+    let ident_name = &input.ident;
+
+    let mut global_param = ParametrizedAttr::with(attr_name, &input.attrs)?;
+    let is_enum = matches!(input.data, Data::Enum(_));
+    let encoding = EncodingDerive::with(
+        &mut global_param,
+        &crate_name,
+        true,
+        is_enum,
+        false,
+    )?;
+    check_no_tlv(attr_name, "borrowed", &global_param, ident_name)?;
+    check_unversioned(attr_name, "borrowed", &global_param, ident_name)?;
+    let import = encoding.use_crate;
+
+    let decode_fields = |fields: &Fields,
+                         param: ParametrizedAttr,
+                         variant: Option<&Ident>| {
+        let context = error_context(ident_name, variant);
+        let mut stream = TokenStream2::new();
+        for (field, (name, skip, validate)) in fields.iter().zip(plain_fields(
+            attr_name,
+            "borrowed",
+            &crate_name,
+            fields,
+            param,
+            variant.is_some(),
+        )?) {
+            stream.append_all(if skip {
+                quote! { #name: Default::default(), }
+            } else {
+                let value = validated_field(
+                    quote! { #trait_name::#decode_name(d) },
+                    validate.as_ref().map(|validate| (validate, &field.ty)),
+                    &import,
+                    &field_lit(&name),
+                    &context,
+                );
+                quote! { #name: #value, }
+            });
+        }
+        Ok::<_, Error>(stream)
+    };
+
+    let inner_impl = match input.data {
+        Data::Struct(ref data) => {
+            let fields = decode_fields(&data.fields, global_param, None)?;
+            quote! { Ok(Self { #fields }) }
+        }
+        Data::Enum(ref data) => {
+            let repr = encoding.repr;
+            let mut variants = TokenStream2::new();
+            for (order, variant) in data.variants.iter().enumerate() {
+                let (local_param, encoding) = variant_param(
+                    attr_name,
+                    &crate_name,
+                    &global_param,
+                    &variant.attrs,
+                )?;
+                if encoding.skip {
+                    continue;
+                }
+
+                let fields = decode_fields(
+                    &variant.fields,
+                    local_param,
+                    Some(&variant.ident),
+                )?;
+
+                let ident = &variant.ident;
+                let value = match (encoding.value, encoding.by_order) {
+                    (Some(val), _) => val.to_token_stream(),
+                    (None, true) => Index::from(order).to_token_stream(),
+                    (None, false) => quote! { Self::#ident as #repr },
+                };
+
+                variants.append_all(quote_spanned! { variant.span() =>
+                    x if x == #value => Self::#ident { #fields },
+                });
+            }
+            let enum_name =
+                LitStr::new(&ident_name.to_string(), Span::call_site());
+            quote! {
+                Ok(match <#repr as #trait_name<#lifetime>>::#decode_name(d)? {
+                    #variants
+                    unknown => Err(#import::Error::EnumValueNotKnown(#enum_name, unknown as usize))?
+                })
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input,
+                format!("Deriving `{}` is not supported in unions", trait_name),
+            ))
+        }
+    };
+
+    let inner_impl =
+        validated_type(inner_impl, encoding.validate.as_ref(), &import);
+
+    Ok(quote! {
+        impl #impl_generics #import::#trait_name<#lifetime> for #ident_name #ty_generics #where_clause {
+            #[allow(clippy::init_numbered_fields)]
+            fn #decode_name(d: &mut &#lifetime [u8]) -> ::core::result::Result<Self, #import::Error> {
+                use #import::#trait_name;
+                #inner_impl
+            }
+        }
+    })
+}
@@ -0,0 +1,238 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+use std::collections::BTreeMap;
+
+use amplify::proc_attr::ParametrizedAttr;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::ToTokens;
+use syn::{
+    Data, DeriveInput, Field, GenericArgument, Generics, Ident, Path,
+    PathArguments, Result, ReturnType, Type, TypePath, WherePredicate,
+};
+
+use crate::param::EncodingDerive;
+
+/// Returns type generics extended with the trait bounds required by the
+/// derived implementation; `bound` constructs the bounding trait from the
+/// path to the encoding crate.
+///
+/// If the type is marked with `bound = "..."` attribute, the provided where
+/// predicates are used instead of the inferred ones. Otherwise each type
+/// parameter and each associated type projection (like `R::Item` or
+/// `<R as Trait>::Item`) used by the non-skipped fields is bounded with the
+/// trait. Fields and enum variants may provide their own `bound = "..."`,
+/// replacing the inferred bounds for their types only.
+pub(crate) fn bounded_generics(
+    attr_name: &'static str,
+    crate_name: &Ident,
+    input: &DeriveInput,
+    bound: impl FnOnce(&Path) -> TokenStream2,
+) -> Result<Generics> {
+    let mut generics = input.generics.clone();
+
+    let is_enum = match input.data {
+        Data::Struct(_) => false,
+        Data::Enum(_) => true,
+        Data::Union(_) => return Ok(generics),
+    };
+    let mut global_param = ParametrizedAttr::with(attr_name, &input.attrs)?;
+    let encoding = EncodingDerive::with(
+        &mut global_param,
+        crate_name,
+        true,
+        is_enum,
+        false,
+    )?;
+
+    let mut predicates = vec![];
+    if let Some(explicit) = encoding.bound {
+        predicates.extend(explicit);
+    } else {
+        let params = generics
+            .type_params()
+            .map(|param| param.ident.clone())
+            .collect::<Vec<_>>();
+        let mut bounded = BTreeMap::new();
+
+        match input.data {
+            Data::Struct(ref data) => collect_fields(
+                attr_name,
+                crate_name,
+                false,
+                data.fields.iter(),
+                &params,
+                &mut predicates,
+                &mut bounded,
+            )?,
+            Data::Enum(ref data) => {
+                for variant in &data.variants {
+                    let mut local_param =
+                        ParametrizedAttr::with(attr_name, &variant.attrs)?;
+                    let encoding = EncodingDerive::with(
+                        &mut local_param,
+                        crate_name,
+                        false,
+                        true,
+                        false,
+                    )?;
+                    match encoding.bound {
+                        Some(explicit) => predicates.extend(explicit),
+                        None => collect_fields(
+                            attr_name,
+                            crate_name,
+                            true,
+                            variant.fields.iter(),
+                            &params,
+                            &mut predicates,
+                            &mut bounded,
+                        )?,
+                    }
+                }
+            }
+            Data::Union(_) => unreachable!(),
+        }
+
+        let trait_bound = bound(&encoding.use_crate);
+        predicates.extend(bounded.into_values().map(|ty| -> WherePredicate {
+            syn::parse_quote! { #ty: #trait_bound }
+        }));
+    }
+
+    if !predicates.is_empty() {
+        generics.make_where_clause().predicates.extend(predicates);
+    }
+    Ok(generics)
+}
+
+/// Collects bounds for the types of the non-skipped fields, either from their
+/// `bound = "..."` attribute or by inferring them from the field type. Types
+/// of the fields encoded with adapters are not bounded unless the field has
+/// `bound = "..."` attribute.
+///
+/// Field attributes are validated here only for the arguments affecting the
+/// bounds; TLV-related arguments are checked later by the derivation itself.
+fn collect_fields<'a>(
+    attr_name: &'static str,
+    crate_name: &Ident,
+    is_enum: bool,
+    fields: impl Iterator<Item = &'a Field>,
+    params: &[Ident],
+    predicates: &mut Vec<WherePredicate>,
+    bounded: &mut BTreeMap<String, Type>,
+) -> Result<()> {
+    for field in fields {
+        let mut local_param = ParametrizedAttr::with(attr_name, &field.attrs)?;
+        let encoding = EncodingDerive::with(
+            &mut local_param,
+            crate_name,
+            false,
+            is_enum,
+            true,
+        )?;
+        match encoding.bound {
+            _ if encoding.skip => {}
+            Some(explicit) => predicates.extend(explicit),
+            // Adapter defines its own requirements for the field type
+            None if encoding.adapter.is_some() => {}
+            None => collect_bounded(&field.ty, params, bounded),
+        }
+    }
+    Ok(())
+}
+
+/// Collects type parameters and associated type projections on type
+/// parameters used by the type `ty`, deduplicating them by their string
+/// representation.
+fn collect_bounded(
+    ty: &Type,
+    params: &[Ident],
+    bounded: &mut BTreeMap<String, Type>,
+) {
+    let mut add = |ty: &Type| {
+        bounded
+            .entry(ty.to_token_stream().to_string())
+            .or_insert_with(|| ty.clone());
+    };
+
+    match ty {
+        Type::Path(TypePath {
+            qself: Some(qself), ..
+        }) => {
+            let mut inner = BTreeMap::new();
+            collect_bounded(&qself.ty, params, &mut inner);
+            if !inner.is_empty() {
+                add(ty);
+            }
+        }
+        Type::Path(TypePath { qself: None, path }) => {
+            match path.segments.first() {
+                Some(first)
+                    if path.leading_colon.is_none()
+                        && params.contains(&first.ident) =>
+                {
+                    // Either a type parameter itself or a projection of its
+                    // associated type, like `R::Item`
+                    add(ty);
+                    return;
+                }
+                _ => {}
+            }
+            for segment in &path.segments {
+                match &segment.arguments {
+                    PathArguments::None => {}
+                    PathArguments::AngleBracketed(args) => {
+                        for arg in &args.args {
+                            match arg {
+                                GenericArgument::Type(ty) => {
+                                    collect_bounded(ty, params, bounded)
+                                }
+                                GenericArgument::Binding(binding) => {
+                                    collect_bounded(
+                                        &binding.ty,
+                                        params,
+                                        bounded,
+                                    )
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                    PathArguments::Parenthesized(args) => {
+                        for ty in &args.inputs {
+                            collect_bounded(ty, params, bounded);
+                        }
+                        if let ReturnType::Type(_, ty) = &args.output {
+                            collect_bounded(ty, params, bounded);
+                        }
+                    }
+                }
+            }
+        }
+        Type::Array(array) => collect_bounded(&array.elem, params, bounded),
+        Type::Group(group) => collect_bounded(&group.elem, params, bounded),
+        Type::Paren(paren) => collect_bounded(&paren.elem, params, bounded),
+        Type::Ptr(ptr) => collect_bounded(&ptr.elem, params, bounded),
+        Type::Reference(reference) => {
+            collect_bounded(&reference.elem, params, bounded)
+        }
+        Type::Slice(slice) => collect_bounded(&slice.elem, params, bounded),
+        Type::Tuple(tuple) => {
+            for ty in &tuple.elems {
+                collect_bounded(ty, params, bounded);
+            }
+        }
+        _ => {}
+    }
+}
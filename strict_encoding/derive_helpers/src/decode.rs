@@ -18,14 +18,22 @@ use quote::{ToTokens, TokenStreamExt};
 use syn::spanned::Spanned;
 use syn::{
     Data, DataEnum, DataStruct, DeriveInput, Error, Field, Fields, Ident,
-    ImplGenerics, Index, LitStr, Result, TypeGenerics, WhereClause,
+    ImplGenerics, Index, LitStr, Path, Result, TypeGenerics, WhereClause,
 };
 
-use crate::param::{EncodingDerive, TlvDerive, CRATE, REPR, USE_TLV};
+use crate::bound::bounded_generics;
+use crate::param::{
+    check_variant_param, error_context, field_lit, tlv_lit, validated_field,
+    validated_type, EncodingDerive, TlvDerive, BOLT, BOUND, CRATE, MIGRATE,
+    REPR, USE_TLV, VALIDATE, VERSION,
+};
 
 /// Performs actual derivation of the decode trait using the provided
 /// information about trait parameters and requirements for TLV support.
 ///
+/// The crate `crate_name` must export `io` module with `Read` trait, which
+/// is used by the trait methods.
+///
 /// You will find example of the function use in the
 /// [crate top-level documentation][crate].
 pub fn decode_derive(
@@ -37,8 +45,11 @@ pub fn decode_derive(
     input: DeriveInput,
     tlv_encoding: bool,
 ) -> Result<TokenStream2> {
-    let (impl_generics, ty_generics, where_clause) =
-        input.generics.split_for_impl();
+    let generics =
+        bounded_generics(attr_name, &crate_name, &input, |import| {
+            quote! { #import::#trait_name }
+        })?;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     let ident_name = &input.ident;
 
     let global_param = ParametrizedAttr::with(attr_name, &input.attrs)?;
@@ -108,44 +119,66 @@ fn decode_struct_impl(
         ));
     }
 
-    let inner_impl = match data.fields {
+    let (inner_impl, tlv) = match data.fields {
         Fields::Named(ref fields) => decode_fields_impl(
             attr_name,
             crate_name,
+            &encoding.use_crate,
             trait_name,
             decode_name,
             deserialize_name,
             ident_name,
             &fields.named,
             global_param,
-            false,
+            None,
             tlv_encoding,
         )?,
         Fields::Unnamed(ref fields) => decode_fields_impl(
             attr_name,
             crate_name,
+            &encoding.use_crate,
             trait_name,
             decode_name,
             deserialize_name,
             ident_name,
             &fields.unnamed,
             global_param,
-            false,
+            None,
             tlv_encoding,
         )?,
-        Fields::Unit => quote! {},
+        Fields::Unit => (quote! { Ok(Self) }, None),
     };
 
+    let version = blocking_version_impl(&encoding, decode_name, ident_name);
+    let inner_impl = validated_type(
+        inner_impl,
+        encoding.validate.as_ref(),
+        &encoding.use_crate,
+    );
     let import = encoding.use_crate;
 
+    let tlv_impl = match tlv {
+        Some(tlv) => quote! {
+            impl #impl_generics #import::ApplyTlvStream for #ident_name #ty_generics #where_clause {
+                fn apply_tlv_stream(&mut self, stream: #import::TlvStream) -> ::core::result::Result<(), #import::Error> {
+                    #tlv
+                }
+            }
+        },
+        None => TokenStream2::new(),
+    };
+
     Ok(quote! {
         impl #impl_generics #import::#trait_name for #ident_name #ty_generics #where_clause {
             #[allow(clippy::init_numbered_fields)]
-            fn #decode_name<D: ::std::io::Read>(mut d: D) -> ::core::result::Result<Self, #import::Error> {
+            fn #decode_name<D: #import::io::Read>(mut d: D) -> ::core::result::Result<Self, #import::Error> {
                 use #import::#trait_name;
+                #version
                 #inner_impl
             }
         }
+
+        #tlv_impl
     })
 }
 
@@ -165,7 +198,10 @@ fn decode_enum_impl(
 ) -> Result<TokenStream2> {
     let encoding =
         EncodingDerive::with(&mut global_param, crate_name, true, true, false)?;
+    let version = blocking_version_impl(&encoding, decode_name, ident_name);
     let repr = encoding.repr;
+    let validate = encoding.validate;
+    let import = encoding.use_crate;
 
     let mut inner_impl = TokenStream2::new();
 
@@ -174,17 +210,22 @@ fn decode_enum_impl(
             ParametrizedAttr::with(attr_name, &variant.attrs)?;
 
         // First, test individual attribute
-        let _ = EncodingDerive::with(
+        let local = EncodingDerive::with(
             &mut local_param,
             crate_name,
             false,
             true,
             false,
         )?;
+        check_variant_param(&local)?;
         // Second, combine global and local together
         let mut combined = global_param.clone().merged(local_param.clone())?;
         combined.args.remove(REPR);
         combined.args.remove(CRATE);
+        combined.args.remove(VERSION);
+        combined.args.remove(MIGRATE);
+        combined.args.remove(BOUND);
+        combined.args.remove(VALIDATE);
         let encoding = EncodingDerive::with(
             &mut combined,
             crate_name,
@@ -198,30 +239,38 @@ fn decode_enum_impl(
         }
 
         let field_impl = match variant.fields {
-            Fields::Named(ref fields) => decode_fields_impl(
-                attr_name,
-                crate_name,
-                trait_name,
-                decode_name,
-                deserialize_name,
-                ident_name,
-                &fields.named,
-                local_param,
-                true,
-                false,
-            )?,
-            Fields::Unnamed(ref fields) => decode_fields_impl(
-                attr_name,
-                crate_name,
-                trait_name,
-                decode_name,
-                deserialize_name,
-                ident_name,
-                &fields.unnamed,
-                local_param,
-                true,
-                false,
-            )?,
+            Fields::Named(ref fields) => {
+                decode_fields_impl(
+                    attr_name,
+                    crate_name,
+                    &import,
+                    trait_name,
+                    decode_name,
+                    deserialize_name,
+                    ident_name,
+                    &fields.named,
+                    local_param,
+                    Some(&variant.ident),
+                    false,
+                )?
+                .0
+            }
+            Fields::Unnamed(ref fields) => {
+                decode_fields_impl(
+                    attr_name,
+                    crate_name,
+                    &import,
+                    trait_name,
+                    decode_name,
+                    deserialize_name,
+                    ident_name,
+                    &fields.unnamed,
+                    local_param,
+                    Some(&variant.ident),
+                    false,
+                )?
+                .0
+            }
             Fields::Unit => TokenStream2::new(),
         };
 
@@ -241,41 +290,124 @@ fn decode_enum_impl(
         });
     }
 
-    let import = encoding.use_crate;
     let enum_name = LitStr::new(&ident_name.to_string(), Span::call_site());
+    let inner_impl = validated_type(
+        quote! {
+            Ok(match #repr::#decode_name(&mut d)? {
+                #inner_impl
+                unknown => Err(#import::Error::EnumValueNotKnown(#enum_name, unknown as usize))?
+            })
+        },
+        validate.as_ref(),
+        &import,
+    );
 
     Ok(quote! {
         impl #impl_generics #import::#trait_name for #ident_name #ty_generics #where_clause {
             #[allow(clippy::init_numbered_fields)]
-            fn #decode_name<D: ::std::io::Read>(mut d: D) -> ::core::result::Result<Self, #import::Error> {
+            fn #decode_name<D: #import::io::Read>(mut d: D) -> ::core::result::Result<Self, #import::Error> {
                 use #import::#trait_name;
-                Ok(match #repr::#decode_name(&mut d)? {
-                    #inner_impl
-                    unknown => Err(#import::Error::EnumValueNotKnown(#enum_name, unknown as usize))?
-                })
+                #version
+                #inner_impl
             }
         }
     })
 }
 
+/// Generates code reading and checking version byte of a versioned data
+/// structure with the blocking decoder.
+fn blocking_version_impl(
+    encoding: &EncodingDerive,
+    decode_name: &Ident,
+    ident_name: &Ident,
+) -> TokenStream2 {
+    let import = &encoding.use_crate;
+    version_impl(
+        encoding,
+        ident_name,
+        quote! { u8::#decode_name(&mut d)? },
+        quote! { <Self as #import::StrictMigrate>::strict_migrate(version, &mut d) },
+    )
+}
+
+/// Generates code reading version byte of a versioned data structure with
+/// `read` expression and checking it. Older versions are decoded with
+/// `migrate` expression if the structure is marked with `migrate` attribute.
+pub(crate) fn version_impl(
+    encoding: &EncodingDerive,
+    ident_name: &Ident,
+    read: TokenStream2,
+    migrate: TokenStream2,
+) -> TokenStream2 {
+    let import = &encoding.use_crate;
+    let version = match encoding.version {
+        Some(version) => version,
+        None => return TokenStream2::new(),
+    };
+    let newer = LitStr::new(
+        &format!(
+            "`{}` data have a version newer than the supported version {}",
+            ident_name, version
+        ),
+        Span::call_site(),
+    );
+    let older = if encoding.migrate {
+        let migrated =
+            validated_type(migrate, encoding.validate.as_ref(), import);
+        quote! {
+            return #migrated;
+        }
+    } else {
+        let older = LitStr::new(
+            &format!(
+                "`{}` data have a version older than the supported version {}",
+                ident_name, version
+            ),
+            Span::call_site(),
+        );
+        quote! {
+            return Err(#import::Error::UnsupportedDataStructure(#older));
+        }
+    };
+    quote! {
+        let version = #read;
+        match version.cmp(&#version) {
+            ::core::cmp::Ordering::Equal => {}
+            ::core::cmp::Ordering::Greater => {
+                return Err(#import::Error::UnsupportedDataStructure(#newer));
+            }
+            ::core::cmp::Ordering::Less => { #older }
+        }
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn decode_fields_impl<'a>(
     attr_name: &'static str,
     crate_name: &Ident,
+    import: &Path,
     trait_name: &Ident,
     decode_name: &Ident,
     deserialize_name: &Ident,
     ident_name: &Ident,
     fields: impl IntoIterator<Item = &'a Field>,
     mut parent_param: ParametrizedAttr,
-    is_enum: bool,
+    variant: Option<&Ident>,
     tlv_encoding: bool,
-) -> Result<TokenStream2> {
+) -> Result<(TokenStream2, Option<TokenStream2>)> {
     let mut stream = TokenStream2::new();
+    let is_enum = variant.is_some();
+    let context = error_context(ident_name, variant);
 
     let use_tlv = parent_param.args.contains_key(USE_TLV);
     parent_param.args.remove(CRATE);
+    let bolt = parent_param.args.contains_key(BOLT);
     parent_param.args.remove(USE_TLV);
+    parent_param.args.remove(BOLT);
+    parent_param.args.remove(VERSION);
+    parent_param.args.remove(MIGRATE);
+    parent_param.args.remove(BOUND);
+    parent_param.args.remove(VALIDATE);
 
     if !tlv_encoding && use_tlv {
         return Err(Error::new(
@@ -284,19 +416,20 @@ fn decode_fields_impl<'a>(
         ));
     }
 
-    let parent_attr = EncodingDerive::with(
+    let _ = EncodingDerive::with(
         &mut parent_param.clone(),
         crate_name,
         false,
         is_enum,
         false,
     )?;
-    let import = parent_attr.use_crate;
 
     let mut skipped_fields = vec![];
     let mut strict_fields = vec![];
     let mut tlv_fields = bmap! {};
     let mut tlv_aggregator = None;
+    let mut validators = bmap! {};
+    let mut adapters = bmap! {};
 
     for (index, field) in fields.into_iter().enumerate() {
         let mut local_param = ParametrizedAttr::with(attr_name, &field.attrs)?;
@@ -329,6 +462,12 @@ fn decode_fields_impl<'a>(
             skipped_fields.push(name);
             continue;
         }
+        if let Some(validate) = encoding.validate {
+            validators.insert(name.to_string(), (validate, &field.ty));
+        }
+        if let Some(adapter) = encoding.adapter {
+            adapters.insert(name.to_string(), adapter.module(import));
+        }
 
         encoding.tlv.unwrap_or(TlvDerive::None).process(
             field,
@@ -340,8 +479,22 @@ fn decode_fields_impl<'a>(
     }
 
     for name in strict_fields {
+        let name_lit = field_lit(&name);
+        let decode = match adapters.get(&name.to_string()) {
+            Some(module) => quote! { #module::#decode_name(&mut d) },
+            None => quote! { #import::#trait_name::#decode_name(&mut d) },
+        };
+        let value = validated_field(
+            decode,
+            validators
+                .get(&name.to_string())
+                .map(|(validate, ty)| (validate, *ty)),
+            import,
+            &name_lit,
+            &context,
+        );
         stream.append_all(quote_spanned! { Span::call_site() =>
-            #name: #import::#trait_name::#decode_name(&mut d)?,
+            #name: #value,
         });
     }
 
@@ -354,48 +507,66 @@ fn decode_fields_impl<'a>(
         });
     }
 
+    let mut tlv = None;
     if !is_enum {
         if use_tlv {
             let mut inner = TokenStream2::new();
             for (type_no, (name, optional)) in tlv_fields {
+                let type_no = tlv_lit(type_no);
                 if optional {
                     inner.append_all(quote_spanned! { Span::call_site() =>
-                        #type_no => s.#name = Some(#import::#trait_name::#deserialize_name(bytes)?),
+                        #type_no => self.#name = Some(
+                            #import::#trait_name::#deserialize_name(bytes)
+                                .map_err(|err| err.in_tlv(#type_no)#context)?
+                        ),
                     });
                 } else {
                     inner.append_all(quote_spanned! { Span::call_site() =>
-                        #type_no => s.#name = #import::#trait_name::#deserialize_name(bytes)?,
+                        #type_no => self.#name =
+                            #import::#trait_name::#deserialize_name(bytes)
+                                .map_err(|err| err.in_tlv(#type_no)#context)?,
                     });
                 }
             }
 
-            let aggregator = if let Some(ref tlv_aggregator) = tlv_aggregator {
-                quote_spanned! { Span::call_site() =>
-                    _ if *type_no % 2 == 0 => return Err(#import::TlvError::UnknownEvenType(*type_no).into()),
-                    _ => { s.#tlv_aggregator.insert(type_no, bytes); },
+            let unknown = match tlv_aggregator {
+                Some(ref tlv_aggregator) => {
+                    quote_spanned! { Span::call_site() =>
+                        _ => { self.#tlv_aggregator.insert(type_no, bytes); }
+                    }
                 }
-            } else {
-                quote_spanned! { Span::call_site() =>
-                    _ if *type_no % 2 == 0 => return Err(#import::TlvError::UnknownEvenType(*type_no).into()),
+                None => quote_spanned! { Span::call_site() =>
                     _ => {}
-                }
+                },
             };
 
-            stream = quote_spanned! { Span::call_site() =>
-                let mut s = #ident_name { #stream };
-                let tlvs = internet2::tlv::Stream::#decode_name(&mut d)?;
-            };
-
-            stream.append_all(quote_spanned! { Span::call_site() =>
-                for (type_no, bytes) in tlvs {
-                    match *type_no as usize {
+            tlv = Some(quote_spanned! { Span::call_site() =>
+                for (type_no, bytes) in stream {
+                    match type_no {
                         #inner
-
-                        #aggregator
+                        _ if type_no % 2 == 0 => {
+                            let err = #import::Error::from(
+                                #import::TlvError::UnknownEvenType(type_no)
+                            );
+                            return Err(err.in_tlv(type_no as usize)#context);
+                        }
+                        #unknown
                     }
                 }
-                Ok(s)
+                Ok(())
             });
+
+            let read_tlvs = if bolt {
+                quote! { #import::TlvStream::bolt_decode(&mut d)? }
+            } else {
+                quote! { #import::TlvStream::#decode_name(&mut d)? }
+            };
+            stream = quote_spanned! { Span::call_site() =>
+                let mut s = #ident_name { #stream };
+                let tlvs = #read_tlvs;
+                #import::ApplyTlvStream::apply_tlv_stream(&mut s, tlvs)?;
+                Ok(s)
+            };
         } else {
             stream = quote_spanned! { Span::call_site() =>
                 Ok(#ident_name { #stream })
@@ -403,5 +574,5 @@ fn decode_fields_impl<'a>(
         }
     }
 
-    Ok(stream)
+    Ok((stream, tlv))
 }
@@ -17,28 +17,46 @@ use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::{ToTokens, TokenStreamExt};
 use syn::spanned::Spanned;
 use syn::{
-    Data, DataEnum, DataStruct, DeriveInput, Error, Field, Fields, Ident,
-    ImplGenerics, Index, Result, TypeGenerics, WhereClause,
+    Data, DataEnum, DataStruct, DeriveInput, Error, Fields, Ident,
+    ImplGenerics, Index, Path, Result, Type, TypeGenerics, WhereClause,
 };
 
-use crate::param::{EncodingDerive, TlvDerive, CRATE, REPR, USE_TLV};
+use crate::bound::bounded_generics;
+use crate::param::{
+    check_variant_param, tlv_lit, EncodingDerive, TlvDerive, BOLT, BOUND,
+    CRATE, MIGRATE, REPR, USE_TLV, VALIDATE, VERSION,
+};
 
 /// Performs actual derivation of the encode trait using the provided
 /// information about trait parameters and requirements for TLV support.
 ///
+/// The crate `crate_name` must export `io` module with `Write` trait, which
+/// is used by the trait methods, and `LenCounter` writer, which is used for
+/// computing length of the fields encoded with adapters from `adapters`
+/// module (or custom modules). Besides encoding, the derived trait must
+/// provide `FIXED_SIZE: Option<usize>` associated constant and
+/// `#encoded_len_name(&self)` method, which are derived for all types except
+/// ones using TLV extensions; their implementation relies on
+/// `fixed_size_sum` and `fixed_size_same` functions exported by the crate.
+///
 /// You will find example of the function use in the
 /// [crate top-level documentation][crate].
+#[allow(clippy::too_many_arguments)]
 pub fn encode_derive(
     attr_name: &'static str,
     crate_name: Ident,
     trait_name: Ident,
     encode_name: Ident,
     serialize_name: Ident,
+    encoded_len_name: Ident,
     input: DeriveInput,
     tlv_encoding: bool,
 ) -> Result<TokenStream2> {
-    let (impl_generics, ty_generics, where_clause) =
-        input.generics.split_for_impl();
+    let generics =
+        bounded_generics(attr_name, &crate_name, &input, |import| {
+            quote! { #import::#trait_name }
+        })?;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     let ident_name = &input.ident;
 
     let global_param = ParametrizedAttr::with(attr_name, &input.attrs)?;
@@ -50,6 +68,7 @@ pub fn encode_derive(
             &trait_name,
             &encode_name,
             &serialize_name,
+            &encoded_len_name,
             data,
             ident_name,
             global_param,
@@ -64,6 +83,7 @@ pub fn encode_derive(
             &trait_name,
             &encode_name,
             &serialize_name,
+            &encoded_len_name,
             data,
             ident_name,
             global_param,
@@ -85,6 +105,7 @@ fn encode_struct_impl(
     trait_name: &Ident,
     encode_name: &Ident,
     serialize_name: &Ident,
+    encoded_len_name: &Ident,
     data: DataStruct,
     ident_name: &Ident,
     mut global_param: ParametrizedAttr,
@@ -108,37 +129,78 @@ fn encode_struct_impl(
         ));
     }
 
-    let inner_impl = match data.fields {
-        Fields::Named(ref fields) => encode_fields_impl(
-            attr_name,
-            crate_name,
-            trait_name,
-            encode_name,
-            serialize_name,
-            &fields.named,
-            global_param,
-            false,
-            tlv_encoding,
-        )?,
-        Fields::Unnamed(ref fields) => encode_fields_impl(
-            attr_name,
-            crate_name,
-            trait_name,
-            encode_name,
-            serialize_name,
-            &fields.unnamed,
-            global_param,
-            false,
-            tlv_encoding,
-        )?,
-        Fields::Unit => quote! { Ok(0) },
+    let import = encoding.use_crate;
+    let fields = encode_fields_impl(
+        attr_name,
+        crate_name,
+        &import,
+        encode_name,
+        serialize_name,
+        &data.fields,
+        global_param,
+        false,
+        tlv_encoding,
+    )?;
+
+    let mut inner_impl = version_impl(encoding.version, encode_name);
+    inner_impl.append_all(fields.encode.clone());
+
+    let tlv_impl = match fields.tlv {
+        Some(ref tlv) => {
+            let write_tlvs = if encoding.bolt {
+                ident!(bolt_encode)
+            } else {
+                encode_name.clone()
+            };
+            inner_impl.append_all(quote! {
+                len += #import::ToTlvStream::to_tlv_stream(data)?.#write_tlvs(&mut e)?;
+            });
+            quote! {
+                impl #impl_generics #import::ToTlvStream for #ident_name #ty_generics #where_clause {
+                    fn to_tlv_stream(&self) -> ::core::result::Result<#import::TlvStream, #import::Error> {
+                        use #import::#trait_name;
+                        let data = self;
+                        let mut tlvs = #import::TlvStream::new();
+                        #tlv
+                        Ok(tlvs)
+                    }
+                }
+            }
+        }
+        None => TokenStream2::new(),
     };
 
-    let import = encoding.use_crate;
+    let len_impl = if fields.tlv.is_some() {
+        TokenStream2::new()
+    } else {
+        let mut sizes = fields.sizes(&import, trait_name);
+        let mut version_len = 0usize;
+        if encoding.version.is_some() {
+            sizes
+                .insert(0, quote! { <u8 as #import::#trait_name>::FIXED_SIZE });
+            version_len = 1;
+        }
+        let lens =
+            fields.lens(&import, trait_name, encode_name, encoded_len_name);
+        quote! {
+            const FIXED_SIZE: ::core::option::Option<usize> =
+                #import::fixed_size_sum(&[#( #sizes ),*]);
+
+            fn #encoded_len_name(&self) -> ::core::result::Result<usize, #import::Error> {
+                if let Some(len) = <Self as #import::#trait_name>::FIXED_SIZE {
+                    return Ok(len);
+                }
+                let data = self;
+                Ok(#version_len #( + #lens )*)
+            }
+        }
+    };
 
     Ok(quote! {
         impl #impl_generics #import::#trait_name for #ident_name #ty_generics #where_clause {
-            fn #encode_name<E: ::std::io::Write>(&self, mut e: E) -> ::core::result::Result<usize, #import::Error> {
+            #len_impl
+
+            fn #encode_name<E: #import::io::Write>(&self, mut e: E) -> ::core::result::Result<usize, #import::Error> {
                 use #import::#trait_name;
                 let mut len = 0;
                 let data = self;
@@ -146,6 +208,8 @@ fn encode_struct_impl(
                 Ok(len)
             }
         }
+
+        #tlv_impl
     })
 }
 
@@ -156,6 +220,7 @@ fn encode_enum_impl(
     trait_name: &Ident,
     encode_name: &Ident,
     serialize_name: &Ident,
+    encoded_len_name: &Ident,
     data: DataEnum,
     ident_name: &Ident,
     mut global_param: ParametrizedAttr,
@@ -166,25 +231,34 @@ fn encode_enum_impl(
     let encoding =
         EncodingDerive::with(&mut global_param, crate_name, true, true, false)?;
     let repr = encoding.repr;
+    let import = encoding.use_crate;
+    let version = version_impl(encoding.version, encode_name);
 
     let mut inner_impl = TokenStream2::new();
+    let mut len_impl = TokenStream2::new();
+    let mut variant_sizes = vec![];
 
     for (order, variant) in data.variants.iter().enumerate() {
         let mut local_param =
             ParametrizedAttr::with(attr_name, &variant.attrs)?;
 
         // First, test individual attribute
-        let _ = EncodingDerive::with(
+        let local = EncodingDerive::with(
             &mut local_param,
             crate_name,
             false,
             true,
             false,
         )?;
+        check_variant_param(&local)?;
         // Second, combine global and local together
         let mut combined = global_param.clone().merged(local_param.clone())?;
         combined.args.remove(REPR);
         combined.args.remove(CRATE);
+        combined.args.remove(VERSION);
+        combined.args.remove(MIGRATE);
+        combined.args.remove(BOUND);
+        combined.args.remove(VALIDATE);
         let encoding = EncodingDerive::with(
             &mut combined,
             crate_name,
@@ -211,37 +285,23 @@ fn encode_enum_impl(
             })
             .collect::<Vec<_>>();
 
-        let (field_impl, bra_captures_ket) = match variant.fields {
-            Fields::Named(ref fields) => (
-                encode_fields_impl(
-                    attr_name,
-                    crate_name,
-                    trait_name,
-                    encode_name,
-                    serialize_name,
-                    &fields.named,
-                    local_param,
-                    true,
-                    false,
-                )?,
-                quote! { { #( #captures ),* } },
-            ),
-            Fields::Unnamed(ref fields) => (
-                encode_fields_impl(
-                    attr_name,
-                    crate_name,
-                    trait_name,
-                    encode_name,
-                    serialize_name,
-                    &fields.unnamed,
-                    local_param,
-                    true,
-                    false,
-                )?,
-                quote! { ( #( #captures ),* ) },
-            ),
-            Fields::Unit => (TokenStream2::new(), TokenStream2::new()),
+        let fields = encode_fields_impl(
+            attr_name,
+            crate_name,
+            &import,
+            encode_name,
+            serialize_name,
+            &variant.fields,
+            local_param,
+            true,
+            false,
+        )?;
+        let bra_captures_ket = match variant.fields {
+            Fields::Named(_) => quote! { { #( #captures ),* } },
+            Fields::Unnamed(_) => quote! { ( #( #captures ),* ) },
+            Fields::Unit => TokenStream2::new(),
         };
+        let field_impl = &fields.encode;
 
         let captures = match captures.len() {
             0 => quote! {},
@@ -263,16 +323,60 @@ fn encode_enum_impl(
                 #field_impl
             }
         });
+
+        let sizes = fields.sizes(&import, trait_name);
+        variant_sizes.push(quote! {
+            #import::fixed_size_sum(&[
+                <#repr as #import::#trait_name>::FIXED_SIZE,
+                #( #sizes ),*
+            ])
+        });
+        let lens =
+            fields.lens(&import, trait_name, encode_name, encoded_len_name);
+        len_impl.append_all(quote_spanned! { variant.span() =>
+            #[allow(clippy::unnecessary_cast)]
+            Self::#ident #bra_captures_ket => {
+                #captures
+                #import::#trait_name::#encoded_len_name(&(#value as #repr))?
+                    #( + #lens )*
+            }
+        });
     }
 
-    let import = encoding.use_crate;
+    let (fixed_size, version_len) = match encoding.version {
+        Some(_) => (
+            quote! {
+                #import::fixed_size_sum(&[
+                    <u8 as #import::#trait_name>::FIXED_SIZE,
+                    #import::fixed_size_same(&[#( #variant_sizes ),*]),
+                ])
+            },
+            1usize,
+        ),
+        None => (
+            quote! { #import::fixed_size_same(&[#( #variant_sizes ),*]) },
+            0usize,
+        ),
+    };
 
     Ok(quote! {
         impl #impl_generics #import::#trait_name for #ident_name #ty_generics #where_clause {
+            const FIXED_SIZE: ::core::option::Option<usize> = #fixed_size;
+
+            fn #encoded_len_name(&self) -> ::core::result::Result<usize, #import::Error> {
+                if let Some(len) = <Self as #import::#trait_name>::FIXED_SIZE {
+                    return Ok(len);
+                }
+                Ok(#version_len + match self {
+                    #len_impl
+                })
+            }
+
             #[inline]
-            fn #encode_name<E: ::std::io::Write>(&self, mut e: E) -> ::core::result::Result<usize, #import::Error> {
+            fn #encode_name<E: #import::io::Write>(&self, mut e: E) -> ::core::result::Result<usize, #import::Error> {
                 use #import::#trait_name;
                 let mut len = 0;
+                #version
                 match self {
                     #inner_impl
                 }
@@ -282,23 +386,98 @@ fn encode_enum_impl(
     })
 }
 
+/// Generates code writing version byte of a versioned data structure.
+fn version_impl(version: Option<u8>, encode_name: &Ident) -> TokenStream2 {
+    match version {
+        Some(version) => quote! {
+            len += #version.#encode_name(&mut e)?;
+        },
+        None => TokenStream2::new(),
+    }
+}
+
+/// Encoding of the data structure fields, together with the information
+/// required for computing their encoded length.
+struct FieldsImpl {
+    /// Code encoding all the fields
+    encode: TokenStream2,
+    /// Strict-encoded (i.e. not skipped and not TLV) fields
+    fields: Vec<StrictField>,
+    /// Code collecting TLV fields into `tlvs` stream, if the fields use TLV
+    /// extensions
+    tlv: Option<TokenStream2>,
+}
+
+/// Field encoded as a part of the data structure
+struct StrictField {
+    /// Name (or index) of the field
+    name: TokenStream2,
+    /// Expression referencing the field value from `data` variable
+    value: TokenStream2,
+    /// Type of the field
+    ty: Type,
+    /// Path to the adapter module encoding the field, if any
+    adapter: Option<TokenStream2>,
+}
+
+impl FieldsImpl {
+    fn sizes(&self, import: &Path, trait_name: &Ident) -> Vec<TokenStream2> {
+        self.fields
+            .iter()
+            .map(|field| match field.adapter {
+                // Adapters do not provide information about the encoded size
+                Some(_) => quote! { None },
+                None => {
+                    let ty = &field.ty;
+                    quote! { <#ty as #import::#trait_name>::FIXED_SIZE }
+                }
+            })
+            .collect()
+    }
+
+    fn lens(
+        &self,
+        import: &Path,
+        trait_name: &Ident,
+        encode_name: &Ident,
+        encoded_len_name: &Ident,
+    ) -> Vec<TokenStream2> {
+        self.fields
+            .iter()
+            .map(|StrictField { value, adapter, .. }| match adapter {
+                Some(module) => quote! {
+                    #module::#encode_name(#value, #import::io::LenCounter::default())?
+                },
+                None => quote! {
+                    #import::#trait_name::#encoded_len_name(#value)?
+                },
+            })
+            .collect()
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
-fn encode_fields_impl<'a>(
+fn encode_fields_impl(
     attr_name: &'static str,
     crate_name: &Ident,
-    _trait_name: &Ident,
+    import: &Path,
     encode_name: &Ident,
     serialize_name: &Ident,
-    fields: impl IntoIterator<Item = &'a Field>,
+    fields: &Fields,
     mut parent_param: ParametrizedAttr,
     is_enum: bool,
     tlv_encoding: bool,
-) -> Result<TokenStream2> {
+) -> Result<FieldsImpl> {
     let mut stream = TokenStream2::new();
 
     let use_tlv = parent_param.args.contains_key(USE_TLV);
     parent_param.args.remove(CRATE);
     parent_param.args.remove(USE_TLV);
+    parent_param.args.remove(BOLT);
+    parent_param.args.remove(VERSION);
+    parent_param.args.remove(MIGRATE);
+    parent_param.args.remove(BOUND);
+    parent_param.args.remove(VALIDATE);
 
     if !tlv_encoding && use_tlv {
         return Err(Error::new(
@@ -308,10 +487,11 @@ fn encode_fields_impl<'a>(
     }
 
     let mut strict_fields = vec![];
+    let mut adapted = vec![];
     let mut tlv_fields = bmap! {};
     let mut tlv_aggregator = None;
 
-    for (index, field) in fields.into_iter().enumerate() {
+    for (index, field) in fields.iter().enumerate() {
         let mut local_param = ParametrizedAttr::with(attr_name, &field.attrs)?;
 
         // First, test individual attribute
@@ -347,6 +527,7 @@ fn encode_fields_impl<'a>(
                 .unwrap_or(index)
         };
 
+        let strict_count = strict_fields.len();
         encoding.tlv.unwrap_or(TlvDerive::None).process(
             field,
             name,
@@ -354,45 +535,80 @@ fn encode_fields_impl<'a>(
             &mut tlv_fields,
             &mut tlv_aggregator,
         )?;
+        if strict_fields.len() > strict_count {
+            adapted.push((
+                field.ty.clone(),
+                encoding.adapter.map(|adapter| adapter.module(import)),
+            ));
+        }
     }
 
-    for name in strict_fields {
-        stream.append_all(quote_spanned! { Span::call_site() =>
-            len += data.#name.#encode_name(&mut e)?;
+    let strict_fields = strict_fields
+        .into_iter()
+        .zip(adapted)
+        .map(|(name, (ty, adapter))| StrictField {
+            // Enum variant fields are captured by reference
+            value: if is_enum {
+                quote! { data.#name }
+            } else {
+                quote! { &data.#name }
+            },
+            name,
+            ty,
+            adapter,
+        })
+        .collect::<Vec<_>>();
+
+    for StrictField {
+        name,
+        value,
+        adapter,
+        ..
+    } in &strict_fields
+    {
+        stream.append_all(match adapter {
+            Some(module) => quote_spanned! { Span::call_site() =>
+                len += #module::#encode_name(#value, &mut e)?;
+            },
+            None => quote_spanned! { Span::call_site() =>
+                len += data.#name.#encode_name(&mut e)?;
+            },
         })
     }
 
-    if use_tlv {
-        stream.append_all(quote_spanned! { Span::call_site() =>
-            let mut tlvs = internet2::tlv::Stream::default();
-        });
+    let tlv = if use_tlv {
+        let mut tlv = TokenStream2::new();
+        if let Some(name) = tlv_aggregator {
+            tlv.append_all(quote_spanned! { Span::call_site() =>
+                for (type_no, val) in &data.#name {
+                    tlvs.insert(*type_no, val);
+                }
+            });
+        }
         for (type_no, (name, optional)) in tlv_fields {
+            let type_no = tlv_lit(type_no);
             if optional {
-                stream.append_all(quote_spanned! { Span::call_site() =>
+                tlv.append_all(quote_spanned! { Span::call_site() =>
                     if let Some(val) = &data.#name {
-                        tlvs.insert(#type_no.into(), val.#serialize_name()?);
+                        tlvs.insert(#type_no, val.#serialize_name()?);
                     }
                 });
             } else {
-                stream.append_all(quote_spanned! { Span::call_site() =>
+                tlv.append_all(quote_spanned! { Span::call_site() =>
                     if data.#name.iter().count() > 0 {
-                        tlvs.insert(#type_no.into(), data.#name.#serialize_name()?);
+                        tlvs.insert(#type_no, data.#name.#serialize_name()?);
                     }
                 });
             }
         }
-        if let Some(name) = tlv_aggregator {
-            stream.append_all(quote_spanned! { Span::call_site() =>
-                for (type_no, val) in &data.#name {
-                    tlvs.insert(*type_no, val);
-                }
-            });
-        }
-
-        stream.append_all(quote_spanned! { Span::call_site() =>
-            len += tlvs.#encode_name(&mut e)?;
-        })
-    }
+        Some(tlv)
+    } else {
+        None
+    };
 
-    Ok(stream)
+    Ok(FieldsImpl {
+        encode: stream,
+        fields: strict_fields,
+        tlv,
+    })
 }
@@ -39,6 +39,7 @@
 //!         ident!(StrictEncode),
 //!         ident!(strict_encode),
 //!         ident!(strict_serialize),
+//!         ident!(strict_encoded_len),
 //!         derive_input,
 //!         TlvEncoding::Denied,
 //!     )
@@ -53,9 +54,16 @@ extern crate amplify;
 #[macro_use]
 extern crate quote;
 
+mod asynchronous;
+mod borrowed;
+mod bound;
 mod decode;
 mod encode;
 mod param;
+mod schema;
 
+pub use asynchronous::{decode_async_derive, encode_async_derive};
+pub use borrowed::decode_borrowed_derive;
 pub use decode::decode_derive;
 pub use encode::encode_derive;
+pub use schema::schema_derive;
@@ -18,10 +18,12 @@ use amplify::proc_attr::{
     ArgValue, ArgValueReq, AttrReq, LiteralClass, ParametrizedAttr, ValueClass,
 };
 use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::ToTokens;
+use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 use syn::{
-    AngleBracketedGenericArguments, Error, Field, GenericArgument, Ident,
-    LitInt, Path, PathArguments, PathSegment, Result, Type, TypePath,
+    Attribute, Error, Field, Fields, Ident, Index, LitInt, LitStr, Path,
+    PathSegment, Result, Token, Type, TypePath, WherePredicate,
 };
 
 pub(crate) const CRATE: &str = "crate";
@@ -31,8 +33,21 @@ pub(crate) const VALUE: &str = "value";
 pub(crate) const BY_ORDER: &str = "by_order";
 pub(crate) const BY_VALUE: &str = "by_value";
 pub(crate) const USE_TLV: &str = "use_tlv";
+pub(crate) const BOLT: &str = "bolt";
 pub(crate) const TLV: &str = "tlv";
 pub(crate) const UNKNOWN_TLVS: &str = "unknown_tlvs";
+pub(crate) const VERSION: &str = "version";
+pub(crate) const MIGRATE: &str = "migrate";
+pub(crate) const BOUND: &str = "bound";
+pub(crate) const VALIDATE: &str = "validate";
+pub(crate) const WITH: &str = "with";
+pub(crate) const AS_U32_LEN: &str = "as_u32_len";
+pub(crate) const FIXED_BYTES: &str = "fixed_bytes";
+pub(crate) const UNIFORM: &str = "uniform";
+
+/// Names of the encoding adapters from `adapters` module of the encoding
+/// crate, which can be applied to a field with a shorthand attribute
+const ADAPTERS: [&str; 3] = [AS_U32_LEN, FIXED_BYTES, UNIFORM];
 
 const EXPECT: &str =
     "amplify_syn is broken: requirements for crate arg are not satisfied";
@@ -47,6 +62,56 @@ pub(crate) struct EncodingDerive {
     /// `None` if TLVs are not allowed at the struct level with
     /// `#[strict_encoding(use_tlv)]` attribute
     pub tlv: Option<TlvDerive>,
+    /// Whether TLV stream must be encoded according to BOLT-1 rules
+    pub bolt: bool,
+    /// Version of the data structure, which is encoded as a byte preceding
+    /// the data, if the type is versioned with
+    /// `#[strict_encoding(version = N)]` attribute
+    pub version: Option<u8>,
+    /// Whether older versions of the data structure must be decoded with
+    /// `StrictMigrate` implementation
+    pub migrate: bool,
+    /// Where predicates provided with `#[strict_encoding(bound = "...")]`
+    /// attribute, which replace the inferred trait bounds
+    pub bound: Option<Vec<WherePredicate>>,
+    /// Path to the function validating decoded data, provided with
+    /// `#[strict_encoding(validate = "path::to::fn")]` attribute
+    pub validate: Option<Path>,
+    /// Module encoding and decoding the field, provided with
+    /// `#[strict_encoding(with = "path::to::module")]` attribute or with
+    /// one of the shorthand adapter attributes
+    pub adapter: Option<FieldAdapter>,
+}
+
+/// Module providing encoding functions for a field
+#[derive(Clone)]
+pub(crate) enum FieldAdapter {
+    /// Module provided by the user
+    Custom(Path),
+    /// Module from `adapters` module of the encoding crate
+    Builtin(Ident),
+}
+
+impl FieldAdapter {
+    /// Returns path to the adapter module
+    pub fn module(&self, import: &Path) -> TokenStream2 {
+        match self {
+            FieldAdapter::Custom(path) => path.to_token_stream(),
+            FieldAdapter::Builtin(name) => quote! { #import::adapters::#name },
+        }
+    }
+
+    /// Generates expression describing the type `ty` of the field encoded
+    /// with the adapter. Built-in adapters are generic over the field type.
+    pub fn strict_type(&self, import: &Path, ty: &Type) -> TokenStream2 {
+        let module = self.module(import);
+        match self {
+            FieldAdapter::Custom(_) => quote! { #module::strict_type() },
+            FieldAdapter::Builtin(_) => {
+                quote! { #module::strict_type::<#ty>() }
+            }
+        }
+    }
 }
 
 #[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
@@ -67,13 +132,24 @@ impl EncodingDerive {
         let mut map = if is_global {
             map! {
                 CRATE => ArgValueReq::with_default(crate_name.clone()),
-                USE_TLV => ArgValueReq::with_default(true)
+                USE_TLV => ArgValueReq::with_default(true),
+                BOLT => ArgValueReq::Prohibited,
+                VERSION => ArgValueReq::Optional(ValueClass::Literal(LiteralClass::Int)),
+                MIGRATE => ArgValueReq::Prohibited,
+                BOUND => ArgValueReq::Optional(ValueClass::Literal(LiteralClass::Str)),
+                VALIDATE => ArgValueReq::Optional(ValueClass::Literal(LiteralClass::Str))
             }
         } else {
             map! {
                 SKIP => ArgValueReq::Prohibited,
                 TLV => ArgValueReq::Optional(ValueClass::Literal(LiteralClass::Int)),
-                UNKNOWN_TLVS => ArgValueReq::with_default(true)
+                UNKNOWN_TLVS => ArgValueReq::with_default(true),
+                BOUND => ArgValueReq::Optional(ValueClass::Literal(LiteralClass::Str)),
+                VALIDATE => ArgValueReq::Optional(ValueClass::Literal(LiteralClass::Str)),
+                WITH => ArgValueReq::Optional(ValueClass::Literal(LiteralClass::Str)),
+                AS_U32_LEN => ArgValueReq::Prohibited,
+                FIXED_BYTES => ArgValueReq::Prohibited,
+                UNIFORM => ArgValueReq::Prohibited
             }
         };
 
@@ -81,6 +157,7 @@ impl EncodingDerive {
             map.insert(BY_ORDER, ArgValueReq::Prohibited);
             map.insert(BY_VALUE, ArgValueReq::Prohibited);
             map.insert(USE_TLV, ArgValueReq::Prohibited);
+            map.insert(BOLT, ArgValueReq::Prohibited);
             map.insert(TLV, ArgValueReq::Prohibited);
             map.insert(UNKNOWN_TLVS, ArgValueReq::Prohibited);
             if is_global {
@@ -141,12 +218,130 @@ impl EncodingDerive {
             .transpose()
             .expect(EXPECT);
 
-        let skip = attr.args.get("skip").is_some();
+        let skip = attr.args.contains_key("skip");
 
         let by_order = !attr.args.contains_key("by_value");
 
         let tlv = TlvDerive::with(attr, is_global, use_tlv)?;
 
+        let bolt = attr.args.contains_key(BOLT);
+        if bolt && !attr.args.contains_key(USE_TLV) {
+            return Err(Error::new(
+                Span::call_site(),
+                "`bolt` attribute requires `use_tlv` to be set",
+            ));
+        }
+
+        let version = attr
+            .args
+            .get(VERSION)
+            .cloned()
+            .map(LitInt::try_from)
+            .transpose()
+            .expect(EXPECT)
+            .map(|version| {
+                version.base10_parse::<u8>().map_err(|_| {
+                    Error::new(
+                        version.span(),
+                        "`version` must be an 8-bit unsigned integer",
+                    )
+                })
+            })
+            .transpose()?;
+
+        let migrate = attr.args.contains_key(MIGRATE);
+        if migrate && version.is_none() {
+            return Err(Error::new(
+                Span::call_site(),
+                "`migrate` attribute requires `version` to be set",
+            ));
+        }
+
+        let bound = attr
+            .args
+            .get(BOUND)
+            .cloned()
+            .map(LitStr::try_from)
+            .transpose()
+            .expect(EXPECT)
+            .map(|bound| {
+                bound
+                    .parse_with(
+                        Punctuated::<WherePredicate, Token![,]>::parse_terminated,
+                    )
+                    .map(|predicates| predicates.into_iter().collect())
+            })
+            .transpose()?;
+
+        let validate = attr
+            .args
+            .get(VALIDATE)
+            .cloned()
+            .map(LitStr::try_from)
+            .transpose()
+            .expect(EXPECT)
+            .map(|validate| validate.parse::<Path>())
+            .transpose()?;
+        if validate.is_some() && !is_global {
+            if attr.args.contains_key(SKIP) {
+                return Err(Error::new(
+                    Span::call_site(),
+                    "`validate` attribute can't be used with skipped fields",
+                ));
+            }
+            if attr.args.contains_key(TLV)
+                || attr.args.contains_key(UNKNOWN_TLVS)
+            {
+                return Err(Error::new(
+                    Span::call_site(),
+                    "`validate` attribute can't be used with TLV fields; \
+                     validate them at the type level instead",
+                ));
+            }
+        }
+
+        let mut adapters = ADAPTERS
+            .iter()
+            .filter(|name| attr.args.contains_key(**name))
+            .map(|name| {
+                FieldAdapter::Builtin(Ident::new(name, Span::call_site()))
+            })
+            .collect::<Vec<_>>();
+        if let Some(with) = attr
+            .args
+            .get(WITH)
+            .cloned()
+            .map(LitStr::try_from)
+            .transpose()
+            .expect(EXPECT)
+        {
+            adapters.push(FieldAdapter::Custom(with.parse::<Path>()?));
+        }
+        if adapters.len() > 1 {
+            return Err(Error::new(
+                Span::call_site(),
+                "`with`, `as_u32_len`, `fixed_bytes` and `uniform` attributes \
+                 are mutually exclusive",
+            ));
+        }
+        let adapter = adapters.pop();
+        if adapter.is_some() {
+            if attr.args.contains_key(SKIP) {
+                return Err(Error::new(
+                    Span::call_site(),
+                    "encoding adapter can't be used with skipped fields",
+                ));
+            }
+            if attr.args.contains_key(TLV)
+                || attr.args.contains_key(UNKNOWN_TLVS)
+            {
+                return Err(Error::new(
+                    Span::call_site(),
+                    "encoding adapter can't be used with TLV fields",
+                ));
+            }
+        }
+
         Ok(EncodingDerive {
             use_crate,
             skip,
@@ -154,6 +349,12 @@ impl EncodingDerive {
             value,
             repr,
             tlv,
+            bolt,
+            version,
+            migrate,
+            bound,
+            validate,
+            adapter,
         })
     }
 }
@@ -264,66 +465,325 @@ impl TlvDerive {
             }
 
             TlvDerive::Unknown => {
-                if let Type::Path(TypePath { path, .. }) = &field.ty {
-                    if aggregator.is_some() {
-                        return Err(Error::new(
-                            field.span(),
-                            "unknown TLVs aggregator can be present only once",
-                        ));
-                    }
-                    if let Some(PathSegment {
-                        ident,
-                        arguments:
-                            PathArguments::AngleBracketed(
-                                AngleBracketedGenericArguments { args, .. },
-                            ),
-                    }) = path.segments.last()
-                    {
-                        if *ident == ident!(BTreeMap) && args.len() == 2 {
-                            match (&args[0], &args[1]) {
-                                (
-                                    GenericArgument::Type(Type::Path(path1)),
-                                    GenericArgument::Type(Type::Path(path2)),
-                                ) if path1.path.is_ident(&ident!(usize))
-                                    && path2
-                                        .path
-                                        .segments
-                                        .last()
-                                        .unwrap()
-                                        .ident
-                                        == ident!(Box) =>
-                                {
-                                    *aggregator = Some(name);
-                                    Ok(())
-                                }
-                                _ => Err(()),
-                            }
-                        } else {
-                            Err(())
-                        }
-                    } else if path
-                        .segments
-                        .last()
-                        .filter(|path| path.ident == ident!(Stream))
-                        .is_some()
+                if aggregator.is_some() {
+                    return Err(Error::new(
+                        field.span(),
+                        "unknown TLVs aggregator can be present only once",
+                    ));
+                }
+                match &field.ty {
+                    Type::Path(TypePath { path, .. })
+                        if path
+                            .segments
+                            .last()
+                            .filter(|path| path.ident == ident!(TlvStream))
+                            .is_some() =>
                     {
                         *aggregator = Some(name);
                         Ok(())
-                    } else {
-                        Err(())
                     }
-                } else {
-                    Err(())
-                }
-                .map_err(|_| {
-                    Error::new(
+                    _ => Err(Error::new(
                         field.span(),
                         "unknown TLVs aggregator field must be of \
-                         `BTreeMap<usize, Box<[u8]>>` or \
-                         `internet2::tlv::Stream` types",
-                    )
-                })
+                         `strict_encoding::TlvStream` type",
+                    )),
+                }
             }
         }
     }
 }
+
+/// Fails if TLV extensions were requested for a derivation which does not
+/// support them; `kind` names that derivation in the error message.
+pub(crate) fn check_no_tlv(
+    attr_name: &'static str,
+    kind: &str,
+    global_param: &ParametrizedAttr,
+    ident_name: &Ident,
+) -> Result<()> {
+    if global_param.args.contains_key(USE_TLV) {
+        return Err(Error::new(
+            ident_name.span(),
+            format!(
+                "TLV extensions are not supported by {} `{}`",
+                kind, attr_name
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Fails if versioned encoding was requested for a derivation which does not
+/// support it; `kind` names that derivation in the error message.
+pub(crate) fn check_unversioned(
+    attr_name: &'static str,
+    kind: &str,
+    global_param: &ParametrizedAttr,
+    ident_name: &Ident,
+) -> Result<()> {
+    if global_param.args.contains_key(VERSION) {
+        return Err(Error::new(
+            ident_name.span(),
+            format!(
+                "versioned encoding is not supported by {} `{}`",
+                kind, attr_name
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Generates method calls attaching type (and enum variant, if present) name
+/// to the error context of a failed field decoding.
+pub(crate) fn error_context(
+    ident_name: &Ident,
+    variant: Option<&Ident>,
+) -> TokenStream2 {
+    let type_lit = LitStr::new(&ident_name.to_string(), Span::call_site());
+    match variant {
+        Some(variant) => {
+            let variant_lit =
+                LitStr::new(&variant.to_string(), Span::call_site());
+            quote! { .in_variant(#variant_lit).in_type(#type_lit) }
+        }
+        None => quote! { .in_type(#type_lit) },
+    }
+}
+
+/// Returns unsuffixed integer literal for the TLV type id.
+pub(crate) fn tlv_lit(type_no: usize) -> LitInt {
+    LitInt::new(&type_no.to_string(), Span::call_site())
+}
+
+/// Returns string literal with the name (or index) of a field.
+pub(crate) fn field_lit(name: &TokenStream2) -> LitStr {
+    LitStr::new(&name.to_string(), Span::call_site())
+}
+
+/// Generates expression decoding a field with `decode` expression and, if
+/// the field has `validate` attribute, checking the decoded value of the
+/// field type with the provided function.
+pub(crate) fn validated_field(
+    decode: TokenStream2,
+    validate: Option<(&Path, &Type)>,
+    import: &Path,
+    name_lit: &LitStr,
+    context: &TokenStream2,
+) -> TokenStream2 {
+    let decode = quote! {
+        #decode.map_err(|err| err.in_field(#name_lit)#context)?
+    };
+    match validate {
+        None => decode,
+        Some((validate, ty)) => quote! {
+            {
+                let value: #ty = #decode;
+                #validate(&value).map_err(|err| {
+                    #import::Error::data_integrity(err).in_field(#name_lit)#context
+                })?;
+                value
+            }
+        },
+    }
+}
+
+/// Wraps `result` expression producing decoded data into a check with the
+/// function provided in type-level `validate` attribute, if any.
+pub(crate) fn validated_type(
+    result: TokenStream2,
+    validate: Option<&Path>,
+    import: &Path,
+) -> TokenStream2 {
+    match validate {
+        None => result,
+        Some(validate) => quote! {
+            {
+                let result: ::core::result::Result<Self, #import::Error> = { #result };
+                result.and_then(|value| {
+                    #validate(&value).map_err(#import::Error::data_integrity)?;
+                    Ok(value)
+                })
+            }
+        },
+    }
+}
+
+/// Fails if enum variant has `validate` attribute, which is supported only
+/// at the type and field level, or an encoding adapter, which is supported
+/// only at the field level.
+pub(crate) fn check_variant_param(encoding: &EncodingDerive) -> Result<()> {
+    if encoding.validate.is_some() {
+        return Err(Error::new(
+            Span::call_site(),
+            "`validate` attribute can't be used with enum variants; validate \
+             the whole enum or individual fields instead",
+        ));
+    }
+    if encoding.adapter.is_some() {
+        return Err(Error::new(
+            Span::call_site(),
+            "encoding adapters can't be used with enum variants; apply them \
+             to individual fields instead",
+        ));
+    }
+    Ok(())
+}
+
+/// Parses enum variant attributes, combining them with the global ones.
+pub(crate) fn variant_param(
+    attr_name: &'static str,
+    crate_name: &Ident,
+    global_param: &ParametrizedAttr,
+    attrs: &[Attribute],
+) -> Result<(ParametrizedAttr, EncodingDerive)> {
+    let mut local_param = ParametrizedAttr::with(attr_name, attrs)?;
+
+    // First, test individual attribute
+    let local =
+        EncodingDerive::with(&mut local_param, crate_name, false, true, false)?;
+    check_variant_param(&local)?;
+    // Second, combine global and local together
+    let mut combined = global_param.clone().merged(local_param.clone())?;
+    combined.args.remove(REPR);
+    combined.args.remove(CRATE);
+    combined.args.remove(VERSION);
+    combined.args.remove(MIGRATE);
+    combined.args.remove(BOUND);
+    combined.args.remove(VALIDATE);
+    let encoding =
+        EncodingDerive::with(&mut combined, crate_name, false, true, false)?;
+
+    Ok((local_param, encoding))
+}
+
+/// Returns name (or index) of each field, together with the flag whether the
+/// field must be skipped and the path to the function validating its decoded
+/// value. Fails if any of the fields uses encoding adapter, which are not
+/// supported by the derivation; `kind` names that derivation in the error
+/// message.
+pub(crate) fn plain_fields(
+    attr_name: &'static str,
+    kind: &str,
+    crate_name: &Ident,
+    fields: &Fields,
+    mut parent_param: ParametrizedAttr,
+    is_enum: bool,
+) -> Result<Vec<(TokenStream2, bool, Option<Path>)>> {
+    parent_param.args.remove(CRATE);
+    parent_param.args.remove(USE_TLV);
+    parent_param.args.remove(BOLT);
+    parent_param.args.remove(VERSION);
+    parent_param.args.remove(MIGRATE);
+    parent_param.args.remove(BOUND);
+    parent_param.args.remove(VALIDATE);
+
+    fields
+        .iter()
+        .enumerate()
+        .map(|(index, field): (usize, &Field)| {
+            let mut local_param =
+                ParametrizedAttr::with(attr_name, &field.attrs)?;
+
+            // First, test individual attribute
+            let _ = EncodingDerive::with(
+                &mut local_param,
+                crate_name,
+                false,
+                is_enum,
+                false,
+            )?;
+            // Second, combine global and local together
+            let mut combined = parent_param.clone().merged(local_param)?;
+            let encoding = EncodingDerive::with(
+                &mut combined,
+                crate_name,
+                false,
+                is_enum,
+                false,
+            )?;
+            if encoding.adapter.is_some() {
+                return Err(Error::new(
+                    field.span(),
+                    format!(
+                        "encoding adapters are not supported by {} `{}`",
+                        kind, attr_name
+                    ),
+                ));
+            }
+
+            let name = field
+                .ident
+                .as_ref()
+                .map(Ident::to_token_stream)
+                .unwrap_or_else(|| Index::from(index).to_token_stream());
+            Ok((name, encoding.skip, encoding.validate))
+        })
+        .collect()
+}
+
+/// Returns name (or index) of each field together with its encoding
+/// parameters. Fields of the structures with `use_tlv` attribute may be TLV
+/// fields, which are checked in the same way as by the blocking derivations.
+pub(crate) fn encoded_fields(
+    attr_name: &'static str,
+    crate_name: &Ident,
+    fields: &Fields,
+    mut parent_param: ParametrizedAttr,
+    is_enum: bool,
+) -> Result<Vec<(TokenStream2, EncodingDerive)>> {
+    let use_tlv = parent_param.args.contains_key(USE_TLV);
+    parent_param.args.remove(CRATE);
+    parent_param.args.remove(USE_TLV);
+    parent_param.args.remove(BOLT);
+    parent_param.args.remove(VERSION);
+    parent_param.args.remove(MIGRATE);
+    parent_param.args.remove(BOUND);
+    parent_param.args.remove(VALIDATE);
+
+    let mut strict_fields = vec![];
+    let mut tlv_fields = bmap! {};
+    let mut tlv_aggregator = None;
+
+    fields
+        .iter()
+        .enumerate()
+        .map(|(index, field): (usize, &Field)| {
+            let mut local_param =
+                ParametrizedAttr::with(attr_name, &field.attrs)?;
+
+            // First, test individual attribute
+            let _ = EncodingDerive::with(
+                &mut local_param,
+                crate_name,
+                false,
+                is_enum,
+                use_tlv,
+            )?;
+            // Second, combine global and local together
+            let mut combined = parent_param.clone().merged(local_param)?;
+            let encoding = EncodingDerive::with(
+                &mut combined,
+                crate_name,
+                false,
+                is_enum,
+                use_tlv,
+            )?;
+
+            let name = field
+                .ident
+                .as_ref()
+                .map(Ident::to_token_stream)
+                .unwrap_or_else(|| Index::from(index).to_token_stream());
+            if !encoding.skip {
+                encoding.tlv.unwrap_or(TlvDerive::None).process(
+                    field,
+                    name.clone(),
+                    &mut strict_fields,
+                    &mut tlv_fields,
+                    &mut tlv_aggregator,
+                )?;
+            }
+            Ok((name, encoding))
+        })
+        .collect()
+}
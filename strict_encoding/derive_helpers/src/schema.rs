@@ -0,0 +1,418 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+use std::collections::BTreeMap;
+
+use amplify::proc_attr::ParametrizedAttr;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::ToTokens;
+use syn::{
+    Data, DataEnum, DataStruct, DeriveInput, Error, Field, Fields, Ident,
+    ImplGenerics, Index, Path, Result, TypeGenerics, WhereClause,
+};
+
+use crate::bound::bounded_generics;
+use crate::param::{
+    check_variant_param, EncodingDerive, TlvDerive, BOLT, BOUND, CRATE,
+    MIGRATE, REPR, USE_TLV, VALIDATE, VERSION,
+};
+
+/// Performs actual derivation of the type schema trait using the provided
+/// information about trait parameters and requirements for TLV support.
+///
+/// The derived trait must have a single `strict_type()` method returning
+/// `TypeDescr` from the `schema` module of the crate `crate_name`.
+pub fn schema_derive(
+    attr_name: &'static str,
+    crate_name: Ident,
+    trait_name: Ident,
+    input: DeriveInput,
+    tlv_encoding: bool,
+) -> Result<TokenStream2> {
+    let generics =
+        bounded_generics(attr_name, &crate_name, &input, |import| {
+            quote! { #import::schema::#trait_name }
+        })?;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let ident_name = &input.ident;
+
+    let global_param = ParametrizedAttr::with(attr_name, &input.attrs)?;
+
+    match input.data {
+        Data::Struct(data) => schema_struct_impl(
+            attr_name,
+            &crate_name,
+            &trait_name,
+            data,
+            ident_name,
+            global_param,
+            impl_generics,
+            ty_generics,
+            where_clause,
+            tlv_encoding,
+        ),
+        Data::Enum(data) => schema_enum_impl(
+            attr_name,
+            &crate_name,
+            &trait_name,
+            data,
+            ident_name,
+            global_param,
+            impl_generics,
+            ty_generics,
+            where_clause,
+        ),
+        Data::Union(_) => Err(Error::new_spanned(
+            &input,
+            format!("Deriving `{}` is not supported in unions", trait_name),
+        )),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn schema_struct_impl(
+    attr_name: &'static str,
+    crate_name: &Ident,
+    trait_name: &Ident,
+    data: DataStruct,
+    ident_name: &Ident,
+    mut global_param: ParametrizedAttr,
+    impl_generics: ImplGenerics,
+    ty_generics: TypeGenerics,
+    where_clause: Option<&WhereClause>,
+    tlv_encoding: bool,
+) -> Result<TokenStream2> {
+    let encoding = EncodingDerive::with(
+        &mut global_param,
+        crate_name,
+        true,
+        false,
+        false,
+    )?;
+
+    if !tlv_encoding && encoding.tlv.is_some() {
+        return Err(Error::new(
+            ident_name.span(),
+            format!("TLV extensions are not allowed in `{}`", attr_name),
+        ));
+    }
+
+    if encoding.bolt {
+        return Err(Error::new(
+            ident_name.span(),
+            "BOLT-1 TLV streams can't be described with strict type schema",
+        ));
+    }
+
+    let import = encoding.use_crate;
+
+    let fields = match data.fields {
+        Fields::Named(ref fields) => schema_fields(
+            attr_name,
+            crate_name,
+            &import,
+            trait_name,
+            &fields.named,
+            global_param,
+            false,
+            tlv_encoding,
+        )?,
+        Fields::Unnamed(ref fields) => schema_fields(
+            attr_name,
+            crate_name,
+            &import,
+            trait_name,
+            &fields.unnamed,
+            global_param,
+            false,
+            tlv_encoding,
+        )?,
+        Fields::Unit => SchemaFields::default(),
+    };
+    let SchemaFields {
+        fields,
+        tlvs,
+        unknown_tlvs,
+    } = fields;
+
+    let name = ident_name.to_string();
+    let descr = versioned(encoding.version, &import, quote! {
+        #import::schema::TypeDescr::Struct(#import::schema::StructDescr {
+            name: ::core::convert::From::from(#name),
+            fields: ::core::convert::From::from([ #( #fields ),* ]),
+            tlvs: ::core::convert::From::from([ #( #tlvs ),* ]),
+            unknown_tlvs: #unknown_tlvs,
+        })
+    });
+
+    Ok(quote! {
+        impl #impl_generics #import::schema::#trait_name for #ident_name #ty_generics #where_clause {
+            fn strict_type() -> #import::schema::TypeDescr {
+                #descr
+            }
+        }
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn schema_enum_impl(
+    attr_name: &'static str,
+    crate_name: &Ident,
+    trait_name: &Ident,
+    data: DataEnum,
+    ident_name: &Ident,
+    mut global_param: ParametrizedAttr,
+    impl_generics: ImplGenerics,
+    ty_generics: TypeGenerics,
+    where_clause: Option<&WhereClause>,
+) -> Result<TokenStream2> {
+    let encoding =
+        EncodingDerive::with(&mut global_param, crate_name, true, true, false)?;
+    let repr = encoding.repr;
+    let by_order = encoding.by_order;
+    let version = encoding.version;
+    let import = encoding.use_crate;
+    let primitive =
+        Ident::new(&repr.to_string().to_uppercase(), Span::call_site());
+
+    let mut variants = vec![];
+
+    for (order, variant) in data.variants.iter().enumerate() {
+        let mut local_param =
+            ParametrizedAttr::with(attr_name, &variant.attrs)?;
+
+        // First, test individual attribute
+        let local = EncodingDerive::with(
+            &mut local_param,
+            crate_name,
+            false,
+            true,
+            false,
+        )?;
+        check_variant_param(&local)?;
+        // Second, combine global and local together
+        let mut combined = global_param.clone().merged(local_param.clone())?;
+        combined.args.remove(REPR);
+        combined.args.remove(CRATE);
+        combined.args.remove(VERSION);
+        combined.args.remove(MIGRATE);
+        combined.args.remove(BOUND);
+        combined.args.remove(VALIDATE);
+        let encoding = EncodingDerive::with(
+            &mut combined,
+            crate_name,
+            false,
+            true,
+            false,
+        )?;
+
+        if encoding.skip {
+            continue;
+        }
+
+        let fields = match variant.fields {
+            Fields::Named(ref fields) => {
+                schema_fields(
+                    attr_name,
+                    crate_name,
+                    &import,
+                    trait_name,
+                    &fields.named,
+                    local_param,
+                    true,
+                    false,
+                )?
+                .fields
+            }
+            Fields::Unnamed(ref fields) => {
+                schema_fields(
+                    attr_name,
+                    crate_name,
+                    &import,
+                    trait_name,
+                    &fields.unnamed,
+                    local_param,
+                    true,
+                    false,
+                )?
+                .fields
+            }
+            Fields::Unit => vec![],
+        };
+
+        let ident = &variant.ident;
+        let value = match (encoding.value, encoding.by_order) {
+            (Some(val), _) => val.to_token_stream(),
+            (None, true) => Index::from(order).to_token_stream(),
+            (None, false) => quote! { Self::#ident },
+        };
+        let name = ident.to_string();
+
+        variants.push(quote! {
+            #import::schema::VariantDescr {
+                name: ::core::convert::From::from(#name),
+                value: (#value as #repr) as u64,
+                fields: ::core::convert::From::from([ #( #fields ),* ]),
+            }
+        });
+    }
+
+    let name = ident_name.to_string();
+    let descr = versioned(version, &import, quote! {
+        #import::schema::TypeDescr::Enum(#import::schema::EnumDescr {
+            name: ::core::convert::From::from(#name),
+            repr: #import::schema::Primitive::#primitive,
+            by_order: #by_order,
+            variants: ::core::convert::From::from([ #( #variants ),* ]),
+        })
+    });
+
+    Ok(quote! {
+        impl #impl_generics #import::schema::#trait_name for #ident_name #ty_generics #where_clause {
+            #[allow(clippy::unnecessary_cast)]
+            fn strict_type() -> #import::schema::TypeDescr {
+                #descr
+            }
+        }
+    })
+}
+
+/// Wraps type description into the description of a versioned data structure,
+/// if the type is versioned.
+fn versioned(
+    version: Option<u8>,
+    import: &Path,
+    descr: TokenStream2,
+) -> TokenStream2 {
+    match version {
+        Some(version) => quote! {
+            #import::schema::TypeDescr::versioned(#version, #descr)
+        },
+        None => descr,
+    }
+}
+
+#[derive(Default)]
+struct SchemaFields {
+    fields: Vec<TokenStream2>,
+    tlvs: Vec<TokenStream2>,
+    unknown_tlvs: bool,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn schema_fields<'a>(
+    attr_name: &'static str,
+    crate_name: &Ident,
+    import: &Path,
+    trait_name: &Ident,
+    fields: impl IntoIterator<Item = &'a Field>,
+    mut parent_param: ParametrizedAttr,
+    is_enum: bool,
+    tlv_encoding: bool,
+) -> Result<SchemaFields> {
+    let use_tlv = parent_param.args.contains_key(USE_TLV);
+    parent_param.args.remove(CRATE);
+    parent_param.args.remove(USE_TLV);
+    parent_param.args.remove(BOLT);
+    parent_param.args.remove(VERSION);
+    parent_param.args.remove(MIGRATE);
+    parent_param.args.remove(BOUND);
+    parent_param.args.remove(VALIDATE);
+
+    if !tlv_encoding && use_tlv {
+        return Err(Error::new(
+            Span::call_site(),
+            format!("TLV extensions are not allowed in `{}`", attr_name),
+        ));
+    }
+
+    let mut schema = SchemaFields::default();
+    let mut tlvs = BTreeMap::new();
+
+    // Used only to validate TLV configuration in the same way as encoders do
+    let mut strict_fields = vec![];
+    let mut tlv_fields = bmap! {};
+    let mut tlv_aggregator = None;
+
+    for (index, field) in fields.into_iter().enumerate() {
+        let mut local_param = ParametrizedAttr::with(attr_name, &field.attrs)?;
+
+        // First, test individual attribute
+        let _ = EncodingDerive::with(
+            &mut local_param,
+            crate_name,
+            false,
+            is_enum,
+            use_tlv,
+        )?;
+        // Second, combine global and local together
+        let mut combined = parent_param.clone().merged(local_param)?;
+        let encoding = EncodingDerive::with(
+            &mut combined,
+            crate_name,
+            false,
+            is_enum,
+            use_tlv,
+        )?;
+
+        if encoding.skip {
+            continue;
+        }
+
+        let tlv = encoding.tlv.unwrap_or(TlvDerive::None);
+        tlv.process(
+            field,
+            Index::from(index).to_token_stream(),
+            &mut strict_fields,
+            &mut tlv_fields,
+            &mut tlv_aggregator,
+        )?;
+
+        let name = match field.ident {
+            Some(ref ident) => {
+                let ident = ident.to_string();
+                quote! { Some(::core::convert::From::from(#ident)) }
+            }
+            None => quote! { None },
+        };
+        let ty = &field.ty;
+        let ty = match encoding.adapter {
+            Some(ref adapter) => adapter.strict_type(import, ty),
+            None => {
+                quote! { <#ty as #import::schema::#trait_name>::strict_type() }
+            }
+        };
+
+        match tlv {
+            TlvDerive::None => schema.fields.push(quote! {
+                #import::schema::FieldDescr { name: #name, ty: #ty }
+            }),
+            TlvDerive::Typed(type_no) => {
+                let id = type_no as u64;
+                tlvs.insert(type_no, quote! {
+                    #import::schema::TlvDescr {
+                        id: #id,
+                        name: #name,
+                        ty: #ty.into_non_optional(),
+                    }
+                });
+            }
+            TlvDerive::Unknown => schema.unknown_tlvs = true,
+        }
+    }
+
+    schema.tlvs = tlvs.into_values().collect();
+
+    Ok(schema)
+}
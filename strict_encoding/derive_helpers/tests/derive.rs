@@ -34,6 +34,7 @@ fn test_custom_derivation() {
             ident!(CustomEncode),
             ident!(custom_encode),
             ident!(custom_serialize),
+            ident!(custom_encoded_len),
             derive_input,
             true,
         )
@@ -0,0 +1,727 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Encoding adapters for individual fields of the types deriving strict
+//! encoding.
+//!
+//! A field marked with `#[strict_encoding(with = "path::to::module")]`
+//! attribute is encoded and decoded with the functions from the provided
+//! module instead of the strict encoding traits implemented by the field
+//! type. This allows to use foreign types, which do not implement the traits,
+//! or to change the encoding of a specific field. The module must provide
+//! functions with the following signatures, where `T` is the field type (the
+//! functions may be generic over it):
+//!
+//! ```ignore
+//! pub fn strict_encode<E: io::Write>(value: &T, e: E) -> Result<usize, Error>;
+//! pub fn strict_decode<D: io::Read>(d: D) -> Result<T, Error>;
+//! // Required only by `StrictType` and `NetworkType` derivations
+//! pub fn strict_type() -> TypeDescr;
+//! // Required only by asynchronous derivations
+//! pub async fn strict_encode_async<E>(value: &T, e: &mut E) -> Result<usize, Error>
+//! where
+//!     E: AsyncWrite + Unpin + Send;
+//! pub async fn strict_decode_async<D>(d: &mut D) -> Result<T, Error>
+//! where
+//!     D: AsyncRead + Unpin + Send;
+//! ```
+//!
+//! Submodules of this module are adapters for the common cases, which are
+//! applied to a field with a shorthand attribute named after the submodule,
+//! like `#[strict_encoding(as_u32_len)]`:
+//! - [`as_u32_len`]: encodes `Vec`, `BTreeSet`, `BTreeMap` collections and
+//!   `String`s with `u32` length prefix, allowing them to have more than
+//!   `u16::MAX` items;
+//! - [`fixed_bytes`]: encodes [`bitcoin_hashes::Hash`] types as fixed-size byte
+//!   strings using [`HashFixedBytes`] strategy;
+//! - [`uniform`]: encodes network addresses in LNPBP-42 uniform encoding using
+//!   [`UsingUniformAddr`] strategy.
+//!
+//! ```
+//! use bitcoin_hashes::{sha256, Hash};
+//! use strict_encoding::{StrictDecode, StrictEncode};
+//!
+//! #[derive(Clone, PartialEq, Eq, Debug)]
+//! #[derive(StrictEncode, StrictDecode)]
+//! struct Block {
+//!     #[strict_encoding(fixed_bytes)]
+//!     prev: sha256::Hash,
+//!     #[strict_encoding(as_u32_len)]
+//!     txids: Vec<u64>,
+//! }
+//!
+//! let block = Block {
+//!     prev: sha256::Hash::hash(b"genesis"),
+//!     txids: vec![1, 2],
+//! };
+//! let data = block.strict_serialize().unwrap();
+//! assert_eq!(&data[32..36], &[2, 0, 0, 0]);
+//! assert_eq!(Block::strict_deserialize(data).unwrap(), block);
+//! ```
+//!
+//! [`HashFixedBytes`]: crate::strategies::HashFixedBytes
+//! [`UsingUniformAddr`]: crate::strategies::UsingUniformAddr
+
+/// Encodes collections and strings with `u32` length prefix, like
+/// [`crate::LargeVec`] does, instead of the default `u16` prefix.
+pub mod as_u32_len {
+    use alloc::collections::{BTreeMap, BTreeSet};
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use core::fmt::Debug;
+
+    #[cfg(feature = "async")]
+    use tokio::io::AsyncWriteExt;
+
+    #[cfg(feature = "async")]
+    use crate::asynchronous::{
+        self, async_trait, AsyncRead, AsyncWrite, StrictDecodeAsync,
+        StrictEncodeAsync,
+    };
+    use crate::collections::{
+        decode_map_items, decode_set_items, decode_vec_items,
+    };
+    use crate::confined::Collection;
+    use crate::schema::{LenPrefix, StrictType, TypeDescr};
+    use crate::{io, Error, StrictDecode, StrictEncode};
+
+    /// Collections which can be encoded with the [`as_u32_len`](self)
+    /// adapter or with a custom length prefix, like [`crate::Confined`]
+    /// does. For strings the items are bytes of their UTF-8 representation.
+    pub trait LargeCollection: Collection + Sized {
+        /// Encodes the collection items without the length prefix.
+        fn encode_items<E: io::Write>(&self, e: E) -> Result<usize, Error>;
+
+        /// Decodes `len` collection items, which length prefix was already
+        /// read.
+        fn decode_items<D: io::Read>(len: usize, d: D) -> Result<Self, Error>;
+    }
+
+    impl<T> LargeCollection for Vec<T>
+    where
+        T: StrictEncode + StrictDecode,
+    {
+        fn encode_items<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+            self.iter()
+                .try_fold(0, |len, item| Ok(len + item.strict_encode(&mut e)?))
+        }
+
+        fn decode_items<D: io::Read>(len: usize, d: D) -> Result<Self, Error> {
+            decode_vec_items(len, d)
+        }
+    }
+
+    impl<T> LargeCollection for BTreeSet<T>
+    where
+        T: StrictEncode + StrictDecode + Eq + Ord + Debug,
+    {
+        fn encode_items<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+            self.iter()
+                .try_fold(0, |len, item| Ok(len + item.strict_encode(&mut e)?))
+        }
+
+        fn decode_items<D: io::Read>(len: usize, d: D) -> Result<Self, Error> {
+            decode_set_items(len, d)
+        }
+    }
+
+    impl<K, V> LargeCollection for BTreeMap<K, V>
+    where
+        K: StrictEncode + StrictDecode + Ord + Clone + Debug,
+        V: StrictEncode + StrictDecode + Clone,
+    {
+        fn encode_items<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+            self.iter().try_fold(0, |len, (key, val)| {
+                Ok(len
+                    + key.strict_encode(&mut e)?
+                    + val.strict_encode(&mut e)?)
+            })
+        }
+
+        fn decode_items<D: io::Read>(len: usize, d: D) -> Result<Self, Error> {
+            decode_map_items(len, d)
+        }
+    }
+
+    impl LargeCollection for String {
+        fn encode_items<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+            e.write_all(self.as_bytes())?;
+            Ok(self.len())
+        }
+
+        fn decode_items<D: io::Read>(len: usize, d: D) -> Result<Self, Error> {
+            String::from_utf8(decode_vec_items(len, d)?).map_err(Error::from)
+        }
+    }
+
+    /// Asynchronous version of [`LargeCollection`], required for the
+    /// collections used with the adapter by the asynchronous derivations.
+    #[cfg(feature = "async")]
+    #[async_trait]
+    pub trait LargeCollectionAsync: LargeCollection + Send + Sync {
+        /// Encodes the collection items without the length prefix.
+        async fn encode_items_async<E>(
+            &self,
+            e: &mut E,
+        ) -> Result<usize, Error>
+        where
+            E: AsyncWrite + Unpin + Send;
+
+        /// Decodes `len` collection items, which length prefix was already
+        /// read.
+        async fn decode_items_async<D>(
+            len: usize,
+            d: &mut D,
+        ) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send;
+    }
+
+    #[cfg(feature = "async")]
+    #[async_trait]
+    impl<T> LargeCollectionAsync for Vec<T>
+    where
+        T: StrictEncode
+            + StrictDecode
+            + StrictEncodeAsync
+            + StrictDecodeAsync
+            + Sync,
+    {
+        async fn encode_items_async<E>(&self, e: &mut E) -> Result<usize, Error>
+        where
+            E: AsyncWrite + Unpin + Send,
+        {
+            asynchronous::encode_items(self, e).await
+        }
+
+        async fn decode_items_async<D>(
+            len: usize,
+            d: &mut D,
+        ) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            asynchronous::decode_items(len, d).await
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[async_trait]
+    impl<T> LargeCollectionAsync for BTreeSet<T>
+    where
+        T: StrictEncode
+            + StrictDecode
+            + StrictEncodeAsync
+            + StrictDecodeAsync
+            + Eq
+            + Ord
+            + Debug
+            + Sync,
+    {
+        async fn encode_items_async<E>(&self, e: &mut E) -> Result<usize, Error>
+        where
+            E: AsyncWrite + Unpin + Send,
+        {
+            asynchronous::encode_items(self, e).await
+        }
+
+        async fn decode_items_async<D>(
+            len: usize,
+            d: &mut D,
+        ) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            asynchronous::decode_set_items(len, d).await
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[async_trait]
+    impl<K, V> LargeCollectionAsync for BTreeMap<K, V>
+    where
+        K: StrictEncode
+            + StrictDecode
+            + StrictEncodeAsync
+            + StrictDecodeAsync
+            + Ord
+            + Clone
+            + Debug
+            + Sync,
+        V: StrictEncode
+            + StrictDecode
+            + StrictEncodeAsync
+            + StrictDecodeAsync
+            + Clone
+            + Sync,
+    {
+        async fn encode_items_async<E>(&self, e: &mut E) -> Result<usize, Error>
+        where
+            E: AsyncWrite + Unpin + Send,
+        {
+            let mut len = 0;
+            for (key, val) in self {
+                len += key.strict_encode_async(e).await?;
+                len += val.strict_encode_async(e).await?;
+            }
+            Ok(len)
+        }
+
+        async fn decode_items_async<D>(
+            len: usize,
+            d: &mut D,
+        ) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            asynchronous::decode_map_items(len, d).await
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[async_trait]
+    impl LargeCollectionAsync for String {
+        async fn encode_items_async<E>(&self, e: &mut E) -> Result<usize, Error>
+        where
+            E: AsyncWrite + Unpin + Send,
+        {
+            e.write_all(self.as_bytes()).await?;
+            Ok(self.len())
+        }
+
+        async fn decode_items_async<D>(
+            len: usize,
+            d: &mut D,
+        ) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            String::from_utf8(asynchronous::decode_items(len, d).await?)
+                .map_err(Error::from)
+        }
+    }
+
+    /// Encodes the collection with `u32` length prefix. Fails with
+    /// [`Error::ExceedMaxItems`] if the collection has more than `u32::MAX`
+    /// items.
+    pub fn strict_encode<C, E>(value: &C, mut e: E) -> Result<usize, Error>
+    where
+        C: LargeCollection,
+        E: io::Write,
+    {
+        let len = value.items_count();
+        if len > u32::MAX as usize {
+            return Err(Error::ExceedMaxItems(len));
+        }
+        Ok((len as u32).strict_encode(&mut e)? + value.encode_items(e)?)
+    }
+
+    /// Decodes the collection with `u32` length prefix.
+    pub fn strict_decode<C, D>(mut d: D) -> Result<C, Error>
+    where
+        C: LargeCollection,
+        D: io::Read,
+    {
+        let len = u32::strict_decode(&mut d)?;
+        C::decode_items(len as usize, d)
+    }
+
+    /// Asynchronous version of [`strict_encode`].
+    #[cfg(feature = "async")]
+    pub async fn strict_encode_async<C, E>(
+        value: &C,
+        e: &mut E,
+    ) -> Result<usize, Error>
+    where
+        C: LargeCollectionAsync,
+        E: AsyncWrite + Unpin + Send,
+    {
+        let len = value.items_count();
+        if len > u32::MAX as usize {
+            return Err(Error::ExceedMaxItems(len));
+        }
+        Ok((len as u32).strict_encode_async(e).await?
+            + value.encode_items_async(e).await?)
+    }
+
+    /// Asynchronous version of [`strict_decode`].
+    #[cfg(feature = "async")]
+    pub async fn strict_decode_async<C, D>(d: &mut D) -> Result<C, Error>
+    where
+        C: LargeCollectionAsync,
+        D: AsyncRead + Unpin + Send,
+    {
+        let len = u32::strict_decode_async(d).await?;
+        C::decode_items_async(len as usize, d).await
+    }
+
+    /// Describes the collection, replacing its length prefix with `u32`.
+    pub fn strict_type<C>() -> TypeDescr
+    where
+        C: LargeCollection + StrictType,
+    {
+        C::strict_type().with_len_prefix(LenPrefix::U32)
+    }
+}
+
+/// Encodes [`bitcoin_hashes::Hash`] types as fixed-size byte strings with
+/// [`HashFixedBytes`](crate::strategies::HashFixedBytes) strategy.
+pub mod fixed_bytes {
+    use amplify::Holder;
+    use bitcoin_hashes::Hash;
+
+    #[cfg(feature = "async")]
+    use crate::asynchronous::{
+        AsyncRead, AsyncWrite, StrictDecodeAsync, StrictEncodeAsync,
+    };
+    use crate::schema::{StrictType, TypeDescr};
+    use crate::strategies::HashFixedBytes;
+    use crate::{io, Error, StrictDecode, StrictEncode};
+
+    /// Encodes the hash as a fixed-size byte string.
+    #[inline]
+    pub fn strict_encode<H, E>(value: &H, e: E) -> Result<usize, Error>
+    where
+        H: Hash,
+        E: io::Write,
+    {
+        Holder::<H, HashFixedBytes>::new(*value).strict_encode(e)
+    }
+
+    /// Decodes the hash from a fixed-size byte string.
+    #[inline]
+    pub fn strict_decode<H, D>(d: D) -> Result<H, Error>
+    where
+        H: Hash,
+        D: io::Read,
+    {
+        Ok(Holder::<H, HashFixedBytes>::strict_decode(d)?.into_inner())
+    }
+
+    /// Asynchronous version of [`strict_encode`].
+    #[cfg(feature = "async")]
+    #[inline]
+    pub async fn strict_encode_async<H, E>(
+        value: &H,
+        e: &mut E,
+    ) -> Result<usize, Error>
+    where
+        H: Hash + Send + Sync,
+        E: AsyncWrite + Unpin + Send,
+    {
+        Holder::<H, HashFixedBytes>::new(*value)
+            .strict_encode_async(e)
+            .await
+    }
+
+    /// Asynchronous version of [`strict_decode`].
+    #[cfg(feature = "async")]
+    #[inline]
+    pub async fn strict_decode_async<H, D>(d: &mut D) -> Result<H, Error>
+    where
+        H: Hash + Send,
+        D: AsyncRead + Unpin + Send,
+    {
+        Ok(Holder::<H, HashFixedBytes>::strict_decode_async(d)
+            .await?
+            .into_inner())
+    }
+
+    /// Describes the hash as a fixed-size byte string.
+    #[inline]
+    pub fn strict_type<H>() -> TypeDescr
+    where
+        H: Hash,
+    {
+        Holder::<H, HashFixedBytes>::strict_type()
+    }
+}
+
+/// Encodes network addresses according to LNPBP-42 uniform address encoding
+/// with [`UsingUniformAddr`](crate::strategies::UsingUniformAddr) strategy.
+pub mod uniform {
+    use amplify::Holder;
+
+    #[cfg(feature = "async")]
+    use crate::asynchronous::{
+        AsyncRead, AsyncWrite, StrictDecodeAsync, StrictEncodeAsync,
+    };
+    use crate::net::Uniform;
+    use crate::schema::{StrictType, TypeDescr};
+    use crate::strategies::UsingUniformAddr;
+    use crate::{io, Error, StrictDecode, StrictEncode};
+
+    /// Encodes the address as a uniform address.
+    #[inline]
+    pub fn strict_encode<A, E>(value: &A, e: E) -> Result<usize, Error>
+    where
+        A: Uniform + Clone,
+        E: io::Write,
+    {
+        Holder::<A, UsingUniformAddr>::new(value.clone()).strict_encode(e)
+    }
+
+    /// Decodes the address from a uniform address.
+    #[inline]
+    pub fn strict_decode<A, D>(d: D) -> Result<A, Error>
+    where
+        A: Uniform,
+        D: io::Read,
+    {
+        Ok(Holder::<A, UsingUniformAddr>::strict_decode(d)?.into_inner())
+    }
+
+    /// Asynchronous version of [`strict_encode`].
+    #[cfg(feature = "async")]
+    #[inline]
+    pub async fn strict_encode_async<A, E>(
+        value: &A,
+        e: &mut E,
+    ) -> Result<usize, Error>
+    where
+        A: Uniform + Clone + Send + Sync,
+        E: AsyncWrite + Unpin + Send,
+    {
+        Holder::<A, UsingUniformAddr>::new(value.clone())
+            .strict_encode_async(e)
+            .await
+    }
+
+    /// Asynchronous version of [`strict_decode`].
+    #[cfg(feature = "async")]
+    #[inline]
+    pub async fn strict_decode_async<A, D>(d: &mut D) -> Result<A, Error>
+    where
+        A: Uniform + Send,
+        D: AsyncRead + Unpin + Send,
+    {
+        Ok(Holder::<A, UsingUniformAddr>::strict_decode_async(d)
+            .await?
+            .into_inner())
+    }
+
+    /// Describes the address as a fixed-size uniform address.
+    #[inline]
+    pub fn strict_type<A>() -> TypeDescr
+    where
+        A: Uniform,
+    {
+        Holder::<A, UsingUniformAddr>::strict_type()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::collections::{BTreeMap, BTreeSet};
+    use alloc::vec::Vec;
+    use std::net::SocketAddr;
+
+    use bitcoin_hashes::{sha256, Hash};
+
+    use super::*;
+    use crate::net::UNIFORM_LEN;
+    use crate::schema::{LenPrefix, Primitive, TypeDescr};
+    use crate::Error;
+
+    fn encode<T>(
+        value: &T,
+        f: fn(&T, &mut Vec<u8>) -> Result<usize, Error>,
+    ) -> Vec<u8> {
+        let mut data = vec![];
+        let len = f(value, &mut data).unwrap();
+        assert_eq!(len, data.len());
+        data
+    }
+
+    #[test]
+    fn u32_len_vec() {
+        let data =
+            encode(&vec![1u8, 2, 3], |v, e| as_u32_len::strict_encode(v, e));
+        assert_eq!(data, [3, 0, 0, 0, 1, 2, 3]);
+        let vec: Vec<u8> = as_u32_len::strict_decode(&data[..]).unwrap();
+        assert_eq!(vec, [1, 2, 3]);
+
+        let large = vec![0u8; u16::MAX as usize + 1];
+        let data = encode(&large, |v, e| as_u32_len::strict_encode(v, e));
+        assert_eq!(&data[..4], &[0x00, 0x00, 0x01, 0x00]);
+        let vec: Vec<u8> = as_u32_len::strict_decode(&data[..]).unwrap();
+        assert_eq!(vec, large);
+
+        assert_eq!(
+            as_u32_len::strict_type::<Vec<u8>>(),
+            TypeDescr::list(
+                LenPrefix::U32,
+                TypeDescr::Primitive(Primitive::U8)
+            )
+        );
+    }
+
+    #[test]
+    fn u32_len_set() {
+        let set = bset! { 2u8, 1 };
+        let data = encode(&set, |v, e| as_u32_len::strict_encode(v, e));
+        assert_eq!(data, [2, 0, 0, 0, 1, 2]);
+        let decoded: BTreeSet<u8> =
+            as_u32_len::strict_decode(&data[..]).unwrap();
+        assert_eq!(decoded, set);
+
+        assert_eq!(
+            as_u32_len::strict_decode::<BTreeSet<u8>, _>(
+                &[2, 0, 0, 0, 1, 1][..]
+            ),
+            Err(Error::RepeatedValue(s!("1")))
+        );
+        assert!(matches!(
+            as_u32_len::strict_decode::<BTreeSet<u8>, _>(
+                &[2, 0, 0, 0, 2, 1][..]
+            ),
+            Err(Error::DataIntegrityError(_))
+        ));
+    }
+
+    #[test]
+    fn u32_len_map() {
+        let map = bmap! { 1u8 => 5u16 };
+        let data = encode(&map, |v, e| as_u32_len::strict_encode(v, e));
+        assert_eq!(data, [1, 0, 0, 0, 1, 5, 0]);
+        let decoded: BTreeMap<u8, u16> =
+            as_u32_len::strict_decode(&data[..]).unwrap();
+        assert_eq!(decoded, map);
+
+        assert_eq!(
+            as_u32_len::strict_decode::<BTreeMap<u8, u16>, _>(
+                &[2, 0, 0, 0, 1, 5, 0, 1, 6, 0][..]
+            ),
+            Err(Error::RepeatedValue(s!("1")))
+        );
+    }
+
+    #[test]
+    fn fixed_bytes() {
+        let hash = sha256::Hash::hash(b"test");
+        let data = encode(&hash, |v, e| fixed_bytes::strict_encode(v, e));
+        assert_eq!(data, &hash[..]);
+        assert_eq!(
+            fixed_bytes::strict_decode::<sha256::Hash, _>(&data[..]).unwrap(),
+            hash
+        );
+        assert!(
+            fixed_bytes::strict_decode::<sha256::Hash, _>(&data[1..]).is_err()
+        );
+        assert_eq!(
+            fixed_bytes::strict_type::<sha256::Hash>(),
+            TypeDescr::Bytes(32)
+        );
+    }
+
+    #[test]
+    fn uniform() {
+        let addr: SocketAddr = "127.0.0.1:9735".parse().unwrap();
+        let data = encode(&addr, |v, e| uniform::strict_encode(v, e));
+        assert_eq!(data.len(), UNIFORM_LEN);
+        assert_eq!(
+            uniform::strict_decode::<SocketAddr, _>(&data[..]).unwrap(),
+            addr
+        );
+        assert_eq!(
+            uniform::strict_type::<SocketAddr>(),
+            TypeDescr::Bytes(UNIFORM_LEN as u16)
+        );
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_adapters() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let vec = vec![1u8, 2, 3];
+            let mut data = vec![];
+            let len = as_u32_len::strict_encode_async(&vec, &mut data)
+                .await
+                .unwrap();
+            assert_eq!(len, data.len());
+            assert_eq!(
+                data,
+                encode(&vec, |v, e| as_u32_len::strict_encode(v, e))
+            );
+            let decoded: Vec<u8> =
+                as_u32_len::strict_decode_async(&mut &data[..])
+                    .await
+                    .unwrap();
+            assert_eq!(decoded, vec);
+
+            let map = bmap! { 1u8 => 5u16 };
+            let mut data = vec![];
+            as_u32_len::strict_encode_async(&map, &mut data)
+                .await
+                .unwrap();
+            assert_eq!(data, [1, 0, 0, 0, 1, 5, 0]);
+            let decoded: BTreeMap<u8, u16> =
+                as_u32_len::strict_decode_async(&mut &data[..])
+                    .await
+                    .unwrap();
+            assert_eq!(decoded, map);
+            assert_eq!(
+                as_u32_len::strict_decode_async::<BTreeSet<u8>, _>(
+                    &mut &[2, 0, 0, 0, 1, 1][..]
+                )
+                .await,
+                Err(Error::RepeatedValue(s!("1")))
+            );
+
+            let string = s!("abc");
+            let mut data = vec![];
+            as_u32_len::strict_encode_async(&string, &mut data)
+                .await
+                .unwrap();
+            assert_eq!(data, [3, 0, 0, 0, b'a', b'b', b'c']);
+            let decoded: String =
+                as_u32_len::strict_decode_async(&mut &data[..])
+                    .await
+                    .unwrap();
+            assert_eq!(decoded, string);
+
+            let hash = sha256::Hash::hash(b"test");
+            let mut data = vec![];
+            fixed_bytes::strict_encode_async(&hash, &mut data)
+                .await
+                .unwrap();
+            assert_eq!(data, &hash[..]);
+            assert_eq!(
+                fixed_bytes::strict_decode_async::<sha256::Hash, _>(
+                    &mut &data[..]
+                )
+                .await,
+                Ok(hash)
+            );
+
+            let addr: SocketAddr = "127.0.0.1:9735".parse().unwrap();
+            let mut data = vec![];
+            uniform::strict_encode_async(&addr, &mut data)
+                .await
+                .unwrap();
+            assert_eq!(
+                data,
+                encode(&addr, |v, e| uniform::strict_encode(v, e))
+            );
+            assert_eq!(
+                uniform::strict_decode_async::<SocketAddr, _>(&mut &data[..])
+                    .await,
+                Ok(addr)
+            );
+        });
+    }
+}
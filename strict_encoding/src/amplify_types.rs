@@ -12,8 +12,7 @@
 // You should have received a copy of the Apache 2.0 License along with this
 // software. If not, see <https://opensource.org/licenses/Apache-2.0>.
 
-use std::io;
-
+#[cfg(feature = "std")]
 use amplify::flags::FlagVec;
 #[cfg(feature = "float")]
 use amplify::num::apfloat::{ieee, Float};
@@ -21,8 +20,12 @@ use amplify::num::{i1024, i256, i512, u1024, u256, u512};
 #[cfg(feature = "float")]
 use half::bf16;
 
-use crate::{Error, StrictDecode, StrictEncode};
+#[cfg(feature = "std")]
+use crate::schema::LenPrefix;
+use crate::schema::{Primitive, StrictType, TypeDescr};
+use crate::{io, Error, StrictDecode, StrictEncode};
 
+#[cfg(feature = "std")]
 impl StrictEncode for FlagVec {
     #[inline]
     fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
@@ -30,6 +33,7 @@ impl StrictEncode for FlagVec {
     }
 }
 
+#[cfg(feature = "std")]
 impl StrictDecode for FlagVec {
     #[inline]
     fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
@@ -38,6 +42,8 @@ impl StrictDecode for FlagVec {
 }
 
 impl StrictEncode for u256 {
+    const FIXED_SIZE: Option<usize> = Some(32);
+
     fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
         self.to_le_bytes().strict_encode(e)
     }
@@ -50,6 +56,8 @@ impl StrictDecode for u256 {
 }
 
 impl StrictEncode for u512 {
+    const FIXED_SIZE: Option<usize> = Some(64);
+
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         let bytes = self.to_le_bytes();
         e.write_all(&bytes)?;
@@ -66,6 +74,8 @@ impl StrictDecode for u512 {
 }
 
 impl StrictEncode for u1024 {
+    const FIXED_SIZE: Option<usize> = Some(128);
+
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         let bytes = self.to_le_bytes();
         e.write_all(&bytes)?;
@@ -82,6 +92,8 @@ impl StrictDecode for u1024 {
 }
 
 impl StrictEncode for i256 {
+    const FIXED_SIZE: Option<usize> = Some(32);
+
     fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
         self.to_le_bytes().strict_encode(e)
     }
@@ -94,6 +106,8 @@ impl StrictDecode for i256 {
 }
 
 impl StrictEncode for i512 {
+    const FIXED_SIZE: Option<usize> = Some(64);
+
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         let bytes = self.to_le_bytes();
         e.write_all(&bytes)?;
@@ -110,6 +124,8 @@ impl StrictDecode for i512 {
 }
 
 impl StrictEncode for i1024 {
+    const FIXED_SIZE: Option<usize> = Some(128);
+
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         let bytes = self.to_le_bytes();
         e.write_all(&bytes)?;
@@ -125,8 +141,35 @@ impl StrictDecode for i1024 {
     }
 }
 
+#[cfg(feature = "std")]
+impl StrictType for FlagVec {
+    fn strict_type() -> TypeDescr {
+        TypeDescr::list(LenPrefix::U16, TypeDescr::Primitive(Primitive::U8))
+    }
+}
+
+macro_rules! impl_strict_type_bigint {
+    ($ty:ty, $primitive:ident) => {
+        impl StrictType for $ty {
+            #[inline]
+            fn strict_type() -> TypeDescr {
+                TypeDescr::Primitive(Primitive::$primitive)
+            }
+        }
+    };
+}
+
+impl_strict_type_bigint!(u256, U256);
+impl_strict_type_bigint!(u512, U512);
+impl_strict_type_bigint!(u1024, U1024);
+impl_strict_type_bigint!(i256, I256);
+impl_strict_type_bigint!(i512, I512);
+impl_strict_type_bigint!(i1024, I1024);
+
 #[cfg(feature = "float")]
 impl StrictEncode for bf16 {
+    const FIXED_SIZE: Option<usize> = Some(2);
+
     fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
         self.to_bits().strict_encode(e)
     }
@@ -141,8 +184,11 @@ impl StrictDecode for bf16 {
 
 #[cfg(feature = "float")]
 impl StrictEncode for ieee::Half {
-    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
-        self.to_bits().to_le_bytes()[..2].strict_encode(e)
+    const FIXED_SIZE: Option<usize> = Some(2);
+
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        e.write_all(&self.to_bits().to_le_bytes()[..2])?;
+        Ok(2)
     }
 }
 
@@ -157,8 +203,11 @@ impl StrictDecode for ieee::Half {
 
 #[cfg(feature = "float")]
 impl StrictEncode for ieee::Quad {
-    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
-        self.to_bits().to_le_bytes()[..16].strict_encode(e)
+    const FIXED_SIZE: Option<usize> = Some(16);
+
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        e.write_all(&self.to_bits().to_le_bytes()[..16])?;
+        Ok(16)
     }
 }
 
@@ -173,6 +222,8 @@ impl StrictDecode for ieee::Oct {
 
 #[cfg(feature = "float")]
 impl StrictEncode for ieee::Oct {
+    const FIXED_SIZE: Option<usize> = Some(32);
+
     fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
         self.to_bits().to_le_bytes().strict_encode(e)
     }
@@ -189,8 +240,11 @@ impl StrictDecode for ieee::Quad {
 
 #[cfg(feature = "float")]
 impl StrictEncode for ieee::X87DoubleExtended {
-    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
-        self.to_bits().to_le_bytes()[..10].strict_encode(e)
+    const FIXED_SIZE: Option<usize> = Some(10);
+
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        e.write_all(&self.to_bits().to_le_bytes()[..10])?;
+        Ok(10)
     }
 }
 
@@ -0,0 +1,602 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Armored text representation of strict-encoded data.
+//!
+//! Armored text is a deterministic human-readable form of strict-encoded data
+//! suitable for copy-pasting over chats and emails. It consists of a header
+//! line with the type tag, base58-encoded payload wrapped into lines of
+//! [`LINE_WIDTH`] characters and a footer repeating the header tag:
+//!
+//! ```text
+//! -----BEGIN STRICT MerkleBlock-----
+//! 3yZe7d...
+//! -----END STRICT MerkleBlock-----
+//! ```
+//!
+//! The payload is base58-encoded in 8-byte blocks, each taking 11 characters
+//! (the last incomplete block takes fewer characters, like in Monero
+//! addresses), so encoding and decoding time is linear in the payload size.
+//! Armored text with payload exceeding [`MAX_PAYLOAD_LEN`] is rejected before
+//! decoding, and the payload is decoded with [`StrictArmor::DECODE_LIMITS`].
+//!
+//! The payload is followed by 4-byte checksum (first bytes of double SHA256
+//! hash of strict-encoded tag string followed by the data), so both the data
+//! and the tag are protected against modifications.
+//!
+//! Types exchanged in armored form implement [`StrictArmor`] trait defining
+//! their tag; [`StrictArmor::from_armored`] rejects data with a different tag
+//! before decoding the payload. [`ArmorRegistry`] allows to ensure that tags
+//! of the types used by an application do not collide and to detect the type
+//! of armored data.
+
+use alloc::collections::BTreeSet;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use bitcoin_hashes::{sha256d, Hash, HashEngine};
+
+use crate::limits::DecodeLimits;
+use crate::{Error, StrictDecode, StrictEncode};
+
+/// Maximal number of payload characters in a line of armored text
+pub const LINE_WIDTH: usize = 64;
+
+/// Maximal length of the type tag
+pub const MAX_TAG_LEN: usize = 64;
+
+/// Maximal size of the data in armored text, in bytes (16 MiB)
+pub const MAX_PAYLOAD_LEN: usize = 0x100_0000;
+
+/// Default limits for decoding armored data, see [`StrictArmor::DECODE_LIMITS`]
+pub const DEFAULT_DECODE_LIMITS: DecodeLimits = DecodeLimits {
+    max_bytes: MAX_PAYLOAD_LEN,
+    max_depth: 128,
+    max_items: MAX_PAYLOAD_LEN,
+    canonical: false,
+};
+
+const HEADER_PREFIX: &str = "-----BEGIN STRICT ";
+const FOOTER_PREFIX: &str = "-----END STRICT ";
+const DELIMITER_SUFFIX: &str = "-----";
+const CHECKSUM_LEN: usize = 4;
+
+const BASE58_ALPHABET: &[u8; 58] =
+    b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BLOCK_LEN: usize = 8;
+/// Number of base58 characters encoding a block of a given number of bytes
+const ENCODED_BLOCK_LEN: [usize; BLOCK_LEN + 1] = [0, 2, 3, 5, 6, 7, 9, 10, 11];
+/// Maximal number of base58 characters in armored payload, including checksum
+const MAX_ENCODED_LEN: usize =
+    base58_encoded_len(MAX_PAYLOAD_LEN + CHECKSUM_LEN);
+
+/// Errors parsing armored text
+#[derive(Clone, PartialEq, Eq, Debug, Display, From)]
+#[cfg_attr(feature = "std", derive(Error))]
+#[display(doc_comments)]
+pub enum ArmorError {
+    /// armored data must start with `-----BEGIN STRICT <TAG>-----` line
+    NoHeader,
+
+    /// armored data must end with `-----END STRICT <TAG>-----` line
+    NoFooter,
+
+    /// armored data footer tag `{1}` does not match header tag `{0}`
+    FooterMismatch(String, String),
+
+    /// invalid armor tag `{0}`; tags must be non-empty strings up to 64
+    /// characters consisting of ASCII letters, digits, `-`, `_` and `.`
+    InvalidTag(String),
+
+    /// armored data contain `{actual}` while `{expected}` was expected
+    TagMismatch {
+        /// Tag expected by the decoder
+        expected: &'static str,
+        /// Tag found in the armored data
+        actual: String,
+    },
+
+    /// armored data has unknown tag `{0}`
+    UnknownTag(String),
+
+    /// armor tag `{0}` is already registered
+    DuplicateTag(&'static str),
+
+    /// invalid character `{0}` in base58-encoded armored payload
+    InvalidChar(char),
+
+    /// invalid base58 block in armored payload
+    InvalidBlock,
+
+    /// armored payload exceeds maximal data size of 16 MiB
+    PayloadTooLarge,
+
+    /// armored payload is too short to contain checksum
+    NoChecksum,
+
+    /// armored payload checksum does not match the data
+    ChecksumMismatch,
+
+    /// unable to decode armored data: {0}
+    #[from]
+    Decoding(Error),
+}
+
+/// Types having armored text representation
+pub trait StrictArmor: StrictEncode + StrictDecode {
+    /// Tag identifying the type in the armored text header
+    const ARMOR_TAG: &'static str;
+
+    /// Limits applied to decoding of the armored payload
+    const DECODE_LIMITS: DecodeLimits = DEFAULT_DECODE_LIMITS;
+
+    /// Produces armored text representation of the data.
+    ///
+    /// # Errors
+    ///
+    /// Fails only if the data can't be strictly encoded.
+    fn to_armored(&self) -> Result<String, Error> {
+        Ok(armor(Self::ARMOR_TAG, &self.strict_serialize()?))
+    }
+
+    /// Parses armored text, checking that it has the type tag before decoding
+    /// the payload with [`Self::DECODE_LIMITS`].
+    fn from_armored(s: &str) -> Result<Self, ArmorError> {
+        let (tag, data) = dearmor(s)?;
+        if tag != Self::ARMOR_TAG {
+            return Err(ArmorError::TagMismatch {
+                expected: Self::ARMOR_TAG,
+                actual: tag,
+            });
+        }
+        crate::strict_deserialize_with_limits(data, Self::DECODE_LIMITS)
+            .map_err(ArmorError::from)
+    }
+}
+
+/// Set of the armor tags known to an application
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct ArmorRegistry(BTreeSet<&'static str>);
+
+impl ArmorRegistry {
+    /// Constructs empty registry.
+    #[inline]
+    pub fn new() -> Self { ArmorRegistry::default() }
+
+    /// Registers tag of the type `T`, failing if the tag is invalid or was
+    /// already registered by some other type.
+    pub fn register<T: StrictArmor>(&mut self) -> Result<(), ArmorError> {
+        check_tag(T::ARMOR_TAG)?;
+        if !self.0.insert(T::ARMOR_TAG) {
+            return Err(ArmorError::DuplicateTag(T::ARMOR_TAG));
+        }
+        Ok(())
+    }
+
+    /// Checks whether the tag is registered.
+    #[inline]
+    pub fn contains(&self, tag: &str) -> bool { self.0.contains(tag) }
+
+    /// Iterates over the registered tags.
+    #[inline]
+    pub fn tags(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.0.iter().copied()
+    }
+
+    /// Detects tag of the armored text, failing if the tag is not registered
+    /// or if the armored data are invalid.
+    pub fn identify(&self, s: &str) -> Result<&'static str, ArmorError> {
+        let (tag, _) = dearmor(s)?;
+        self.0
+            .get(tag.as_str())
+            .copied()
+            .ok_or(ArmorError::UnknownTag(tag))
+    }
+}
+
+/// Produces armored text for the data with the given tag.
+///
+/// # Panics
+///
+/// If the tag is invalid, i.e. is empty, longer than [`MAX_TAG_LEN`] or
+/// contains characters other than ASCII letters, digits, `-`, `_` and `.`.
+pub fn armor(tag: &str, data: &[u8]) -> String {
+    check_tag(tag).expect("invalid armor tag");
+
+    let mut payload = data.to_vec();
+    payload.extend(checksum(tag, data));
+    let payload = base58_encode(&payload);
+
+    let mut s = format!("{}{}{}\n", HEADER_PREFIX, tag, DELIMITER_SUFFIX);
+    for line in payload.as_bytes().chunks(LINE_WIDTH) {
+        // Base58 alphabet is ASCII-only
+        s.extend(line.iter().map(|c| *c as char));
+        s.push('\n');
+    }
+    s.push_str(&format!("{}{}{}\n", FOOTER_PREFIX, tag, DELIMITER_SUFFIX));
+    s
+}
+
+/// Parses armored text, returning its tag and verified data.
+///
+/// Fails with [`ArmorError::PayloadTooLarge`] before decoding the payload if it
+/// can't fit [`MAX_PAYLOAD_LEN`] bytes.
+pub fn dearmor(s: &str) -> Result<(String, Vec<u8>), ArmorError> {
+    let mut lines = s.lines().map(str::trim).filter(|line| !line.is_empty());
+
+    let tag = lines
+        .next()
+        .and_then(|line| line.strip_prefix(HEADER_PREFIX))
+        .and_then(|line| line.strip_suffix(DELIMITER_SUFFIX))
+        .ok_or(ArmorError::NoHeader)?;
+    check_tag(tag)?;
+
+    let mut payload = String::new();
+    let mut footer = None;
+    for line in lines.by_ref() {
+        if let Some(rest) = line.strip_prefix(FOOTER_PREFIX) {
+            footer = Some(
+                rest.strip_suffix(DELIMITER_SUFFIX)
+                    .ok_or(ArmorError::NoFooter)?,
+            );
+            break;
+        }
+        if payload.len() + line.len() > MAX_ENCODED_LEN {
+            return Err(ArmorError::PayloadTooLarge);
+        }
+        payload.push_str(line);
+    }
+    let footer = footer.ok_or(ArmorError::NoFooter)?;
+    if footer != tag {
+        return Err(ArmorError::FooterMismatch(
+            tag.to_string(),
+            footer.to_string(),
+        ));
+    }
+    if lines.next().is_some() {
+        return Err(ArmorError::NoFooter);
+    }
+
+    let mut data = base58_decode(&payload)?;
+    if data.len() < CHECKSUM_LEN {
+        return Err(ArmorError::NoChecksum);
+    }
+    let check = data.split_off(data.len() - CHECKSUM_LEN);
+    if check != checksum(tag, &data) {
+        return Err(ArmorError::ChecksumMismatch);
+    }
+    Ok((tag.to_string(), data))
+}
+
+fn check_tag(tag: &str) -> Result<(), ArmorError> {
+    if tag.is_empty()
+        || tag.len() > MAX_TAG_LEN
+        || !tag.chars().all(|c| {
+            c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.'
+        })
+    {
+        return Err(ArmorError::InvalidTag(tag.to_string()));
+    }
+    Ok(())
+}
+
+fn checksum(tag: &str, data: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let mut engine = sha256d::Hash::engine();
+    tag.strict_encode(&mut engine)
+        .expect("tag length is checked to fit u16");
+    engine.input(data);
+    let hash = sha256d::Hash::from_engine(engine);
+    let mut checksum = [0u8; CHECKSUM_LEN];
+    checksum.copy_from_slice(&hash[..CHECKSUM_LEN]);
+    checksum
+}
+
+const fn base58_encoded_len(len: usize) -> usize {
+    len / BLOCK_LEN * ENCODED_BLOCK_LEN[BLOCK_LEN]
+        + ENCODED_BLOCK_LEN[len % BLOCK_LEN]
+}
+
+fn base58_encode(data: &[u8]) -> String {
+    let mut s = String::with_capacity(base58_encoded_len(data.len()));
+    let mut digits = [0u8; ENCODED_BLOCK_LEN[BLOCK_LEN]];
+    for block in data.chunks(BLOCK_LEN) {
+        let mut num =
+            block.iter().fold(0u64, |num, byte| num << 8 | *byte as u64);
+        let digits = &mut digits[..ENCODED_BLOCK_LEN[block.len()]];
+        for digit in digits.iter_mut().rev() {
+            *digit = BASE58_ALPHABET[(num % 58) as usize];
+            num /= 58;
+        }
+        // Base58 alphabet is ASCII-only
+        s.extend(digits.iter().map(|c| *c as char));
+    }
+    s
+}
+
+fn base58_decode(s: &str) -> Result<Vec<u8>, ArmorError> {
+    let digits = s
+        .chars()
+        .map(|c| {
+            BASE58_ALPHABET
+                .iter()
+                .position(|a| *a as char == c)
+                .map(|digit| digit as u64)
+                .ok_or(ArmorError::InvalidChar(c))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut data =
+        Vec::with_capacity(digits.len() / ENCODED_BLOCK_LEN[BLOCK_LEN] * 8 + 8);
+    for block in digits.chunks(ENCODED_BLOCK_LEN[BLOCK_LEN]) {
+        let len = ENCODED_BLOCK_LEN
+            .iter()
+            .position(|len| *len == block.len())
+            .ok_or(ArmorError::InvalidBlock)?;
+        let num = block
+            .iter()
+            .try_fold(0u64, |num, digit| {
+                num.checked_mul(58)?.checked_add(*digit)
+            })
+            .ok_or(ArmorError::InvalidBlock)?;
+        if len < BLOCK_LEN && num >> (len * 8) != 0 {
+            return Err(ArmorError::InvalidBlock);
+        }
+        data.extend_from_slice(&num.to_be_bytes()[BLOCK_LEN - len..]);
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[strict_encoding(crate = crate)]
+    struct Invoice {
+        amount: u64,
+        memo: String,
+    }
+
+    impl StrictArmor for Invoice {
+        const ARMOR_TAG: &'static str = "Invoice";
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[strict_encoding(crate = crate)]
+    struct Proof(Vec<u8>);
+
+    impl StrictArmor for Proof {
+        const ARMOR_TAG: &'static str = "Proof";
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[strict_encoding(crate = crate)]
+    struct OtherProof(Vec<u8>);
+
+    impl StrictArmor for OtherProof {
+        const ARMOR_TAG: &'static str = "Proof";
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictEncode, StrictDecode)]
+    #[strict_encoding(crate = crate)]
+    struct LimitedProof(Vec<u8>);
+
+    impl StrictArmor for LimitedProof {
+        const ARMOR_TAG: &'static str = "LimitedProof";
+        const DECODE_LIMITS: DecodeLimits = DecodeLimits {
+            max_items: 2,
+            ..DEFAULT_DECODE_LIMITS
+        };
+    }
+
+    #[test]
+    fn base58_vectors() {
+        // Block test vectors from Monero
+        for (hex, b58) in [
+            ("", ""),
+            ("00", "11"),
+            ("39", "1z"),
+            ("FF", "5Q"),
+            ("0000", "111"),
+            ("0039", "11z"),
+            ("0100", "15R"),
+            ("FFFF", "LUv"),
+            ("000000", "11111"),
+            ("000039", "1111z"),
+            ("010000", "11LUw"),
+            ("FFFFFF", "2UzHL"),
+            ("00000039", "11111z"),
+            ("FFFFFFFF", "7YXq9G"),
+            ("0000000039", "111111z"),
+            ("FFFFFFFFFF", "VtB5VXc"),
+            ("FFFFFFFFFFFF", "3CUsUpv9t"),
+            ("FFFFFFFFFFFFFF", "Ahg1opVcGW"),
+            ("06156013762879F7", "22222222222"),
+            ("05E022BA374B2A00", "1z111111111"),
+            ("FFFFFFFFFFFFFFFF", "jpXCZedGfVQ"),
+            ("0000000000000000000000", "1111111111111111"),
+            ("06156013762879F7FFFFFFFF", "222222222227YXq9G"),
+        ] {
+            let data = Vec::<u8>::from_hex(hex).unwrap();
+            assert_eq!(base58_encode(&data), b58);
+            assert_eq!(base58_decode(b58).unwrap(), data);
+        }
+        assert_eq!(base58_decode("0OIl"), Err(ArmorError::InvalidChar('0')));
+        for invalid in ["1", "1111", "5R", "jpXCZedGfVR", "111111111111"] {
+            assert_eq!(base58_decode(invalid), Err(ArmorError::InvalidBlock));
+        }
+    }
+
+    #[test]
+    fn payload_size_limit() {
+        // 4-byte checksum forms incomplete block of 6 characters
+        assert_eq!(MAX_ENCODED_LEN, MAX_PAYLOAD_LEN / BLOCK_LEN * 11 + 6);
+        let max = "1".repeat(MAX_ENCODED_LEN);
+        let armored = format!(
+            "-----BEGIN STRICT X-----\n{}1\n-----END STRICT X-----",
+            max
+        );
+        assert_eq!(dearmor(&armored), Err(ArmorError::PayloadTooLarge));
+    }
+
+    use amplify::hex::FromHex;
+
+    #[test]
+    fn roundtrip() {
+        let invoice = Invoice {
+            amount: 100_000,
+            memo: s!("coffee"),
+        };
+        let armored = invoice.to_armored().unwrap();
+        assert!(armored.starts_with("-----BEGIN STRICT Invoice-----\n"));
+        assert!(armored.ends_with("\n-----END STRICT Invoice-----\n"));
+        assert_eq!(Invoice::from_armored(&armored).unwrap(), invoice);
+        // Deterministic
+        assert_eq!(invoice.to_armored().unwrap(), armored);
+
+        // Whitespace and CRLF are tolerated
+        let crlf = armored.replace('\n', "\r\n  ");
+        assert_eq!(Invoice::from_armored(&crlf).unwrap(), invoice);
+    }
+
+    #[test]
+    fn line_wrapping() {
+        let proof = Proof(vec![0xA5; 200]);
+        let armored = proof.to_armored().unwrap();
+        let lines = armored.lines().collect::<Vec<_>>();
+        assert!(lines.len() > 4);
+        for line in &lines[1..lines.len() - 1] {
+            assert!(line.len() <= LINE_WIDTH);
+        }
+        assert_eq!(lines[1].len(), LINE_WIDTH);
+        assert_eq!(Proof::from_armored(&armored).unwrap(), proof);
+    }
+
+    #[test]
+    fn tag_checks() {
+        let proof = Proof(vec![1, 2, 3]);
+        let armored = proof.to_armored().unwrap();
+        assert_eq!(
+            Invoice::from_armored(&armored),
+            Err(ArmorError::TagMismatch {
+                expected: "Invoice",
+                actual: s!("Proof"),
+            })
+        );
+
+        // Changing tag in both header and footer breaks checksum
+        let renamed = armored.replace("Proof", "Invoice");
+        assert_eq!(
+            Invoice::from_armored(&renamed),
+            Err(ArmorError::ChecksumMismatch)
+        );
+        let renamed = armored.replacen("Proof", "Other", 1);
+        assert_eq!(
+            Proof::from_armored(&renamed),
+            Err(ArmorError::FooterMismatch(s!("Other"), s!("Proof")))
+        );
+        assert_eq!(
+            dearmor(
+                "-----BEGIN STRICT In voice-----\n-----END STRICT In \
+                 voice-----"
+            ),
+            Err(ArmorError::InvalidTag(s!("In voice")))
+        );
+    }
+
+    #[test]
+    fn corrupted() {
+        let invoice = Invoice {
+            amount: 1,
+            memo: s!("memo"),
+        };
+        let armored = invoice.to_armored().unwrap();
+        let mut lines = armored.lines().map(String::from).collect::<Vec<_>>();
+
+        let mut payload = lines[1].clone().into_bytes();
+        payload[3] = if payload[3] == b'2' { b'3' } else { b'2' };
+        let mut corrupted = lines.clone();
+        corrupted[1] = String::from_utf8(payload).unwrap();
+        assert_eq!(
+            Invoice::from_armored(&corrupted.join("\n")),
+            Err(ArmorError::ChecksumMismatch)
+        );
+
+        corrupted[1] = s!("0") + &lines[1];
+        assert_eq!(
+            Invoice::from_armored(&corrupted.join("\n")),
+            Err(ArmorError::InvalidChar('0'))
+        );
+
+        assert_eq!(
+            Invoice::from_armored(&lines[1..].join("\n")),
+            Err(ArmorError::NoHeader)
+        );
+        lines.pop();
+        assert_eq!(
+            Invoice::from_armored(&lines.join("\n")),
+            Err(ArmorError::NoFooter)
+        );
+        assert_eq!(
+            dearmor("-----BEGIN STRICT X-----\n2g\n-----END STRICT X-----"),
+            Err(ArmorError::NoChecksum)
+        );
+    }
+
+    #[test]
+    fn payload_decoding_error() {
+        let armored = armor(Invoice::ARMOR_TAG, &[1, 2, 3]);
+        assert!(matches!(
+            Invoice::from_armored(&armored),
+            Err(ArmorError::Decoding(_))
+        ));
+
+        let armored = armor(LimitedProof::ARMOR_TAG, &[3, 0, 1, 2, 3]);
+        assert_eq!(
+            LimitedProof::from_armored(&armored)
+                .map_err(|err| match err {
+                    ArmorError::Decoding(err) => err.into_root_cause(),
+                    _ => unreachable!(),
+                })
+                .unwrap_err(),
+            Error::ItemsLimitExceeded(3, 2)
+        );
+    }
+
+    #[test]
+    fn registry() {
+        let mut registry = ArmorRegistry::new();
+        registry.register::<Invoice>().unwrap();
+        registry.register::<Proof>().unwrap();
+        assert_eq!(
+            registry.register::<OtherProof>(),
+            Err(ArmorError::DuplicateTag("Proof"))
+        );
+        assert!(registry.contains("Invoice"));
+        assert_eq!(registry.tags().collect::<Vec<_>>(), vec![
+            "Invoice", "Proof"
+        ]);
+
+        let armored = Proof(vec![]).to_armored().unwrap();
+        assert_eq!(registry.identify(&armored), Ok("Proof"));
+        let armored = armor("Unknown", &[]);
+        assert_eq!(
+            registry.identify(&armored),
+            Err(ArmorError::UnknownTag(s!("Unknown")))
+        );
+    }
+}
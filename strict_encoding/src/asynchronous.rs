@@ -0,0 +1,3243 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Asynchronous versions of strict encoding traits working with tokio
+//! [`AsyncRead`] and [`AsyncWrite`] streams.
+//!
+//! Data produced and consumed by [`StrictEncodeAsync`] and
+//! [`StrictDecodeAsync`] are byte-to-byte identical to the ones of their
+//! blocking counterparts, [`StrictEncode`] and [`StrictDecode`]. Both traits
+//! can be derived with `#[derive(StrictEncodeAsync, StrictDecodeAsync)]` using
+//! the same `#[strict_encoding]` attributes as the blocking derives. Types
+//! with TLV extensions derive them with
+//! `#[derive(NetworkEncodeAsync, NetworkDecodeAsync)]`, which rely on the TLV
+//! stream conversions provided by the blocking `NetworkEncode` and
+//! `NetworkDecode` derives.
+//!
+//! Asynchronous decoders check the same [`DecodeLimits`] as the blocking ones
+//! when they are run with [`LimitedAsyncReader::decode`].
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Debug;
+use std::future::Future;
+use std::hash::Hash;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use std::{any, io};
+
+#[cfg(feature = "std")]
+use amplify::flags::FlagVec;
+#[cfg(feature = "float")]
+use amplify::num::apfloat::ieee;
+use amplify::num::{i1024, i256, i512, u1024, u24, u256, u512};
+use amplify::{Slice32, Wrapper};
+pub use async_trait::async_trait;
+#[cfg(feature = "float")]
+use half::bf16;
+pub use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, ReadBuf};
+
+use crate::limits::{
+    check_items, is_canonical, prealloc_capacity, AsyncContext, DepthGuard,
+};
+use crate::strategies::{HashFixedBytes, Strategy, UsingUniformAddr, Wrapped};
+use crate::tlv::TlvError;
+use crate::{
+    net, BigSize, DecodeLimits, Error, LargeVec, MediumVec, StrictDecode,
+    StrictEncode, TlvStream,
+};
+
+/// Asynchronous version of [`StrictEncode`] trait, producing the same data
+/// as the blocking version does.
+#[async_trait]
+pub trait StrictEncodeAsync: Sync {
+    /// Encode with the given [`AsyncWrite`] instance; must return result
+    /// with either amount of bytes encoded – or implementation-specific
+    /// error type.
+    async fn strict_encode_async<E>(&self, e: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send;
+
+    /// Serializes data as a byte array using
+    /// [`StrictEncodeAsync::strict_encode_async`] function
+    async fn strict_serialize_async(&self) -> Result<Vec<u8>, Error> {
+        let mut e = vec![];
+        let _ = self.strict_encode_async(&mut e).await?;
+        Ok(e)
+    }
+}
+
+/// Asynchronous version of [`StrictDecode`] trait, consuming the same data
+/// as the blocking version does.
+#[async_trait]
+pub trait StrictDecodeAsync: Sized + Send {
+    /// Decode with the given [`AsyncRead`] instance; must either construct an
+    /// instance or return implementation-specific error type.
+    async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+    where
+        D: AsyncRead + Unpin + Send;
+
+    /// Tries to deserialize byte array into the current type using
+    /// [`StrictDecodeAsync::strict_decode_async`]. If there are some data
+    /// remains in the buffer once deserialization is completed, fails with
+    /// [`Error::DataNotEntirelyConsumed`].
+    async fn strict_deserialize_async(
+        data: impl AsRef<[u8]> + Send + 'async_trait,
+    ) -> Result<Self, Error> {
+        let mut data = data.as_ref();
+        let obj = Self::strict_decode_async(&mut data).await?;
+        if data.is_empty() {
+            Ok(obj)
+        } else {
+            Err(Error::DataNotEntirelyConsumed)
+        }
+    }
+}
+
+/// Asynchronous version of [`crate::StrictMigrate`] trait, decoding data of
+/// the older versions of the data structure. Required by
+/// `#[derive(StrictDecodeAsync)]` for the types using `migrate` attribute.
+#[async_trait]
+pub trait StrictMigrateAsync: Sized + Send {
+    /// Decodes data of the older `version` of the data structure, which
+    /// version byte was already read by the caller, converting it into the
+    /// current version.
+    async fn strict_migrate_async<D>(
+        version: u8,
+        d: &mut D,
+    ) -> Result<Self, Error>
+    where
+        D: AsyncRead + Unpin + Send;
+}
+
+/// Asynchronous reader wrapper enforcing [`DecodeLimits`] during strict
+/// decoding, an asynchronous counterpart of [`crate::LimitedReader`].
+#[derive(Debug)]
+pub struct LimitedAsyncReader<R>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    inner: R,
+    limits: DecodeLimits,
+    consumed: usize,
+    exhausted: bool,
+}
+
+impl<R> LimitedAsyncReader<R>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    /// Constructs reader applying `limits` to the data read from `inner`
+    pub fn new(inner: R, limits: DecodeLimits) -> Self {
+        LimitedAsyncReader {
+            inner,
+            limits,
+            consumed: 0,
+            exhausted: false,
+        }
+    }
+
+    /// Returns limits applied by the reader
+    pub fn limits(&self) -> DecodeLimits { self.limits }
+
+    /// Returns number of bytes consumed from the inner reader
+    pub fn consumed(&self) -> usize { self.consumed }
+
+    /// Releases inner reader
+    pub fn into_inner(self) -> R { self.inner }
+
+    /// Decodes object of type `T` from the reader, applying reader limits to
+    /// all nested collections.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::BytesLimitExceeded`], [`Error::DepthLimitExceeded`]
+    /// or [`Error::ItemsLimitExceeded`] when the data violate the limits, or
+    /// with any other error returned by the decoder of `T`.
+    pub async fn decode<T>(&mut self) -> Result<T, Error>
+    where
+        T: StrictDecodeAsync,
+    {
+        let limits = self.limits;
+        let res = LimitedDecoding {
+            context: AsyncContext::new(limits),
+            future: Some(T::strict_decode_async(&mut *self)),
+        }
+        .await;
+        res.map_err(|err| {
+            if self.exhausted {
+                Error::BytesLimitExceeded(limits.max_bytes)
+            } else {
+                err
+            }
+        })
+    }
+}
+
+impl<R> AsyncRead for LimitedAsyncReader<R>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+        let remaining = this.limits.max_bytes - this.consumed;
+        // Decoders may probe for the end of data, which must not fail if the
+        // budget matches the data length exactly, so with exhausted budget we
+        // still read a single byte
+        let mut chunk = [0u8; 1024];
+        let max = buf.remaining().min(remaining).min(chunk.len()).max(1);
+        let mut limited = ReadBuf::new(&mut chunk[..max]);
+        match Pin::new(&mut this.inner).poll_read(cx, &mut limited) {
+            Poll::Ready(Ok(())) => {}
+            other => return other,
+        }
+        let read = limited.filled().len();
+        if read > remaining {
+            this.exhausted = true;
+            return Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::Other,
+                "strict decoding byte limit exceeded",
+            )));
+        }
+        this.consumed += read;
+        buf.put_slice(limited.filled());
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Future running decoding procedure with the decoding context being active
+/// each time the procedure is polled. The procedure is also dropped inside the
+/// context, since it may hold [`DepthGuard`]s.
+struct LimitedDecoding<F>
+where
+    F: Future + Unpin,
+{
+    context: AsyncContext,
+    future: Option<F>,
+}
+
+impl<F> Future for LimitedDecoding<F>
+where
+    F: Future + Unpin,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let future = &mut this.future;
+        this.context.activate(|| {
+            let poll = Pin::new(
+                future
+                    .as_mut()
+                    .expect("decoding future is polled after completion"),
+            )
+            .poll(cx);
+            if poll.is_ready() {
+                *future = None;
+            }
+            poll
+        })
+    }
+}
+
+impl<F> Drop for LimitedDecoding<F>
+where
+    F: Future + Unpin,
+{
+    fn drop(&mut self) {
+        let future = &mut self.future;
+        self.context.activate(|| *future = None);
+    }
+}
+
+/// Implements asynchronous encoding for types with fixed-size encoding by
+/// running the blocking implementation over a stack buffer.
+macro_rules! impl_async_fixed_size {
+    ($len:expr => $($ty:ty),+ $(,)?) => {
+        $(
+            #[async_trait]
+            impl StrictEncodeAsync for $ty {
+                async fn strict_encode_async<E>(
+                    &self,
+                    e: &mut E,
+                ) -> Result<usize, Error>
+                where
+                    E: AsyncWrite + Unpin + Send,
+                {
+                    let mut buf = [0u8; $len];
+                    let len = self.strict_encode(&mut buf[..])?;
+                    e.write_all(&buf[..len]).await?;
+                    Ok(len)
+                }
+            }
+
+            #[async_trait]
+            impl StrictDecodeAsync for $ty {
+                async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+                where
+                    D: AsyncRead + Unpin + Send,
+                {
+                    let mut buf = [0u8; $len];
+                    d.read_exact(&mut buf).await?;
+                    Self::strict_decode(&buf[..])
+                }
+            }
+        )+
+    };
+}
+
+impl_async_fixed_size!(1 => bool, u8, i8);
+impl_async_fixed_size!(2 => u16, i16, usize);
+impl_async_fixed_size!(3 => u24);
+impl_async_fixed_size!(4 => u32, i32, f32);
+impl_async_fixed_size!(8 => u64, i64, f64);
+impl_async_fixed_size!(12 => Duration);
+impl_async_fixed_size!(16 => u128, i128);
+impl_async_fixed_size!(32 => u256, i256);
+impl_async_fixed_size!(64 => u512, i512);
+impl_async_fixed_size!(128 => u1024, i1024);
+impl_async_fixed_size!(32 => Slice32);
+#[cfg(feature = "float")]
+impl_async_fixed_size!(2 => bf16, ieee::Half);
+#[cfg(feature = "float")]
+impl_async_fixed_size!(10 => ieee::X87DoubleExtended);
+#[cfg(feature = "float")]
+impl_async_fixed_size!(16 => ieee::Quad);
+#[cfg(feature = "float")]
+impl_async_fixed_size!(32 => ieee::Oct);
+#[cfg(feature = "chrono")]
+impl_async_fixed_size!(8 => chrono::NaiveDateTime, chrono::DateTime<chrono::Utc>);
+
+#[async_trait]
+impl StrictEncodeAsync for () {
+    async fn strict_encode_async<E>(&self, _: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        Ok(0)
+    }
+}
+
+#[async_trait]
+impl StrictDecodeAsync for () {
+    async fn strict_decode_async<D>(_: &mut D) -> Result<Self, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<const LEN: usize> StrictEncodeAsync for [u8; LEN] {
+    async fn strict_encode_async<E>(&self, e: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        e.write_all(self).await?;
+        Ok(LEN)
+    }
+}
+
+#[async_trait]
+impl<const LEN: usize> StrictDecodeAsync for [u8; LEN] {
+    async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        let mut buf = [0u8; LEN];
+        d.read_exact(&mut buf).await?;
+        Ok(buf)
+    }
+}
+
+async fn encode_bytes<E>(bytes: &[u8], e: &mut E) -> Result<usize, Error>
+where
+    E: AsyncWrite + Unpin + Send,
+{
+    let len = bytes.len().strict_encode_async(e).await?;
+    e.write_all(bytes).await?;
+    Ok(len + bytes.len())
+}
+
+async fn decode_bytes<D>(d: &mut D) -> Result<Vec<u8>, Error>
+where
+    D: AsyncRead + Unpin + Send,
+{
+    let len = usize::strict_decode_async(d).await?;
+    check_items(len)?;
+    let mut buf = vec![0u8; len];
+    d.read_exact(&mut buf).await?;
+    Ok(buf)
+}
+
+/// Decodes bytes in the same way the decoder of `Vec<u8>` does, reading all
+/// of them at once.
+async fn decode_byte_vec<D>(d: &mut D) -> Result<Vec<u8>, Error>
+where
+    D: AsyncRead + Unpin + Send,
+{
+    let _depth = DepthGuard::enter()?;
+    decode_bytes(d).await
+}
+
+/// Encodes value with its blocking encoder into a memory buffer, writing the
+/// buffer afterwards. Used for the types which are never large and which do
+/// not benefit from asynchronous encoding.
+async fn encode_buffered<T, E>(value: &T, e: &mut E) -> Result<usize, Error>
+where
+    T: StrictEncode + Sync + ?Sized,
+    E: AsyncWrite + Unpin + Send,
+{
+    let data = value.strict_serialize()?;
+    e.write_all(&data).await?;
+    Ok(data.len())
+}
+
+#[async_trait]
+impl StrictEncodeAsync for Box<[u8]> {
+    async fn strict_encode_async<E>(&self, e: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        encode_bytes(self, e).await
+    }
+}
+
+#[async_trait]
+impl StrictDecodeAsync for Box<[u8]> {
+    async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        Ok(decode_bytes(d).await?.into_boxed_slice())
+    }
+}
+
+// Lifetime introduced by `async_trait` expansion
+#[allow(clippy::needless_lifetimes)]
+#[async_trait]
+impl StrictEncodeAsync for &str {
+    async fn strict_encode_async<E>(&self, e: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        encode_bytes(self.as_bytes(), e).await
+    }
+}
+
+#[async_trait]
+impl StrictEncodeAsync for String {
+    async fn strict_encode_async<E>(&self, e: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        encode_bytes(self.as_bytes(), e).await
+    }
+}
+
+#[async_trait]
+impl StrictDecodeAsync for String {
+    async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        String::from_utf8(decode_byte_vec(d).await?).map_err(Error::from)
+    }
+}
+
+#[async_trait]
+impl<T> StrictEncodeAsync for Arc<T>
+where
+    T: StrictEncodeAsync + Send,
+{
+    async fn strict_encode_async<E>(&self, e: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        self.as_ref().strict_encode_async(e).await
+    }
+}
+
+#[async_trait]
+impl<T> StrictDecodeAsync for Arc<T>
+where
+    T: StrictDecodeAsync + Sync,
+{
+    async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        let _depth = DepthGuard::enter()?;
+        Ok(Arc::new(T::strict_decode_async(d).await?))
+    }
+}
+
+#[async_trait]
+impl<T> StrictEncodeAsync for Option<T>
+where
+    T: StrictEncodeAsync,
+{
+    async fn strict_encode_async<E>(&self, e: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        Ok(match self {
+            None => 0u8.strict_encode_async(e).await?,
+            Some(val) => {
+                1u8.strict_encode_async(e).await?
+                    + val.strict_encode_async(e).await?
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl<T> StrictDecodeAsync for Option<T>
+where
+    T: StrictDecodeAsync,
+{
+    async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        match u8::strict_decode_async(d).await? {
+            0 => Ok(None),
+            1 => Ok(Some(T::strict_decode_async(d).await?)),
+            invalid => Err(Error::WrongOptionalEncoding(invalid)),
+        }
+    }
+}
+
+/// Encodes items of a collection without its length prefix.
+pub(crate) async fn encode_items<'a, T, E>(
+    items: impl IntoIterator<Item = &'a T> + Send + 'a,
+    e: &mut E,
+) -> Result<usize, Error>
+where
+    T: StrictEncodeAsync + 'a,
+    E: AsyncWrite + Unpin + Send,
+{
+    let mut len = 0;
+    for item in items {
+        len += item.strict_encode_async(e).await?;
+    }
+    Ok(len)
+}
+
+/// Decodes `len` items of a list, which length prefix was already read by the
+/// caller.
+pub(crate) async fn decode_items<T, D>(
+    len: usize,
+    d: &mut D,
+) -> Result<Vec<T>, Error>
+where
+    T: StrictDecodeAsync,
+    D: AsyncRead + Unpin + Send,
+{
+    check_items(len)?;
+    let _depth = DepthGuard::enter()?;
+    let mut items = Vec::with_capacity(prealloc_capacity::<T>(len));
+    for index in 0..len {
+        items.push(
+            T::strict_decode_async(d)
+                .await
+                .map_err(|err| err.within_item(index))?,
+        );
+    }
+    Ok(items)
+}
+
+#[async_trait]
+impl<T> StrictEncodeAsync for Vec<T>
+where
+    T: StrictEncodeAsync,
+{
+    async fn strict_encode_async<E>(&self, e: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        Ok(self.len().strict_encode_async(e).await?
+            + encode_items(self, e).await?)
+    }
+}
+
+#[async_trait]
+impl<T> StrictDecodeAsync for Vec<T>
+where
+    T: StrictDecodeAsync,
+{
+    async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        let len = usize::strict_decode_async(d).await?;
+        decode_items(len, d).await
+    }
+}
+
+#[async_trait]
+impl<T> StrictEncodeAsync for MediumVec<T>
+where
+    T: StrictEncode + StrictDecode + StrictEncodeAsync,
+{
+    async fn strict_encode_async<E>(&self, e: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        let len = u24::try_from(self.len() as u32)
+            .map_err(|_| Error::ExceedMaxItems(self.len()))?;
+        Ok(len.strict_encode_async(e).await? + encode_items(self, e).await?)
+    }
+}
+
+#[async_trait]
+impl<T> StrictDecodeAsync for MediumVec<T>
+where
+    T: StrictEncode + StrictDecode + StrictDecodeAsync,
+{
+    async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        let len = u24::strict_decode_async(d).await?.as_u32() as usize;
+        MediumVec::try_from(decode_items(len, d).await?)
+    }
+}
+
+#[async_trait]
+impl<T> StrictEncodeAsync for LargeVec<T>
+where
+    T: StrictEncode + StrictDecode + StrictEncodeAsync,
+{
+    async fn strict_encode_async<E>(&self, e: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        let len = u32::try_from(self.len())
+            .map_err(|_| Error::ExceedMaxItems(self.len()))?;
+        Ok(len.strict_encode_async(e).await? + encode_items(self, e).await?)
+    }
+}
+
+#[async_trait]
+impl<T> StrictDecodeAsync for LargeVec<T>
+where
+    T: StrictEncode + StrictDecode + StrictDecodeAsync,
+{
+    async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        let len = u32::strict_decode_async(d).await? as usize;
+        LargeVec::try_from(decode_items(len, d).await?)
+    }
+}
+
+#[async_trait]
+impl<T> StrictEncodeAsync for HashSet<T>
+where
+    T: StrictEncodeAsync + Eq + Ord + Hash + Debug,
+{
+    async fn strict_encode_async<E>(&self, e: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        let mut vec: Vec<&T> = self.iter().collect();
+        vec.sort();
+        Ok(self.len().strict_encode_async(e).await?
+            + encode_items(vec, e).await?)
+    }
+}
+
+#[async_trait]
+impl<T> StrictDecodeAsync for HashSet<T>
+where
+    T: StrictDecodeAsync + Eq + Ord + Hash + Debug,
+{
+    async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        let len = usize::strict_decode_async(d).await?;
+        check_items(len)?;
+        let _depth = DepthGuard::enter()?;
+        if is_canonical() {
+            // Values are sorted, so it is sufficient to compare each of them
+            // with the previous one
+            let mut data = Vec::<T>::with_capacity(prealloc_capacity::<T>(len));
+            for index in 0..len {
+                let val = T::strict_decode_async(d)
+                    .await
+                    .map_err(|err| err.within_item(index))?;
+                match data.last() {
+                    Some(last) if *last == val => {
+                        return Err(Error::RepeatedValue(format!("{:?}", val)))
+                    }
+                    Some(last) if *last > val => {
+                        return Err(Error::NonCanonical(
+                            any::type_name::<Self>(),
+                        ))
+                    }
+                    _ => data.push(val),
+                }
+            }
+            return Ok(data.into_iter().collect());
+        }
+        let mut data = HashSet::<T>::with_capacity(prealloc_capacity::<T>(len));
+        for index in 0..len {
+            let val = T::strict_decode_async(d)
+                .await
+                .map_err(|err| err.within_item(index))?;
+            if data.contains(&val) {
+                return Err(Error::RepeatedValue(format!("{:?}", val)));
+            }
+            data.insert(val);
+        }
+        Ok(data)
+    }
+}
+
+#[async_trait]
+impl<T> StrictEncodeAsync for BTreeSet<T>
+where
+    T: StrictEncodeAsync + Eq + Ord + Debug,
+{
+    async fn strict_encode_async<E>(&self, e: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        Ok(self.len().strict_encode_async(e).await?
+            + encode_items(self, e).await?)
+    }
+}
+
+#[async_trait]
+impl<T> StrictDecodeAsync for BTreeSet<T>
+where
+    T: StrictDecodeAsync + Eq + Ord + Debug,
+{
+    async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        let len = usize::strict_decode_async(d).await?;
+        decode_set_items(len, d).await
+    }
+}
+
+/// Decodes `len` unique items of a set, which length prefix was already read
+/// by the caller, checking their order.
+pub(crate) async fn decode_set_items<T, D>(
+    len: usize,
+    d: &mut D,
+) -> Result<BTreeSet<T>, Error>
+where
+    T: StrictDecodeAsync + Eq + Ord + Debug,
+    D: AsyncRead + Unpin + Send,
+{
+    check_items(len)?;
+    let _depth = DepthGuard::enter()?;
+    let mut data = BTreeSet::<T>::new();
+    for index in 0..len {
+        let val = T::strict_decode_async(d)
+            .await
+            .map_err(|err| err.within_item(index))?;
+        if let Some(max) = data.iter().next_back() {
+            if max > &val {
+                if is_canonical() {
+                    return Err(Error::NonCanonical(any::type_name::<
+                        BTreeSet<T>,
+                    >()));
+                }
+                return Err(Error::DataIntegrityError(format!(
+                    "encoded values are not deterministically ordered: value \
+                     `{:?}` should go before `{:?}`",
+                    val, max
+                )));
+            }
+        }
+        if data.contains(&val) {
+            return Err(Error::RepeatedValue(format!("{:?}", val)));
+        }
+        data.insert(val);
+    }
+    Ok(data)
+}
+
+#[async_trait]
+impl<T> StrictEncodeAsync for HashMap<usize, T>
+where
+    T: StrictEncodeAsync + Clone,
+{
+    async fn strict_encode_async<E>(&self, e: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        let ordered: BTreeMap<usize, &T> =
+            self.iter().map(|(key, val)| (*key, val)).collect();
+        let mut len = self.len().strict_encode_async(e).await?;
+        for (key, val) in ordered {
+            len += key.strict_encode_async(e).await?;
+            len += val.strict_encode_async(e).await?;
+        }
+        Ok(len)
+    }
+}
+
+#[async_trait]
+impl<T> StrictDecodeAsync for HashMap<usize, T>
+where
+    T: StrictDecodeAsync + Clone,
+{
+    async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        Ok(BTreeMap::<usize, T>::strict_decode_async(d)
+            .await?
+            .into_iter()
+            .collect())
+    }
+}
+
+#[async_trait]
+impl<K, V> StrictEncodeAsync for BTreeMap<K, V>
+where
+    K: StrictEncodeAsync + Ord + Clone,
+    V: StrictEncodeAsync + Clone,
+{
+    async fn strict_encode_async<E>(&self, e: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        let mut len = self.len().strict_encode_async(e).await?;
+        for (key, val) in self {
+            len += key.strict_encode_async(e).await?;
+            len += val.strict_encode_async(e).await?;
+        }
+        Ok(len)
+    }
+}
+
+#[async_trait]
+impl<K, V> StrictDecodeAsync for BTreeMap<K, V>
+where
+    K: StrictDecodeAsync + Ord + Clone + Debug,
+    V: StrictDecodeAsync + Clone,
+{
+    async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        let len = usize::strict_decode_async(d).await?;
+        decode_map_items(len, d).await
+    }
+}
+
+/// Decodes `len` key-value pairs of a map, which length prefix was already
+/// read by the caller, checking the order of the keys.
+pub(crate) async fn decode_map_items<K, V, D>(
+    len: usize,
+    d: &mut D,
+) -> Result<BTreeMap<K, V>, Error>
+where
+    K: StrictDecodeAsync + Ord + Clone + Debug,
+    V: StrictDecodeAsync + Clone,
+    D: AsyncRead + Unpin + Send,
+{
+    check_items(len)?;
+    let _depth = DepthGuard::enter()?;
+    let mut map = BTreeMap::<K, V>::new();
+    for index in 0..len {
+        let key = K::strict_decode_async(d)
+            .await
+            .map_err(|err| err.within_item(index))?;
+        let val = V::strict_decode_async(d)
+            .await
+            .map_err(|err| err.within_item(index))?;
+        if let Some(max) = map.keys().next_back() {
+            if max > &key {
+                if is_canonical() {
+                    return Err(Error::NonCanonical(any::type_name::<
+                        BTreeMap<K, V>,
+                    >()));
+                }
+                return Err(Error::DataIntegrityError(format!(
+                    "encoded values are not deterministically ordered: value \
+                     `{:?}` should go before `{:?}`",
+                    key, max
+                )));
+            }
+        }
+        if map.contains_key(&key) {
+            return Err(Error::RepeatedValue(format!("{:?}", key)));
+        }
+        map.insert(key, val);
+    }
+    Ok(map)
+}
+
+#[async_trait]
+impl<K, V> StrictEncodeAsync for (K, V)
+where
+    K: StrictEncodeAsync + Clone,
+    V: StrictEncodeAsync + Clone,
+{
+    async fn strict_encode_async<E>(&self, e: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        Ok(self.0.strict_encode_async(e).await?
+            + self.1.strict_encode_async(e).await?)
+    }
+}
+
+#[async_trait]
+impl<K, V> StrictDecodeAsync for (K, V)
+where
+    K: StrictDecodeAsync + Clone,
+    V: StrictDecodeAsync + Clone,
+{
+    async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        let a = K::strict_decode_async(d).await?;
+        let b = V::strict_decode_async(d).await?;
+        Ok((a, b))
+    }
+}
+
+#[cfg(feature = "std")]
+#[async_trait]
+impl StrictEncodeAsync for FlagVec {
+    async fn strict_encode_async<E>(&self, e: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        encode_bytes(self.shrunk().as_inner(), e).await
+    }
+}
+
+#[cfg(feature = "std")]
+#[async_trait]
+impl StrictDecodeAsync for FlagVec {
+    async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        Ok(Self::from_inner(decode_byte_vec(d).await?))
+    }
+}
+
+#[async_trait]
+impl StrictEncodeAsync for BigSize {
+    async fn strict_encode_async<E>(&self, e: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        let mut buf = [0u8; 9];
+        let len = self.strict_encode(&mut buf[..])?;
+        e.write_all(&buf[..len]).await?;
+        Ok(len)
+    }
+}
+
+#[async_trait]
+impl StrictDecodeAsync for BigSize {
+    async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        let marker = d.read_u8().await?;
+        decode_big_size_after(marker, d).await
+    }
+}
+
+/// Decodes the rest of the [`BigSize`] value once its first byte is already
+/// read.
+async fn decode_big_size_after<D>(
+    marker: u8,
+    d: &mut D,
+) -> Result<BigSize, Error>
+where
+    D: AsyncRead + Unpin + Send,
+{
+    let mut buf = [0u8; 8];
+    let len = match marker {
+        0xFD => 2,
+        0xFE => 4,
+        0xFF => 8,
+        _ => 0,
+    };
+    d.read_exact(&mut buf[..len]).await?;
+    BigSize::decode_after(marker, &buf[..len])
+}
+
+#[async_trait]
+impl StrictEncodeAsync for TlvStream {
+    async fn strict_encode_async<E>(&self, e: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        if self.is_empty() {
+            return Ok(0);
+        }
+        let mut len = self.len().strict_encode_async(e).await?;
+        for (type_no, value) in self {
+            len += type_no.strict_encode_async(e).await?;
+            len += value.strict_encode_async(e).await?;
+        }
+        Ok(len)
+    }
+}
+
+#[async_trait]
+impl StrictDecodeAsync for TlvStream {
+    async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        // Empty TLV stream is not encoded at all, so the end of data here
+        // means absence of TLV records. Zero length prefix and truncated
+        // length prefix are not canonical encodings of the empty stream.
+        let mut prefix = [0u8; 2];
+        let mut read = 0;
+        while read < prefix.len() {
+            match d.read(&mut prefix[read..]).await? {
+                0 => break,
+                len => read += len,
+            }
+        }
+        let len = u16::from_le_bytes(prefix) as usize;
+        if read < prefix.len() || len == 0 {
+            if read > 0 && is_canonical() {
+                return Err(Error::NonCanonical(any::type_name::<Self>()));
+            }
+            return Ok(TlvStream::new());
+        }
+        check_items(len)?;
+        let _depth = DepthGuard::enter()?;
+        let mut stream = BTreeMap::new();
+        let mut max = None;
+        for _ in 0..len {
+            let type_no = u64::strict_decode_async(d).await?;
+            let value = Box::<[u8]>::strict_decode_async(d).await?;
+            check_tlv_order(type_no, max)?;
+            max = Some(type_no);
+            stream.insert(type_no, value);
+        }
+        Ok(TlvStream::from(stream))
+    }
+}
+
+fn check_tlv_order(type_no: u64, max: Option<u64>) -> Result<(), TlvError> {
+    match max {
+        Some(max) if max == type_no => Err(TlvError::Repeated(type_no)),
+        Some(max) if max > type_no => {
+            Err(TlvError::Order { read: type_no, max })
+        }
+        _ => Ok(()),
+    }
+}
+
+impl TlvStream {
+    /// Asynchronous version of [`TlvStream::bolt_encode`].
+    pub async fn bolt_encode_async<E>(&self, e: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        let mut len = 0;
+        for (type_no, value) in self {
+            len += BigSize::from(*type_no).strict_encode_async(e).await?;
+            len += BigSize::from(value.len() as u64)
+                .strict_encode_async(e)
+                .await?;
+            e.write_all(value).await?;
+            len += value.len();
+        }
+        Ok(len)
+    }
+
+    /// Asynchronous version of [`TlvStream::bolt_decode`].
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`TlvError::Order`] or [`TlvError::Repeated`] if the
+    /// records are not ordered or repeated, and with [`TlvError::Len`] if
+    /// the data end before the end of a record value.
+    pub async fn bolt_decode_async<D>(d: &mut D) -> Result<TlvStream, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        let mut stream = BTreeMap::new();
+        let mut max = None;
+        loop {
+            let mut marker = [0u8; 1];
+            let type_no = match d.read(&mut marker).await? {
+                0 => break,
+                _ => decode_big_size_after(marker[0], d).await?.into_inner(),
+            };
+            check_tlv_order(type_no, max)?;
+            max = Some(type_no);
+            let expected = BigSize::strict_decode_async(d).await?.into_inner();
+            let mut value = Vec::new();
+            let read = (&mut *d).take(expected).read_to_end(&mut value).await?;
+            if (read as u64) < expected {
+                return Err(TlvError::Len {
+                    expected,
+                    actual: read as u64,
+                }
+                .into());
+            }
+            stream.insert(type_no, value.into_boxed_slice());
+        }
+        Ok(TlvStream::from(stream))
+    }
+}
+
+#[async_trait]
+impl<T> StrictEncodeAsync for T
+where
+    T: Strategy + Clone + Sync + Send,
+    <T as Strategy>::Strategy: Sync + Send,
+    amplify::Holder<T, <T as Strategy>::Strategy>: StrictEncodeAsync,
+{
+    #[inline]
+    async fn strict_encode_async<E>(&self, e: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        amplify::Holder::new(self.clone())
+            .strict_encode_async(e)
+            .await
+    }
+}
+
+#[async_trait]
+impl<T> StrictDecodeAsync for T
+where
+    T: Strategy + Send,
+    amplify::Holder<T, <T as Strategy>::Strategy>: StrictDecodeAsync,
+{
+    #[inline]
+    async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        Ok(amplify::Holder::strict_decode_async(d).await?.into_inner())
+    }
+}
+
+#[async_trait]
+impl<W> StrictEncodeAsync for amplify::Holder<W, Wrapped>
+where
+    W: Wrapper + Sync,
+    W::Inner: StrictEncodeAsync,
+{
+    #[inline]
+    async fn strict_encode_async<E>(&self, e: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        self.as_inner().as_inner().strict_encode_async(e).await
+    }
+}
+
+#[async_trait]
+impl<W> StrictDecodeAsync for amplify::Holder<W, Wrapped>
+where
+    W: Wrapper + Send,
+    W::Inner: StrictDecodeAsync,
+{
+    #[inline]
+    async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        Ok(Self::new(W::from_inner(
+            W::Inner::strict_decode_async(d).await?,
+        )))
+    }
+}
+
+#[async_trait]
+impl<H> StrictEncodeAsync for amplify::Holder<H, HashFixedBytes>
+where
+    H: bitcoin_hashes::Hash + Sync,
+{
+    #[inline]
+    async fn strict_encode_async<E>(&self, e: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        e.write_all(&self.as_inner()[..]).await?;
+        Ok(H::LEN)
+    }
+}
+
+#[async_trait]
+impl<H> StrictDecodeAsync for amplify::Holder<H, HashFixedBytes>
+where
+    H: bitcoin_hashes::Hash + Send,
+{
+    #[inline]
+    async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        let mut buf = vec![0u8; H::LEN];
+        d.read_exact(&mut buf).await?;
+        Ok(Self::new(H::from_slice(&buf).expect(
+            "internal hash data representation length mismatch between \
+             `from_slice` requirements and `LEN` constant balue",
+        )))
+    }
+}
+
+#[async_trait]
+impl<A> StrictEncodeAsync for amplify::Holder<A, UsingUniformAddr>
+where
+    A: net::Uniform + Sync,
+{
+    #[inline]
+    async fn strict_encode_async<E>(&self, e: &mut E) -> Result<usize, Error>
+    where
+        E: AsyncWrite + Unpin + Send,
+    {
+        self.as_inner()
+            .to_raw_uniform()
+            .strict_encode_async(e)
+            .await
+    }
+}
+
+#[async_trait]
+impl<A> StrictDecodeAsync for amplify::Holder<A, UsingUniformAddr>
+where
+    A: net::Uniform + Send,
+{
+    #[inline]
+    async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        let raw = net::RawUniformAddr::strict_decode_async(d).await?;
+        Ok(Self::new(A::from_raw_uniform_addr(raw).map_err(|err| {
+            Error::DataIntegrityError(err.to_string())
+        })?))
+    }
+}
+
+#[cfg(feature = "bitcoin")]
+pub use self::bitcoin::ConsensusFrame;
+
+#[cfg(feature = "bitcoin")]
+mod bitcoin {
+    use ::bitcoin::consensus::encode::MAX_VEC_SIZE;
+    use ::bitcoin::psbt::{self, PsbtSighashType, TapTree};
+    use ::bitcoin::secp256k1::{self, ecdsa, schnorr, XOnlyPublicKey};
+    use ::bitcoin::util::address::{self, Address, WitnessVersion};
+    use ::bitcoin::util::taproot::{
+        ControlBlock, FutureLeafVersion, LeafVersion, ScriptLeaf,
+        TaprootMerkleBranch,
+    };
+    use ::bitcoin::util::{bip32, schnorr as bip340};
+    use ::bitcoin::{
+        Amount, EcdsaSig, EcdsaSighashType, KeyPair, LockTime, OutPoint,
+        PackedLockTime, PubkeyHash, SchnorrSig, SchnorrSighashType, Script,
+        ScriptHash, Sequence, Transaction, TxIn, TxOut, Witness,
+    };
+    use bitcoin_hashes::sha256;
+
+    use super::*;
+    use crate::strategies::BitcoinConsensus;
+
+    impl_async_fixed_size!(1 => LeafVersion, FutureLeafVersion, SchnorrSighashType);
+    impl_async_fixed_size!(4 => ::bitcoin::Network, EcdsaSighashType, PsbtSighashType);
+    impl_async_fixed_size!(8 => Amount);
+    impl_async_fixed_size!(
+        32 => XOnlyPublicKey,
+        bip340::TweakedPublicKey,
+        secp256k1::SecretKey,
+        KeyPair,
+        bip340::TweakedKeyPair,
+    );
+    impl_async_fixed_size!(33 => secp256k1::PublicKey);
+    impl_async_fixed_size!(64 => ecdsa::Signature, schnorr::Signature);
+    impl_async_fixed_size!(4 => bip32::Fingerprint);
+    impl_async_fixed_size!(5 => bip32::ChildNumber);
+    impl_async_fixed_size!(32 => bip32::ChainCode);
+    impl_async_fixed_size!(78 => bip32::ExtendedPubKey, bip32::ExtendedPrivKey);
+
+    /// Implements asynchronous encoding for the types encoded as a byte
+    /// vector by reading the vector and running the blocking decoder over
+    /// it.
+    macro_rules! impl_async_byte_frame {
+        ($($ty:ty),+ $(,)?) => {
+            $(
+                #[async_trait]
+                impl StrictEncodeAsync for $ty {
+                    async fn strict_encode_async<E>(
+                        &self,
+                        e: &mut E,
+                    ) -> Result<usize, Error>
+                    where
+                        E: AsyncWrite + Unpin + Send,
+                    {
+                        encode_buffered(self, e).await
+                    }
+                }
+
+                #[async_trait]
+                impl StrictDecodeAsync for $ty {
+                    async fn strict_decode_async<D>(
+                        d: &mut D,
+                    ) -> Result<Self, Error>
+                    where
+                        D: AsyncRead + Unpin + Send,
+                    {
+                        let data = decode_bytes(d).await?;
+                        let mut frame = data.len().strict_serialize()?;
+                        frame.extend(data);
+                        Self::strict_decode(&frame[..])
+                    }
+                }
+            )+
+        };
+    }
+
+    impl_async_byte_frame!(EcdsaSig, SchnorrSig, ControlBlock, TapTree);
+
+    #[doc(hidden)]
+    #[allow(useless_deprecated)]
+    #[deprecated(
+        since = "1.4.0",
+        note = "Uncompressed PublicKey serialization is not recommended, use \
+                `secp256k1::PublicKey` type instead"
+    )]
+    #[async_trait]
+    impl StrictEncodeAsync for ::bitcoin::PublicKey {
+        async fn strict_encode_async<E>(
+            &self,
+            e: &mut E,
+        ) -> Result<usize, Error>
+        where
+            E: AsyncWrite + Unpin + Send,
+        {
+            encode_buffered(self, e).await
+        }
+    }
+
+    #[doc(hidden)]
+    #[allow(useless_deprecated)]
+    #[deprecated(
+        since = "1.4.0",
+        note = "Uncompressed PublicKey serialization is not recommended, use \
+                `secp256k1::PublicKey` type instead"
+    )]
+    #[async_trait]
+    impl StrictDecodeAsync for ::bitcoin::PublicKey {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            let marker = d.read_u8().await?;
+            let len = match marker {
+                0x04 => secp256k1::constants::UNCOMPRESSED_PUBLIC_KEY_SIZE,
+                0x03 | 0x02 => secp256k1::constants::PUBLIC_KEY_SIZE,
+                // Invalid marker is reported by the blocking decoder
+                _ => 1,
+            };
+            let mut buf = vec![0u8; len];
+            buf[0] = marker;
+            d.read_exact(&mut buf[1..]).await?;
+            Self::strict_decode(&buf[..])
+        }
+    }
+
+    #[async_trait]
+    impl StrictEncodeAsync for address::Payload {
+        async fn strict_encode_async<E>(
+            &self,
+            e: &mut E,
+        ) -> Result<usize, Error>
+        where
+            E: AsyncWrite + Unpin + Send,
+        {
+            encode_buffered(self, e).await
+        }
+    }
+
+    #[async_trait]
+    impl StrictDecodeAsync for address::Payload {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            Ok(match d.read_u8().await? {
+                32u8 => address::Payload::PubkeyHash(
+                    PubkeyHash::strict_decode_async(d).await?,
+                ),
+                33u8 => address::Payload::ScriptHash(
+                    ScriptHash::strict_decode_async(d).await?,
+                ),
+                version if version <= 16 => address::Payload::WitnessProgram {
+                    version: WitnessVersion::try_from(version)
+                        .expect("bech32::u8 decider is broken"),
+                    program: Vec::strict_decode_async(d).await?,
+                },
+                wrong => {
+                    return Err(Error::ValueOutOfRange(
+                        "witness program version",
+                        0..17,
+                        wrong as u128,
+                    ))
+                }
+            })
+        }
+    }
+
+    #[async_trait]
+    impl StrictEncodeAsync for Address {
+        async fn strict_encode_async<E>(
+            &self,
+            e: &mut E,
+        ) -> Result<usize, Error>
+        where
+            E: AsyncWrite + Unpin + Send,
+        {
+            Ok(self.network.strict_encode_async(e).await?
+                + self.payload.strict_encode_async(e).await?)
+        }
+    }
+
+    #[async_trait]
+    impl StrictDecodeAsync for Address {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            Ok(Address {
+                network: ::bitcoin::Network::strict_decode_async(d).await?,
+                payload: address::Payload::strict_decode_async(d).await?,
+            })
+        }
+    }
+
+    #[async_trait]
+    impl StrictEncodeAsync for TaprootMerkleBranch {
+        async fn strict_encode_async<E>(
+            &self,
+            e: &mut E,
+        ) -> Result<usize, Error>
+        where
+            E: AsyncWrite + Unpin + Send,
+        {
+            encode_buffered(self, e).await
+        }
+    }
+
+    #[async_trait]
+    impl StrictDecodeAsync for TaprootMerkleBranch {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            let data = Vec::<sha256::Hash>::strict_decode_async(d).await?;
+            TaprootMerkleBranch::try_from(data).map_err(|_| {
+                Error::DataIntegrityError(s!(
+                    "taproot merkle branch length exceeds 128 consensus limit"
+                ))
+            })
+        }
+    }
+
+    #[async_trait]
+    impl StrictEncodeAsync for ScriptLeaf {
+        async fn strict_encode_async<E>(
+            &self,
+            e: &mut E,
+        ) -> Result<usize, Error>
+        where
+            E: AsyncWrite + Unpin + Send,
+        {
+            encode_buffered(self, e).await
+        }
+    }
+
+    #[async_trait]
+    impl StrictEncodeAsync for bip32::DerivationPath {
+        async fn strict_encode_async<E>(
+            &self,
+            e: &mut E,
+        ) -> Result<usize, Error>
+        where
+            E: AsyncWrite + Unpin + Send,
+        {
+            encode_buffered(self, e).await
+        }
+    }
+
+    #[async_trait]
+    impl StrictDecodeAsync for bip32::DerivationPath {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            Ok(Self::from(
+                Vec::<bip32::ChildNumber>::strict_decode_async(d).await?,
+            ))
+        }
+    }
+
+    #[async_trait]
+    impl StrictEncodeAsync for psbt::raw::Key {
+        async fn strict_encode_async<E>(
+            &self,
+            e: &mut E,
+        ) -> Result<usize, Error>
+        where
+            E: AsyncWrite + Unpin + Send,
+        {
+            encode_buffered(self, e).await
+        }
+    }
+
+    #[async_trait]
+    impl StrictDecodeAsync for psbt::raw::Key {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            Ok(psbt::raw::Key {
+                type_value: u8::strict_decode_async(d).await?,
+                key: decode_byte_vec(d).await?,
+            })
+        }
+    }
+
+    #[async_trait]
+    impl StrictEncodeAsync for psbt::raw::Pair {
+        async fn strict_encode_async<E>(
+            &self,
+            e: &mut E,
+        ) -> Result<usize, Error>
+        where
+            E: AsyncWrite + Unpin + Send,
+        {
+            encode_buffered(self, e).await
+        }
+    }
+
+    #[async_trait]
+    impl StrictDecodeAsync for psbt::raw::Pair {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            Ok(psbt::raw::Pair {
+                key: psbt::raw::Key::strict_decode_async(d).await?,
+                value: decode_byte_vec(d).await?,
+            })
+        }
+    }
+
+    #[async_trait]
+    impl StrictEncodeAsync for psbt::raw::ProprietaryKey {
+        async fn strict_encode_async<E>(
+            &self,
+            e: &mut E,
+        ) -> Result<usize, Error>
+        where
+            E: AsyncWrite + Unpin + Send,
+        {
+            encode_buffered(self, e).await
+        }
+    }
+
+    #[async_trait]
+    impl StrictDecodeAsync for psbt::raw::ProprietaryKey {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            Ok(psbt::raw::ProprietaryKey {
+                prefix: decode_byte_vec(d).await?,
+                subtype: u8::strict_decode_async(d).await?,
+                key: decode_byte_vec(d).await?,
+            })
+        }
+    }
+
+    #[async_trait]
+    impl StrictEncodeAsync for Script {
+        #[inline]
+        async fn strict_encode_async<E>(
+            &self,
+            e: &mut E,
+        ) -> Result<usize, Error>
+        where
+            E: AsyncWrite + Unpin + Send,
+        {
+            encode_bytes(self.as_bytes(), e).await
+        }
+    }
+
+    #[async_trait]
+    impl StrictDecodeAsync for Script {
+        #[inline]
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            Ok(Self::from(decode_byte_vec(d).await?))
+        }
+    }
+
+    /// Consensus-encoded bitcoin data structures do not have fixed size, so
+    /// to decode them asynchronously we first read all bytes of the data
+    /// structure ("frame"), following its length prefixes, and then run the
+    /// blocking consensus decoder over the collected bytes.
+    #[async_trait]
+    pub trait ConsensusFrame {
+        /// Reads all bytes of the consensus-encoded data structure from the
+        /// reader, appending them to the `buf`.
+        async fn read_consensus_frame<D>(
+            d: &mut D,
+            buf: &mut Vec<u8>,
+        ) -> Result<(), Error>
+        where
+            D: AsyncRead + Unpin + Send;
+    }
+
+    async fn read_bytes<D>(
+        d: &mut D,
+        buf: &mut Vec<u8>,
+        len: u64,
+    ) -> Result<(), Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        if len > MAX_VEC_SIZE as u64 {
+            return Err(Error::ExceedMaxItems(len as usize));
+        }
+        let read = d.take(len).read_to_end(buf).await?;
+        if read as u64 != len {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        Ok(())
+    }
+
+    async fn read_var_int<D>(d: &mut D, buf: &mut Vec<u8>) -> Result<u64, Error>
+    where
+        D: AsyncRead + Unpin + Send,
+    {
+        let prefix = d.read_u8().await?;
+        buf.push(prefix);
+        let len = match prefix {
+            0xFF => 8,
+            0xFE => 4,
+            0xFD => 2,
+            val => return Ok(val as u64),
+        };
+        let start = buf.len();
+        read_bytes(d, buf, len).await?;
+        let mut val = [0u8; 8];
+        val[..len as usize].copy_from_slice(&buf[start..]);
+        Ok(u64::from_le_bytes(val))
+    }
+
+    async fn read_items<T, D>(
+        d: &mut D,
+        buf: &mut Vec<u8>,
+        count: u64,
+    ) -> Result<(), Error>
+    where
+        T: ConsensusFrame,
+        D: AsyncRead + Unpin + Send,
+    {
+        for _ in 0..count {
+            T::read_consensus_frame(d, buf).await?;
+        }
+        Ok(())
+    }
+
+    macro_rules! impl_fixed_frame {
+        ($len:expr => $($ty:ty),+ $(,)?) => {
+            $(
+                #[async_trait]
+                impl ConsensusFrame for $ty {
+                    async fn read_consensus_frame<D>(
+                        d: &mut D,
+                        buf: &mut Vec<u8>,
+                    ) -> Result<(), Error>
+                    where
+                        D: AsyncRead + Unpin + Send,
+                    {
+                        read_bytes(d, buf, $len).await
+                    }
+                }
+            )+
+        };
+    }
+
+    impl_fixed_frame!(4 => Sequence, LockTime, PackedLockTime);
+    impl_fixed_frame!(36 => OutPoint);
+
+    #[async_trait]
+    impl ConsensusFrame for Script {
+        async fn read_consensus_frame<D>(
+            d: &mut D,
+            buf: &mut Vec<u8>,
+        ) -> Result<(), Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            let len = read_var_int(d, buf).await?;
+            read_bytes(d, buf, len).await
+        }
+    }
+
+    #[async_trait]
+    impl ConsensusFrame for TxIn {
+        async fn read_consensus_frame<D>(
+            d: &mut D,
+            buf: &mut Vec<u8>,
+        ) -> Result<(), Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            OutPoint::read_consensus_frame(d, buf).await?;
+            Script::read_consensus_frame(d, buf).await?;
+            Sequence::read_consensus_frame(d, buf).await
+        }
+    }
+
+    #[async_trait]
+    impl ConsensusFrame for TxOut {
+        async fn read_consensus_frame<D>(
+            d: &mut D,
+            buf: &mut Vec<u8>,
+        ) -> Result<(), Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            read_bytes(d, buf, 8).await?;
+            Script::read_consensus_frame(d, buf).await
+        }
+    }
+
+    #[async_trait]
+    impl ConsensusFrame for Witness {
+        async fn read_consensus_frame<D>(
+            d: &mut D,
+            buf: &mut Vec<u8>,
+        ) -> Result<(), Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            let count = read_var_int(d, buf).await?;
+            read_items::<Script, _>(d, buf, count).await
+        }
+    }
+
+    #[async_trait]
+    impl ConsensusFrame for Transaction {
+        async fn read_consensus_frame<D>(
+            d: &mut D,
+            buf: &mut Vec<u8>,
+        ) -> Result<(), Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            read_bytes(d, buf, 4).await?;
+            let mut inputs = read_var_int(d, buf).await?;
+            let segwit = inputs == 0;
+            if segwit {
+                let flag = d.read_u8().await?;
+                buf.push(flag);
+                if flag != 1 {
+                    // Unsupported flag will be reported by the consensus
+                    // decoder
+                    return Ok(());
+                }
+                inputs = read_var_int(d, buf).await?;
+            }
+            read_items::<TxIn, _>(d, buf, inputs).await?;
+            let outputs = read_var_int(d, buf).await?;
+            read_items::<TxOut, _>(d, buf, outputs).await?;
+            if segwit {
+                read_items::<Witness, _>(d, buf, inputs).await?;
+            }
+            PackedLockTime::read_consensus_frame(d, buf).await
+        }
+    }
+
+    #[async_trait]
+    impl<B> StrictEncodeAsync for amplify::Holder<B, BitcoinConsensus>
+    where
+        B: ::bitcoin::consensus::Encodable + Sync,
+    {
+        #[inline]
+        async fn strict_encode_async<E>(
+            &self,
+            e: &mut E,
+        ) -> Result<usize, Error>
+        where
+            E: AsyncWrite + Unpin + Send,
+        {
+            let data = ::bitcoin::consensus::serialize(self.as_inner());
+            e.write_all(&data).await?;
+            Ok(data.len())
+        }
+    }
+
+    #[async_trait]
+    impl<B> StrictDecodeAsync for amplify::Holder<B, BitcoinConsensus>
+    where
+        B: ::bitcoin::consensus::Decodable + ConsensusFrame + Send,
+    {
+        #[inline]
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            let mut buf = vec![];
+            B::read_consensus_frame(d, &mut buf).await?;
+            Ok(Self::new(
+                B::consensus_decode(&mut &buf[..]).map_err(Error::from)?,
+            ))
+        }
+    }
+}
+
+#[cfg(feature = "miniscript")]
+mod miniscript {
+    use ::bitcoin::XOnlyPublicKey;
+    use ::miniscript::descriptor::{
+        self, Descriptor, DescriptorPublicKey, DescriptorXKey, InnerXKey,
+        SinglePub, SinglePubKey, TapTree, Wildcard,
+    };
+    use ::miniscript::policy::concrete::Policy;
+    use ::miniscript::{
+        BareCtx, Legacy, Miniscript, MiniscriptKey, ScriptContext, Segwitv0,
+        Tap, Terminal,
+    };
+
+    use super::*;
+    use crate::miniscript::*;
+
+    type Decoding<'a, T> =
+        Pin<Box<dyn Future<Output = Result<T, Error>> + Send + 'a>>;
+
+    /// Miniscript data structures are encoded with their blocking encoders
+    /// into a memory buffer, since they are limited in size by the bitcoin
+    /// consensus rules.
+    macro_rules! impl_async_encode_buffered {
+        ($([$($params:tt)*] $ty:ty),+ $(,)?) => {
+            $(
+                #[async_trait]
+                impl<$($params)*> StrictEncodeAsync for $ty
+                where
+                    $ty: StrictEncode + Sync,
+                {
+                    async fn strict_encode_async<E>(
+                        &self,
+                        e: &mut E,
+                    ) -> Result<usize, Error>
+                    where
+                        E: AsyncWrite + Unpin + Send,
+                    {
+                        encode_buffered(self, e).await
+                    }
+                }
+            )+
+        };
+    }
+
+    impl_async_encode_buffered!(
+        [] BareCtx,
+        [] Legacy,
+        [] Segwitv0,
+        [] Tap,
+        [Pk: MiniscriptKey] TapTree<Pk>,
+        [Pk: MiniscriptKey] Policy<Pk>,
+        [Pk: MiniscriptKey, Ctx: ScriptContext] Miniscript<Pk, Ctx>,
+        [] SinglePubKey,
+        [] DescriptorPublicKey,
+        [] SinglePub,
+        [Pk: InnerXKey] DescriptorXKey<Pk>,
+        [] Wildcard,
+        [Pk: MiniscriptKey] Descriptor<Pk>,
+        [Pk: MiniscriptKey] descriptor::Bare<Pk>,
+        [Pk: MiniscriptKey] descriptor::Pkh<Pk>,
+        [Pk: MiniscriptKey] descriptor::Wpkh<Pk>,
+        [Pk: MiniscriptKey] descriptor::Sh<Pk>,
+        [Pk: MiniscriptKey] descriptor::Wsh<Pk>,
+        [Pk: MiniscriptKey] descriptor::Tr<Pk>,
+        [Pk: MiniscriptKey, Ctx: ScriptContext] descriptor::SortedMultiVec<Pk, Ctx>,
+        [Pk: MiniscriptKey] descriptor::ShInner<Pk>,
+        [Pk: MiniscriptKey] descriptor::WshInner<Pk>,
+    );
+
+    /// Script context types are never decoded; the implementations exist
+    /// only to satisfy the trait bounds of the generic miniscript types.
+    macro_rules! impl_async_decode_context {
+        ($($ty:ty),+ $(,)?) => {
+            $(
+                #[async_trait]
+                impl StrictDecodeAsync for $ty {
+                    async fn strict_decode_async<D>(
+                        _: &mut D,
+                    ) -> Result<Self, Error>
+                    where
+                        D: AsyncRead + Unpin + Send,
+                    {
+                        <$ty as StrictDecode>::strict_decode(io::empty())
+                    }
+                }
+            )+
+        };
+    }
+
+    impl_async_decode_context!(BareCtx, Legacy, Segwitv0, Tap);
+
+    #[async_trait]
+    impl<Pk> StrictDecodeAsync for TapTree<Pk>
+    where
+        Pk: MiniscriptKey + StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Ripemd160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash256: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Sha256: StrictDecodeAsync + Sync,
+    {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            let _depth = DepthGuard::enter()?;
+            match u8::strict_decode_async(d).await? {
+                1u8 => Ok(TapTree::Leaf(Arc::strict_decode_async(d).await?)),
+                2u8 => {
+                    let vec1 = decode_byte_vec(d).await?;
+                    let vec2 = decode_byte_vec(d).await?;
+                    Ok(TapTree::Tree(
+                        Arc::new(decode_subtree(&vec1).await?),
+                        Arc::new(decode_subtree(&vec2).await?),
+                    ))
+                }
+                wrong => {
+                    Err(Error::EnumValueNotKnown("TapTree", wrong as usize))
+                }
+            }
+        }
+    }
+
+    async fn decode_subtree<Pk>(mut data: &[u8]) -> Result<TapTree<Pk>, Error>
+    where
+        Pk: MiniscriptKey + StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Ripemd160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash256: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Sha256: StrictDecodeAsync + Sync,
+    {
+        let tree = TapTree::strict_decode_async(&mut data).await?;
+        // Subtrees followed by some extra data are accepted for backward
+        // compatibility, but they are not canonical
+        if !data.is_empty() && is_canonical() {
+            return Err(Error::NonCanonical(any::type_name::<TapTree<Pk>>()));
+        }
+        Ok(tree)
+    }
+
+    #[async_trait]
+    impl<Pk> StrictDecodeAsync for Policy<Pk>
+    where
+        Pk: MiniscriptKey + StrictDecodeAsync,
+        <Pk as MiniscriptKey>::Hash160: StrictDecodeAsync,
+        <Pk as MiniscriptKey>::Ripemd160: StrictDecodeAsync,
+        <Pk as MiniscriptKey>::Hash256: StrictDecodeAsync,
+        <Pk as MiniscriptKey>::Sha256: StrictDecodeAsync,
+    {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            decode_policy_inner(d, 1).await
+        }
+    }
+
+    fn decode_policy_inner<Pk, D>(
+        d: &mut D,
+        mut depth: u8,
+    ) -> Decoding<'_, Policy<Pk>>
+    where
+        Pk: MiniscriptKey + StrictDecodeAsync,
+        <Pk as MiniscriptKey>::Hash160: StrictDecodeAsync,
+        <Pk as MiniscriptKey>::Ripemd160: StrictDecodeAsync,
+        <Pk as MiniscriptKey>::Hash256: StrictDecodeAsync,
+        <Pk as MiniscriptKey>::Sha256: StrictDecodeAsync,
+        D: AsyncRead + Unpin + Send,
+    {
+        Box::pin(async move {
+            if depth > MINISCRIPT_DEPTH_LIMIT {
+                return Err(Error::ExceedMaxItems(
+                    MINISCRIPT_DEPTH_LIMIT as usize,
+                ));
+            }
+            depth += 1;
+            let _depth = DepthGuard::enter()?;
+
+            let byte = d.read_u8().await?;
+            Ok(match byte {
+                MS_TRUE => Policy::Trivial,
+                MS_FALSE => Policy::Unsatisfiable,
+                MS_KEY => Policy::Key(Pk::strict_decode_async(d).await?),
+                MS_AFTER => Policy::After(
+                    StrictDecodeAsync::strict_decode_async(d).await?,
+                ),
+                MS_OLDER => Policy::Older(
+                    StrictDecodeAsync::strict_decode_async(d).await?,
+                ),
+                MS_SHA256 => Policy::Sha256(
+                    StrictDecodeAsync::strict_decode_async(d).await?,
+                ),
+                MS_HASH256 => Policy::Hash256(
+                    StrictDecodeAsync::strict_decode_async(d).await?,
+                ),
+                MS_RIPEMD160 => Policy::Ripemd160(
+                    StrictDecodeAsync::strict_decode_async(d).await?,
+                ),
+                MS_HASH160 => Policy::Hash160(
+                    StrictDecodeAsync::strict_decode_async(d).await?,
+                ),
+                MS_AND_B => {
+                    let len = d.read_u16_le().await?;
+                    check_items(len as usize)?;
+                    let mut vec =
+                        Vec::with_capacity(prealloc_capacity::<Policy<Pk>>(
+                            len as usize,
+                        ));
+                    for _ in 0..len {
+                        vec.push(decode_policy_inner(d, depth).await?);
+                    }
+                    Policy::And(vec)
+                }
+                MS_OR_B => {
+                    let len = d.read_u16_le().await?;
+                    check_items(len as usize)?;
+                    let mut vec = Vec::with_capacity(prealloc_capacity::<(
+                        usize,
+                        Policy<Pk>,
+                    )>(
+                        len as usize
+                    ));
+                    for _ in 0..len {
+                        vec.push((
+                            d.read_u16_le().await? as usize,
+                            decode_policy_inner(d, depth).await?,
+                        ));
+                    }
+                    Policy::Or(vec)
+                }
+                MS_THRESH => {
+                    let thresh = d.read_u16_le().await? as usize;
+                    let len = d.read_u16_le().await?;
+                    check_items(len as usize)?;
+                    let mut vec =
+                        Vec::with_capacity(prealloc_capacity::<Policy<Pk>>(
+                            len as usize,
+                        ));
+                    for _ in 0..len {
+                        vec.push(decode_policy_inner(d, depth).await?);
+                    }
+                    Policy::Threshold(thresh, vec)
+                }
+
+                MS_KEY_HASH | MS_HASHED_KEY | MS_ALT | MS_SWAP | MS_CHECK
+                | MS_DUP_IF | MS_VERIFY | MS_NON_ZERO | MS_ZERO_NE
+                | MS_AND_V | MS_AND_OR | MS_OR_D | MS_OR_C | MS_OR_I
+                | MS_MULTI => {
+                    return Err(Error::DataIntegrityError(format!(
+                        "byte {:#04X} is a valid miniscript instruction, but \
+                         does  not belong to a set of concrete policy \
+                         instructions. Try to decode data using different \
+                         miniscript type",
+                        byte
+                    )))
+                }
+
+                wrong => {
+                    return Err(Error::DataIntegrityError(format!(
+                        "byte {:#04X} does not correspond to any of \
+                         miniscript concrete policy instructions",
+                        wrong
+                    )))
+                }
+            })
+        })
+    }
+
+    #[async_trait]
+    impl<Pk, Ctx> StrictDecodeAsync for Miniscript<Pk, Ctx>
+    where
+        Pk: MiniscriptKey + StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Ripemd160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash256: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Sha256: StrictDecodeAsync + Sync,
+        Ctx: ScriptContext + Send + Sync,
+    {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            decode_miniscript_inner(d, 1).await
+        }
+    }
+
+    fn decode_miniscript_inner<Pk, Ctx, D>(
+        d: &mut D,
+        mut depth: u8,
+    ) -> Decoding<'_, Miniscript<Pk, Ctx>>
+    where
+        Pk: MiniscriptKey + StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Ripemd160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash256: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Sha256: StrictDecodeAsync + Sync,
+        Ctx: ScriptContext + Send + Sync,
+        D: AsyncRead + Unpin + Send,
+    {
+        Box::pin(async move {
+            if depth > MINISCRIPT_DEPTH_LIMIT {
+                return Err(Error::ExceedMaxItems(
+                    MINISCRIPT_DEPTH_LIMIT as usize,
+                ));
+            }
+            depth += 1;
+            let _depth = DepthGuard::enter()?;
+
+            let term = match d.read_u8().await? {
+                MS_TRUE => Terminal::True,
+                MS_FALSE => Terminal::False,
+                MS_KEY => Terminal::PkK(Pk::strict_decode_async(d).await?),
+                MS_HASHED_KEY => {
+                    Terminal::PkH(Pk::strict_decode_async(d).await?)
+                }
+                MS_KEY_HASH => Terminal::RawPkH(
+                    StrictDecodeAsync::strict_decode_async(d).await?,
+                ),
+
+                MS_AFTER => Terminal::After(
+                    StrictDecodeAsync::strict_decode_async(d).await?,
+                ),
+                MS_OLDER => Terminal::Older(
+                    StrictDecodeAsync::strict_decode_async(d).await?,
+                ),
+                MS_SHA256 => Terminal::Sha256(
+                    StrictDecodeAsync::strict_decode_async(d).await?,
+                ),
+                MS_HASH256 => Terminal::Hash256(
+                    StrictDecodeAsync::strict_decode_async(d).await?,
+                ),
+                MS_RIPEMD160 => Terminal::Ripemd160(
+                    StrictDecodeAsync::strict_decode_async(d).await?,
+                ),
+                MS_HASH160 => Terminal::Hash160(
+                    StrictDecodeAsync::strict_decode_async(d).await?,
+                ),
+
+                MS_ALT => Terminal::Alt(
+                    decode_miniscript_inner(d, depth).await?.into(),
+                ),
+                MS_SWAP => Terminal::Swap(
+                    decode_miniscript_inner(d, depth).await?.into(),
+                ),
+                MS_CHECK => Terminal::Check(
+                    decode_miniscript_inner(d, depth).await?.into(),
+                ),
+                MS_DUP_IF => Terminal::DupIf(
+                    decode_miniscript_inner(d, depth).await?.into(),
+                ),
+                MS_VERIFY => Terminal::Verify(
+                    decode_miniscript_inner(d, depth).await?.into(),
+                ),
+                MS_NON_ZERO => Terminal::NonZero(
+                    decode_miniscript_inner(d, depth).await?.into(),
+                ),
+                MS_ZERO_NE => Terminal::ZeroNotEqual(
+                    decode_miniscript_inner(d, depth).await?.into(),
+                ),
+
+                MS_AND_V => Terminal::AndV(
+                    decode_miniscript_inner(d, depth).await?.into(),
+                    decode_miniscript_inner(d, depth).await?.into(),
+                ),
+                MS_AND_B => Terminal::AndB(
+                    decode_miniscript_inner(d, depth).await?.into(),
+                    decode_miniscript_inner(d, depth).await?.into(),
+                ),
+                MS_AND_OR => Terminal::AndOr(
+                    decode_miniscript_inner(d, depth).await?.into(),
+                    decode_miniscript_inner(d, depth).await?.into(),
+                    decode_miniscript_inner(d, depth).await?.into(),
+                ),
+                MS_OR_B => Terminal::OrB(
+                    decode_miniscript_inner(d, depth).await?.into(),
+                    decode_miniscript_inner(d, depth).await?.into(),
+                ),
+                MS_OR_D => Terminal::OrD(
+                    decode_miniscript_inner(d, depth).await?.into(),
+                    decode_miniscript_inner(d, depth).await?.into(),
+                ),
+                MS_OR_C => Terminal::OrC(
+                    decode_miniscript_inner(d, depth).await?.into(),
+                    decode_miniscript_inner(d, depth).await?.into(),
+                ),
+                MS_OR_I => Terminal::OrI(
+                    decode_miniscript_inner(d, depth).await?.into(),
+                    decode_miniscript_inner(d, depth).await?.into(),
+                ),
+                MS_MULTI => Terminal::Multi(
+                    d.read_u16_le().await? as usize,
+                    Vec::strict_decode_async(d).await?,
+                ),
+                MS_THRESH => {
+                    let thresh = d.read_u16_le().await? as usize;
+                    let len = d.read_u16_le().await?;
+                    check_items(len as usize)?;
+                    let mut vec = Vec::with_capacity(prealloc_capacity::<
+                        Arc<Miniscript<Pk, Ctx>>,
+                    >(
+                        len as usize
+                    ));
+                    for _ in 0..len {
+                        vec.push(
+                            decode_miniscript_inner(d, depth).await?.into(),
+                        );
+                    }
+                    Terminal::Thresh(thresh, vec)
+                }
+                MS_MULTI_A => Terminal::MultiA(
+                    d.read_u16_le().await? as usize,
+                    Vec::strict_decode_async(d).await?,
+                ),
+
+                wrong => {
+                    return Err(Error::DataIntegrityError(format!(
+                        "byte {:#04X} does not correspond to any of \
+                         miniscript instructions",
+                        wrong
+                    )))
+                }
+            };
+            Miniscript::from_ast(term).map_err(|err| {
+                Error::DataIntegrityError(format!(
+                    "miniscript does not pass check: {}",
+                    err
+                ))
+            })
+        })
+    }
+
+    #[async_trait]
+    impl StrictDecodeAsync for SinglePubKey {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            Ok(match u8::strict_decode_async(d).await? {
+                0x01 => SinglePubKey::FullKey(
+                    ::bitcoin::PublicKey::strict_decode_async(d).await?,
+                ),
+                0x02 => SinglePubKey::XOnly(
+                    XOnlyPublicKey::strict_decode_async(d).await?,
+                ),
+                wrong => {
+                    return Err(Error::DataIntegrityError(format!(
+                        "unknown miniscript single pubkey tag `{:#04X}",
+                        wrong
+                    )))
+                }
+            })
+        }
+    }
+
+    #[async_trait]
+    impl StrictDecodeAsync for DescriptorPublicKey {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            Ok(match u8::strict_decode_async(d).await? {
+                0x01 => DescriptorPublicKey::Single(
+                    SinglePub::strict_decode_async(d).await?,
+                ),
+                0x02 => DescriptorPublicKey::XPub(
+                    DescriptorXKey::strict_decode_async(d).await?,
+                ),
+                wrong => {
+                    return Err(Error::DataIntegrityError(format!(
+                        "unknown descriptor key tag `{:#04X}",
+                        wrong
+                    )))
+                }
+            })
+        }
+    }
+
+    #[async_trait]
+    impl StrictDecodeAsync for SinglePub {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            Ok(SinglePub {
+                origin: StrictDecodeAsync::strict_decode_async(d).await?,
+                key: StrictDecodeAsync::strict_decode_async(d).await?,
+            })
+        }
+    }
+
+    #[async_trait]
+    impl<Pk> StrictDecodeAsync for DescriptorXKey<Pk>
+    where
+        Pk: InnerXKey + StrictDecodeAsync,
+    {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            Ok(DescriptorXKey {
+                origin: StrictDecodeAsync::strict_decode_async(d).await?,
+                derivation_path: StrictDecodeAsync::strict_decode_async(d)
+                    .await?,
+                xkey: StrictDecodeAsync::strict_decode_async(d).await?,
+                wildcard: StrictDecodeAsync::strict_decode_async(d).await?,
+            })
+        }
+    }
+
+    #[async_trait]
+    impl StrictDecodeAsync for Wildcard {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            Ok(match u8::strict_decode_async(d).await? {
+                0 => Wildcard::None,
+                1 => Wildcard::Unhardened,
+                2 => Wildcard::Hardened,
+                wrong => {
+                    return Err(Error::DataIntegrityError(format!(
+                        "unknown descriptor xpub wildcard type `{:#04X}`",
+                        wrong
+                    )))
+                }
+            })
+        }
+    }
+
+    #[async_trait]
+    impl<Pk> StrictDecodeAsync for Descriptor<Pk>
+    where
+        Pk: MiniscriptKey + StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Ripemd160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash256: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Sha256: StrictDecodeAsync + Sync,
+    {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            Ok(match u8::strict_decode_async(d).await? {
+                DESCRIPTOR_BARE => Descriptor::Bare(
+                    descriptor::Bare::strict_decode_async(d).await?,
+                ),
+                DESCRIPTOR_PKH => Descriptor::Pkh(
+                    descriptor::Pkh::strict_decode_async(d).await?,
+                ),
+                DESCRIPTOR_SH => Descriptor::Sh(
+                    descriptor::Sh::strict_decode_async(d).await?,
+                ),
+                DESCRIPTOR_WPKH => Descriptor::Wpkh(
+                    descriptor::Wpkh::strict_decode_async(d).await?,
+                ),
+                DESCRIPTOR_WSH => Descriptor::Wsh(
+                    descriptor::Wsh::strict_decode_async(d).await?,
+                ),
+                DESCRIPTOR_TR => Descriptor::Tr(
+                    descriptor::Tr::strict_decode_async(d).await?,
+                ),
+                wrong => {
+                    return Err(Error::DataIntegrityError(format!(
+                        "unknown miniscript descriptor type: #{:#04X}",
+                        wrong
+                    )))
+                }
+            })
+        }
+    }
+
+    #[async_trait]
+    impl<Pk> StrictDecodeAsync for descriptor::Bare<Pk>
+    where
+        Pk: MiniscriptKey + StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Ripemd160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash256: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Sha256: StrictDecodeAsync + Sync,
+    {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            Self::new(StrictDecodeAsync::strict_decode_async(d).await?)
+                .map_err(Error::from)
+        }
+    }
+
+    #[async_trait]
+    impl<Pk> StrictDecodeAsync for descriptor::Pkh<Pk>
+    where
+        Pk: MiniscriptKey + StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Ripemd160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash256: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Sha256: StrictDecodeAsync + Sync,
+    {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            Ok(Self::new(StrictDecodeAsync::strict_decode_async(d).await?))
+        }
+    }
+
+    #[async_trait]
+    impl<Pk> StrictDecodeAsync for descriptor::Wpkh<Pk>
+    where
+        Pk: MiniscriptKey + StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Ripemd160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash256: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Sha256: StrictDecodeAsync + Sync,
+    {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            Self::new(StrictDecodeAsync::strict_decode_async(d).await?)
+                .map_err(Error::from)
+        }
+    }
+
+    #[async_trait]
+    impl<Pk> StrictDecodeAsync for descriptor::Sh<Pk>
+    where
+        Pk: MiniscriptKey + StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Ripemd160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash256: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Sha256: StrictDecodeAsync + Sync,
+    {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            Ok(match descriptor::ShInner::strict_decode_async(d).await? {
+                descriptor::ShInner::Wsh(wsh) => {
+                    descriptor::Sh::new_with_wsh(wsh)
+                }
+                descriptor::ShInner::Wpkh(wpkh) => {
+                    descriptor::Sh::new_with_wpkh(wpkh)
+                }
+                descriptor::ShInner::SortedMulti(inner) => {
+                    descriptor::Sh::new_sortedmulti(inner.k, inner.pks)?
+                }
+                descriptor::ShInner::Ms(ms) => descriptor::Sh::new(ms)?,
+            })
+        }
+    }
+
+    #[async_trait]
+    impl<Pk> StrictDecodeAsync for descriptor::Wsh<Pk>
+    where
+        Pk: MiniscriptKey + StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Ripemd160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash256: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Sha256: StrictDecodeAsync + Sync,
+    {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            match descriptor::WshInner::strict_decode_async(d).await? {
+                descriptor::WshInner::SortedMulti(inner) => {
+                    descriptor::Wsh::new_sortedmulti(inner.k, inner.pks)
+                }
+                descriptor::WshInner::Ms(ms) => descriptor::Wsh::new(ms),
+            }
+            .map_err(Error::from)
+        }
+    }
+
+    #[async_trait]
+    impl<Pk> StrictDecodeAsync for descriptor::Tr<Pk>
+    where
+        Pk: MiniscriptKey + StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Ripemd160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash256: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Sha256: StrictDecodeAsync + Sync,
+    {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            descriptor::Tr::new(
+                StrictDecodeAsync::strict_decode_async(d).await?,
+                StrictDecodeAsync::strict_decode_async(d).await?,
+            )
+            .map_err(Error::from)
+        }
+    }
+
+    #[async_trait]
+    impl<Pk, Ctx> StrictDecodeAsync for descriptor::SortedMultiVec<Pk, Ctx>
+    where
+        Pk: MiniscriptKey + StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Ripemd160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash256: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Sha256: StrictDecodeAsync + Sync,
+        Ctx: ScriptContext + Send + Sync,
+    {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            descriptor::SortedMultiVec::new(
+                StrictDecodeAsync::strict_decode_async(d).await?,
+                StrictDecodeAsync::strict_decode_async(d).await?,
+            )
+            .map_err(Error::from)
+        }
+    }
+
+    #[async_trait]
+    impl<Pk> StrictDecodeAsync for descriptor::ShInner<Pk>
+    where
+        Pk: MiniscriptKey + StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Ripemd160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash256: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Sha256: StrictDecodeAsync + Sync,
+    {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            Ok(match u8::strict_decode_async(d).await? {
+                DESCRIPTOR_MINISCRIPT => descriptor::ShInner::Ms(
+                    StrictDecodeAsync::strict_decode_async(d).await?,
+                ),
+                DESCRIPTOR_SORTED_MULTI => descriptor::ShInner::SortedMulti(
+                    StrictDecodeAsync::strict_decode_async(d).await?,
+                ),
+                DESCRIPTOR_WPKH => descriptor::ShInner::Wpkh(
+                    StrictDecodeAsync::strict_decode_async(d).await?,
+                ),
+                DESCRIPTOR_WSH => descriptor::ShInner::Wsh(
+                    StrictDecodeAsync::strict_decode_async(d).await?,
+                ),
+                wrong => {
+                    return Err(Error::DataIntegrityError(format!(
+                        "invalid miniscript ShInner descriptor type: #{:#04X}",
+                        wrong
+                    )))
+                }
+            })
+        }
+    }
+
+    #[async_trait]
+    impl<Pk> StrictDecodeAsync for descriptor::WshInner<Pk>
+    where
+        Pk: MiniscriptKey + StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Ripemd160: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Hash256: StrictDecodeAsync + Sync,
+        <Pk as MiniscriptKey>::Sha256: StrictDecodeAsync + Sync,
+    {
+        async fn strict_decode_async<D>(d: &mut D) -> Result<Self, Error>
+        where
+            D: AsyncRead + Unpin + Send,
+        {
+            Ok(match u8::strict_decode_async(d).await? {
+                DESCRIPTOR_MINISCRIPT => descriptor::WshInner::Ms(
+                    StrictDecodeAsync::strict_decode_async(d).await?,
+                ),
+                DESCRIPTOR_SORTED_MULTI => descriptor::WshInner::SortedMulti(
+                    StrictDecodeAsync::strict_decode_async(d).await?,
+                ),
+                wrong => {
+                    return Err(Error::DataIntegrityError(format!(
+                        "invalid miniscript WshInner descriptor type: #{:#04X}",
+                        wrong
+                    )))
+                }
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use strict_encoding_test::{
+        test_async_encoding_roundtrip, test_async_object_encoding_roundtrip,
+    };
+
+    use super::*;
+    use crate::net::{AddrFormat, Transport, Uniform};
+
+    /// Types defined inside this crate can't be tested with
+    /// `strict_encoding_test` helpers, so we check them against blocking
+    /// encoding here.
+    fn check_local_roundtrip<T>(object: &T) -> Vec<u8>
+    where
+        T: StrictEncode
+            + StrictDecode
+            + StrictEncodeAsync
+            + StrictDecodeAsync
+            + PartialEq
+            + core::fmt::Debug,
+    {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let blocking = object.strict_serialize().unwrap();
+        let asynchronous =
+            rt.block_on(object.strict_serialize_async()).unwrap();
+        assert_eq!(blocking, asynchronous);
+        let decoded = rt
+            .block_on(T::strict_deserialize_async(asynchronous.clone()))
+            .unwrap();
+        assert_eq!(&decoded, object);
+        asynchronous
+    }
+
+    #[test]
+    fn primitives() {
+        test_async_encoding_roundtrip(&true, [0x01]).unwrap();
+        test_async_encoding_roundtrip(&0xCAFEu16, [0xFE, 0xCA]).unwrap();
+        test_async_encoding_roundtrip(&-2i32, [0xFE, 0xFF, 0xFF, 0xFF])
+            .unwrap();
+        test_async_encoding_roundtrip(&u24::with(0x010203), [0x03, 0x02, 0x01])
+            .unwrap();
+        test_async_encoding_roundtrip(&u128::MAX, [0xFF; 16]).unwrap();
+        test_async_encoding_roundtrip(&u256::from(1u64), {
+            let mut data = [0u8; 32];
+            data[0] = 1;
+            data
+        })
+        .unwrap();
+        test_async_encoding_roundtrip(&1.5f64, 1.5f64.to_le_bytes()).unwrap();
+        test_async_object_encoding_roundtrip(&Duration::from_millis(1500))
+            .unwrap();
+    }
+
+    #[test]
+    fn collections() {
+        test_async_encoding_roundtrip(&s!("abc"), [
+            0x03, 0x00, b'a', b'b', b'c',
+        ])
+        .unwrap();
+        test_async_encoding_roundtrip(&Some(vec![1u8, 2]), [
+            0x01, 0x02, 0x00, 0x01, 0x02,
+        ])
+        .unwrap();
+        test_async_encoding_roundtrip(&None::<u8>, [0x00]).unwrap();
+        test_async_object_encoding_roundtrip(&bset! { 3u16, 1, 2 }).unwrap();
+        test_async_object_encoding_roundtrip(
+            &bmap! { 1u8 => s!("a"), 2 => s!("b") },
+        )
+        .unwrap();
+        test_async_object_encoding_roundtrip(&HashSet::from([10u64, 20, 30]))
+            .unwrap();
+        test_async_object_encoding_roundtrip(&HashMap::from([
+            (10usize, 1u8),
+            (2, 3),
+        ]))
+        .unwrap();
+        check_local_roundtrip(&MediumVec::try_from(vec![1u8, 2, 3]).unwrap());
+        check_local_roundtrip(&LargeVec::try_from(vec![(1u8, -1i8)]).unwrap());
+        test_async_object_encoding_roundtrip(&[0xAAu8; 32]).unwrap();
+        test_async_object_encoding_roundtrip(&Arc::new(7u32)).unwrap();
+    }
+
+    #[test]
+    fn uniform_addr() {
+        let addr = IpAddr::V4(Ipv4Addr::LOCALHOST).to_uniform_addr();
+        check_local_roundtrip(&addr);
+        test_async_object_encoding_roundtrip(&IpAddr::V4(Ipv4Addr::LOCALHOST))
+            .unwrap();
+        assert_eq!(check_local_roundtrip(&AddrFormat::OnionV3), [0x03]);
+        assert_eq!(check_local_roundtrip(&Transport::Tcp), [0x01]);
+    }
+
+    #[test]
+    fn decoding_errors() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            assert_eq!(
+                Option::<u8>::strict_deserialize_async([0x02]).await,
+                Err(Error::WrongOptionalEncoding(2))
+            );
+            assert_eq!(
+                u8::strict_deserialize_async([0x01, 0x02]).await,
+                Err(Error::DataNotEntirelyConsumed)
+            );
+            assert!(matches!(
+                u32::strict_deserialize_async([0x01]).await,
+                Err(Error::Io(_))
+            ));
+            assert_eq!(
+                BTreeSet::<u8>::strict_deserialize_async([
+                    0x02, 0x00, 0x02, 0x01
+                ])
+                .await,
+                BTreeSet::<u8>::strict_deserialize([0x02, 0x00, 0x02, 0x01])
+            );
+        });
+    }
+
+    #[cfg(feature = "bitcoin")]
+    #[test]
+    fn bitcoin_types() {
+        use ::bitcoin::hashes::hex::FromHex;
+        use ::bitcoin::{
+            OutPoint, PackedLockTime, Script, Sequence, Transaction, TxIn,
+            TxOut, Txid, Witness,
+        };
+
+        let input = TxIn {
+            previous_output: OutPoint::new(
+                Txid::from_hex(
+                    "0f1e2d3c4b5a69788796a5b4c3d2e1f00f1e2d3c4b5a69788796a5b4c3d2e1f0",
+                )
+                .unwrap(),
+                1,
+            ),
+            script_sig: Script::from(vec![0x00; 300]),
+            sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+            witness: Witness::default(),
+        };
+        let output = TxOut {
+            value: 100_000,
+            script_pubkey: Script::from(vec![0x51, 0x52]),
+        };
+        let mut tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime(800_000),
+            input: vec![input.clone(), input],
+            output: vec![output.clone(), output],
+        };
+        // Legacy transaction
+        test_async_object_encoding_roundtrip(&tx).unwrap();
+        // Consensus encoding of a transaction input does not include witness
+        test_async_object_encoding_roundtrip(&tx.input[0]).unwrap();
+        // Segwit transaction
+        tx.input[0].witness = Witness::from_vec(vec![vec![0xAA; 72], vec![]]);
+        test_async_object_encoding_roundtrip(&tx).unwrap();
+        for input in &tx.input {
+            test_async_object_encoding_roundtrip(&input.witness).unwrap();
+        }
+        for output in &tx.output {
+            test_async_object_encoding_roundtrip(output).unwrap();
+        }
+        test_async_object_encoding_roundtrip(&OutPoint::null()).unwrap();
+        test_async_object_encoding_roundtrip(&Txid::from_hex(
+            "0f1e2d3c4b5a69788796a5b4c3d2e1f00f1e2d3c4b5a69788796a5b4c3d2e1f0",
+        )
+        .unwrap())
+        .unwrap();
+        test_async_object_encoding_roundtrip(&Script::from(vec![0x51, 0x52]))
+            .unwrap();
+        test_async_object_encoding_roundtrip(&::bitcoin::Network::Testnet)
+            .unwrap();
+    }
+
+    #[cfg(feature = "bitcoin")]
+    #[test]
+    fn bitcoin_keys() {
+        use ::bitcoin::secp256k1::{Message, Secp256k1, SecretKey};
+        use ::bitcoin::util::schnorr::TweakedKeyPair;
+        use ::bitcoin::{
+            EcdsaSig, EcdsaSighashType, KeyPair, PublicKey, SchnorrSig,
+            SchnorrSighashType,
+        };
+
+        let secp = Secp256k1::new();
+        let sk = SecretKey::from_slice(&[0x15; 32]).unwrap();
+        test_async_encoding_roundtrip(&sk, [0x15; 32]).unwrap();
+        let keypair = KeyPair::from_secret_key(&secp, &sk);
+        test_async_encoding_roundtrip(&keypair, [0x15; 32]).unwrap();
+        test_async_encoding_roundtrip(
+            &TweakedKeyPair::dangerous_assume_tweaked(keypair),
+            [0x15; 32],
+        )
+        .unwrap();
+
+        let pk = sk.public_key(&secp);
+        test_async_object_encoding_roundtrip(&PublicKey::new(pk)).unwrap();
+        test_async_object_encoding_roundtrip(&PublicKey::new_uncompressed(pk))
+            .unwrap();
+
+        let msg = Message::from_slice(&[1u8; 32]).unwrap();
+        for hash_ty in [
+            EcdsaSighashType::All,
+            EcdsaSighashType::SinglePlusAnyoneCanPay,
+        ] {
+            test_async_object_encoding_roundtrip(&hash_ty).unwrap();
+            test_async_object_encoding_roundtrip(&EcdsaSig {
+                sig: secp.sign_ecdsa(&msg, &sk),
+                hash_ty,
+            })
+            .unwrap();
+        }
+        for hash_ty in [
+            SchnorrSighashType::Default,
+            SchnorrSighashType::NonePlusAnyoneCanPay,
+        ] {
+            test_async_object_encoding_roundtrip(&hash_ty).unwrap();
+            test_async_object_encoding_roundtrip(&SchnorrSig {
+                sig: secp
+                    .sign_schnorr_with_aux_rand(&msg, &keypair, &[0u8; 32]),
+                hash_ty,
+            })
+            .unwrap();
+        }
+        test_async_object_encoding_roundtrip(
+            &::bitcoin::psbt::PsbtSighashType::from(EcdsaSighashType::None),
+        )
+        .unwrap();
+    }
+
+    #[cfg(feature = "bitcoin")]
+    #[test]
+    fn bitcoin_taproot() {
+        use ::bitcoin::psbt::TapTree;
+        use ::bitcoin::secp256k1::{Secp256k1, XOnlyPublicKey};
+        use ::bitcoin::util::taproot::{LeafVersion, TaprootBuilder};
+        use ::bitcoin::Script;
+
+        let leaf_version = LeafVersion::from_consensus(0xC2).unwrap();
+        test_async_encoding_roundtrip(&LeafVersion::TapScript, [0xC0]).unwrap();
+        test_async_encoding_roundtrip(&leaf_version, [0xC2]).unwrap();
+        match leaf_version {
+            LeafVersion::Future(version) => {
+                test_async_encoding_roundtrip(&version, [0xC2]).unwrap()
+            }
+            LeafVersion::TapScript => unreachable!(),
+        }
+
+        let secp = Secp256k1::new();
+        let internal_key = XOnlyPublicKey::from_slice(&[
+            0x79, 0xbe, 0x66, 0x7e, 0xf9, 0xdc, 0xbb, 0xac, 0x55, 0xa0, 0x62,
+            0x95, 0xce, 0x87, 0x0b, 0x07, 0x02, 0x9b, 0xfc, 0xdb, 0x2d, 0xce,
+            0x28, 0xd9, 0x59, 0xf2, 0x81, 0x5b, 0x16, 0xf8, 0x17, 0x98,
+        ])
+        .unwrap();
+        let script = Script::new_op_return(&[]);
+        let builder = TaprootBuilder::new()
+            .add_leaf(1, Script::new())
+            .unwrap()
+            .add_leaf(2, script.clone())
+            .unwrap()
+            .add_leaf(2, Script::from(vec![0x51]))
+            .unwrap();
+        let tree = TapTree::try_from(builder.clone()).unwrap();
+        test_async_object_encoding_roundtrip(&tree).unwrap();
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        for leaf in tree.script_leaves() {
+            assert_eq!(
+                rt.block_on(leaf.strict_serialize_async()).unwrap(),
+                leaf.strict_serialize().unwrap()
+            );
+        }
+
+        let spend_info = builder.finalize(&secp, internal_key).unwrap();
+        let control_block = spend_info
+            .control_block(&(script, LeafVersion::TapScript))
+            .unwrap();
+        test_async_object_encoding_roundtrip(&control_block.merkle_branch)
+            .unwrap();
+        test_async_object_encoding_roundtrip(&control_block).unwrap();
+    }
+
+    #[cfg(feature = "bitcoin")]
+    #[test]
+    fn bitcoin_bip32() {
+        use std::str::FromStr;
+
+        use ::bitcoin::secp256k1::Secp256k1;
+        use ::bitcoin::util::bip32::{
+            ChildNumber, DerivationPath, ExtendedPrivKey, ExtendedPubKey,
+        };
+
+        let secp = Secp256k1::new();
+        let xpriv = ExtendedPrivKey::new_master(
+            ::bitcoin::Network::Testnet,
+            &[0x42; 32],
+        )
+        .unwrap();
+        let xpub = ExtendedPubKey::from_priv(&secp, &xpriv);
+        test_async_object_encoding_roundtrip(&xpriv).unwrap();
+        test_async_object_encoding_roundtrip(&xpub).unwrap();
+        test_async_object_encoding_roundtrip(&xpub.fingerprint()).unwrap();
+        test_async_object_encoding_roundtrip(&xpub.chain_code).unwrap();
+        test_async_object_encoding_roundtrip(
+            &ChildNumber::from_hardened_idx(84).unwrap(),
+        )
+        .unwrap();
+        test_async_object_encoding_roundtrip(
+            &DerivationPath::from_str("m/84'/0'/0'/1/7").unwrap(),
+        )
+        .unwrap();
+    }
+
+    #[cfg(feature = "bitcoin")]
+    #[test]
+    fn bitcoin_address() {
+        use std::str::FromStr;
+
+        use ::bitcoin::psbt::raw;
+        use ::bitcoin::Address;
+
+        for addr in [
+            "12CL4K2eVqj7hQTix7dM7CVHCkpP17Pry3",
+            "3AfyxhpBVVLmBR4ZYX2onGzRqjv5QZ7FqD",
+            "bc1qp5wfcq48h6d63wyy9qz0awtpfqwwv4sma86mhz",
+            "tb1pqqqqp399et2xygdj5xreqhjjvcmzhxw4aywxecjdzew6hylgvsesf3hn0c",
+        ] {
+            let addr = Address::from_str(addr).unwrap();
+            test_async_object_encoding_roundtrip(&addr.payload).unwrap();
+            test_async_object_encoding_roundtrip(&addr).unwrap();
+        }
+
+        let key = raw::Key {
+            type_value: 0xFC,
+            key: vec![0x01, 0x02],
+        };
+        test_async_object_encoding_roundtrip(&key).unwrap();
+        check_local_roundtrip(&raw::Pair {
+            key,
+            value: vec![0xAA; 3],
+        });
+        test_async_object_encoding_roundtrip(&raw::ProprietaryKey {
+            prefix: b"test".to_vec(),
+            subtype: 0x03,
+            key: vec![0x04],
+        })
+        .unwrap();
+    }
+
+    #[cfg(feature = "miniscript")]
+    #[test]
+    fn miniscript_types() {
+        use std::str::FromStr;
+
+        use ::miniscript::descriptor::{Descriptor, DescriptorPublicKey};
+        use ::miniscript::policy::Concrete;
+        use ::miniscript::{Miniscript, Segwitv0};
+
+        let policy = Concrete::<String>::from_str(
+            "and(pk(A),or(and(after(9),pk(B)),and(after(1000000000),pk(C))))",
+        )
+        .unwrap();
+        test_async_object_encoding_roundtrip(&policy).unwrap();
+        let data = policy.strict_serialize().unwrap();
+        assert_eq!(
+            decode_limited::<Concrete<String>>(&data, limits(1024, 8, 64)),
+            Ok(policy)
+        );
+        assert_eq!(
+            decode_limited::<Concrete<String>>(&data, limits(1024, 3, 64)),
+            Err(Error::DepthLimitExceeded(3))
+        );
+
+        let ms = Miniscript::<::bitcoin::PublicKey, Segwitv0>::from_str_insane(
+            "or_d(sha256(38df1c1f64a24a77b23393bca50dff872e31edc4f3b5aa3b90ad0b82f4f089b6),and_n(un:after(499999999),older(4194305)))",
+        )
+        .unwrap();
+        test_async_object_encoding_roundtrip(&ms).unwrap();
+
+        let leaf = ::miniscript::descriptor::TapTree::<String>::Leaf(Arc::new(
+            Miniscript::from_str("pk(A)").unwrap(),
+        ));
+        test_async_object_encoding_roundtrip(
+            &::miniscript::descriptor::TapTree::Tree(
+                Arc::new(leaf.clone()),
+                Arc::new(leaf),
+            ),
+        )
+        .unwrap();
+
+        let secp = ::bitcoin::secp256k1::Secp256k1::new();
+        for descr in [
+            "pk(020000000000000000000000000000000000000000000000000000000000000002)",
+            "pkh(020000000000000000000000000000000000000000000000000000000000000002)",
+            "sh(wpkh(03ad1d8e89212f0b92c74d23bb710c00662ad1470198ac48c43f7d6f93a2a26873))",
+            "wsh(multi(2,03789ed0bb717d88f7d321a368d905e7430207ebbd82bd342cf11ae157a7ace5fd,03dbc6764b8884a92e871274b87583e6d5c2a58819473e17e107ef3f6aa5a61626))",
+            "sh(wsh(sortedmulti(1,xpub661MyMwAqRbcFW31YEwpkMuc5THy2PSt5bDMsktWQcFF8syAmRUapSCGu8ED9W6oDMSgv6Zz8idoc4a6mr8BDzTJY47LJhkJ8UB7WEGuduB/1/0/*,xpub69H7F5d8KSRgmmdJg2KhpAK8SR3DjMwAdkxj3ZuxV27CprR9LgpeyGmXUbC6wb7ERfvrnKZjXoUmmDznezpbZb7ap6r1D3tgFxHmwMkQTPH/0/0/*)))",
+            "wpkh([2cbe2a6d/44'/0'/0']tpubDCvNhURocXGZsLNqWcqD3syHTqPXrMSTwi8feKVwAcpi29oYKsDD3Vex7x2TDneKMVN23RbLprfxB69v94iYqdaYHsVz3kPR37NQXeqouVz/0/*)",
+            "tr(020000000000000000000000000000000000000000000000000000000000000002,{pk(03ad1d8e89212f0b92c74d23bb710c00662ad1470198ac48c43f7d6f93a2a26873),pk(025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee6357)})",
+        ] {
+            let (descr, _) =
+                Descriptor::<DescriptorPublicKey>::parse_descriptor(&secp, descr)
+                    .unwrap();
+            test_async_object_encoding_roundtrip(&descr).unwrap();
+        }
+    }
+
+    #[test]
+    fn amplify_types() {
+        test_async_object_encoding_roundtrip(&Slice32::from_inner([0xA5; 32]))
+            .unwrap();
+        let mut flags = FlagVec::new();
+        flags.set(3);
+        flags.set(11);
+        test_async_object_encoding_roundtrip(&flags).unwrap();
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn float_types() {
+        use amplify::num::apfloat::Float;
+
+        test_async_encoding_roundtrip(&bf16::from_f32(1.5), [0xC0, 0x3F])
+            .unwrap();
+        test_async_encoding_roundtrip(&"1.5".parse::<ieee::Half>().unwrap(), [
+            0x00, 0x3E,
+        ])
+        .unwrap();
+        // `X87DoubleExtended::from_bits` drops the explicit integer bit, so
+        // only zero survives the roundtrip; other values must just
+        // match the blocking encoding
+        test_async_object_encoding_roundtrip(&ieee::X87DoubleExtended::ZERO)
+            .unwrap();
+        let x87 = "-2.25".parse::<ieee::X87DoubleExtended>().unwrap();
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let data = rt.block_on(x87.strict_serialize_async()).unwrap();
+        assert_eq!(data, x87.strict_serialize().unwrap());
+        assert_eq!(
+            rt.block_on(ieee::X87DoubleExtended::strict_deserialize_async(
+                &data
+            ))
+            .unwrap()
+            .to_bits(),
+            ieee::X87DoubleExtended::strict_deserialize(&data)
+                .unwrap()
+                .to_bits()
+        );
+        test_async_object_encoding_roundtrip(
+            &"1e100".parse::<ieee::Quad>().unwrap(),
+        )
+        .unwrap();
+        test_async_object_encoding_roundtrip(
+            &"-0.125".parse::<ieee::Oct>().unwrap(),
+        )
+        .unwrap();
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_types() {
+        use chrono::{DateTime, NaiveDateTime, Utc};
+
+        let naive =
+            NaiveDateTime::from_timestamp_opt(1_600_000_000, 0).unwrap();
+        test_async_encoding_roundtrip(&naive, 1_600_000_000i64.to_le_bytes())
+            .unwrap();
+        test_async_encoding_roundtrip(
+            &DateTime::<Utc>::from_utc(naive, Utc),
+            1_600_000_000i64.to_le_bytes(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn tlv_stream() {
+        assert_eq!(check_local_roundtrip(&BigSize::from(0xFCu64)), [0xFC]);
+        assert_eq!(check_local_roundtrip(&BigSize::from(0xFDu64)), [
+            0xFD, 0x00, 0xFD
+        ]);
+        check_local_roundtrip(&BigSize::from(u64::MAX));
+
+        let mut stream = TlvStream::new();
+        assert!(check_local_roundtrip(&stream).is_empty());
+        stream.insert(1, [0xAB]);
+        stream.insert(0x1_0000, vec![0xCD; 300]);
+        check_local_roundtrip(&stream);
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let mut blocking = vec![];
+        stream.bolt_encode(&mut blocking).unwrap();
+        let mut asynchronous = vec![];
+        rt.block_on(stream.bolt_encode_async(&mut asynchronous))
+            .unwrap();
+        assert_eq!(blocking, asynchronous);
+        assert_eq!(
+            rt.block_on(TlvStream::bolt_decode_async(&mut &asynchronous[..])),
+            Ok(stream)
+        );
+
+        // Records must follow in ascending order of their type ids
+        let mut unordered = 2u16.to_le_bytes().to_vec();
+        for type_no in [2u64, 1] {
+            unordered.extend(type_no.to_le_bytes());
+            unordered.extend([0x00, 0x00]);
+        }
+        let res = rt.block_on(TlvStream::strict_deserialize_async(&unordered));
+        assert!(res.is_err());
+        assert_eq!(res, TlvStream::strict_deserialize(&unordered));
+    }
+
+    fn limits(
+        max_bytes: usize,
+        max_depth: usize,
+        max_items: usize,
+    ) -> DecodeLimits {
+        DecodeLimits {
+            max_bytes,
+            max_depth,
+            max_items,
+            canonical: false,
+        }
+    }
+
+    fn decode_limited<T>(data: &[u8], limits: DecodeLimits) -> Result<T, Error>
+    where
+        T: StrictDecodeAsync,
+    {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        rt.block_on(LimitedAsyncReader::new(data, limits).decode::<T>())
+    }
+
+    #[test]
+    fn limited_reader() {
+        let data = s!("some string exceeding the limit");
+        let ser = data.strict_serialize().unwrap();
+        assert_eq!(
+            decode_limited::<String>(&ser, limits(ser.len(), 8, 64)),
+            Ok(data)
+        );
+        assert_eq!(
+            decode_limited::<String>(&ser, limits(ser.len() - 1, 8, 64)),
+            Err(Error::BytesLimitExceeded(ser.len() - 1))
+        );
+
+        let mut stream = TlvStream::new();
+        stream.insert(1, [0xAB]);
+        let data = (7u8, stream);
+        let ser = data.strict_serialize().unwrap();
+        assert_eq!(
+            decode_limited::<(u8, TlvStream)>(&ser, limits(ser.len(), 8, 64)),
+            Ok(data)
+        );
+
+        let data = (0u8..10).collect::<BTreeSet<_>>();
+        let ser = data.strict_serialize().unwrap();
+        assert_eq!(
+            decode_limited::<BTreeSet<u8>>(&ser, limits(1024, 8, 10)),
+            Ok(data)
+        );
+        assert_eq!(
+            decode_limited::<BTreeSet<u8>>(&ser, limits(1024, 8, 9)),
+            Err(Error::ItemsLimitExceeded(10, 9))
+        );
+        assert_eq!(
+            decode_limited::<LargeVec<u64>>(
+                &[0xFF, 0xFF, 0xFF, 0xFF, 0x01],
+                limits(1024, 8, 1024)
+            ),
+            Err(Error::ItemsLimitExceeded(u32::MAX as usize, 1024))
+        );
+
+        let data = vec![vec![vec![0u8]]];
+        let ser = data.strict_serialize().unwrap();
+        assert_eq!(
+            decode_limited::<Vec<Vec<Vec<u8>>>>(&ser, limits(1024, 3, 64)),
+            Ok(data)
+        );
+        assert_eq!(
+            decode_limited::<Vec<Vec<Vec<u8>>>>(&ser, limits(1024, 2, 64)),
+            Err(Error::DepthLimitExceeded(2))
+        );
+
+        // Limits are not applied outside of the limited reader
+        let ser = vec![0u8; 16].strict_serialize().unwrap();
+        assert!(decode_limited::<Vec<u8>>(&ser, limits(1024, 8, 1)).is_err());
+        test_async_object_encoding_roundtrip(&vec![0u8; 16]).unwrap();
+    }
+
+    #[test]
+    fn limited_reader_canonical() {
+        let canonical = DecodeLimits {
+            canonical: true,
+            ..DecodeLimits::UNLIMITED
+        };
+        let unsorted = [0x02, 0x00, 0x02, 0x01];
+        assert_eq!(
+            decode_limited::<BTreeSet<u8>>(&unsorted, canonical),
+            Err(Error::NonCanonical(any::type_name::<BTreeSet<u8>>()))
+        );
+        assert_eq!(
+            decode_limited::<BTreeSet<u8>>(&unsorted, DecodeLimits::UNLIMITED),
+            BTreeSet::<u8>::strict_deserialize(unsorted)
+        );
+        assert_eq!(
+            decode_limited::<TlvStream>(&[0x00, 0x00], canonical),
+            Err(Error::NonCanonical(any::type_name::<TlvStream>()))
+        );
+    }
+}
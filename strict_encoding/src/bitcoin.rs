@@ -33,6 +33,11 @@ use bitcoin::{
 };
 use bitcoin_hashes::sha256;
 
+use crate::limits::{check_canonical, decode_canonical};
+use crate::schema::{
+    EnumDescr, FieldDescr, LenPrefix, Primitive, StrictType, TypeDescr,
+    VariantDescr,
+};
 use crate::{strategies, Error, Strategy, StrictDecode, StrictEncode};
 
 impl Strategy for Txid {
@@ -130,6 +135,9 @@ impl StrictDecode for TaprootMerkleBranch {
 }
 
 impl StrictEncode for secp256k1::SecretKey {
+    const FIXED_SIZE: Option<usize> =
+        Some(secp256k1::constants::SECRET_KEY_SIZE);
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         Ok(e.write(&self[..])?)
@@ -148,6 +156,9 @@ impl StrictDecode for secp256k1::SecretKey {
 }
 
 impl StrictEncode for bip340::TweakedKeyPair {
+    const FIXED_SIZE: Option<usize> =
+        Some(secp256k1::constants::SECRET_KEY_SIZE);
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
         self.to_inner().strict_encode(e)
@@ -171,6 +182,9 @@ impl StrictDecode for bip340::TweakedKeyPair {
 }
 
 impl StrictEncode for KeyPair {
+    const FIXED_SIZE: Option<usize> =
+        Some(secp256k1::constants::SECRET_KEY_SIZE);
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         Ok(e.write(&self.secret_bytes())?)
@@ -190,6 +204,9 @@ impl StrictDecode for KeyPair {
 }
 
 impl StrictEncode for secp256k1::PublicKey {
+    const FIXED_SIZE: Option<usize> =
+        Some(secp256k1::constants::PUBLIC_KEY_SIZE);
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         Ok(e.write(&self.serialize())?)
@@ -217,6 +234,9 @@ impl StrictDecode for secp256k1::PublicKey {
 }
 
 impl StrictEncode for XOnlyPublicKey {
+    const FIXED_SIZE: Option<usize> =
+        Some(secp256k1::constants::SCHNORR_PUBLIC_KEY_SIZE);
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         Ok(e.write(&self.serialize())?)
@@ -235,6 +255,9 @@ impl StrictDecode for XOnlyPublicKey {
 }
 
 impl StrictEncode for bip340::TweakedPublicKey {
+    const FIXED_SIZE: Option<usize> =
+        Some(secp256k1::constants::SCHNORR_PUBLIC_KEY_SIZE);
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         Ok(e.write(&self.serialize())?)
@@ -255,6 +278,9 @@ impl StrictDecode for bip340::TweakedPublicKey {
 }
 
 impl StrictEncode for ecdsa::Signature {
+    const FIXED_SIZE: Option<usize> =
+        Some(secp256k1::constants::COMPACT_SIGNATURE_SIZE);
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         Ok(e.write(&self.serialize_compact())?)
@@ -275,6 +301,9 @@ impl StrictDecode for ecdsa::Signature {
 }
 
 impl StrictEncode for schnorr::Signature {
+    const FIXED_SIZE: Option<usize> =
+        Some(secp256k1::constants::SCHNORR_SIGNATURE_SIZE);
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         Ok(e.write(&self[..])?)
@@ -368,8 +397,12 @@ impl StrictEncode for SchnorrSig {
 
 impl StrictDecode for SchnorrSig {
     fn strict_decode<D: Read>(d: D) -> Result<Self, Error> {
-        SchnorrSig::from_slice(&Vec::strict_decode(d)?).map_err(|_| {
-            Error::DataIntegrityError(s!("invalid BIP431 signature data"))
+        // Explicit `SIGHASH_DEFAULT` flag is accepted by the parser, but it is
+        // never serialized
+        decode_canonical(d, |d| {
+            SchnorrSig::from_slice(&Vec::strict_decode(d)?).map_err(|_| {
+                Error::DataIntegrityError(s!("invalid BIP431 signature data"))
+            })
         })
     }
 }
@@ -403,34 +436,27 @@ impl StrictDecode for bitcoin::PublicKey {
     #[inline]
     fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
         let marker = u8::strict_decode(&mut d)?;
-        match marker {
-            0x04 => {
-                let mut buf =
-                    [0u8; secp256k1::constants::UNCOMPRESSED_PUBLIC_KEY_SIZE];
-                buf[0] = marker;
-                d.read_exact(&mut buf[1..])?;
-                Ok(Self::from_slice(&buf).map_err(|_| {
-                    Error::DataIntegrityError(
-                        "Wrong public key data sequence".to_string(),
-                    )
-                })?)
-            }
-            0x03 | 0x02 => {
-                let mut buf = [0u8; secp256k1::constants::PUBLIC_KEY_SIZE];
-                buf[0] = marker;
-                d.read_exact(&mut buf[1..])?;
-                Ok(Self::from_slice(&buf).map_err(|_| {
-                    Error::DataIntegrityError(
-                        "Wrong public key data sequence".to_string(),
-                    )
-                })?)
+        let mut buf = [0u8; secp256k1::constants::UNCOMPRESSED_PUBLIC_KEY_SIZE];
+        let buf = match marker {
+            0x04 => &mut buf[..],
+            0x03 | 0x02 => &mut buf[..secp256k1::constants::PUBLIC_KEY_SIZE],
+            invalid_flag => {
+                return Err(Error::DataIntegrityError(format!(
+                    "Invalid public key encoding flag {:#04x}; must be either \
+                     0x02, 0x03 or 0x04",
+                    invalid_flag
+                )))
             }
-            invalid_flag => Err(Error::DataIntegrityError(format!(
-                "Invalid public key encoding flag {:#04x}; must be either \
-                 0x02, 0x03 or 0x04",
-                invalid_flag
-            ))),
-        }
+        };
+        buf[0] = marker;
+        d.read_exact(&mut buf[1..])?;
+        let key = Self::from_slice(buf).map_err(|_| {
+            Error::DataIntegrityError(
+                "Wrong public key data sequence".to_string(),
+            )
+        })?;
+        check_canonical(&key, buf)?;
+        Ok(key)
     }
 }
 
@@ -748,8 +774,171 @@ impl StrictEncode for TapTree {
 
 impl StrictDecode for TapTree {
     fn strict_decode<D: Read>(d: D) -> Result<Self, Error> {
-        Ok(TapTree::deserialize(&Vec::<u8>::strict_decode(d)?)
-            .expect("incomplete tree"))
+        decode_canonical(d, |d| {
+            TapTree::deserialize(&Vec::<u8>::strict_decode(d)?).map_err(|err| {
+                Error::DataIntegrityError(format!(
+                    "invalid taproot tree: {}",
+                    err
+                ))
+            })
+        })
+    }
+}
+
+macro_rules! impl_strict_type {
+    ($ty:ty, $descr:expr) => {
+        impl StrictType for $ty {
+            #[inline]
+            fn strict_type() -> TypeDescr { $descr }
+        }
+    };
+}
+
+fn byte_list() -> TypeDescr {
+    TypeDescr::list(LenPrefix::U16, TypeDescr::Primitive(Primitive::U8))
+}
+
+impl_strict_type!(LeafVersion, TypeDescr::Primitive(Primitive::U8));
+impl_strict_type!(FutureLeafVersion, TypeDescr::Primitive(Primitive::U8));
+impl_strict_type!(TaprootMerkleBranch, Vec::<sha256::Hash>::strict_type());
+impl_strict_type!(
+    secp256k1::SecretKey,
+    TypeDescr::Bytes(secp256k1::constants::SECRET_KEY_SIZE as u16)
+);
+impl_strict_type!(
+    bip340::TweakedKeyPair,
+    TypeDescr::Bytes(secp256k1::constants::SECRET_KEY_SIZE as u16)
+);
+impl_strict_type!(
+    KeyPair,
+    TypeDescr::Bytes(secp256k1::constants::SECRET_KEY_SIZE as u16)
+);
+impl_strict_type!(
+    secp256k1::PublicKey,
+    TypeDescr::Bytes(secp256k1::constants::PUBLIC_KEY_SIZE as u16)
+);
+impl_strict_type!(
+    XOnlyPublicKey,
+    TypeDescr::Bytes(secp256k1::constants::SCHNORR_PUBLIC_KEY_SIZE as u16)
+);
+impl_strict_type!(
+    bip340::TweakedPublicKey,
+    TypeDescr::Bytes(secp256k1::constants::SCHNORR_PUBLIC_KEY_SIZE as u16)
+);
+impl_strict_type!(
+    ecdsa::Signature,
+    TypeDescr::Bytes(secp256k1::constants::COMPACT_SIGNATURE_SIZE as u16)
+);
+impl_strict_type!(
+    schnorr::Signature,
+    TypeDescr::Bytes(secp256k1::constants::SCHNORR_SIGNATURE_SIZE as u16)
+);
+impl_strict_type!(PsbtSighashType, TypeDescr::Primitive(Primitive::U32));
+impl_strict_type!(EcdsaSighashType, TypeDescr::Primitive(Primitive::U32));
+impl_strict_type!(SchnorrSighashType, TypeDescr::Primitive(Primitive::U8));
+impl_strict_type!(EcdsaSig, byte_list());
+impl_strict_type!(SchnorrSig, byte_list());
+// Public key length depends on the first byte of the encoding, which can't be
+// expressed with the type description
+impl_strict_type!(bitcoin::PublicKey, TypeDescr::opaque("PublicKey"));
+impl_strict_type!(Amount, TypeDescr::Primitive(Primitive::U64));
+impl_strict_type!(Script, byte_list());
+impl_strict_type!(ControlBlock, byte_list());
+impl_strict_type!(TapTree, byte_list());
+impl_strict_type!(
+    ScriptLeaf,
+    TypeDescr::named_struct("ScriptLeaf", [
+        ("script", Script::strict_type()),
+        ("leaf_version", LeafVersion::strict_type()),
+    ])
+);
+impl_strict_type!(bitcoin::Network, TypeDescr::Primitive(Primitive::U32));
+impl_strict_type!(bip32::ChainCode, TypeDescr::Bytes(32));
+impl_strict_type!(bip32::Fingerprint, TypeDescr::Bytes(4));
+impl_strict_type!(bip32::ExtendedPubKey, TypeDescr::Bytes(78));
+impl_strict_type!(bip32::ExtendedPrivKey, TypeDescr::Bytes(78));
+impl_strict_type!(
+    bip32::DerivationPath,
+    Vec::<bip32::ChildNumber>::strict_type()
+);
+impl_strict_type!(
+    psbt::raw::Key,
+    TypeDescr::named_struct("Key", [
+        ("type_value", u8::strict_type()),
+        ("key", byte_list()),
+    ])
+);
+impl_strict_type!(
+    psbt::raw::Pair,
+    TypeDescr::named_struct("Pair", [
+        ("key", psbt::raw::Key::strict_type()),
+        ("value", byte_list()),
+    ])
+);
+impl_strict_type!(
+    psbt::raw::ProprietaryKey,
+    TypeDescr::named_struct("ProprietaryKey", [
+        ("prefix", byte_list()),
+        ("subtype", u8::strict_type()),
+        ("key", byte_list()),
+    ])
+);
+impl_strict_type!(
+    Address,
+    TypeDescr::named_struct("Address", [
+        ("network", bitcoin::Network::strict_type()),
+        ("payload", address::Payload::strict_type()),
+    ])
+);
+
+impl StrictType for bip32::ChildNumber {
+    fn strict_type() -> TypeDescr {
+        let index = vec![FieldDescr::named("index", u32::strict_type())];
+        TypeDescr::Enum(EnumDescr {
+            name: s!("ChildNumber"),
+            repr: Primitive::U8,
+            by_order: true,
+            variants: vec![
+                VariantDescr {
+                    name: s!("Normal"),
+                    value: 0,
+                    fields: index.clone(),
+                },
+                VariantDescr {
+                    name: s!("Hardened"),
+                    value: 1,
+                    fields: index,
+                },
+            ],
+        })
+    }
+}
+
+impl StrictType for address::Payload {
+    fn strict_type() -> TypeDescr {
+        let mut variants = (0u64..=16)
+            .map(|version| VariantDescr {
+                name: format!("WitnessProgramV{}", version),
+                value: version,
+                fields: vec![FieldDescr::named("program", byte_list())],
+            })
+            .collect::<Vec<_>>();
+        variants.push(VariantDescr {
+            name: s!("PubkeyHash"),
+            value: 32,
+            fields: vec![FieldDescr::unnamed(PubkeyHash::strict_type())],
+        });
+        variants.push(VariantDescr {
+            name: s!("ScriptHash"),
+            value: 33,
+            fields: vec![FieldDescr::unnamed(ScriptHash::strict_type())],
+        });
+        TypeDescr::Enum(EnumDescr {
+            name: s!("Payload"),
+            repr: Primitive::U8,
+            by_order: false,
+            variants,
+        })
     }
 }
 
@@ -765,6 +954,7 @@ pub(crate) mod test {
     use strict_encoding_test::*;
 
     use super::*;
+    use crate::strict_deserialize_canonical;
 
     #[test]
     fn test_encoding_hashes() {
@@ -1413,4 +1603,39 @@ pub(crate) mod test {
         let p2wsh: Script = test_vec_decoding_roundtrip(P2WSH).unwrap();
         assert!(p2wsh.is_v0_p2wsh());
     }
+
+    #[test]
+    fn test_tap_tree() {
+        let builder = bitcoin::util::taproot::TaprootBuilder::new()
+            .add_leaf(1, Script::new())
+            .unwrap()
+            .add_leaf(1, Script::new_op_return(&[]))
+            .unwrap();
+        let tree = TapTree::try_from(builder).unwrap();
+        test_object_encoding_roundtrip(&tree).unwrap();
+
+        // Incomplete tree with a single leaf at depth 1
+        assert!(matches!(
+            TapTree::strict_deserialize([0x03, 0x00, 0x01, 0xC0, 0x00]),
+            Err(Error::DataIntegrityError(_))
+        ));
+    }
+
+    #[test]
+    fn test_schnorr_sig_canonical() {
+        let mut data = vec![0x41, 0x00];
+        data.extend([0x01; 64]);
+        // Explicit `SIGHASH_DEFAULT` flag
+        data.push(0x00);
+        let sig = SchnorrSig::strict_deserialize(&data).unwrap();
+        assert_eq!(sig.hash_ty, SchnorrSighashType::Default);
+        assert_eq!(
+            strict_deserialize_canonical::<SchnorrSig>(&data).unwrap_err(),
+            Error::NonCanonical(core::any::type_name::<SchnorrSig>())
+        );
+
+        let data = sig.strict_serialize().unwrap();
+        assert_eq!(data.len(), 66);
+        assert_eq!(strict_deserialize_canonical::<SchnorrSig>(&data), Ok(sig));
+    }
 }
@@ -0,0 +1,302 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Zero-copy decoding of strictly encoded data from byte slices.
+//!
+//! [`StrictDecodeBorrowed`] decodes the same data as [`StrictDecode`] does,
+//! but instead of copying byte strings and strings into newly allocated
+//! buffers returns `&'a [u8]` and `&'a str` views into the source data.
+//! Length-prefixed collections of such values are decoded into
+//! [`BorrowedIter`], which decodes its items lazily on iteration.
+//!
+//! Any type implementing [`StrictDecode`] implements [`StrictDecodeBorrowed`]
+//! for any lifetime, so owned values can be mixed with borrowed ones inside
+//! structures deriving `#[derive(StrictDecodeBorrowed)]`.
+
+use core::fmt::{self, Debug, Formatter};
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+
+use crate::limits::{check_items, DepthGuard};
+use crate::{io, Error, StrictDecode, StrictEncode};
+
+/// Binary decoding of the strictly encoded data from a byte slice, borrowing
+/// parts of the decoded value from that slice instead of copying them.
+///
+/// The trait is automatically implemented for all types implementing
+/// [`StrictDecode`]; borrowed implementations are provided for `&'a [u8]`,
+/// `&'a str`, `&'a [u8; LEN]` and [`BorrowedIter`]. The trait can be derived
+/// for structures and enums with `#[derive(StrictDecodeBorrowed)]`.
+pub trait StrictDecodeBorrowed<'a>: Sized {
+    /// Decodes value from the beginning of the byte slice, advancing the
+    /// slice past the consumed data.
+    fn strict_decode_borrowed(d: &mut &'a [u8]) -> Result<Self, Error>;
+
+    /// Tries to deserialize the whole byte slice into the current type using
+    /// [`StrictDecodeBorrowed::strict_decode_borrowed`]. If there are some
+    /// data remains in the slice once deserialization is completed, fails
+    /// with [`Error::DataNotEntirelyConsumed`].
+    fn strict_deserialize_borrowed(data: &'a [u8]) -> Result<Self, Error> {
+        let mut decoder = data;
+        let rv = Self::strict_decode_borrowed(&mut decoder)
+            .map_err(|err| err.at_offset(data.len() - decoder.len()))?;
+        if decoder.is_empty() {
+            Ok(rv)
+        } else {
+            Err(Error::DataNotEntirelyConsumed)
+        }
+    }
+}
+
+impl<'a, T> StrictDecodeBorrowed<'a> for T
+where
+    T: StrictDecode,
+{
+    #[inline]
+    fn strict_decode_borrowed(d: &mut &'a [u8]) -> Result<Self, Error> {
+        T::strict_decode(d)
+    }
+}
+
+/// Splits off the first `len` bytes from the slice, failing with
+/// [`Error::Io`] if the slice is shorter.
+fn take<'a>(d: &mut &'a [u8], len: usize) -> Result<&'a [u8], Error> {
+    if d.len() < len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+    let (data, rest) = d.split_at(len);
+    *d = rest;
+    Ok(data)
+}
+
+impl<'a> StrictDecodeBorrowed<'a> for &'a [u8] {
+    fn strict_decode_borrowed(d: &mut &'a [u8]) -> Result<Self, Error> {
+        let len = usize::strict_decode(&mut *d)?;
+        take(d, len)
+    }
+}
+
+impl<'a> StrictDecodeBorrowed<'a> for &'a str {
+    fn strict_decode_borrowed(d: &mut &'a [u8]) -> Result<Self, Error> {
+        let data = <&'a [u8]>::strict_decode_borrowed(d)?;
+        core::str::from_utf8(data).map_err(Error::from)
+    }
+}
+
+impl<'a, const LEN: usize> StrictDecodeBorrowed<'a> for &'a [u8; LEN] {
+    fn strict_decode_borrowed(d: &mut &'a [u8]) -> Result<Self, Error> {
+        if LEN > u16::MAX as usize {
+            return Err(Error::ExceedMaxItems(LEN));
+        }
+        let data = take(d, LEN)?;
+        Ok(data.try_into().expect("slice length is checked above"))
+    }
+}
+
+/// Lazy iterator over the items of a length-prefixed collection (`Vec`,
+/// `BTreeSet` etc), borrowing them from the source data.
+///
+/// All items are validated once the collection is decoded, so the iteration
+/// itself does not fail. The original encoding is preserved, so the
+/// collection may be strict-encoded back without decoding the items.
+pub struct BorrowedIter<'a, T>
+where
+    T: StrictDecodeBorrowed<'a>,
+{
+    data: &'a [u8],
+    remaining: usize,
+    _phantom: PhantomData<T>,
+}
+
+impl<'a, T> BorrowedIter<'a, T>
+where
+    T: StrictDecodeBorrowed<'a>,
+{
+    /// Returns strict-encoded data of the items which are not iterated yet,
+    /// without the length prefix.
+    #[inline]
+    pub fn as_bytes(&self) -> &'a [u8] { self.data }
+}
+
+impl<'a, T> Clone for BorrowedIter<'a, T>
+where
+    T: StrictDecodeBorrowed<'a>,
+{
+    fn clone(&self) -> Self {
+        BorrowedIter {
+            data: self.data,
+            remaining: self.remaining,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Debug for BorrowedIter<'a, T>
+where
+    T: StrictDecodeBorrowed<'a>,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BorrowedIter")
+            .field("remaining", &self.remaining)
+            .field("data", &self.data)
+            .finish()
+    }
+}
+
+impl<'a, T> Iterator for BorrowedIter<'a, T>
+where
+    T: StrictDecodeBorrowed<'a>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(
+            T::strict_decode_borrowed(&mut self.data)
+                .expect("collection items are validated during decoding"),
+        )
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for BorrowedIter<'a, T> where
+    T: StrictDecodeBorrowed<'a>
+{
+}
+
+impl<'a, T> FusedIterator for BorrowedIter<'a, T> where
+    T: StrictDecodeBorrowed<'a>
+{
+}
+
+impl<'a, T> StrictDecodeBorrowed<'a> for BorrowedIter<'a, T>
+where
+    T: StrictDecodeBorrowed<'a>,
+{
+    fn strict_decode_borrowed(d: &mut &'a [u8]) -> Result<Self, Error> {
+        let len = usize::strict_decode(&mut *d)?;
+        check_items(len)?;
+        let _depth = DepthGuard::enter()?;
+        let start = *d;
+        for index in 0..len {
+            T::strict_decode_borrowed(d)
+                .map_err(|err| err.within_item(index))?;
+        }
+        Ok(BorrowedIter {
+            data: &start[..start.len() - d.len()],
+            remaining: len,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<'a, T> StrictEncode for BorrowedIter<'a, T>
+where
+    T: StrictDecodeBorrowed<'a>,
+{
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        let len = self.remaining.strict_encode(&mut e)?;
+        e.write_all(self.data)?;
+        Ok(len + self.data.len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    #[test]
+    fn bytes_and_str() {
+        let data = vec![0x03, 0x00, b'a', b'b', b'c', 0xFF];
+        let mut d = &data[..];
+        let s = <&str>::strict_decode_borrowed(&mut d).unwrap();
+        assert_eq!(s, "abc");
+        assert_eq!(d, &[0xFF]);
+        assert_eq!(s.as_ptr(), data[2..].as_ptr());
+
+        let bytes = <&[u8]>::strict_deserialize_borrowed(&data[..5]).unwrap();
+        assert_eq!(bytes, b"abc");
+        assert_eq!(
+            <&[u8]>::strict_deserialize_borrowed(&data),
+            Err(Error::DataNotEntirelyConsumed)
+        );
+
+        let owned = "owned".strict_serialize().unwrap();
+        assert_eq!(<&str>::strict_deserialize_borrowed(&owned), Ok("owned"));
+        assert_eq!(
+            String::strict_deserialize_borrowed(&owned),
+            Ok(s!("owned"))
+        );
+    }
+
+    #[test]
+    fn invalid_data() {
+        assert!(matches!(
+            <&[u8]>::strict_deserialize_borrowed(&[0x04, 0x00, 1, 2, 3]),
+            Err(Error::Io(_))
+        ));
+        assert!(matches!(
+            <&str>::strict_deserialize_borrowed(&[0x01, 0x00, 0xFF]),
+            Err(Error::Utf8Conversion(_))
+        ));
+        assert!(matches!(
+            <&[u8; 4]>::strict_deserialize_borrowed(&[1, 2, 3]),
+            Err(Error::Io(_))
+        ));
+        assert_eq!(
+            <&[u8; 3]>::strict_deserialize_borrowed(&[1, 2, 3]),
+            Ok(&[1u8, 2, 3])
+        );
+    }
+
+    #[test]
+    fn collections() {
+        let list = vec![s!("a"), s!("bc"), s!("")];
+        let data = list.strict_serialize().unwrap();
+        let iter =
+            BorrowedIter::<&str>::strict_deserialize_borrowed(&data).unwrap();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.strict_serialize().unwrap(), data);
+        assert_eq!(iter.clone().collect::<Vec<_>>(), vec!["a", "bc", ""]);
+
+        let nested = vec![vec![1u8, 2], vec![], vec![3]];
+        let data = nested.strict_serialize().unwrap();
+        let iter =
+            BorrowedIter::<BorrowedIter<u8>>::strict_deserialize_borrowed(
+                &data,
+            )
+            .unwrap();
+        assert_eq!(
+            iter.map(Iterator::collect::<Vec<_>>).collect::<Vec<_>>(),
+            nested
+        );
+
+        // Broken item is detected before iteration
+        let data = [0x02, 0x00, 0x01, 0x00, b'a', 0x01, 0x00, 0xFF];
+        assert!(matches!(
+            BorrowedIter::<&str>::strict_deserialize_borrowed(&data),
+            Err(Error::Utf8Conversion(_))
+        ));
+    }
+}
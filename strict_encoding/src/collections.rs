@@ -12,18 +12,24 @@
 // You should have received a copy of the Apache 2.0 License along with this
 // software. If not, see <https://opensource.org/licenses/Apache-2.0>.
 
-use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
-use std::fmt::Debug;
-use std::hash::Hash;
-use std::io;
-use std::io::{Read, Write};
-use std::ops::{
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+use core::any;
+use core::fmt::Debug;
+#[cfg(feature = "std")]
+use core::hash::Hash;
+use core::ops::{
     Deref, Range, RangeFrom, RangeInclusive, RangeTo, RangeToInclusive,
 };
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
 
 use amplify::num::u24;
 
-use crate::{Error, StrictDecode, StrictEncode};
+use crate::io::{Read, Write};
+use crate::limits::{check_items, is_canonical, prealloc_capacity, DepthGuard};
+use crate::schema::{LenPrefix, StrictType, TypeDescr};
+use crate::{io, Error, StrictDecode, StrictEncode};
 
 /// In terms of strict encoding, ranges are encoded as a tuples of two values:
 /// start and end.
@@ -31,6 +37,9 @@ impl<T> StrictEncode for Range<T>
 where
     T: StrictEncode,
 {
+    const FIXED_SIZE: Option<usize> =
+        crate::fixed_size_sum(&[T::FIXED_SIZE, T::FIXED_SIZE]);
+
     fn strict_encode<E: Write>(&self, mut e: E) -> Result<usize, Error> {
         Ok(self.start.strict_encode(&mut e)?
             + self.end.strict_encode(&mut e)?)
@@ -57,6 +66,9 @@ impl<T> StrictEncode for RangeInclusive<T>
 where
     T: StrictEncode,
 {
+    const FIXED_SIZE: Option<usize> =
+        crate::fixed_size_sum(&[T::FIXED_SIZE, T::FIXED_SIZE]);
+
     fn strict_encode<E: Write>(&self, mut e: E) -> Result<usize, Error> {
         Ok(self.start().strict_encode(&mut e)?
             + self.end().strict_encode(&mut e)?)
@@ -82,6 +94,8 @@ impl<T> StrictEncode for RangeFrom<T>
 where
     T: StrictEncode,
 {
+    const FIXED_SIZE: Option<usize> = T::FIXED_SIZE;
+
     #[inline]
     fn strict_encode<E: Write>(&self, mut e: E) -> Result<usize, Error> {
         self.start.strict_encode(&mut e)
@@ -107,6 +121,8 @@ impl<T> StrictEncode for RangeTo<T>
 where
     T: StrictEncode,
 {
+    const FIXED_SIZE: Option<usize> = T::FIXED_SIZE;
+
     #[inline]
     fn strict_encode<E: Write>(&self, mut e: E) -> Result<usize, Error> {
         self.end.strict_encode(&mut e)
@@ -132,6 +148,8 @@ impl<T> StrictEncode for RangeToInclusive<T>
 where
     T: StrictEncode,
 {
+    const FIXED_SIZE: Option<usize> = T::FIXED_SIZE;
+
     #[inline]
     fn strict_encode<E: Write>(&self, mut e: E) -> Result<usize, Error> {
         self.end.strict_encode(&mut e)
@@ -205,6 +223,19 @@ where
         }
         Ok(encoded)
     }
+
+    fn strict_encoded_len(&self) -> Result<usize, Error> {
+        let mut len = self.len().strict_encoded_len()?;
+        match T::FIXED_SIZE {
+            Some(size) => len += size * self.len(),
+            None => {
+                for item in self {
+                    len += item.strict_encoded_len()?;
+                }
+            }
+        }
+        Ok(len)
+    }
 }
 
 /// Wrapper for vectors which may have up to `u32::MAX` elements in strict
@@ -255,7 +286,7 @@ where
     T: StrictEncode + StrictDecode,
 {
     type Item = &'me T;
-    type IntoIter = std::slice::Iter<'me, T>;
+    type IntoIter = core::slice::Iter<'me, T>;
 
     fn into_iter(self) -> Self::IntoIter { self.0.iter() }
 }
@@ -265,7 +296,7 @@ where
     T: StrictEncode + StrictDecode,
 {
     type Item = T;
-    type IntoIter = std::vec::IntoIter<T>;
+    type IntoIter = alloc::vec::IntoIter<T>;
 
     fn into_iter(self) -> Self::IntoIter { self.0.into_iter() }
 }
@@ -293,11 +324,7 @@ where
 {
     fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
         let len = u32::strict_decode(&mut d)?;
-        let mut data = Vec::<T>::with_capacity(len as usize);
-        for _ in 0..len {
-            data.push(T::strict_decode(&mut d)?);
-        }
-        Ok(Self(data))
+        decode_vec_items(len as usize, d).map(Self)
     }
 }
 
@@ -313,23 +340,48 @@ where
     pub fn len_u32(&self) -> u32 { self.0.len() as u32 }
 
     /// Returns iterator over mutable elements of the collection.
-    pub fn iter_mut(&mut self) -> std::slice::IterMut<T> { self.0.iter_mut() }
+    pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, T> {
+        self.0.iter_mut()
+    }
 
     /// Appends an element to the back of a collection.
     ///
     /// # Errors
     ///
     /// Errors with [`Error::ExceedMaxItems`] if the new capacity exceeds
-    /// `u32::MAX` bytes.
+    /// `u32::MAX` items.
     pub fn push(&mut self, item: T) -> Result<usize, Error> {
         let len = self.0.len();
-        if len > u32::MAX as usize {
-            return Err(Error::ExceedMaxItems(len));
+        if len >= u32::MAX as usize {
+            return Err(Error::ExceedMaxItems(len + 1));
         }
         self.0.push(item);
         Ok(len)
     }
 
+    /// Inserts an element at position `index` within the vector, shifting all
+    /// elements after it to the right.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::ExceedMaxItems`] if the vector is already full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, item: T) -> Result<(), Error> {
+        let len = self.0.len();
+        if len >= u32::MAX as usize {
+            return Err(Error::ExceedMaxItems(len + 1));
+        }
+        self.0.insert(index, item);
+        Ok(())
+    }
+
+    /// Removes the last element from a vector and returns it, or `None` if it
+    /// is empty.
+    pub fn pop(&mut self) -> Option<T> { self.0.pop() }
+
     /// Removes and returns the element at position `index` within the vector,
     /// shifting all elements after it to the left.
     ///
@@ -337,6 +389,58 @@ where
     ///
     /// Panics if `index` is out of bounds.
     pub fn remove(&mut self, index: usize) -> T { self.0.remove(index) }
+
+    /// Returns mutable reference to the element at position `index`, if any.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.0.get_mut(index)
+    }
+
+    /// Appends items yielded by the iterator to the back of the vector, one
+    /// by one. The items appended before the vector gets full are kept.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::ExceedMaxItems`] if the vector gets more than
+    /// `u32::MAX` items.
+    pub fn try_extend<I>(&mut self, iter: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iter {
+            self.push(item)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the wrapped vector.
+    pub fn into_inner(self) -> Vec<T> { self.0 }
+}
+
+impl<T> FromIterator<T> for LargeVec<T>
+where
+    T: StrictEncode + StrictDecode,
+{
+    /// # Panics
+    ///
+    /// Panics if the iterator yields more items than the vector may hold; use
+    /// [`TryFrom`] on a collected `Vec` for a fallible version.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::try_from(iter.into_iter().collect::<Vec<_>>())
+            .expect("LargeVec size limit exceeded")
+    }
+}
+
+impl<T> Extend<T> for LargeVec<T>
+where
+    T: StrictEncode + StrictDecode,
+{
+    /// # Panics
+    ///
+    /// Panics if the vector gets more items than it may hold; use
+    /// [`LargeVec::try_extend`] for a fallible version.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.try_extend(iter).expect("LargeVec size limit exceeded")
+    }
 }
 
 /// Wrapper for vectors which may have up to `u24::MAX` elements in strict
@@ -375,7 +479,7 @@ where
 
     fn try_from(value: Vec<T>) -> Result<Self, Self::Error> {
         let len = value.len();
-        if len > u32::MAX as usize {
+        if len > u24::MAX.as_u32() as usize {
             return Err(Error::ExceedMaxItems(len));
         }
         Ok(Self(value))
@@ -387,7 +491,7 @@ where
     T: StrictEncode + StrictDecode,
 {
     type Item = &'me T;
-    type IntoIter = std::slice::Iter<'me, T>;
+    type IntoIter = core::slice::Iter<'me, T>;
 
     fn into_iter(self) -> Self::IntoIter { self.0.iter() }
 }
@@ -397,7 +501,7 @@ where
     T: StrictEncode + StrictDecode,
 {
     type Item = T;
-    type IntoIter = std::vec::IntoIter<T>;
+    type IntoIter = alloc::vec::IntoIter<T>;
 
     fn into_iter(self) -> Self::IntoIter { self.0.into_iter() }
 }
@@ -427,11 +531,7 @@ where
 {
     fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
         let len = u24::strict_decode(&mut d)?.as_u32() as usize;
-        let mut data = Vec::<T>::with_capacity(len);
-        for _ in 0..len {
-            data.push(T::strict_decode(&mut d)?);
-        }
-        Ok(Self(data))
+        decode_vec_items(len, d).map(Self)
     }
 }
 
@@ -439,7 +539,7 @@ impl<T> MediumVec<T>
 where
     T: StrictEncode + StrictDecode,
 {
-    /// Constructs empty [`LargeVec`].
+    /// Constructs empty [`MediumVec`].
     pub fn new() -> Self { Self(vec![]) }
 
     /// Returns the number of elements in the vector, also referred to as its
@@ -450,23 +550,48 @@ where
     }
 
     /// Returns iterator over mutable elements of the collection.
-    pub fn iter_mut(&mut self) -> std::slice::IterMut<T> { self.0.iter_mut() }
+    pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, T> {
+        self.0.iter_mut()
+    }
 
     /// Appends an element to the back of a collection.
     ///
     /// # Errors
     ///
     /// Errors with [`Error::ExceedMaxItems`] if the new capacity exceeds
-    /// `u32::MAX` bytes.
+    /// `u24::MAX` items.
     pub fn push(&mut self, item: T) -> Result<usize, Error> {
         let len = self.0.len();
-        if len > u24::MAX.as_u32() as usize {
-            return Err(Error::ExceedMaxItems(len));
+        if len >= u24::MAX.as_u32() as usize {
+            return Err(Error::ExceedMaxItems(len + 1));
         }
         self.0.push(item);
         Ok(len)
     }
 
+    /// Inserts an element at position `index` within the vector, shifting all
+    /// elements after it to the right.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::ExceedMaxItems`] if the vector is already full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, item: T) -> Result<(), Error> {
+        let len = self.0.len();
+        if len >= u24::MAX.as_u32() as usize {
+            return Err(Error::ExceedMaxItems(len + 1));
+        }
+        self.0.insert(index, item);
+        Ok(())
+    }
+
+    /// Removes the last element from a vector and returns it, or `None` if it
+    /// is empty.
+    pub fn pop(&mut self) -> Option<T> { self.0.pop() }
+
     /// Removes and returns the element at position `index` within the vector,
     /// shifting all elements after it to the left.
     ///
@@ -474,6 +599,59 @@ where
     ///
     /// Panics if `index` is out of bounds.
     pub fn remove(&mut self, index: usize) -> T { self.0.remove(index) }
+
+    /// Returns mutable reference to the element at position `index`, if any.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.0.get_mut(index)
+    }
+
+    /// Appends items yielded by the iterator to the back of the vector, one
+    /// by one. The items appended before the vector gets full are kept.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::ExceedMaxItems`] if the vector gets more than
+    /// `u24::MAX` items.
+    pub fn try_extend<I>(&mut self, iter: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iter {
+            self.push(item)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the wrapped vector.
+    pub fn into_inner(self) -> Vec<T> { self.0 }
+}
+
+impl<T> FromIterator<T> for MediumVec<T>
+where
+    T: StrictEncode + StrictDecode,
+{
+    /// # Panics
+    ///
+    /// Panics if the iterator yields more items than the vector may hold; use
+    /// [`TryFrom`] on a collected `Vec` for a fallible version.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::try_from(iter.into_iter().collect::<Vec<_>>())
+            .expect("MediumVec size limit exceeded")
+    }
+}
+
+impl<T> Extend<T> for MediumVec<T>
+where
+    T: StrictEncode + StrictDecode,
+{
+    /// # Panics
+    ///
+    /// Panics if the vector gets more items than it may hold; use
+    /// [`MediumVec::try_extend`] for a fallible version.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.try_extend(iter)
+            .expect("MediumVec size limit exceeded")
+    }
 }
 
 /// In terms of strict encoding, `Vec` is stored in form of
@@ -488,6 +666,10 @@ where
     fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
         self.as_slice().strict_encode(e)
     }
+
+    fn strict_encoded_len(&self) -> Result<usize, Error> {
+        self.as_slice().strict_encoded_len()
+    }
 }
 
 /// In terms of strict encoding, `Vec` is stored in form of
@@ -504,19 +686,37 @@ where
 {
     fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
         let len = usize::strict_decode(&mut d)?;
-        let mut data = Vec::<T>::with_capacity(len);
-        for _ in 0..len {
-            data.push(T::strict_decode(&mut d)?);
-        }
-        Ok(data)
+        decode_vec_items(len, d)
     }
 }
 
+/// Decodes `len` items of a list, which length prefix was already read by the
+/// caller.
+pub(crate) fn decode_vec_items<T, D>(
+    len: usize,
+    mut d: D,
+) -> Result<Vec<T>, Error>
+where
+    T: StrictDecode,
+    D: io::Read,
+{
+    check_items(len)?;
+    let _depth = DepthGuard::enter()?;
+    let mut data = Vec::<T>::with_capacity(prealloc_capacity::<T>(len));
+    for index in 0..len {
+        data.push(
+            T::strict_decode(&mut d).map_err(|err| err.within_item(index))?,
+        );
+    }
+    Ok(data)
+}
+
 /// Strict encoding for a unique value collection represented by a rust
 /// `HashSet` type is performed in the same way as `Vec` encoding.
 /// NB: Array members must are ordered with the sort operation, so type
 /// `T` must implement `Ord` trait in such a way that it produces
 /// deterministically-sorted result
+#[cfg(feature = "std")]
 impl<T> StrictEncode for HashSet<T>
 where
     T: StrictEncode + Eq + Ord + Hash + Debug,
@@ -536,16 +736,42 @@ where
 /// Strict decoding of a unique value collection represented by a rust
 /// `HashSet` type is performed alike `Vec` decoding with the only
 /// exception: if the repeated value met a [Error::RepeatedValue] is
-/// returned.
+/// returned. In canonical decoding mode the values must also be sorted,
+/// otherwise [`Error::NonCanonical`] is returned.
+#[cfg(feature = "std")]
 impl<T> StrictDecode for HashSet<T>
 where
     T: StrictDecode + Eq + Ord + Hash + Debug,
 {
     fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
         let len = usize::strict_decode(&mut d)?;
-        let mut data = HashSet::<T>::with_capacity(len);
-        for _ in 0..len {
-            let val = T::strict_decode(&mut d)?;
+        check_items(len)?;
+        let _depth = DepthGuard::enter()?;
+        if is_canonical() {
+            // Values are sorted, so it is sufficient to compare each of them
+            // with the previous one
+            let mut data = Vec::<T>::with_capacity(prealloc_capacity::<T>(len));
+            for index in 0..len {
+                let val = T::strict_decode(&mut d)
+                    .map_err(|err| err.within_item(index))?;
+                match data.last() {
+                    Some(last) if *last == val => {
+                        return Err(Error::RepeatedValue(format!("{:?}", val)))
+                    }
+                    Some(last) if *last > val => {
+                        return Err(Error::NonCanonical(
+                            any::type_name::<Self>(),
+                        ))
+                    }
+                    _ => data.push(val),
+                }
+            }
+            return Ok(data.into_iter().collect());
+        }
+        let mut data = HashSet::<T>::with_capacity(prealloc_capacity::<T>(len));
+        for index in 0..len {
+            let val = T::strict_decode(&mut d)
+                .map_err(|err| err.within_item(index))?;
             if data.contains(&val) {
                 return Err(Error::RepeatedValue(format!("{:?}", val)));
             } else {
@@ -580,33 +806,55 @@ where
 /// Strict decoding of a unique value collection represented by a rust
 /// `BTreeSet` type is performed alike `Vec` decoding with the only
 /// exception: if the repeated value met a [Error::RepeatedValue] is
-/// returned.
+/// returned. Values must be sorted; in canonical decoding mode unsorted values
+/// result in [`Error::NonCanonical`].
 impl<T> StrictDecode for BTreeSet<T>
 where
     T: StrictDecode + Eq + Ord + Debug,
 {
     fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
         let len = usize::strict_decode(&mut d)?;
-        let mut data = BTreeSet::<T>::new();
-        for _ in 0..len {
-            let val = T::strict_decode(&mut d)?;
-            if let Some(max) = data.iter().max() {
-                if max > &val {
-                    // TODO: Introduce new error type on 2.0 release
-                    return Err(Error::DataIntegrityError(format!(
-                        "encoded values are not deterministically ordered: \
-                         value `{:?}` should go before `{:?}`",
-                        val, max
-                    )));
+        decode_set_items(len, d)
+    }
+}
+
+/// Decodes `len` unique items of a set, which length prefix was already read
+/// by the caller, checking their order.
+pub(crate) fn decode_set_items<T, D>(
+    len: usize,
+    mut d: D,
+) -> Result<BTreeSet<T>, Error>
+where
+    T: StrictDecode + Eq + Ord + Debug,
+    D: io::Read,
+{
+    check_items(len)?;
+    let _depth = DepthGuard::enter()?;
+    let mut data = BTreeSet::<T>::new();
+    for index in 0..len {
+        let val =
+            T::strict_decode(&mut d).map_err(|err| err.within_item(index))?;
+        if let Some(max) = data.iter().next_back() {
+            if max > &val {
+                if is_canonical() {
+                    return Err(Error::NonCanonical(any::type_name::<
+                        BTreeSet<T>,
+                    >()));
                 }
+                // TODO: Introduce new error type on 2.0 release
+                return Err(Error::DataIntegrityError(format!(
+                    "encoded values are not deterministically ordered: value \
+                     `{:?}` should go before `{:?}`",
+                    val, max
+                )));
             }
-            if data.contains(&val) {
-                return Err(Error::RepeatedValue(format!("{:?}", val)));
-            }
-            data.insert(val);
         }
-        Ok(data)
+        if data.contains(&val) {
+            return Err(Error::RepeatedValue(format!("{:?}", val)));
+        }
+        data.insert(val);
     }
+    Ok(data)
 }
 
 /// LNP/BP library uses `HashMap<usize, T: StrictEncode>`s to encode
@@ -619,6 +867,7 @@ where
 /// converting into a fixed-order `Vec<T>` and serializing it according to
 /// the `Vec` strict encoding rules. This operation is internally
 /// performed via conversion into `BTreeMap<usize, T: StrictEncode>`.
+#[cfg(feature = "std")]
 impl<T> StrictEncode for HashMap<usize, T>
 where
     T: StrictEncode + Clone,
@@ -640,6 +889,7 @@ where
 /// converting into a fixed-order `Vec<T>` and serializing it according to
 /// the `Vec` strict encoding rules. This operation is internally
 /// performed via conversion into `BTreeMap<usize, T: StrictEncode>`.
+#[cfg(feature = "std")]
 impl<T> StrictDecode for HashMap<usize, T>
 where
     T: StrictDecode + Clone,
@@ -688,7 +938,8 @@ where
 ///
 /// Strict encoding of the `BTreeMap<usize, T>` type is performed
 /// by converting into a fixed-order `Vec<T>` and serializing it according
-/// to the `Vec` strict encoding rules.
+/// to the `Vec` strict encoding rules. Keys must be sorted; in canonical
+/// decoding mode unsorted keys result in [`Error::NonCanonical`].
 impl<K, V> StrictDecode for BTreeMap<K, V>
 where
     K: StrictDecode + Ord + Clone + Debug,
@@ -696,27 +947,50 @@ where
 {
     fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
         let len = usize::strict_decode(&mut d)?;
-        let mut map = BTreeMap::<K, V>::new();
-        for _ in 0..len {
-            let key = K::strict_decode(&mut d)?;
-            let val = V::strict_decode(&mut d)?;
-            if let Some(max) = map.keys().max() {
-                if max > &key {
-                    // TODO: Introduce new error type on 2.0 release
-                    return Err(Error::DataIntegrityError(format!(
-                        "encoded values are not deterministically ordered: \
-                         value `{:?}` should go before `{:?}`",
-                        key, max
-                    )));
+        decode_map_items(len, d)
+    }
+}
+
+/// Decodes `len` key-value pairs of a map, which length prefix was already
+/// read by the caller, checking the order of the keys.
+pub(crate) fn decode_map_items<K, V, D>(
+    len: usize,
+    mut d: D,
+) -> Result<BTreeMap<K, V>, Error>
+where
+    K: StrictDecode + Ord + Clone + Debug,
+    V: StrictDecode + Clone,
+    D: io::Read,
+{
+    check_items(len)?;
+    let _depth = DepthGuard::enter()?;
+    let mut map = BTreeMap::<K, V>::new();
+    for index in 0..len {
+        let key =
+            K::strict_decode(&mut d).map_err(|err| err.within_item(index))?;
+        let val =
+            V::strict_decode(&mut d).map_err(|err| err.within_item(index))?;
+        if let Some(max) = map.keys().next_back() {
+            if max > &key {
+                if is_canonical() {
+                    return Err(Error::NonCanonical(any::type_name::<
+                        BTreeMap<K, V>,
+                    >()));
                 }
+                // TODO: Introduce new error type on 2.0 release
+                return Err(Error::DataIntegrityError(format!(
+                    "encoded values are not deterministically ordered: value \
+                     `{:?}` should go before `{:?}`",
+                    key, max
+                )));
             }
-            if map.contains_key(&key) {
-                return Err(Error::RepeatedValue(format!("{:?}", key)));
-            }
-            map.insert(key, val);
         }
-        Ok(map)
+        if map.contains_key(&key) {
+            return Err(Error::RepeatedValue(format!("{:?}", key)));
+        }
+        map.insert(key, val);
     }
+    Ok(map)
 }
 
 /// Two-component tuples are encoded as they were fields in the parent
@@ -726,6 +1000,9 @@ where
     K: StrictEncode + Clone,
     V: StrictEncode + Clone,
 {
+    const FIXED_SIZE: Option<usize> =
+        crate::fixed_size_sum(&[K::FIXED_SIZE, V::FIXED_SIZE]);
+
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         Ok(self.0.strict_encode(&mut e)? + self.1.strict_encode(&mut e)?)
     }
@@ -745,10 +1022,143 @@ where
     }
 }
 
+impl<T> StrictType for Range<T>
+where
+    T: StrictType,
+{
+    fn strict_type() -> TypeDescr {
+        TypeDescr::Tuple(vec![T::strict_type(), T::strict_type()])
+    }
+}
+
+impl<T> StrictType for RangeInclusive<T>
+where
+    T: StrictType,
+{
+    fn strict_type() -> TypeDescr {
+        TypeDescr::Tuple(vec![T::strict_type(), T::strict_type()])
+    }
+}
+
+impl<T> StrictType for RangeFrom<T>
+where
+    T: StrictType,
+{
+    fn strict_type() -> TypeDescr { T::strict_type() }
+}
+
+impl<T> StrictType for RangeTo<T>
+where
+    T: StrictType,
+{
+    fn strict_type() -> TypeDescr { T::strict_type() }
+}
+
+impl<T> StrictType for RangeToInclusive<T>
+where
+    T: StrictType,
+{
+    fn strict_type() -> TypeDescr { T::strict_type() }
+}
+
+impl<T> StrictType for Option<T>
+where
+    T: StrictType,
+{
+    fn strict_type() -> TypeDescr { TypeDescr::option(T::strict_type()) }
+}
+
+impl<T> StrictType for [T]
+where
+    T: StrictType,
+{
+    fn strict_type() -> TypeDescr {
+        TypeDescr::list(LenPrefix::U16, T::strict_type())
+    }
+}
+
+impl<T> StrictType for LargeVec<T>
+where
+    T: StrictEncode + StrictDecode + StrictType,
+{
+    fn strict_type() -> TypeDescr {
+        TypeDescr::list(LenPrefix::U32, T::strict_type())
+    }
+}
+
+impl<T> StrictType for MediumVec<T>
+where
+    T: StrictEncode + StrictDecode + StrictType,
+{
+    fn strict_type() -> TypeDescr {
+        TypeDescr::list(LenPrefix::U24, T::strict_type())
+    }
+}
+
+impl<T> StrictType for Vec<T>
+where
+    T: StrictType,
+{
+    fn strict_type() -> TypeDescr {
+        TypeDescr::list(LenPrefix::U16, T::strict_type())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> StrictType for HashSet<T>
+where
+    T: StrictType,
+{
+    fn strict_type() -> TypeDescr {
+        TypeDescr::set(LenPrefix::U16, T::strict_type())
+    }
+}
+
+impl<T> StrictType for BTreeSet<T>
+where
+    T: StrictType,
+{
+    fn strict_type() -> TypeDescr {
+        TypeDescr::set(LenPrefix::U16, T::strict_type())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> StrictType for HashMap<usize, T>
+where
+    T: StrictType,
+{
+    fn strict_type() -> TypeDescr {
+        TypeDescr::map(LenPrefix::U16, usize::strict_type(), T::strict_type())
+    }
+}
+
+impl<K, V> StrictType for BTreeMap<K, V>
+where
+    K: StrictType,
+    V: StrictType,
+{
+    fn strict_type() -> TypeDescr {
+        TypeDescr::map(LenPrefix::U16, K::strict_type(), V::strict_type())
+    }
+}
+
+impl<K, V> StrictType for (K, V)
+where
+    K: StrictType,
+    V: StrictType,
+{
+    fn strict_type() -> TypeDescr {
+        TypeDescr::Tuple(vec![K::strict_type(), V::strict_type()])
+    }
+}
+
 #[cfg(test)]
 pub mod test {
     use super::*;
-    use crate::strict_serialize;
+    use crate::{
+        strict_deserialize, strict_deserialize_canonical, strict_serialize,
+    };
 
     /// Test for checking the following rule from LNPBP-5:
     ///
@@ -871,4 +1281,125 @@ pub mod test {
         assert_eq!(Vec::<u8>::strict_decode(s2).unwrap(), v2);
         assert_eq!(Vec::<u64>::strict_decode(s3).unwrap(), v3);
     }
+
+    #[test]
+    fn test_encoded_len() {
+        assert_eq!(<(u8, u64)>::FIXED_SIZE, Some(9));
+        assert_eq!(<Range<u32>>::FIXED_SIZE, Some(8));
+        assert_eq!(<(u8, String)>::FIXED_SIZE, None);
+        assert_eq!(Option::<u8>::FIXED_SIZE, None);
+        assert_eq!(Vec::<u8>::FIXED_SIZE, None);
+
+        let v1: Vec<u64> = vec![0, 13, 13];
+        let v2: Vec<String> = vec![s!("a"), s!(""), s!("abc")];
+        let v3: Vec<u8> = vec![0; 0x10000];
+        let v4: Vec<Vec<u8>> = vec![vec![], vec![0; 0x10000]];
+
+        assert_eq!(v1.strict_encoded_len(), Ok(2 + 3 * 8));
+        assert_eq!(
+            v2.strict_encoded_len(),
+            Ok(strict_serialize(&v2).unwrap().len())
+        );
+        assert_eq!(
+            v3.strict_encoded_len(),
+            Err(Error::ExceedMaxItems(0x10000))
+        );
+        assert_eq!(
+            v4.strict_encoded_len(),
+            Err(Error::ExceedMaxItems(0x10000))
+        );
+        assert_eq!(
+            Some(v1.clone()).strict_encoded_len(),
+            Ok(1 + v1.strict_encoded_len().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_huge_len_prefix() {
+        // Length prefixes are not trusted for memory allocation, so decoding
+        // fails on the end of data instead of trying to allocate gigabytes
+        let data = [0xFF, 0xFF, 0xFF, 0xFF, 0x01];
+        assert!(LargeVec::<[u8; 32]>::strict_decode(&data[..]).is_err());
+        assert!(MediumVec::<u64>::strict_decode(&data[..]).is_err());
+    }
+
+    #[test]
+    fn test_medium_large_vec_api() {
+        let mut vec = (1u8..4).collect::<MediumVec<_>>();
+        assert_eq!(vec.push(4), Ok(3));
+        vec.insert(0, 0).unwrap();
+        vec.extend([5, 6]);
+        assert_eq!(vec.pop(), Some(6));
+        assert_eq!(vec.remove(1), 1);
+        *vec.get_mut(0).unwrap() = 1;
+        assert_eq!(vec.into_inner(), vec![1, 2, 3, 4, 5]);
+
+        let mut vec = LargeVec::<u8>::default();
+        vec.extend(0..3);
+        assert_eq!(vec.len_u32(), 3);
+        assert_eq!(strict_serialize(&vec).unwrap(), [3, 0, 0, 0, 0, 1, 2]);
+
+        assert_eq!(
+            MediumVec::try_from(vec![0u8; 0x1000000]),
+            Err(Error::ExceedMaxItems(0x1000000))
+        );
+        let mut vec = MediumVec::try_from(vec![0u8; 0xFFFFFF]).unwrap();
+        assert_eq!(vec.push(0), Err(Error::ExceedMaxItems(0x1000000)));
+        assert_eq!(vec.insert(0, 0), Err(Error::ExceedMaxItems(0x1000000)));
+
+        vec.pop();
+        assert_eq!(
+            vec.try_extend([1, 2]),
+            Err(Error::ExceedMaxItems(0x1000000))
+        );
+        assert_eq!(vec.len(), 0xFFFFFF);
+        assert_eq!(vec.last(), Some(&1));
+    }
+
+    #[test]
+    fn test_canonical_sets() {
+        let unordered = [0x03, 0x00, 0x02, 0x01, 0x03];
+        let ordered = [0x03, 0x00, 0x01, 0x02, 0x03];
+
+        // Hash sets do not keep the order of the items, so any order is
+        // accepted unless canonical encoding is required
+        let set = strict_deserialize::<HashSet<u8>>(unordered).unwrap();
+        assert_eq!(set, bset![1u8, 2, 3].into_iter().collect());
+        assert_eq!(
+            strict_deserialize_canonical::<HashSet<u8>>(unordered).unwrap_err(),
+            Error::NonCanonical(any::type_name::<HashSet<u8>>())
+        );
+        assert_eq!(
+            strict_deserialize_canonical::<HashSet<u8>>(ordered).unwrap(),
+            set
+        );
+        assert_eq!(
+            strict_deserialize_canonical::<HashSet<u8>>([
+                0x02, 0x00, 0x01, 0x01
+            ])
+            .unwrap_err(),
+            Error::RepeatedValue(s!("1"))
+        );
+
+        assert!(matches!(
+            strict_deserialize::<BTreeSet<u8>>(unordered).unwrap_err(),
+            Error::DataIntegrityError(_)
+        ));
+        assert_eq!(
+            strict_deserialize_canonical::<BTreeSet<u8>>(unordered)
+                .unwrap_err(),
+            Error::NonCanonical(any::type_name::<BTreeSet<u8>>())
+        );
+        assert_eq!(
+            strict_deserialize_canonical::<BTreeSet<u8>>(ordered).unwrap(),
+            bset![1u8, 2, 3]
+        );
+
+        let unordered = [0x02, 0x00, 0x02, 0xA2, 0x01, 0xA1];
+        assert_eq!(
+            strict_deserialize_canonical::<BTreeMap<u8, u8>>(unordered)
+                .unwrap_err(),
+            Error::NonCanonical(any::type_name::<BTreeMap<u8, u8>>())
+        );
+    }
 }
@@ -0,0 +1,860 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Collections confined in the number of their items with compile-time
+//! bounds.
+//!
+//! [`Confined`] wraps a `Vec`, `String`, `BTreeSet` or `BTreeMap` and
+//! guarantees that it always contains from `MIN` to `MAX` items (bytes for
+//! strings). The guarantee is checked when the collection is constructed,
+//! mutated and decoded; violations result in [`Error::LenOutOfBounds`]. The
+//! width of the encoded length prefix is the narrowest one which is able to
+//! hold `MAX` (see [`LenPrefix::with_max_len`]), so
+//! `ConfinedVec<T, 1, 32>` is encoded like `Vec<T>`, while
+//! `ConfinedString<0, 0x10000>` uses `u24` length prefix.
+//!
+//! Collections which are bounded only by the width of their length prefix,
+//! like [`MediumString`], [`MediumSet`], [`LargeSet`], [`MediumMap`] and
+//! [`LargeMap`], are confined collections with `MIN` set to zero; they can be
+//! constructed empty and collected from iterators.
+//!
+//! ```
+//! use strict_encoding::{ConfinedVec, Error, StrictDecode, StrictEncode};
+//!
+//! let keys = ConfinedVec::<u8, 1, 3>::try_from(vec![1, 2]).unwrap();
+//! let data = keys.strict_serialize().unwrap();
+//! assert_eq!(data, vec![2, 0, 1, 2]);
+//!
+//! assert_eq!(
+//!     ConfinedVec::<u8, 1, 3>::try_from(vec![]),
+//!     Err(Error::LenOutOfBounds(0, 1, 3))
+//! );
+//! assert_eq!(
+//!     ConfinedVec::<u8, 3, 3>::strict_deserialize(data),
+//!     Err(Error::LenOutOfBounds(2, 3, 3))
+//! );
+//! ```
+
+use alloc::collections::{btree_map, BTreeMap, BTreeSet};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::iter::FromIterator;
+use core::ops::Deref;
+use core::slice;
+
+use crate::adapters::as_u32_len::LargeCollection;
+use crate::schema::{LenPrefix, StrictType, TypeDescr};
+use crate::value::{decode_len, encode_len};
+use crate::{io, Error, StrictDecode, StrictEncode};
+
+/// Collections which can be confined in the number of their items.
+pub trait Collection {
+    /// Returns number of the collection items; for strings returns their
+    /// length in bytes.
+    fn items_count(&self) -> usize;
+}
+
+impl<T> Collection for Vec<T> {
+    fn items_count(&self) -> usize { self.len() }
+}
+
+impl Collection for String {
+    fn items_count(&self) -> usize { self.len() }
+}
+
+impl<T> Collection for BTreeSet<T> {
+    fn items_count(&self) -> usize { self.len() }
+}
+
+impl<K, V> Collection for BTreeMap<K, V> {
+    fn items_count(&self) -> usize { self.len() }
+}
+
+/// Collection which always contains from `MIN` to `MAX` items. `MAX` must not
+/// be less than `MIN` and must not exceed `u32::MAX`; otherwise the code
+/// using the type fails to compile.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Confined<C, const MIN: usize, const MAX: usize>(C)
+where
+    C: Collection;
+
+/// `Vec` confined to have from `MIN` to `MAX` items
+pub type ConfinedVec<T, const MIN: usize, const MAX: usize> =
+    Confined<Vec<T>, MIN, MAX>;
+
+/// `String` confined to have from `MIN` to `MAX` bytes
+pub type ConfinedString<const MIN: usize, const MAX: usize> =
+    Confined<String, MIN, MAX>;
+
+/// `BTreeSet` confined to have from `MIN` to `MAX` items
+pub type ConfinedSet<T, const MIN: usize, const MAX: usize> =
+    Confined<BTreeSet<T>, MIN, MAX>;
+
+/// `BTreeMap` confined to have from `MIN` to `MAX` items
+pub type ConfinedMap<K, V, const MIN: usize, const MAX: usize> =
+    Confined<BTreeMap<K, V>, MIN, MAX>;
+
+const U24_MAX: usize = 0xFF_FFFF;
+const U32_MAX: usize = u32::MAX as usize;
+
+/// `String` which may have up to `u24::MAX` bytes, encoded with `u24` length
+/// prefix
+pub type MediumString = Confined<String, 0, U24_MAX>;
+
+/// `BTreeSet` which may have up to `u24::MAX` items, encoded with `u24`
+/// length prefix
+pub type MediumSet<T> = Confined<BTreeSet<T>, 0, U24_MAX>;
+
+/// `BTreeSet` which may have up to `u32::MAX` items, encoded with `u32`
+/// length prefix
+pub type LargeSet<T> = Confined<BTreeSet<T>, 0, U32_MAX>;
+
+/// `BTreeMap` which may have up to `u24::MAX` items, encoded with `u24`
+/// length prefix
+pub type MediumMap<K, V> = Confined<BTreeMap<K, V>, 0, U24_MAX>;
+
+/// `BTreeMap` which may have up to `u32::MAX` items, encoded with `u32`
+/// length prefix
+pub type LargeMap<K, V> = Confined<BTreeMap<K, V>, 0, U32_MAX>;
+
+impl<C, const MIN: usize, const MAX: usize> Confined<C, MIN, MAX>
+where
+    C: Collection,
+{
+    const BOUNDS: () = assert!(
+        MIN <= MAX && MAX <= u32::MAX as usize,
+        "confined collection bounds must satisfy MIN <= MAX <= u32::MAX"
+    );
+
+    /// Minimal number of the collection items.
+    pub const MIN_LEN: usize = MIN;
+
+    /// Maximal number of the collection items.
+    pub const MAX_LEN: usize = MAX;
+
+    fn check_len(len: usize) -> Result<(), Error> {
+        #[allow(clippy::let_unit_value)]
+        let _ = Self::BOUNDS;
+        if (MIN..=MAX).contains(&len) {
+            Ok(())
+        } else {
+            Err(Error::LenOutOfBounds(len, MIN, MAX))
+        }
+    }
+
+    /// Constructs confined collection from an iterator, failing with
+    /// [`Error::LenOutOfBounds`] if the number of the collected items is out
+    /// of the bounds.
+    pub fn try_from_iter<I>(iter: I) -> Result<Self, Error>
+    where
+        I: IntoIterator,
+        C: FromIterator<I::Item>,
+    {
+        let inner = iter.into_iter().collect::<C>();
+        Self::check_len(inner.items_count())?;
+        Ok(Confined(inner))
+    }
+
+    /// Returns the wrapped collection.
+    #[inline]
+    pub fn into_inner(self) -> C { self.0 }
+
+    /// Returns reference to the wrapped collection.
+    #[inline]
+    pub fn as_inner(&self) -> &C { &self.0 }
+}
+
+impl<C, const MAX: usize> Confined<C, 0, MAX>
+where
+    C: Collection + Default,
+{
+    /// Constructs empty collection.
+    pub fn new() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let _ = Self::BOUNDS;
+        Confined(C::default())
+    }
+}
+
+impl<C, const MAX: usize> Default for Confined<C, 0, MAX>
+where
+    C: Collection + Default,
+{
+    fn default() -> Self { Self::new() }
+}
+
+impl<C, T, const MAX: usize> FromIterator<T> for Confined<C, 0, MAX>
+where
+    C: Collection + FromIterator<T>,
+{
+    /// # Panics
+    ///
+    /// Panics if the iterator yields more than `MAX` items; use
+    /// [`Confined::try_from_iter`] for a fallible version.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::try_from_iter(iter)
+            .expect("number of items exceeds confined collection bounds")
+    }
+}
+
+macro_rules! impl_try_from {
+    ($ty:ty $(, $param:ident)*) => {
+        impl<$($param,)* const MIN: usize, const MAX: usize> TryFrom<$ty>
+            for Confined<$ty, MIN, MAX>
+        {
+            type Error = Error;
+
+            fn try_from(inner: $ty) -> Result<Self, Error> {
+                Self::check_len(inner.items_count())?;
+                Ok(Confined(inner))
+            }
+        }
+    };
+}
+
+impl_try_from!(Vec<T>, T);
+impl_try_from!(String);
+impl_try_from!(BTreeSet<T>, T);
+impl_try_from!(BTreeMap<K, V>, K, V);
+
+impl<C, const MIN: usize, const MAX: usize> Deref for Confined<C, MIN, MAX>
+where
+    C: Collection,
+{
+    type Target = C;
+
+    #[inline]
+    fn deref(&self) -> &C { &self.0 }
+}
+
+impl<C, const MIN: usize, const MAX: usize> AsRef<C> for Confined<C, MIN, MAX>
+where
+    C: Collection,
+{
+    #[inline]
+    fn as_ref(&self) -> &C { &self.0 }
+}
+
+impl<C, const MIN: usize, const MAX: usize> IntoIterator
+    for Confined<C, MIN, MAX>
+where
+    C: Collection + IntoIterator,
+{
+    type Item = C::Item;
+    type IntoIter = C::IntoIter;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.0.into_iter() }
+}
+
+impl<'a, C, const MIN: usize, const MAX: usize> IntoIterator
+    for &'a Confined<C, MIN, MAX>
+where
+    C: Collection,
+    &'a C: IntoIterator,
+{
+    type Item = <&'a C as IntoIterator>::Item;
+    type IntoIter = <&'a C as IntoIterator>::IntoIter;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { (&self.0).into_iter() }
+}
+
+impl<T, const MIN: usize, const MAX: usize> Confined<Vec<T>, MIN, MAX> {
+    /// Appends an item to the back of the vector, failing if the vector
+    /// already has `MAX` items.
+    pub fn push(&mut self, item: T) -> Result<(), Error> {
+        Self::check_len(self.0.len() + 1)?;
+        self.0.push(item);
+        Ok(())
+    }
+
+    /// Inserts an item at position `index`, failing if the vector already
+    /// has `MAX` items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, item: T) -> Result<(), Error> {
+        Self::check_len(self.0.len() + 1)?;
+        self.0.insert(index, item);
+        Ok(())
+    }
+
+    /// Removes the last item from the vector and returns it, or `None` if
+    /// the vector is empty. Fails if the vector has only `MIN` items.
+    pub fn pop(&mut self) -> Result<Option<T>, Error> {
+        if self.0.is_empty() {
+            return Ok(None);
+        }
+        Self::check_len(self.0.len() - 1)?;
+        Ok(self.0.pop())
+    }
+
+    /// Removes and returns the item at position `index`, failing if the
+    /// vector has only `MIN` items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Result<T, Error> {
+        Self::check_len(self.0.len().saturating_sub(1))?;
+        Ok(self.0.remove(index))
+    }
+
+    /// Returns mutable reference to the item at position `index`, if any.
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.0.get_mut(index)
+    }
+
+    /// Returns iterator allowing to modify each of the vector items.
+    #[inline]
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, T> { self.0.iter_mut() }
+
+    /// Appends items yielded by the iterator to the back of the vector, one
+    /// by one, failing once the vector would get more than `MAX` items. The
+    /// items appended before the failure are kept.
+    pub fn try_extend<I>(&mut self, iter: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iter {
+            self.push(item)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T, const MIN: usize, const MAX: usize> Extend<T>
+    for Confined<Vec<T>, MIN, MAX>
+{
+    /// # Panics
+    ///
+    /// Panics if the vector gets more than `MAX` items; use
+    /// [`Confined::try_extend`] for a fallible version.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.try_extend(iter)
+            .expect("number of items exceeds confined collection bounds")
+    }
+}
+
+impl<const MIN: usize, const MAX: usize> Confined<String, MIN, MAX> {
+    /// Appends a character to the end of the string, failing if the string
+    /// length in bytes would exceed `MAX`.
+    pub fn push(&mut self, ch: char) -> Result<(), Error> {
+        Self::check_len(self.0.len() + ch.len_utf8())?;
+        self.0.push(ch);
+        Ok(())
+    }
+
+    /// Appends a string slice to the end of the string, failing if the
+    /// string length in bytes would exceed `MAX`.
+    pub fn push_str(&mut self, s: &str) -> Result<(), Error> {
+        Self::check_len(self.0.len() + s.len())?;
+        self.0.push_str(s);
+        Ok(())
+    }
+
+    /// Inserts a character at byte position `index`, failing if the string
+    /// length in bytes would exceed `MAX`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is larger than the string length or does not lie on
+    /// a `char` boundary.
+    pub fn insert(&mut self, index: usize, ch: char) -> Result<(), Error> {
+        Self::check_len(self.0.len() + ch.len_utf8())?;
+        self.0.insert(index, ch);
+        Ok(())
+    }
+
+    /// Removes the last character from the string and returns it, or `None`
+    /// if the string is empty. Fails if the string length in bytes would get
+    /// below `MIN`.
+    pub fn pop(&mut self) -> Result<Option<char>, Error> {
+        let ch = match self.0.chars().last() {
+            None => return Ok(None),
+            Some(ch) => ch,
+        };
+        Self::check_len(self.0.len() - ch.len_utf8())?;
+        Ok(self.0.pop())
+    }
+
+    /// Removes and returns the character at byte position `index`, failing
+    /// if the string length in bytes would get below `MIN`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than the string length or does not lie
+    /// on a `char` boundary.
+    pub fn remove(&mut self, index: usize) -> Result<char, Error> {
+        let ch = self.0[index..]
+            .chars()
+            .next()
+            .expect("cannot remove a char from the end of a string");
+        Self::check_len(self.0.len() - ch.len_utf8())?;
+        Ok(self.0.remove(index))
+    }
+
+    /// Appends characters yielded by the iterator to the end of the string,
+    /// one by one, failing once the string length in bytes would exceed
+    /// `MAX`. The characters appended before the failure are kept.
+    pub fn try_extend<I>(&mut self, iter: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = char>,
+    {
+        for ch in iter {
+            self.push(ch)?;
+        }
+        Ok(())
+    }
+}
+
+impl<const MIN: usize, const MAX: usize> Extend<char>
+    for Confined<String, MIN, MAX>
+{
+    /// # Panics
+    ///
+    /// Panics if the string length in bytes gets above `MAX`; use
+    /// [`Confined::try_extend`] for a fallible version.
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        self.try_extend(iter)
+            .expect("number of items exceeds confined collection bounds")
+    }
+}
+
+impl<T, const MIN: usize, const MAX: usize> Confined<BTreeSet<T>, MIN, MAX>
+where
+    T: Ord,
+{
+    /// Adds an item to the set, returning whether it was not present in the
+    /// set before. Fails if a new item is added to the set already having
+    /// `MAX` items.
+    pub fn insert(&mut self, item: T) -> Result<bool, Error> {
+        if self.0.contains(&item) {
+            return Ok(false);
+        }
+        Self::check_len(self.0.len() + 1)?;
+        Ok(self.0.insert(item))
+    }
+
+    /// Removes an item from the set, returning whether it was present in the
+    /// set. Fails if the item is present and the set has only `MIN` items.
+    pub fn remove(&mut self, item: &T) -> Result<bool, Error> {
+        if !self.0.contains(item) {
+            return Ok(false);
+        }
+        Self::check_len(self.0.len() - 1)?;
+        Ok(self.0.remove(item))
+    }
+
+    /// Adds items yielded by the iterator to the set, one by one, failing
+    /// once the set would get more than `MAX` items. The items added before
+    /// the failure are kept.
+    pub fn try_extend<I>(&mut self, iter: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iter {
+            self.insert(item)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T, const MIN: usize, const MAX: usize> Extend<T>
+    for Confined<BTreeSet<T>, MIN, MAX>
+where
+    T: Ord,
+{
+    /// # Panics
+    ///
+    /// Panics if the set gets more than `MAX` items; use
+    /// [`Confined::try_extend`] for a fallible version.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.try_extend(iter)
+            .expect("number of items exceeds confined collection bounds")
+    }
+}
+
+impl<K, V, const MIN: usize, const MAX: usize>
+    Confined<BTreeMap<K, V>, MIN, MAX>
+where
+    K: Ord,
+{
+    /// Inserts a key-value pair into the map, returning the value previously
+    /// associated with the key, if any. Fails if a new key is added to the
+    /// map already having `MAX` items.
+    pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>, Error> {
+        if !self.0.contains_key(&key) {
+            Self::check_len(self.0.len() + 1)?;
+        }
+        Ok(self.0.insert(key, value))
+    }
+
+    /// Removes a key from the map, returning the value associated with it,
+    /// if any. Fails if the key is present and the map has only `MIN` items.
+    pub fn remove(&mut self, key: &K) -> Result<Option<V>, Error> {
+        if !self.0.contains_key(key) {
+            return Ok(None);
+        }
+        Self::check_len(self.0.len() - 1)?;
+        Ok(self.0.remove(key))
+    }
+
+    /// Returns mutable reference to the value associated with the key, if
+    /// any.
+    #[inline]
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> { self.0.get_mut(key) }
+
+    /// Returns iterator allowing to modify each of the map values.
+    #[inline]
+    pub fn values_mut(&mut self) -> btree_map::ValuesMut<'_, K, V> {
+        self.0.values_mut()
+    }
+
+    /// Returns iterator over the map items allowing to modify their values.
+    #[inline]
+    pub fn iter_mut(&mut self) -> btree_map::IterMut<'_, K, V> {
+        self.0.iter_mut()
+    }
+
+    /// Inserts key-value pairs yielded by the iterator into the map, one by
+    /// one, failing once the map would get more than `MAX` items. The pairs
+    /// inserted before the failure are kept.
+    pub fn try_extend<I>(&mut self, iter: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        for (key, value) in iter {
+            self.insert(key, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl<K, V, const MIN: usize, const MAX: usize> Extend<(K, V)>
+    for Confined<BTreeMap<K, V>, MIN, MAX>
+where
+    K: Ord,
+{
+    /// # Panics
+    ///
+    /// Panics if the map gets more than `MAX` items; use
+    /// [`Confined::try_extend`] for a fallible version.
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        self.try_extend(iter)
+            .expect("number of items exceeds confined collection bounds")
+    }
+}
+
+impl<C, const MIN: usize, const MAX: usize> StrictEncode
+    for Confined<C, MIN, MAX>
+where
+    C: LargeCollection,
+{
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        let prefix = LenPrefix::with_max_len(MAX);
+        Ok(encode_len(prefix, self.0.items_count(), &mut e)?
+            + self.0.encode_items(e)?)
+    }
+}
+
+impl<C, const MIN: usize, const MAX: usize> StrictDecode
+    for Confined<C, MIN, MAX>
+where
+    C: LargeCollection,
+{
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        let len = decode_len(LenPrefix::with_max_len(MAX), &mut d)?;
+        Self::check_len(len)?;
+        C::decode_items(len, d).map(Confined)
+    }
+}
+
+impl<C, const MIN: usize, const MAX: usize> StrictType for Confined<C, MIN, MAX>
+where
+    C: Collection + StrictType,
+{
+    fn strict_type() -> TypeDescr {
+        C::strict_type().with_len_prefix(LenPrefix::with_max_len(MAX))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::any;
+
+    use amplify::num::u24;
+
+    use super::*;
+    use crate::schema::Primitive;
+    use crate::{
+        strict_deserialize, strict_deserialize_canonical, strict_serialize,
+    };
+
+    #[test]
+    fn construction() {
+        let vec = ConfinedVec::<u8, 1, 2>::try_from(vec![1]).unwrap();
+        assert_eq!(vec.as_slice(), &[1]);
+        assert_eq!(
+            ConfinedVec::<u8, 1, 2>::try_from(vec![]),
+            Err(Error::LenOutOfBounds(0, 1, 2))
+        );
+        assert_eq!(
+            ConfinedVec::<u8, 1, 2>::try_from_iter(0..3),
+            Err(Error::LenOutOfBounds(3, 1, 2))
+        );
+        assert_eq!(
+            ConfinedString::<0, 4>::try_from(s!("abcde")),
+            Err(Error::LenOutOfBounds(5, 0, 4))
+        );
+        assert_eq!(
+            ConfinedSet::<u8, 1, 3>::try_from_iter([1, 1, 1, 1])
+                .unwrap()
+                .into_inner(),
+            bset! {1}
+        );
+        assert_eq!(
+            ConfinedMap::<u8, u8, 2, 3>::try_from(bmap! {1 => 1}),
+            Err(Error::LenOutOfBounds(1, 2, 3))
+        );
+    }
+
+    #[test]
+    fn vec_mutation() {
+        let mut vec = ConfinedVec::<u8, 1, 2>::try_from(vec![1]).unwrap();
+        assert_eq!(vec.pop(), Err(Error::LenOutOfBounds(0, 1, 2)));
+        vec.push(2).unwrap();
+        assert_eq!(vec.push(3), Err(Error::LenOutOfBounds(3, 1, 2)));
+        assert_eq!(vec.insert(0, 3), Err(Error::LenOutOfBounds(3, 1, 2)));
+        assert_eq!(vec.remove(0), Ok(1));
+        vec.iter_mut().for_each(|item| *item *= 2);
+        assert_eq!(vec.into_inner(), vec![4]);
+    }
+
+    #[test]
+    fn string_mutation() {
+        let mut s = ConfinedString::<1, 4>::try_from(s!("ab")).unwrap();
+        s.push('c').unwrap();
+        assert_eq!(s.push('ш'), Err(Error::LenOutOfBounds(5, 1, 4)));
+        assert_eq!(s.push_str("de"), Err(Error::LenOutOfBounds(5, 1, 4)));
+        assert_eq!(s.as_str(), "abc");
+    }
+
+    #[test]
+    fn set_mutation() {
+        let mut set = ConfinedSet::<u8, 1, 2>::try_from(bset! {1}).unwrap();
+        assert_eq!(set.remove(&2), Ok(false));
+        assert_eq!(set.remove(&1), Err(Error::LenOutOfBounds(0, 1, 2)));
+        assert_eq!(set.insert(2), Ok(true));
+        assert_eq!(set.insert(2), Ok(false));
+        assert_eq!(set.insert(3), Err(Error::LenOutOfBounds(3, 1, 2)));
+        assert_eq!((&set).into_iter().copied().collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn map_mutation() {
+        let mut map =
+            ConfinedMap::<u8, u8, 1, 1>::try_from(bmap! {1 => 1}).unwrap();
+        assert_eq!(map.insert(1, 2), Ok(Some(1)));
+        assert_eq!(map.insert(2, 2), Err(Error::LenOutOfBounds(2, 1, 1)));
+        assert_eq!(map.remove(&2), Ok(None));
+        assert_eq!(map.remove(&1), Err(Error::LenOutOfBounds(0, 1, 1)));
+        map.values_mut().for_each(|val| *val += 1);
+        *map.get_mut(&1).unwrap() += 1;
+        assert_eq!(map.into_inner(), bmap! {1 => 4});
+    }
+
+    #[test]
+    fn len_prefix() {
+        let vec = ConfinedVec::<u8, 0, 0xFFFF>::try_from(vec![7]).unwrap();
+        assert_eq!(strict_serialize(&vec).unwrap(), vec![1, 0, 7]);
+
+        let s = ConfinedString::<0, 0x10000>::try_from(s!("a")).unwrap();
+        let data = strict_serialize(&s).unwrap();
+        assert_eq!(data, vec![1, 0, 0, b'a']);
+        assert_eq!(
+            strict_deserialize::<ConfinedString<0, 0x10000>>(&data),
+            Ok(s)
+        );
+
+        let set =
+            ConfinedSet::<u8, 0, 0x1000000>::try_from(bset! {1, 2}).unwrap();
+        let data = strict_serialize(&set).unwrap();
+        assert_eq!(data, vec![2, 0, 0, 0, 1, 2]);
+        assert_eq!(strict_deserialize(&data), Ok(set));
+
+        let map =
+            ConfinedMap::<u8, bool, 1, 8>::try_from(bmap! {1 => true}).unwrap();
+        let data = strict_serialize(&map).unwrap();
+        assert_eq!(data, vec![1, 0, 1, 1]);
+        assert_eq!(strict_deserialize(&data), Ok(map));
+    }
+
+    #[test]
+    fn medium_large() {
+        let mut s = MediumString::new();
+        s.push_str("ab").unwrap();
+        s.insert(0, 'ш').unwrap();
+        s.extend(['c', 'd']);
+        assert_eq!(s.pop(), Ok(Some('d')));
+        assert_eq!(s.remove(0), Ok('ш'));
+        assert_eq!(s.as_str(), "abc");
+        let data = strict_serialize(&s).unwrap();
+        assert_eq!(data, b"\x03\x00\x00abc");
+        assert_eq!(strict_deserialize(&data), Ok(s));
+
+        let set = [3u8, 1, 2, 1].into_iter().collect::<MediumSet<_>>();
+        let data = strict_serialize(&set).unwrap();
+        assert_eq!(data, vec![3, 0, 0, 1, 2, 3]);
+        assert_eq!(strict_deserialize(&data), Ok(set));
+
+        let mut set = LargeSet::<u8>::default();
+        assert_eq!(set.insert(1), Ok(true));
+        assert_eq!(set.remove(&1), Ok(true));
+        assert_eq!(strict_serialize(&set).unwrap(), vec![0, 0, 0, 0]);
+
+        let mut map = MediumMap::<u8, u8>::try_from(bmap! {2 => 0}).unwrap();
+        assert_eq!(map.insert(1, 1), Ok(None));
+        map.iter_mut().for_each(|(key, val)| *val += *key);
+        assert_eq!(map.remove(&2), Ok(Some(2)));
+        let data = strict_serialize(&map).unwrap();
+        assert_eq!(data, vec![1, 0, 0, 1, 2]);
+        assert_eq!(strict_deserialize(&data), Ok(map));
+
+        let map = (0u8..3).map(|key| (key, ())).collect::<LargeMap<_, _>>();
+        let data = strict_serialize(&map).unwrap();
+        assert_eq!(data, vec![3, 0, 0, 0, 0, 1, 2]);
+        assert_eq!(strict_deserialize(&data), Ok(map));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "number of items exceeds confined collection bounds"
+    )]
+    fn extend_overflow() {
+        let mut vec = ConfinedVec::<u8, 0, 2>::new();
+        vec.extend([1, 2, 3]);
+    }
+
+    #[test]
+    fn try_extend() {
+        let mut vec = ConfinedVec::<u8, 0, 2>::new();
+        assert_eq!(
+            vec.try_extend([1, 2, 3]),
+            Err(Error::LenOutOfBounds(3, 0, 2))
+        );
+        assert_eq!(vec.as_slice(), &[1, 2]);
+
+        let mut s = ConfinedString::<0, 4>::try_from(s!("ab")).unwrap();
+        assert_eq!(
+            s.try_extend(['c', 'ш']),
+            Err(Error::LenOutOfBounds(5, 0, 4))
+        );
+        assert_eq!(s.as_str(), "abc");
+
+        let mut set = ConfinedSet::<u8, 0, 2>::new();
+        set.try_extend([1, 1, 2, 2]).unwrap();
+        assert_eq!(set.try_extend([3]), Err(Error::LenOutOfBounds(3, 0, 2)));
+        assert_eq!(set.into_inner(), bset! {1, 2});
+
+        let mut map = ConfinedMap::<u8, u8, 0, 1>::new();
+        assert_eq!(
+            map.try_extend([(1, 1), (1, 2), (2, 2)]),
+            Err(Error::LenOutOfBounds(2, 0, 1))
+        );
+        assert_eq!(map.into_inner(), bmap! {1 => 2});
+
+        // Bounds hold even if the panic of `Extend` is caught
+        let mut vec = ConfinedVec::<u8, 0, 2>::new();
+        let res =
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                vec.extend([1, 2, 3])
+            }));
+        assert!(res.is_err());
+        assert_eq!(vec.len(), 2);
+    }
+
+    #[test]
+    fn medium_large_decode_order() {
+        assert_eq!(
+            strict_deserialize::<MediumSet<u8>>(&[2, 0, 0, 1, 1]),
+            Err(Error::RepeatedValue(s!("1")))
+        );
+        assert!(matches!(
+            strict_deserialize::<LargeMap<u8, u8>>(&[2, 0, 0, 0, 2, 0, 1, 0]),
+            Err(Error::DataIntegrityError(_))
+        ));
+        assert_eq!(
+            strict_deserialize_canonical::<LargeSet<u8>>(&[2, 0, 0, 0, 2, 1]),
+            Err(Error::NonCanonical(any::type_name::<BTreeSet<u8>>()))
+        );
+    }
+
+    #[test]
+    fn decode_bounds() {
+        assert_eq!(
+            strict_deserialize::<ConfinedVec<u8, 2, 4>>(&[1, 0, 7]),
+            Err(Error::LenOutOfBounds(1, 2, 4))
+        );
+        // Bounds are checked before reading the items
+        assert_eq!(
+            strict_deserialize::<ConfinedVec<u8, 0, 4>>(&[5, 0]),
+            Err(Error::LenOutOfBounds(5, 0, 4))
+        );
+        assert_eq!(
+            strict_deserialize::<ConfinedString<0, 0x100000>>(&[
+                0xFF, 0xFF, 0xFF
+            ]),
+            Err(Error::LenOutOfBounds(
+                u24::MAX.as_u32() as usize,
+                0,
+                0x100000
+            ))
+        );
+        assert_eq!(
+            strict_deserialize::<ConfinedSet<u8, 0, 4>>(&[2, 0, 2, 1]),
+            Err(Error::DataIntegrityError(s!("encoded values are not \
+                                              deterministically ordered: \
+                                              value `1` should go before \
+                                              `2`")))
+        );
+    }
+
+    #[test]
+    fn strict_type() {
+        assert_eq!(
+            ConfinedVec::<u8, 1, 32>::strict_type(),
+            TypeDescr::list(
+                LenPrefix::U16,
+                TypeDescr::Primitive(Primitive::U8)
+            )
+        );
+        assert_eq!(
+            ConfinedString::<0, 0xFFFFFF>::strict_type(),
+            TypeDescr::Unicode(LenPrefix::U24)
+        );
+        assert_eq!(
+            ConfinedMap::<u8, u8, 0, 0x1000000>::strict_type(),
+            TypeDescr::map(
+                LenPrefix::U32,
+                TypeDescr::Primitive(Primitive::U8),
+                TypeDescr::Primitive(Primitive::U8)
+            )
+        );
+    }
+}
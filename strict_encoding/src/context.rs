@@ -0,0 +1,257 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Location of decoding failures inside nested data structures.
+//!
+//! Decoders derived with `#[derive(StrictDecode)]` wrap errors happening
+//! inside struct fields and enum variants into [`Error::Context`], which
+//! keeps the original error together with the path to the failed value,
+//! like `Consignment.transitions[12].assignments.<tlv 3>`. Collections add
+//! item indexes to such paths, and convenience deserialization functions
+//! working with byte slices add the offset at which decoding failed.
+//!
+//! Errors which happen outside of derived data structures are left as they
+//! are; use [`Error::root_cause`] to get the original error regardless of
+//! the context.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+use crate::Error;
+
+/// Segment of the path to a value inside a decoded data structure.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum PathSegment {
+    /// Named or numbered field of a structure or enum variant.
+    Field(&'static str),
+
+    /// Enum variant.
+    Variant(&'static str),
+
+    /// Item of a collection with the given index.
+    Index(usize),
+
+    /// TLV record with the given type.
+    Tlv(usize),
+}
+
+impl Display for PathSegment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PathSegment::Field(name) => write!(f, ".{}", name),
+            PathSegment::Variant(name) => write!(f, "::{}", name),
+            PathSegment::Index(index) => write!(f, "[{}]", index),
+            PathSegment::Tlv(type_no) => write!(f, ".<tlv {}>", type_no),
+        }
+    }
+}
+
+/// Decoding error together with the information on where it has happened.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ErrorContext {
+    /// Original decoding error; never an [`Error::Context`] itself.
+    pub error: Error,
+
+    /// Name of the outermost derived type which was decoded.
+    pub type_name: Option<&'static str>,
+
+    /// Path to the failed value, starting from the outermost type.
+    pub path: Vec<PathSegment>,
+
+    /// Number of bytes consumed from the input by the moment the decoding
+    /// has failed, if known.
+    pub offset: Option<usize>,
+}
+
+impl Display for ErrorContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("decoding of ")?;
+        if let Some(name) = self.type_name {
+            f.write_str(name)?;
+        }
+        for segment in &self.path {
+            Display::fmt(segment, f)?;
+        }
+        if let Some(offset) = self.offset {
+            write!(f, " at byte offset {}", offset)?;
+        }
+        write!(f, " failed: {}", self.error)
+    }
+}
+
+impl Error {
+    /// Returns context of the error, if it was attached during decoding.
+    #[inline]
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Error::Context(context) => Some(context),
+            _ => None,
+        }
+    }
+
+    /// Returns original error, without the context information.
+    #[inline]
+    pub fn root_cause(&self) -> &Error {
+        match self {
+            Error::Context(context) => &context.error,
+            err => err,
+        }
+    }
+
+    /// Converts the error into the original one, dropping the context
+    /// information.
+    #[inline]
+    pub fn into_root_cause(self) -> Error {
+        match self {
+            Error::Context(context) => context.error,
+            err => err,
+        }
+    }
+
+    fn with_segment(self, segment: PathSegment) -> Error {
+        let mut context = match self {
+            Error::Context(context) => context,
+            error => Box::new(ErrorContext {
+                error,
+                type_name: None,
+                path: vec![],
+                offset: None,
+            }),
+        };
+        context.path.insert(0, segment);
+        Error::Context(context)
+    }
+
+    /// Adds field with the given name to the beginning of the error path.
+    #[inline]
+    pub fn in_field(self, name: &'static str) -> Error {
+        self.with_segment(PathSegment::Field(name))
+    }
+
+    /// Adds enum variant with the given name to the beginning of the error
+    /// path.
+    #[inline]
+    pub fn in_variant(self, name: &'static str) -> Error {
+        self.with_segment(PathSegment::Variant(name))
+    }
+
+    /// Adds collection item index to the beginning of the error path.
+    #[inline]
+    pub fn at_index(self, index: usize) -> Error {
+        self.with_segment(PathSegment::Index(index))
+    }
+
+    /// Adds TLV record type to the beginning of the error path.
+    #[inline]
+    pub fn in_tlv(self, type_no: usize) -> Error {
+        self.with_segment(PathSegment::Tlv(type_no))
+    }
+
+    /// Adds collection item index to the error path if the error already
+    /// has a context, i.e. it has happened inside a derived data structure.
+    /// Other errors are returned unchanged.
+    #[inline]
+    pub fn within_item(self, index: usize) -> Error {
+        match self {
+            Error::Context(_) => self.at_index(index),
+            err => err,
+        }
+    }
+
+    /// Sets the name of the outermost type for the errors having context;
+    /// other errors are returned unchanged.
+    #[inline]
+    pub fn in_type(self, name: &'static str) -> Error {
+        match self {
+            Error::Context(mut context) => {
+                context.type_name = Some(name);
+                Error::Context(context)
+            }
+            err => err,
+        }
+    }
+
+    /// Sets the byte offset for the errors having context, unless it was
+    /// already set; other errors are returned unchanged.
+    #[inline]
+    pub fn at_offset(self, offset: usize) -> Error {
+        match self {
+            Error::Context(mut context) => {
+                context.offset.get_or_insert(offset);
+                Error::Context(context)
+            }
+            err => err,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn path_display() {
+        let err = Error::EnumValueNotKnown("Assignment", 7)
+            .in_tlv(3)
+            .in_field("assignments")
+            .in_type("Transition")
+            .at_index(12)
+            .in_field("transitions")
+            .in_type("Consignment")
+            .at_offset(1234);
+        assert_eq!(
+            err.to_string(),
+            "decoding of Consignment.transitions[12].assignments.<tlv 3> at \
+             byte offset 1234 failed: An unsupported value `Assignment` for \
+             enum `Assignment` encountered during decode operation"
+        );
+        let context = err.context().unwrap();
+        assert_eq!(context.path, vec![
+            PathSegment::Field("transitions"),
+            PathSegment::Index(12),
+            PathSegment::Field("assignments"),
+            PathSegment::Tlv(3),
+        ]);
+        assert_eq!(
+            err.root_cause(),
+            &Error::EnumValueNotKnown("Assignment", 7)
+        );
+        assert_eq!(
+            err.into_root_cause(),
+            Error::EnumValueNotKnown("Assignment", 7)
+        );
+    }
+
+    #[test]
+    fn plain_errors() {
+        let err = Error::DataNotEntirelyConsumed
+            .within_item(2)
+            .in_type("Some")
+            .at_offset(4);
+        assert_eq!(err, Error::DataNotEntirelyConsumed);
+        assert_eq!(err.root_cause(), &Error::DataNotEntirelyConsumed);
+        assert_eq!(err.context(), None);
+
+        let err = Error::DataNotEntirelyConsumed
+            .in_variant("Variant")
+            .at_offset(4)
+            .at_offset(8);
+        assert_eq!(
+            err.to_string(),
+            "decoding of ::Variant at byte offset 4 failed: Data were not \
+             consumed entirely during strict decoding procedure"
+        );
+    }
+}
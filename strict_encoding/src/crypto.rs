@@ -21,6 +21,8 @@ use crate::{Error, StrictDecode, StrictEncode};
 
 #[cfg(feature = "ed25519-dalek")]
 impl StrictEncode for ed25519_dalek::PublicKey {
+    const FIXED_SIZE: Option<usize> = Some(ed25519_dalek::PUBLIC_KEY_LENGTH);
+
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         Ok(e.write(&self.as_bytes()[..])?)
     }
@@ -41,6 +43,8 @@ impl StrictDecode for ed25519_dalek::PublicKey {
 
 #[cfg(feature = "ed25519-dalek")]
 impl StrictEncode for ed25519_dalek::Signature {
+    const FIXED_SIZE: Option<usize> = Some(ed25519_dalek::SIGNATURE_LENGTH);
+
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         Ok(e.write(self.as_bytes())?)
     }
@@ -0,0 +1,319 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Input/output traits used by strict encoding.
+//!
+//! With `std` feature (used by default) the module re-exports types from
+//! [`std::io`], so any standard reader or writer may be used with strict
+//! encoding. Without `std` feature the module provides minimal [`Read`] and
+//! [`Write`] traits with the same method signatures, implemented for byte
+//! slices, `Vec<u8>` and `bitcoin_hashes` hash engines.
+//!
+//! [`LenCounter`] writer, which discards the data and counts their length, is
+//! available in both modes.
+
+#[cfg(feature = "std")]
+pub use std::io::{Error, ErrorKind, Read, Result, Write};
+
+#[cfg(feature = "std")]
+pub use amplify::IoError;
+
+#[cfg(not(feature = "std"))]
+pub use self::no_std::*;
+
+/// Writer discarding all the data and counting their length, used for
+/// computing [`crate::StrictEncode::strict_encoded_len`].
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct LenCounter(usize);
+
+impl LenCounter {
+    /// Returns number of bytes written so far.
+    #[inline]
+    pub fn len(&self) -> usize { self.0 }
+
+    /// Detects whether no bytes were written so far.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.0 == 0 }
+}
+
+impl Write for LenCounter {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<()> { Ok(()) }
+}
+
+#[cfg(not(feature = "std"))]
+mod no_std {
+    use alloc::vec::Vec;
+    use core::fmt::{self, Debug, Display, Formatter};
+
+    /// A list specifying general categories of I/O error, mirroring the
+    /// subset of `std::io::ErrorKind` used by strict encoding.
+    #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+    #[non_exhaustive]
+    pub enum ErrorKind {
+        /// An operation could not be completed, because it failed to
+        /// allocate enough memory.
+        OutOfMemory,
+
+        /// An operation could not be completed, because an "end of file" was
+        /// reached prematurely.
+        UnexpectedEof,
+
+        /// An error returned when an operation could not be completed
+        /// because a call to `write` returned `Ok(0)`.
+        WriteZero,
+
+        /// Data not valid for the operation were encountered.
+        InvalidData,
+
+        /// A parameter was incorrect.
+        InvalidInput,
+
+        /// This operation was interrupted and can typically be retried.
+        Interrupted,
+
+        /// A custom error that does not fall under any other I/O error kind.
+        Other,
+    }
+
+    impl ErrorKind {
+        fn as_str(self) -> &'static str {
+            match self {
+                ErrorKind::OutOfMemory => "out of memory",
+                ErrorKind::UnexpectedEof => "unexpected end of file",
+                ErrorKind::WriteZero => "write zero",
+                ErrorKind::InvalidData => "invalid data",
+                ErrorKind::InvalidInput => "invalid input parameter",
+                ErrorKind::Interrupted => "operation interrupted",
+                ErrorKind::Other => "other error",
+            }
+        }
+    }
+
+    impl Display for ErrorKind {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            f.write_str(self.as_str())
+        }
+    }
+
+    /// The error type for I/O operations of the [`Read`] and [`Write`]
+    /// traits.
+    #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+    pub struct Error {
+        kind: ErrorKind,
+        msg: Option<&'static str>,
+    }
+
+    impl Error {
+        /// Creates a new I/O error from a known kind of error and a static
+        /// error description.
+        pub fn new(kind: ErrorKind, msg: &'static str) -> Error {
+            Error {
+                kind,
+                msg: Some(msg),
+            }
+        }
+
+        /// Returns the corresponding [`ErrorKind`] for this error.
+        pub fn kind(&self) -> ErrorKind { self.kind }
+    }
+
+    impl From<ErrorKind> for Error {
+        fn from(kind: ErrorKind) -> Self { Error { kind, msg: None } }
+    }
+
+    impl Display for Error {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            match self.msg {
+                Some(msg) => f.write_str(msg),
+                None => Display::fmt(&self.kind, f),
+            }
+        }
+    }
+
+    /// A specialized `Result` type for I/O operations.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// Copyable & cloneable I/O error type represented by the error kind,
+    /// matching `amplify::IoError` available with `std` feature.
+    #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+    pub struct IoError(ErrorKind);
+
+    impl From<Error> for IoError {
+        fn from(err: Error) -> Self { IoError(err.kind()) }
+    }
+
+    impl From<ErrorKind> for IoError {
+        fn from(kind: ErrorKind) -> Self { IoError(kind) }
+    }
+
+    impl From<IoError> for Error {
+        fn from(err: IoError) -> Self { Error::from(err.0) }
+    }
+
+    impl Display for IoError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            Display::fmt(&self.0, f)
+        }
+    }
+
+    /// Minimal version of `std::io::Read` trait for no-std environments.
+    pub trait Read {
+        /// Pull some bytes from this source into the specified buffer,
+        /// returning how many bytes were read.
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
+
+        /// Read the exact number of bytes required to fill `buf`.
+        fn read_exact(&mut self, mut buf: &mut [u8]) -> Result<()> {
+            while !buf.is_empty() {
+                match self.read(buf) {
+                    Ok(0) => break,
+                    Ok(n) => buf = &mut buf[n..],
+                    Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            if !buf.is_empty() {
+                Err(ErrorKind::UnexpectedEof.into())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    /// Minimal version of `std::io::Write` trait for no-std environments.
+    pub trait Write {
+        /// Write a buffer into this writer, returning how many bytes were
+        /// written.
+        fn write(&mut self, buf: &[u8]) -> Result<usize>;
+
+        /// Flush this output stream, ensuring that all intermediately
+        /// buffered contents reach their destination.
+        fn flush(&mut self) -> Result<()>;
+
+        /// Attempts to write an entire buffer into this writer.
+        fn write_all(&mut self, mut buf: &[u8]) -> Result<()> {
+            while !buf.is_empty() {
+                match self.write(buf) {
+                    Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                    Ok(n) => buf = &buf[n..],
+                    Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl<R: Read + ?Sized> Read for &mut R {
+        #[inline]
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            (**self).read(buf)
+        }
+
+        #[inline]
+        fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+            (**self).read_exact(buf)
+        }
+    }
+
+    impl Read for &[u8] {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let amt = buf.len().min(self.len());
+            let (a, b) = self.split_at(amt);
+            buf[..amt].copy_from_slice(a);
+            *self = b;
+            Ok(amt)
+        }
+    }
+
+    impl<W: Write + ?Sized> Write for &mut W {
+        #[inline]
+        fn write(&mut self, buf: &[u8]) -> Result<usize> { (**self).write(buf) }
+
+        #[inline]
+        fn flush(&mut self) -> Result<()> { (**self).flush() }
+
+        #[inline]
+        fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+            (**self).write_all(buf)
+        }
+    }
+
+    impl Write for Vec<u8> {
+        #[inline]
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            self.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        #[inline]
+        fn flush(&mut self) -> Result<()> { Ok(()) }
+    }
+
+    impl Write for &mut [u8] {
+        fn write(&mut self, data: &[u8]) -> Result<usize> {
+            let amt = data.len().min(self.len());
+            let (a, b) = core::mem::take(self).split_at_mut(amt);
+            a.copy_from_slice(&data[..amt]);
+            *self = b;
+            Ok(amt)
+        }
+
+        #[inline]
+        fn flush(&mut self) -> Result<()> { Ok(()) }
+    }
+
+    macro_rules! impl_write_engine {
+        ($($engine:ty),+ $(,)?) => {
+            $(
+                impl Write for $engine {
+                    #[inline]
+                    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+                        bitcoin_hashes::HashEngine::input(self, buf);
+                        Ok(buf.len())
+                    }
+
+                    #[inline]
+                    fn flush(&mut self) -> Result<()> { Ok(()) }
+                }
+            )+
+        };
+    }
+
+    impl_write_engine!(
+        bitcoin_hashes::sha1::HashEngine,
+        bitcoin_hashes::sha256::HashEngine,
+        bitcoin_hashes::sha512::HashEngine,
+        bitcoin_hashes::ripemd160::HashEngine,
+        bitcoin_hashes::siphash24::HashEngine,
+    );
+
+    impl<T: bitcoin_hashes::Hash> Write for bitcoin_hashes::hmac::HmacEngine<T> {
+        #[inline]
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            bitcoin_hashes::HashEngine::input(self, buf);
+            Ok(buf.len())
+        }
+
+        #[inline]
+        fn flush(&mut self) -> Result<()> { Ok(()) }
+    }
+}
@@ -15,6 +15,7 @@
 // Coding conventions
 #![recursion_limit = "256"]
 #![deny(dead_code, missing_docs, warnings)]
+#![cfg_attr(not(feature = "std"), no_std)]
 
 //! Library implementing **strict encoding** standard, defined by
 //! [LNPBP-7](https://github.com/LNP-BP/LNPBPs/blob/master/lnpbp-0007.md).
@@ -40,13 +41,32 @@
 //! defined by rust standard library and frequently used crates; the latter
 //! increases the number of dependencies and thus can be controlled with
 //! feature flags:
+//! - `std` (used by default): encoding with standard library I/O types, file
+//!   helpers and hash-based collections. Without this feature the crate is
+//!   `no_std` and requires only `alloc`, working with the minimal [`io::Read`]
+//!   and [`io::Write`] traits defined in the [`io`] module. All other features
+//!   listed below require `std`.
 //! - `chrono` (used by default): date & time types from `chrono` crate
+//! - `tor` (used by default): Tor onion v3 addresses, [`net::OnionAddrV3`],
+//!   which checksums require SHA3 hashing from `tiny-keccak` crate. This is the
+//!   only feature which doesn't require `std`.
 //! - `miniscript`: types defined in bitcoin Miniscript
 //! - `crypto`: non-bitcoin cryptographic primitives, which include Ed25519
 //!   curve, X25519 signatures from `ed25519-dalek` library and pedersen
 //!   commitments + bulletproofs from `lnpbp_secp256k1zkp` library. Encodings
 //!   for other cryptography-related types, such as Secp256k1 and hashes, are
 //!   always included as a part of the library - see NB below.
+//! - `async`: asynchronous versions of the encoding traits,
+//!   [`StrictEncodeAsync`] and [`StrictDecodeAsync`], working with `tokio`
+//!   streams.
+//! - `serde`: serde serialization of schema and value types, plus [`serde`]
+//!   module bridging serde data model to strict encoding layout.
+//!
+//! Layout of the strictly encoded data can be described in a machine-readable
+//! form with [`StrictType`] trait, which can be derived with
+//! `#[derive(StrictType)]`; see [`schema`] module for the details. Such
+//! descriptions can be used to decode data without knowing its rust type into
+//! a generic [`StrictValue`] tree, see [`value`] module.
 //!
 //! NB: this crate requires `bitcoin` as an upstream dependency since many of
 //!     strict-encoded formats are standardized as using *bitcoin consensus
@@ -55,8 +75,32 @@
 #[cfg(feature = "derive")]
 pub extern crate strict_encoding_derive as derive;
 #[cfg(feature = "derive")]
-pub use derive::{NetworkDecode, NetworkEncode, StrictDecode, StrictEncode};
+pub use derive::{
+    NetworkDecode, NetworkDecodeAsync, NetworkEncode, NetworkEncodeAsync,
+    NetworkType, StrictDecode, StrictDecodeAsync, StrictDecodeBorrowed,
+    StrictEncode, StrictEncodeAsync, StrictType,
+};
+
+#[cfg(all(
+    not(feature = "std"),
+    any(
+        feature = "bitcoin",
+        feature = "miniscript",
+        feature = "monero",
+        feature = "crypto",
+        feature = "chrono",
+        feature = "serde",
+        feature = "float",
+        feature = "async"
+    )
+))]
+compile_error!(
+    "features `bitcoin`, `miniscript`, `monero`, `crypto`, `chrono`, `serde`, \
+     `float` and `async` require `std` feature"
+);
 
+#[macro_use]
+extern crate alloc;
 #[macro_use]
 extern crate amplify;
 #[cfg(test)]
@@ -64,20 +108,29 @@ extern crate amplify;
 extern crate strict_encoding_test;
 #[cfg(feature = "serde")]
 #[macro_use]
-extern crate serde;
+extern crate serde as serde_crate;
 #[cfg(feature = "lnpbp_secp256k1zkp")]
 extern crate lnpbp_secp256k1zkp as secp256k1zkp;
 
 #[macro_use]
 mod macros;
 
+pub mod adapters;
 mod amplify_types;
+pub mod armor;
+#[cfg(feature = "async")]
+pub mod asynchronous;
 #[cfg(feature = "bitcoin")]
 mod bitcoin;
 mod bitcoin_hashes;
+pub mod borrowed;
 mod collections;
+pub mod confined;
+pub mod context;
 #[cfg(feature = "crypto")]
 mod crypto;
+pub mod io;
+pub mod limits;
 #[cfg(feature = "miniscript")]
 mod miniscript;
 #[cfg(feature = "monero")]
@@ -85,23 +138,56 @@ mod monero;
 pub mod net;
 mod pointers;
 mod primitives;
+pub mod schema;
+#[cfg(feature = "serde")]
+pub mod serde;
 mod slice32;
 pub mod strategies;
-
+pub mod stream;
+pub mod tlv;
+pub mod value;
+pub mod version;
+
+use alloc::boxed::Box;
+use alloc::string::{FromUtf8Error, String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+#[cfg(feature = "std")]
+use std::fs;
+#[cfg(feature = "std")]
 use std::io::Seek;
-use std::ops::Range;
+#[cfg(feature = "std")]
 use std::path::Path;
-use std::string::FromUtf8Error;
-use std::{fmt, fs, io};
 
 /// Re-exporting extended read and write functions from bitcoin consensus
 /// module so others may use semantic convenience
 /// `strict_encode::ReadExt`
 #[cfg(feature = "bitcoin")]
 pub use ::bitcoin::consensus::encode::{ReadExt, WriteExt};
-use amplify::IoError;
+pub use armor::{ArmorError, ArmorRegistry, StrictArmor};
+#[cfg(feature = "async")]
+#[doc(hidden)]
+pub use asynchronous::async_trait;
+#[cfg(feature = "async")]
+pub use asynchronous::{
+    AsyncRead, AsyncWrite, LimitedAsyncReader, StrictDecodeAsync,
+    StrictEncodeAsync, StrictMigrateAsync,
+};
+pub use borrowed::{BorrowedIter, StrictDecodeBorrowed};
 pub use collections::{LargeVec, MediumVec};
+pub use confined::{
+    Confined, ConfinedMap, ConfinedSet, ConfinedString, ConfinedVec, LargeMap,
+    LargeSet, MediumMap, MediumSet, MediumString,
+};
+pub use context::{ErrorContext, PathSegment};
+pub use limits::{DecodeLimits, LimitedReader};
+pub use schema::StrictType;
 pub use strategies::Strategy;
+pub use stream::{StreamReader, StreamWriter};
+pub use tlv::{ApplyTlvStream, BigSize, TlvError, TlvStream, ToTlvStream};
+pub use value::StrictValue;
+pub use version::StrictMigrate;
 
 /// Binary encoding according to the strict rules that usually apply to
 /// consensus-critical data structures. May be used for network communications;
@@ -112,11 +198,33 @@ pub use strategies::Strategy;
 /// utilize `CommitVerify`, `TryCommitVerify` and `EmbedCommitVerify` traits  
 /// from `commit_verify` module.
 pub trait StrictEncode {
-    /// Encode with the given [`std::io::Write`] instance; must return result
+    /// Length of the strict-encoded data, if it is the same for all values of
+    /// the type, or `None` for the types with variable-length encoding.
+    const FIXED_SIZE: Option<usize> = None;
+
+    /// Encode with the given [`io::Write`] instance; must return result
     /// with either amount of bytes encoded – or implementation-specific
     /// error type.
     fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error>;
 
+    /// Computes length of the strict-encoded data without serializing them
+    /// into a buffer. Fails with the same errors as
+    /// [`StrictEncode::strict_encode`], like [`Error::ExceedMaxItems`].
+    ///
+    /// Default implementation returns [`StrictEncode::FIXED_SIZE`], if
+    /// present, or counts bytes produced by [`StrictEncode::strict_encode`]
+    /// with [`io::LenCounter`].
+    fn strict_encoded_len(&self) -> Result<usize, Error> {
+        match Self::FIXED_SIZE {
+            Some(len) => Ok(len),
+            None => {
+                let mut counter = io::LenCounter::default();
+                self.strict_encode(&mut counter)?;
+                Ok(counter.len())
+            }
+        }
+    }
+
     /// Serializes data as a byte array using [`StrictEncode::strict_encode`]
     /// function
     fn strict_serialize(&self) -> Result<Vec<u8>, Error> {
@@ -128,6 +236,7 @@ pub trait StrictEncode {
     /// Saves data to a file at a given `path`. If the file does not exists,
     /// attempts to create the file. If the file already exists, it gets
     /// truncated.
+    #[cfg(feature = "std")]
     fn strict_file_save(&self, path: impl AsRef<Path>) -> Result<usize, Error> {
         let file = fs::File::create(path)?;
         self.strict_encode(file)
@@ -143,25 +252,63 @@ pub trait StrictEncode {
 /// commitment procedure for the revealed message and verify it against the
 /// provided commitment.
 pub trait StrictDecode: Sized {
-    /// Decode with the given [`std::io::Read`] instance; must either
+    /// Decode with the given [`io::Read`] instance; must either
     /// construct an instance or return implementation-specific error type.
     fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error>;
 
     /// Tries to deserialize byte array into the current type using
     /// [`StrictDecode::strict_decode`]. If there are some data remains in the
     /// buffer once deserialization is completed, fails with
-    /// [`Error::DataNotEntirelyConsumed`]. Use `io::Cursor` over the buffer and
-    /// [`StrictDecode::strict_decode`] to avoid such failures.
+    /// [`Error::DataNotEntirelyConsumed`]. Use [`StrictDecode::strict_decode`]
+    /// over the byte slice to avoid such failures.
     fn strict_deserialize(data: impl AsRef<[u8]>) -> Result<Self, Error> {
-        Self::strict_decode(data.as_ref())
+        let data = data.as_ref();
+        let mut decoder = data;
+        Self::strict_decode(&mut decoder)
+            .map_err(|err| err.at_offset(data.len() - decoder.len()))
+    }
+
+    /// Tries to deserialize byte array into the current type using
+    /// [`StrictDecode::strict_decode`] under the provided decoding `limits`.
+    /// Fails with [`Error::DataNotEntirelyConsumed`] if there are some data
+    /// remaining in the buffer once deserialization is completed.
+    fn strict_deserialize_with_limits(
+        data: impl AsRef<[u8]>,
+        limits: DecodeLimits,
+    ) -> Result<Self, Error> {
+        strict_deserialize_with_limits(data, limits)
     }
 
     /// Reads data from file at `path` and reconstructs object from it. Fails
     /// with [`Error::DataNotEntirelyConsumed`] if file contains remaining
     /// data after the object reconstruction.
+    #[cfg(feature = "std")]
     fn strict_file_load(path: impl AsRef<Path>) -> Result<Self, Error> {
         let mut file = fs::File::open(path)?;
-        let obj = Self::strict_decode(&mut file)?;
+        let obj = Self::strict_decode(&mut file).map_err(|err| {
+            match file.stream_position() {
+                Ok(pos) => err.at_offset(pos as usize),
+                Err(_) => err,
+            }
+        })?;
+        if file.stream_position()? != file.metadata()?.len() {
+            Err(Error::DataNotEntirelyConsumed)
+        } else {
+            Ok(obj)
+        }
+    }
+
+    /// Reads data from file at `path` and reconstructs object from it under
+    /// the provided decoding `limits`. Fails with
+    /// [`Error::DataNotEntirelyConsumed`] if file contains remaining data
+    /// after the object reconstruction.
+    #[cfg(feature = "std")]
+    fn strict_file_load_with_limits(
+        path: impl AsRef<Path>,
+        limits: DecodeLimits,
+    ) -> Result<Self, Error> {
+        let mut file = fs::File::open(path)?;
+        let obj = LimitedReader::new(&mut file, limits).decode()?;
         if file.stream_position()? != file.metadata()?.len() {
             Err(Error::DataNotEntirelyConsumed)
         } else {
@@ -176,9 +323,9 @@ pub fn strict_serialize<T>(data: &T) -> Result<Vec<u8>, Error>
 where
     T: StrictEncode,
 {
-    let mut encoder = io::Cursor::new(vec![]);
+    let mut encoder = vec![];
     data.strict_encode(&mut encoder)?;
-    Ok(encoder.into_inner())
+    Ok(encoder)
 }
 
 /// Convenience method for strict decoding of data structures implementing
@@ -187,30 +334,121 @@ pub fn strict_deserialize<T>(data: impl AsRef<[u8]>) -> Result<T, Error>
 where
     T: StrictDecode,
 {
-    let mut decoder = io::Cursor::new(data.as_ref());
-    let rv = T::strict_decode(&mut decoder)?;
-    let consumed = decoder.position() as usize;
+    let data = data.as_ref();
+    let mut decoder = data;
+    let rv = T::strict_decode(&mut decoder)
+        .map_err(|err| err.at_offset(data.len() - decoder.len()))?;
+
+    // Fail if data are not consumed entirely.
+    if decoder.is_empty() {
+        Ok(rv)
+    } else {
+        Err(Error::DataNotEntirelyConsumed)
+    }
+}
+
+/// Convenience method for strict decoding of data structures implementing
+/// [`StrictDecode`] from any byte data source under the provided decoding
+/// `limits`.
+pub fn strict_deserialize_with_limits<T>(
+    data: impl AsRef<[u8]>,
+    limits: DecodeLimits,
+) -> Result<T, Error>
+where
+    T: StrictDecode,
+{
+    let mut decoder = LimitedReader::new(data.as_ref(), limits);
+    let rv = decoder
+        .decode()
+        .map_err(|err| err.at_offset(decoder.consumed()))?;
 
     // Fail if data are not consumed entirely.
-    if consumed == data.as_ref().len() {
+    if decoder.consumed() == data.as_ref().len() {
         Ok(rv)
     } else {
         Err(Error::DataNotEntirelyConsumed)
     }
 }
 
+/// Convenience method for strict decoding of data structures implementing
+/// [`StrictDecode`] from any byte data source, which rejects non-canonical
+/// encodings: all decoders run in canonical mode (see
+/// [`DecodeLimits::canonical`]) and the decoded value must be strict-encoded
+/// exactly into the provided `data`.
+///
+/// # Errors
+///
+/// Errors with [`Error::NonCanonical`] if the data are not a canonical
+/// encoding of the decoded value, or with any other decoding error.
+pub fn strict_deserialize_canonical<T>(
+    data: impl AsRef<[u8]>,
+) -> Result<T, Error>
+where
+    T: StrictEncode + StrictDecode,
+{
+    let data = data.as_ref();
+    let limits = DecodeLimits {
+        canonical: true,
+        ..DecodeLimits::UNLIMITED
+    };
+    let rv = strict_deserialize_with_limits::<T>(data, limits)?;
+    if rv.strict_serialize()? != data {
+        return Err(Error::NonCanonical(core::any::type_name::<T>()));
+    }
+    Ok(rv)
+}
+
+/// Computes [`StrictEncode::FIXED_SIZE`] of a data structure from the fixed
+/// sizes of its fields; returns `None` if some of the fields have
+/// variable-length encoding.
+pub const fn fixed_size_sum(sizes: &[Option<usize>]) -> Option<usize> {
+    let mut sum = 0usize;
+    let mut i = 0;
+    while i < sizes.len() {
+        match sizes[i] {
+            Some(size) => sum += size,
+            None => return None,
+        }
+        i += 1;
+    }
+    Some(sum)
+}
+
+/// Computes [`StrictEncode::FIXED_SIZE`] of an enum from the fixed sizes of
+/// its variants; returns `None` unless all the variants have the same fixed
+/// size.
+pub const fn fixed_size_same(sizes: &[Option<usize>]) -> Option<usize> {
+    if sizes.is_empty() {
+        return None;
+    }
+    let first = match sizes[0] {
+        Some(size) => size,
+        None => return None,
+    };
+    let mut i = 1;
+    while i < sizes.len() {
+        match sizes[i] {
+            Some(size) if size == first => {}
+            _ => return None,
+        }
+        i += 1;
+    }
+    Some(first)
+}
+
 /// Possible errors during strict encoding and decoding process
-#[derive(Clone, PartialEq, Eq, Debug, Display, From, Error)]
+#[derive(Clone, PartialEq, Eq, Debug, Display, From)]
+#[cfg_attr(feature = "std", derive(Error))]
 #[display(doc_comments)]
 pub enum Error {
     /// I/O error during data strict encoding
     #[from(io::Error)]
     #[from(io::ErrorKind)]
-    Io(IoError),
+    Io(io::IoError),
 
     /// String data are not in valid UTF-8 encoding
     #[from]
-    Utf8Conversion(std::str::Utf8Error),
+    Utf8Conversion(core::str::Utf8Error),
 
     /// A collection (slice, vector or other type) has more items ({0}) than
     /// 2^16 (i.e. maximum value which may be held by `u16` `size`
@@ -245,6 +483,10 @@ pub enum Error {
     /// supported range {1:#?}
     ValueOutOfRange(&'static str, Range<u128>, u128),
 
+    /// A confined collection has {0} items, while it must have from {1} to
+    /// {2} items
+    LenOutOfBounds(usize, usize, usize),
+
     /// A repeated value for `{0}` found during set collection deserialization
     RepeatedValue(String),
 
@@ -257,6 +499,40 @@ pub enum Error {
 
     /// Data integrity problem during strict decoding operation: {0}
     DataIntegrityError(String),
+
+    /// Non-canonical encoding of `{0}` met while decoding data in canonical
+    /// mode: the decoded value does not encode back into the same bytes
+    NonCanonical(&'static str),
+
+    /// Decoding of the data requires more than {0} bytes allowed by the
+    /// decoding limits
+    BytesLimitExceeded(usize),
+
+    /// Data nesting exceeds maximum depth of {0} allowed by the decoding
+    /// limits
+    DepthLimitExceeded(usize),
+
+    /// A collection has {0} items, exceeding maximum of {1} items allowed by
+    /// the decoding limits
+    ItemsLimitExceeded(usize, usize),
+
+    /// Another strict decoding with resource limits is in progress; without
+    /// `std` only a single one may run at a time
+    DecodingContextBusy,
+
+    /// A stream was declared to contain {0} items, while {1} items were
+    /// written into it
+    ItemsCountMismatch(usize, usize),
+
+    /// TLV extension error: {0}
+    #[from]
+    Tlv(TlvError),
+
+    /// Serde data model error: {0}
+    Serde(String),
+
+    /// {0}
+    Context(Box<ErrorContext>),
 }
 
 impl From<Error> for fmt::Error {
@@ -270,41 +546,11 @@ impl From<FromUtf8Error> for Error {
     }
 }
 
-/// Possible errors during TLV extension encoding and decoding process
-#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display, Error)]
-#[display(doc_comments)]
-pub enum TlvError {
-    /// deterministic order of TLV records is broken: type {read} follows after
-    /// type {max}
-    Order {
-        /// TLV type id read at the current position
-        read: u64,
-        /// maximum value of TLV type id read previously
-        max: u64,
-    },
-
-    /// incorrect length of TLV record value: expected {expected}, but only
-    /// {actual} bytes read
-    Len {
-        /// TLV value length encoded in the TLV record
-        expected: u64,
-        /// Actual remaining length of the TLV stream
-        actual: u64,
-    },
-
-    /// repeated TLV record with id {0}
-    Repeated(u64),
-
-    /// an unknown even TLV type {0}
-    UnknownEvenType(u64),
-}
-
-// TODO: With 2.0 release add Tlv case to the Error enum
-impl From<TlvError> for Error {
-    fn from(err: TlvError) -> Self {
-        match err {
-            TlvError::Repeated(size) => Error::RepeatedValue(size.to_string()),
-            err => Error::DataIntegrityError(err.to_string()),
-        }
+impl Error {
+    /// Constructs [`Error::DataIntegrityError`] from the error returned by a
+    /// function validating decoded data, like the ones provided with
+    /// `#[strict_encoding(validate = "...")]` derive attribute.
+    pub fn data_integrity(err: impl fmt::Display) -> Self {
+        Error::DataIntegrityError(err.to_string())
     }
 }
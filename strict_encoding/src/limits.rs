@@ -0,0 +1,699 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Resource limits for strict decoding of untrusted data.
+//!
+//! Strict decoders trust length prefixes and nesting met in the input, which
+//! allows a malicious peer to make the decoding side allocate a lot of memory
+//! or recurse deeply. [`LimitedReader`] wraps a reader with a set of
+//! [`DecodeLimits`]: a total byte budget, maximum nesting depth and maximum
+//! number of items in a single collection. While a [`LimitedReader::decode`]
+//! call is in progress, collection and recursive decoders check the active
+//! limits with [`check_items`] and [`DepthGuard::enter`]; outside of it these
+//! checks are no-ops.
+//!
+//! Decoding context may also require canonical encoding of the data
+//! ([`DecodeLimits::canonical`]). In this mode decoders which accept several
+//! encodings of the same value reject all of them except the one produced by
+//! the strict encoding of the value, failing with [`Error::NonCanonical`]. Such
+//! decoders use [`is_canonical`], [`check_canonical`] and [`decode_canonical`]
+//! functions.
+//!
+//! Asynchronous decoders apply the same limits when run by
+//! `LimitedAsyncReader` from the `asynchronous` module.
+
+use alloc::vec::Vec;
+use core::any;
+#[cfg(feature = "std")]
+use core::cell::Cell;
+#[cfg(not(feature = "std"))]
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrdering};
+
+use crate::{io, Error, StrictDecode, StrictEncode};
+
+/// Limits applied to the strict decoding procedure
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct DecodeLimits {
+    /// Maximum number of bytes which may be consumed from the input
+    pub max_bytes: usize,
+
+    /// Maximum nesting depth of collections and recursive data structures
+    pub max_depth: usize,
+
+    /// Maximum number of items in a single collection
+    pub max_items: usize,
+
+    /// Whether decoders must reject encodings which differ from the strict
+    /// encoding of the decoded value
+    pub canonical: bool,
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self { DecodeLimits::UNLIMITED }
+}
+
+impl DecodeLimits {
+    /// Limits which do not restrict the decoding procedure in any way
+    pub const UNLIMITED: DecodeLimits = DecodeLimits {
+        max_bytes: usize::MAX,
+        max_depth: usize::MAX,
+        max_items: usize::MAX,
+        canonical: false,
+    };
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Context {
+    limits: DecodeLimits,
+    depth: usize,
+}
+
+#[cfg(feature = "std")]
+thread_local! {
+    static CONTEXT: Cell<Option<Context>> = const { Cell::new(None) };
+}
+
+/// Without `std` there are no thread-local variables, so the decoding context
+/// is kept in a global variable. Only a single decoding procedure with limits
+/// may run at a time: activation of the context fails with
+/// [`Error::DecodingContextBusy`] while another one is active, including the
+/// case of nested [`LimitedReader::decode`] calls.
+#[cfg(not(feature = "std"))]
+static CONTEXT: GlobalContext = GlobalContext::new();
+
+#[cfg(not(feature = "std"))]
+struct GlobalContext {
+    owned: AtomicBool,
+    active: AtomicBool,
+    max_bytes: AtomicUsize,
+    max_depth: AtomicUsize,
+    max_items: AtomicUsize,
+    canonical: AtomicBool,
+    depth: AtomicUsize,
+}
+
+#[cfg(not(feature = "std"))]
+impl GlobalContext {
+    const fn new() -> Self {
+        GlobalContext {
+            owned: AtomicBool::new(false),
+            active: AtomicBool::new(false),
+            max_bytes: AtomicUsize::new(0),
+            max_depth: AtomicUsize::new(0),
+            max_items: AtomicUsize::new(0),
+            canonical: AtomicBool::new(false),
+            depth: AtomicUsize::new(0),
+        }
+    }
+
+    // Mirrors `LocalKey::with` API used with `std`
+    fn with<R>(&'static self, f: impl FnOnce(&Self) -> R) -> R { f(self) }
+
+    fn get(&self) -> Option<Context> {
+        if !self.active.load(AtomicOrdering::Acquire) {
+            return None;
+        }
+        Some(Context {
+            limits: DecodeLimits {
+                max_bytes: self.max_bytes.load(AtomicOrdering::Relaxed),
+                max_depth: self.max_depth.load(AtomicOrdering::Relaxed),
+                max_items: self.max_items.load(AtomicOrdering::Relaxed),
+                canonical: self.canonical.load(AtomicOrdering::Relaxed),
+            },
+            depth: self.depth.load(AtomicOrdering::Relaxed),
+        })
+    }
+
+    fn set(&self, context: Option<Context>) {
+        if let Some(Context { limits, depth }) = context {
+            self.max_bytes
+                .store(limits.max_bytes, AtomicOrdering::Relaxed);
+            self.max_depth
+                .store(limits.max_depth, AtomicOrdering::Relaxed);
+            self.max_items
+                .store(limits.max_items, AtomicOrdering::Relaxed);
+            self.canonical
+                .store(limits.canonical, AtomicOrdering::Relaxed);
+            self.depth.store(depth, AtomicOrdering::Relaxed);
+        }
+        self.active
+            .store(context.is_some(), AtomicOrdering::Release);
+    }
+
+    fn acquire(&self, context: Context) -> Result<(), Error> {
+        self.owned
+            .compare_exchange(
+                false,
+                true,
+                AtomicOrdering::Acquire,
+                AtomicOrdering::Relaxed,
+            )
+            .map_err(|_| Error::DecodingContextBusy)?;
+        self.set(Some(context));
+        Ok(())
+    }
+
+    fn release(&self) {
+        self.set(None);
+        self.owned.store(false, AtomicOrdering::Release);
+    }
+}
+
+/// Activates decoding context for the lifetime of the value, restoring the
+/// previous context on drop.
+#[cfg(feature = "std")]
+struct Activation(Option<Context>);
+
+#[cfg(feature = "std")]
+impl Activation {
+    fn new(limits: DecodeLimits) -> Result<Self, Error> {
+        Ok(Activation(CONTEXT.with(|ctx| {
+            ctx.replace(Some(Context { limits, depth: 0 }))
+        })))
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for Activation {
+    fn drop(&mut self) { CONTEXT.with(|ctx| ctx.set(self.0)) }
+}
+
+/// Activates the global decoding context for the lifetime of the value,
+/// releasing it on drop.
+#[cfg(not(feature = "std"))]
+struct Activation;
+
+#[cfg(not(feature = "std"))]
+impl Activation {
+    fn new(limits: DecodeLimits) -> Result<Self, Error> {
+        CONTEXT.acquire(Context { limits, depth: 0 })?;
+        Ok(Activation)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Drop for Activation {
+    fn drop(&mut self) { CONTEXT.release() }
+}
+
+/// Decoding context of an asynchronous decoding procedure.
+///
+/// Asynchronous decoding may be suspended at any `await` point, letting other
+/// tasks run on the same thread, so its context can't stay in the thread-local
+/// storage for the whole procedure. Instead the context is installed only
+/// while the decoding future is polled, and the updated nesting depth is saved
+/// back once the poll completes.
+#[cfg(feature = "async")]
+#[derive(Debug)]
+pub(crate) struct AsyncContext(Context);
+
+#[cfg(feature = "async")]
+impl AsyncContext {
+    pub(crate) fn new(limits: DecodeLimits) -> Self {
+        AsyncContext(Context { limits, depth: 0 })
+    }
+
+    /// Runs `f` with the context being active, restoring the previous
+    /// context afterwards.
+    pub(crate) fn activate<R>(&mut self, f: impl FnOnce() -> R) -> R {
+        struct Restore<'a>(&'a mut Context, Option<Context>);
+
+        impl Drop for Restore<'_> {
+            fn drop(&mut self) {
+                if let Some(context) = CONTEXT.with(|ctx| ctx.replace(self.1)) {
+                    *self.0 = context;
+                }
+            }
+        }
+
+        let prev = CONTEXT.with(|ctx| ctx.replace(Some(self.0)));
+        let _restore = Restore(&mut self.0, prev);
+        f()
+    }
+}
+
+/// Checks that a collection with `len` items fits into the item limit of the
+/// currently active decoding context.
+///
+/// # Errors
+///
+/// Errors with [`Error::ItemsLimitExceeded`] if the number of items exceeds
+/// [`DecodeLimits::max_items`].
+pub fn check_items(len: usize) -> Result<(), Error> {
+    CONTEXT.with(|ctx| match ctx.get() {
+        Some(Context { limits, .. }) if len > limits.max_items => {
+            Err(Error::ItemsLimitExceeded(len, limits.max_items))
+        }
+        _ => Ok(()),
+    })
+}
+
+/// Detects whether the currently active decoding context requires canonical
+/// encoding of the data (see [`DecodeLimits::canonical`]).
+pub fn is_canonical() -> bool {
+    CONTEXT.with(|ctx| {
+        matches!(ctx.get(), Some(Context { limits, .. }) if limits.canonical)
+    })
+}
+
+/// Checks that `value` decoded from `data` is strict-encoded exactly into the
+/// same `data`, if the currently active decoding context requires canonical
+/// encoding.
+///
+/// # Errors
+///
+/// Errors with [`Error::NonCanonical`] naming type `T` if the encodings
+/// differ.
+pub fn check_canonical<T>(value: &T, data: &[u8]) -> Result<(), Error>
+where
+    T: StrictEncode,
+{
+    if is_canonical() && value.strict_serialize()? != data {
+        return Err(Error::NonCanonical(any::type_name::<T>()));
+    }
+    Ok(())
+}
+
+/// Decodes value with `decode` procedure reading data from `d`. If the
+/// currently active decoding context requires canonical encoding, keeps the
+/// data consumed by the procedure and checks them with [`check_canonical`].
+///
+/// Used by decoders of types which may have several encodings of the same
+/// value, for instance because the values get normalized on construction.
+///
+/// # Errors
+///
+/// Errors with [`Error::NonCanonical`] naming type `T` if the encoding is not
+/// canonical, or with any error returned by `decode`.
+pub fn decode_canonical<T, D>(
+    mut d: D,
+    decode: impl FnOnce(&mut dyn io::Read) -> Result<T, Error>,
+) -> Result<T, Error>
+where
+    T: StrictEncode,
+    D: io::Read,
+{
+    if !is_canonical() {
+        return decode(&mut d);
+    }
+    let mut reader = RecordingReader {
+        inner: &mut d,
+        data: vec![],
+    };
+    let value = decode(&mut reader)?;
+    check_canonical(&value, &reader.data)?;
+    Ok(value)
+}
+
+/// Reader keeping copy of all data read from the inner reader
+struct RecordingReader<'a> {
+    inner: &'a mut dyn io::Read,
+    data: Vec<u8>,
+}
+
+impl io::Read for RecordingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.data.extend_from_slice(&buf[..read]);
+        Ok(read)
+    }
+}
+
+/// Maximum number of bytes which collection decoders allocate in advance,
+/// before the collection items are actually read from the input.
+pub const MAX_PREALLOC_BYTES: usize = 0x10000;
+
+/// Returns capacity which should be allocated in advance by a decoder of a
+/// collection with `len` items of type `T`.
+///
+/// Collection lengths are read from untrusted input, so the capacity is
+/// limited to [`MAX_PREALLOC_BYTES`]; the collection grows further only as
+/// its items get decoded.
+#[inline]
+pub fn prealloc_capacity<T>(len: usize) -> usize {
+    len.min(MAX_PREALLOC_BYTES / core::mem::size_of::<T>().max(1))
+}
+
+/// Guard tracking nesting depth of the data being decoded. Must be kept alive
+/// while nested data are decoded.
+#[derive(Debug)]
+pub struct DepthGuard(bool);
+
+impl DepthGuard {
+    /// Enters the next nesting level of the currently active decoding
+    /// context.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::DepthLimitExceeded`] if the new nesting depth
+    /// exceeds [`DecodeLimits::max_depth`].
+    pub fn enter() -> Result<DepthGuard, Error> {
+        CONTEXT.with(|ctx| match ctx.get() {
+            None => Ok(DepthGuard(false)),
+            Some(Context { limits, depth }) if depth >= limits.max_depth => {
+                Err(Error::DepthLimitExceeded(limits.max_depth))
+            }
+            Some(mut context) => {
+                context.depth += 1;
+                ctx.set(Some(context));
+                Ok(DepthGuard(true))
+            }
+        })
+    }
+}
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        if !self.0 {
+            return;
+        }
+        CONTEXT.with(|ctx| {
+            if let Some(mut context) = ctx.get() {
+                context.depth = context.depth.saturating_sub(1);
+                ctx.set(Some(context));
+            }
+        })
+    }
+}
+
+/// Reader wrapper enforcing [`DecodeLimits`] during strict decoding
+#[derive(Debug)]
+pub struct LimitedReader<R>
+where
+    R: io::Read,
+{
+    inner: R,
+    limits: DecodeLimits,
+    consumed: usize,
+    exhausted: bool,
+}
+
+impl<R> LimitedReader<R>
+where
+    R: io::Read,
+{
+    /// Constructs reader applying `limits` to the data read from `inner`
+    pub fn new(inner: R, limits: DecodeLimits) -> Self {
+        LimitedReader {
+            inner,
+            limits,
+            consumed: 0,
+            exhausted: false,
+        }
+    }
+
+    /// Returns limits applied by the reader
+    pub fn limits(&self) -> DecodeLimits { self.limits }
+
+    /// Returns number of bytes consumed from the inner reader
+    pub fn consumed(&self) -> usize { self.consumed }
+
+    /// Releases inner reader
+    pub fn into_inner(self) -> R { self.inner }
+
+    /// Decodes object of type `T` from the reader, applying reader limits to
+    /// all nested collections.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::BytesLimitExceeded`], [`Error::DepthLimitExceeded`]
+    /// or [`Error::ItemsLimitExceeded`] when the data violate the limits, or
+    /// with any other error returned by the decoder of `T`. Without `std`
+    /// errors with [`Error::DecodingContextBusy`] if another decoding with
+    /// limits is in progress.
+    pub fn decode<T>(&mut self) -> Result<T, Error>
+    where
+        T: StrictDecode,
+    {
+        let _activation = Activation::new(self.limits)?;
+        T::strict_decode(&mut *self).map_err(|err| {
+            if self.exhausted {
+                Error::BytesLimitExceeded(self.limits.max_bytes)
+            } else {
+                err
+            }
+        })
+    }
+}
+
+impl<R> io::Read for LimitedReader<R>
+where
+    R: io::Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let remaining = self.limits.max_bytes - self.consumed;
+        if remaining == 0 {
+            // Decoders may probe for the end of data, which must not fail if
+            // the budget matches the data length exactly
+            if self.inner.read(&mut [0u8; 1])? == 0 {
+                return Ok(0);
+            }
+            self.exhausted = true;
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "strict decoding byte limit exceeded",
+            ));
+        }
+        let max = buf.len().min(remaining);
+        let read = self.inner.read(&mut buf[..max])?;
+        self.consumed += read;
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::{strict_serialize, LargeVec, StrictEncode, TlvStream};
+
+    fn limits(
+        max_bytes: usize,
+        max_depth: usize,
+        max_items: usize,
+    ) -> DecodeLimits {
+        DecodeLimits {
+            max_bytes,
+            max_depth,
+            max_items,
+            canonical: false,
+        }
+    }
+
+    #[test]
+    fn unlimited() {
+        let data = vec![vec![1u8, 2, 3], vec![], vec![4]];
+        let ser = strict_serialize(&data).unwrap();
+        let mut reader = LimitedReader::new(&ser[..], DecodeLimits::UNLIMITED);
+        assert_eq!(reader.decode::<Vec<Vec<u8>>>().unwrap(), data);
+        assert_eq!(reader.consumed(), ser.len());
+    }
+
+    #[test]
+    fn bytes_limit() {
+        let data = s!("some string exceeding the limit");
+        let ser = strict_serialize(&data).unwrap();
+
+        let mut reader = LimitedReader::new(&ser[..], limits(ser.len(), 8, 64));
+        assert_eq!(reader.decode::<String>().unwrap(), data);
+
+        let mut reader =
+            LimitedReader::new(&ser[..], limits(ser.len() - 1, 8, 64));
+        assert_eq!(
+            reader.decode::<String>().unwrap_err(),
+            Error::BytesLimitExceeded(ser.len() - 1)
+        );
+    }
+
+    #[test]
+    fn exact_bytes_limit() {
+        // Decoders probing for the end of data must succeed when the budget
+        // is equal to the data length
+        let mut stream = TlvStream::new();
+        stream.insert(1, [0xAB]);
+        let data = (7u8, stream);
+        let ser = strict_serialize(&data).unwrap();
+        let mut reader = LimitedReader::new(&ser[..], limits(ser.len(), 8, 64));
+        assert_eq!(reader.decode::<(u8, TlvStream)>().unwrap(), data);
+
+        let ser = strict_serialize(&7u8).unwrap();
+        let mut reader = LimitedReader::new(&ser[..], limits(1, 8, 64));
+        assert_eq!(
+            reader.decode::<(u8, TlvStream)>().unwrap(),
+            (7u8, TlvStream::new())
+        );
+
+        let mut ser = vec![];
+        data.1.bolt_encode(&mut ser).unwrap();
+        let mut reader = LimitedReader::new(&ser[..], limits(ser.len(), 8, 64));
+        assert_eq!(TlvStream::bolt_decode(&mut reader).unwrap(), data.1);
+    }
+
+    #[test]
+    fn items_limit() {
+        let data = (0u8..10).collect::<BTreeSet<_>>();
+        let ser = strict_serialize(&data).unwrap();
+
+        let mut reader = LimitedReader::new(&ser[..], limits(1024, 8, 10));
+        assert_eq!(reader.decode::<BTreeSet<u8>>().unwrap(), data);
+
+        let mut reader = LimitedReader::new(&ser[..], limits(1024, 8, 9));
+        assert_eq!(
+            reader.decode::<BTreeSet<u8>>().unwrap_err(),
+            Error::ItemsLimitExceeded(10, 9)
+        );
+    }
+
+    #[test]
+    fn large_vec_prefix() {
+        // Length prefix claiming u32::MAX items must not result in the
+        // allocation of the memory for them
+        let ser = [0xFFu8, 0xFF, 0xFF, 0xFF, 0x01];
+        let mut reader = LimitedReader::new(&ser[..], limits(1024, 8, 1024));
+        assert_eq!(
+            reader.decode::<LargeVec<u64>>().unwrap_err(),
+            Error::ItemsLimitExceeded(u32::MAX as usize, 1024)
+        );
+    }
+
+    #[test]
+    fn depth_limit() {
+        let data = vec![vec![vec![0u8]]];
+        let ser = strict_serialize(&data).unwrap();
+
+        let mut reader = LimitedReader::new(&ser[..], limits(1024, 3, 64));
+        assert_eq!(reader.decode::<Vec<Vec<Vec<u8>>>>().unwrap(), data);
+
+        let mut reader = LimitedReader::new(&ser[..], limits(1024, 2, 64));
+        assert_eq!(
+            reader.decode::<Vec<Vec<Vec<u8>>>>().unwrap_err(),
+            Error::DepthLimitExceeded(2)
+        );
+    }
+
+    #[test]
+    fn context_is_released() {
+        let data = vec![0u8; 16];
+        let ser = strict_serialize(&data).unwrap();
+        let mut reader = LimitedReader::new(&ser[..], limits(1024, 8, 1));
+        assert!(reader.decode::<Vec<u8>>().is_err());
+        assert_eq!(Vec::<u8>::strict_deserialize(&ser).unwrap(), data);
+        assert_eq!(data.strict_serialize().unwrap(), ser);
+    }
+
+    #[test]
+    fn canonical_mode() {
+        #[derive(Debug)]
+        struct Probe;
+        impl StrictDecode for Probe {
+            fn strict_decode<D: io::Read>(_: D) -> Result<Self, Error> {
+                assert!(is_canonical());
+                Ok(Probe)
+            }
+        }
+
+        assert!(!is_canonical());
+        let mut reader = LimitedReader::new(&[][..], DecodeLimits {
+            canonical: true,
+            ..DecodeLimits::UNLIMITED
+        });
+        reader.decode::<Probe>().unwrap();
+        assert!(!is_canonical());
+    }
+
+    #[test]
+    fn nested_activation() {
+        #[derive(Debug)]
+        struct Nested;
+        impl StrictDecode for Nested {
+            fn strict_decode<D: io::Read>(_: D) -> Result<Self, Error> {
+                LimitedReader::new(&[0u8][..], limits(1, 8, 64))
+                    .decode::<u8>()?;
+                assert_eq!(
+                    check_items(2),
+                    Err(Error::ItemsLimitExceeded(2, 1))
+                );
+                Ok(Nested)
+            }
+        }
+
+        let mut reader = LimitedReader::new(&[][..], limits(0, 8, 1));
+        // Without thread-local storage the context can't be shared safely
+        #[cfg(not(feature = "std"))]
+        assert_eq!(
+            reader.decode::<Nested>().unwrap_err(),
+            Error::DecodingContextBusy
+        );
+        #[cfg(feature = "std")]
+        reader.decode::<Nested>().unwrap();
+        assert_eq!(check_items(2), Ok(()));
+    }
+
+    #[test]
+    fn check_canonical_outside_context() {
+        // Without canonical decoding context any data are accepted
+        check_canonical(&1u16, &[1]).unwrap();
+        assert_eq!(decode_canonical(&[1u8, 0][..], |_| Ok(1u8)).unwrap(), 1u8);
+    }
+
+    #[test]
+    fn canonical_check() {
+        #[derive(Debug)]
+        struct Lax(u8);
+        impl StrictEncode for Lax {
+            fn strict_encode<E: io::Write>(
+                &self,
+                e: E,
+            ) -> Result<usize, Error> {
+                self.0.strict_encode(e)
+            }
+        }
+        impl StrictDecode for Lax {
+            // Accepts one- and two-byte encodings of the same value
+            fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
+                decode_canonical(d, |d| {
+                    let val = u8::strict_decode(&mut *d)?;
+                    if val == 0xFF {
+                        u8::strict_decode(d).map(Lax)
+                    } else {
+                        Ok(Lax(val))
+                    }
+                })
+            }
+        }
+
+        let lax = limits(1024, 8, 64);
+        let canonical = DecodeLimits {
+            canonical: true,
+            ..lax
+        };
+        for (ser, limits) in [(&[5u8][..], lax), (&[5u8][..], canonical)] {
+            let mut reader = LimitedReader::new(ser, limits);
+            assert_eq!(reader.decode::<Lax>().unwrap().0, 5);
+        }
+
+        let ser = [0xFFu8, 5];
+        let mut reader = LimitedReader::new(&ser[..], lax);
+        assert_eq!(reader.decode::<Lax>().unwrap().0, 5);
+        let mut reader = LimitedReader::new(&ser[..], canonical);
+        assert_eq!(
+            reader.decode::<Lax>().unwrap_err(),
+            Error::NonCanonical(any::type_name::<Lax>())
+        );
+    }
+}
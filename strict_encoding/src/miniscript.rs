@@ -12,9 +12,9 @@
 // You should have received a copy of the Apache 2.0 License along with this
 // software. If not, see <https://opensource.org/licenses/Apache-2.0>.
 
-use std::io;
 use std::io::{Read, Write};
 use std::sync::Arc;
+use std::{any, io};
 
 use bitcoin::consensus::ReadExt;
 use bitcoin::XOnlyPublicKey;
@@ -28,6 +28,7 @@ use miniscript::{
     Terminal,
 };
 
+use crate::limits::{check_items, is_canonical, prealloc_capacity, DepthGuard};
 use crate::{strategies, Error, Strategy, StrictDecode, StrictEncode};
 
 /// Maximum level of nested miniscript and miniscript concrete policy levels
@@ -41,38 +42,38 @@ use crate::{strategies, Error, Strategy, StrictDecode, StrictEncode};
 /// of bitcoin transactions), and thus we need this redundant control.
 pub const MINISCRIPT_DEPTH_LIMIT: u8 = 64;
 
-const MS_FALSE: u8 = 0;
-const MS_TRUE: u8 = 1;
-
-const MS_KEY: u8 = 0x02;
-const MS_KEY_HASH: u8 = 0x0a;
-const MS_HASHED_KEY: u8 = 0x03;
-const MS_THRESH: u8 = 0x20;
-const MS_MULTI: u8 = 0x21;
-const MS_MULTI_A: u8 = 0x2c;
-
-const MS_AFTER: u8 = 0x09;
-const MS_OLDER: u8 = 0x08;
-const MS_SHA256: u8 = 0x04;
-const MS_HASH256: u8 = 0x05;
-const MS_RIPEMD160: u8 = 0x06;
-const MS_HASH160: u8 = 0x07;
-
-const MS_AND_V: u8 = 0x22;
-const MS_AND_B: u8 = 0x23;
-const MS_AND_OR: u8 = 0x24;
-const MS_OR_B: u8 = 0x28;
-const MS_OR_D: u8 = 0x29;
-const MS_OR_C: u8 = 0x2a;
-const MS_OR_I: u8 = 0x2b;
-
-const MS_ALT: u8 = 0x10;
-const MS_SWAP: u8 = 0x11;
-const MS_CHECK: u8 = 0x12;
-const MS_DUP_IF: u8 = 0x13;
-const MS_VERIFY: u8 = 0x18;
-const MS_NON_ZERO: u8 = 0x19;
-const MS_ZERO_NE: u8 = 0x1a;
+pub(crate) const MS_FALSE: u8 = 0;
+pub(crate) const MS_TRUE: u8 = 1;
+
+pub(crate) const MS_KEY: u8 = 0x02;
+pub(crate) const MS_KEY_HASH: u8 = 0x0a;
+pub(crate) const MS_HASHED_KEY: u8 = 0x03;
+pub(crate) const MS_THRESH: u8 = 0x20;
+pub(crate) const MS_MULTI: u8 = 0x21;
+pub(crate) const MS_MULTI_A: u8 = 0x2c;
+
+pub(crate) const MS_AFTER: u8 = 0x09;
+pub(crate) const MS_OLDER: u8 = 0x08;
+pub(crate) const MS_SHA256: u8 = 0x04;
+pub(crate) const MS_HASH256: u8 = 0x05;
+pub(crate) const MS_RIPEMD160: u8 = 0x06;
+pub(crate) const MS_HASH160: u8 = 0x07;
+
+pub(crate) const MS_AND_V: u8 = 0x22;
+pub(crate) const MS_AND_B: u8 = 0x23;
+pub(crate) const MS_AND_OR: u8 = 0x24;
+pub(crate) const MS_OR_B: u8 = 0x28;
+pub(crate) const MS_OR_D: u8 = 0x29;
+pub(crate) const MS_OR_C: u8 = 0x2a;
+pub(crate) const MS_OR_I: u8 = 0x2b;
+
+pub(crate) const MS_ALT: u8 = 0x10;
+pub(crate) const MS_SWAP: u8 = 0x11;
+pub(crate) const MS_CHECK: u8 = 0x12;
+pub(crate) const MS_DUP_IF: u8 = 0x13;
+pub(crate) const MS_VERIFY: u8 = 0x18;
+pub(crate) const MS_NON_ZERO: u8 = 0x19;
+pub(crate) const MS_ZERO_NE: u8 = 0x1a;
 
 /// We need this because of rust compiler limitations.
 ///
@@ -187,14 +188,31 @@ where
     <Pk as MiniscriptKey>::Sha256: StrictDecode,
 {
     fn strict_decode<D: Read>(mut d: D) -> Result<Self, Error> {
+        let _depth = DepthGuard::enter()?;
         match u8::strict_decode(&mut d)? {
             1u8 => Ok(TapTree::Leaf(StrictDecode::strict_decode(&mut d)?)),
             2u8 => {
                 let vec1 = Vec::<u8>::strict_decode(&mut d)?;
                 let vec2 = Vec::<u8>::strict_decode(&mut d)?;
+                // Subtrees followed by some extra data are accepted for
+                // backward compatibility, but they are not canonical
+                let decode = |data: Vec<u8>| {
+                    if is_canonical() {
+                        crate::strict_deserialize(data).map_err(|err| match err
+                            .root_cause()
+                        {
+                            Error::DataNotEntirelyConsumed => {
+                                Error::NonCanonical(any::type_name::<Self>())
+                            }
+                            _ => err,
+                        })
+                    } else {
+                        TapTree::strict_deserialize(data)
+                    }
+                };
                 Ok(TapTree::Tree(
-                    Arc::new(TapTree::strict_deserialize(vec1)?),
-                    Arc::new(TapTree::strict_deserialize(vec2)?),
+                    Arc::new(decode(vec1)?),
+                    Arc::new(decode(vec2)?),
                 ))
             }
             wrong => Err(Error::EnumValueNotKnown("TapTree", wrong as usize)),
@@ -315,6 +333,7 @@ where
                 ));
             }
             depth += 1;
+            let _depth = DepthGuard::enter()?;
 
             let byte = d.read_u8()?;
             Ok(match byte {
@@ -331,7 +350,11 @@ where
                 MS_HASH160 => Policy::Hash160(StrictDecode::strict_decode(d)?),
                 MS_AND_B => {
                     let len = d.read_u16()?;
-                    let mut vec = Vec::with_capacity(len as usize);
+                    check_items(len as usize)?;
+                    let mut vec =
+                        Vec::with_capacity(prealloc_capacity::<Policy<Pk>>(
+                            len as usize,
+                        ));
                     for _ in 0..len {
                         vec.push(decode_policy_inner(d, depth)?);
                     }
@@ -339,7 +362,13 @@ where
                 }
                 MS_OR_B => {
                     let len = d.read_u16()?;
-                    let mut vec = Vec::with_capacity(len as usize);
+                    check_items(len as usize)?;
+                    let mut vec = Vec::with_capacity(prealloc_capacity::<(
+                        usize,
+                        Policy<Pk>,
+                    )>(
+                        len as usize
+                    ));
                     for _ in 0..len {
                         vec.push((
                             d.read_u16()? as usize,
@@ -351,7 +380,11 @@ where
                 MS_THRESH => {
                     let thresh = d.read_u16()? as usize;
                     let len = d.read_u16()?;
-                    let mut vec = Vec::with_capacity(len as usize);
+                    check_items(len as usize)?;
+                    let mut vec =
+                        Vec::with_capacity(prealloc_capacity::<Policy<Pk>>(
+                            len as usize,
+                        ));
                     for _ in 0..len {
                         vec.push(decode_policy_inner(d, depth)?);
                     }
@@ -560,6 +593,8 @@ where
                 ));
             }
             depth += 1;
+            let _depth = DepthGuard::enter()?;
+
             let term = match d.read_u8()? {
                 MS_TRUE => Terminal::True,
                 MS_FALSE => Terminal::False,
@@ -640,7 +675,12 @@ where
                 MS_THRESH => {
                     let thresh = d.read_u16()? as usize;
                     let len = d.read_u16()?;
-                    let mut vec = Vec::with_capacity(len as usize);
+                    check_items(len as usize)?;
+                    let mut vec = Vec::with_capacity(prealloc_capacity::<
+                        Arc<Miniscript<Pk, Ctx>>,
+                    >(
+                        len as usize
+                    ));
                     for _ in 0..len {
                         vec.push(decode_miniscript_inner(d, depth)?.into());
                     }
@@ -671,14 +711,14 @@ where
     }
 }
 
-const DESCRIPTOR_BARE: u8 = 0x00;
-const DESCRIPTOR_PKH: u8 = 0x01;
-const DESCRIPTOR_SH: u8 = 0x02;
-const DESCRIPTOR_WPKH: u8 = 0x10;
-const DESCRIPTOR_WSH: u8 = 0x11;
-const DESCRIPTOR_TR: u8 = 0x20;
-const DESCRIPTOR_SORTED_MULTI: u8 = 0x03;
-const DESCRIPTOR_MINISCRIPT: u8 = 0x04;
+pub(crate) const DESCRIPTOR_BARE: u8 = 0x00;
+pub(crate) const DESCRIPTOR_PKH: u8 = 0x01;
+pub(crate) const DESCRIPTOR_SH: u8 = 0x02;
+pub(crate) const DESCRIPTOR_WPKH: u8 = 0x10;
+pub(crate) const DESCRIPTOR_WSH: u8 = 0x11;
+pub(crate) const DESCRIPTOR_TR: u8 = 0x20;
+pub(crate) const DESCRIPTOR_SORTED_MULTI: u8 = 0x03;
+pub(crate) const DESCRIPTOR_MINISCRIPT: u8 = 0x04;
 
 impl StrictEncode for SinglePubKey {
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
@@ -1203,7 +1243,11 @@ mod test {
     };
     use strict_encoding_test::*;
 
-    use crate::StrictDecode;
+    use super::*;
+    use crate::{
+        strict_deserialize_canonical, strict_deserialize_with_limits,
+        DecodeLimits, Error, StrictDecode, StrictEncode,
+    };
 
     #[test]
     #[should_panic]
@@ -1240,6 +1284,39 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_policy_limits() {
+        let policy = policy::Concrete::<String>::from_str(
+            "and(pk(A),or(and(after(9),pk(B)),and(after(1000000000),pk(C))))",
+        )
+        .unwrap();
+        let data = policy.strict_serialize().unwrap();
+
+        let limits = DecodeLimits {
+            max_depth: 8,
+            ..DecodeLimits::UNLIMITED
+        };
+        assert_eq!(
+            strict_deserialize_with_limits::<policy::Concrete<String>>(
+                &data, limits
+            )
+            .unwrap(),
+            policy
+        );
+
+        let limits = DecodeLimits {
+            max_depth: 3,
+            ..DecodeLimits::UNLIMITED
+        };
+        assert_eq!(
+            strict_deserialize_with_limits::<policy::Concrete<String>>(
+                &data, limits
+            )
+            .unwrap_err(),
+            Error::DepthLimitExceeded(3)
+        );
+    }
+
     #[test]
     fn test_miniscript() {
         const SET: [&str; 28] = [
@@ -1282,6 +1359,29 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_tap_tree() {
+        let leaf = TapTree::<String>::Leaf(Arc::new(
+            Miniscript::from_str("pk(A)").unwrap(),
+        ));
+        let tree =
+            TapTree::Tree(Arc::new(leaf.clone()), Arc::new(leaf.clone()));
+        test_object_encoding_roundtrip(&tree).unwrap();
+
+        // Subtree followed by an extra byte
+        let leaf_data = leaf.strict_serialize().unwrap();
+        let mut padded = leaf_data.clone();
+        padded.push(0);
+        let mut data = vec![2u8];
+        data.extend(padded.strict_serialize().unwrap());
+        data.extend(leaf_data.strict_serialize().unwrap());
+        assert_eq!(TapTree::<String>::strict_deserialize(&data).unwrap(), tree);
+        assert_eq!(
+            strict_deserialize_canonical::<TapTree<String>>(&data).unwrap_err(),
+            Error::NonCanonical(any::type_name::<TapTree<String>>())
+        );
+    }
+
     #[test]
     fn test_descriptor() {
         const SET: [&str; 16] = [
@@ -24,20 +24,45 @@
 //! - IPv4 and IPv6
 //! - Tor, both ONION v2 and v3 addresses
 //! - Lightning peer network addresses (Secp256k1 public keys)
-//! This list may be extended with future LNPBP-42 revisions
+//! - DNS names: stored inline if they fit 32 bytes; otherwise only the name
+//!   hash is encoded and the name itself must be provided out of band
+//! - I2P destinations (hashes of the destination, as in `.b32.i2p` names)
+//!
+//! This list may be extended with future LNPBP-42 revisions. Decoders built
+//! before some format was added reject it with
+//! [`DecodeError::UnknownAddrFormat`].
 //!
 //! Currently supported transport protocols (see [`Transport`]):
 //! - TCP
 //! - UDP
 //! - MTCP (multi-path TCP)
 //! - QUIC (more efficient UDP version)
+//!
 //! This list may be extended with future LNPBP-42 revisions
-
+//!
+//! With `std` feature [`UniformAddr`] also has a textual representation in
+//! form of `[<transport>://]<host>[:<port>]`, where host is an IPv4 address,
+//! IPv6 address in square brackets, Tor onion hostname, hex-encoded
+//! Lightning node public key, DNS name (hashed names are shown as hex-encoded
+//! hash), or I2P `.b32.i2p` hostname. Lightning peers, known by both their
+//! public key and network address, are represented by [`NodeAddr`] in form of
+//! `[<transport>://]<pubkey>@<host>[:<port>]`.
+
+#[cfg(feature = "std")]
+use std::fmt::{self, Display, Formatter};
+#[cfg(feature = "std")]
 use std::net::{
     IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6,
 };
+#[cfg(feature = "std")]
+use std::str::FromStr;
+
+#[cfg(feature = "std")]
+use bitcoin_hashes::hex::{FromHex, ToHex};
 
-use crate::{strategies, Strategy, StrictDecode, StrictEncode};
+use crate::{strategies, Strategy, StrictDecode, StrictEncode, StrictType};
+#[cfg(feature = "async")]
+use crate::{StrictDecodeAsync, StrictEncodeAsync};
 
 /// Standard length of the host-specific part of the encoding, in bytes
 pub const ADDR_LEN: usize = 33; // Maximum Tor public key size
@@ -57,9 +82,8 @@ pub type RawAddr = [u8; ADDR_LEN];
 pub type RawUniformAddr = [u8; UNIFORM_LEN];
 
 /// Uniform ecoding error types
-#[derive(
-    Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display, Error
-)]
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display)]
+#[cfg_attr(feature = "std", derive(Error))]
 #[display(doc_comments)]
 #[non_exhaustive]
 pub enum DecodeError {
@@ -89,12 +113,19 @@ pub enum DecodeError {
     /// Data provided by the uniform-encoded network address does not
     /// sufficient for target address structure
     InsufficientData,
+
+    /// Port number in the textual address representation is invalid
+    InvalidPort,
+
+    /// Address format is deprecated and must not be used anymore
+    DeprecatedAddrFormat,
 }
 
 /// Format of the host address
 #[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display)]
 #[repr(u8)]
-#[derive(StrictEncode, StrictDecode)]
+#[derive(StrictEncode, StrictDecode, StrictType)]
+#[cfg_attr(feature = "async", derive(StrictEncodeAsync, StrictDecodeAsync))]
 #[strict_encoding(crate = crate, by_value)]
 #[non_exhaustive]
 pub enum AddrFormat {
@@ -107,6 +138,9 @@ pub enum AddrFormat {
     IpV6 = 1,
 
     /// Tor ONION v2 address
+    ///
+    /// Onion v2 services are deprecated by Tor; [`OnionAddrV3`] rejects them
+    /// with [`DecodeError::DeprecatedAddrFormat`].
     #[display("onion(v2)")]
     OnionV2 = 2,
 
@@ -117,12 +151,29 @@ pub enum AddrFormat {
     /// Lightning network node address (Secp256k1 public key)
     #[display("lightning")]
     Lightning = 4,
+
+    /// DNS name up to 32 bytes long, stored inline: the first byte keeps the
+    /// name length, followed by the name in lower case and zero padding
+    #[display("dns")]
+    Dns = 5,
+
+    /// DNS name longer than 32 bytes, represented by SHA256 hash of the name
+    /// in lower case (prefixed with a zero byte). The name itself must be
+    /// provided out of band.
+    #[display("dns(hash)")]
+    DnsHash = 6,
+
+    /// I2P destination, represented by SHA256 hash of the destination
+    /// (prefixed with a zero byte)
+    #[display("i2p")]
+    I2p = 7,
 }
 
 /// Supported transport protocols
 #[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display)]
 #[repr(u8)]
-#[derive(StrictEncode, StrictDecode)]
+#[derive(StrictEncode, StrictDecode, StrictType)]
+#[cfg_attr(feature = "async", derive(StrictEncodeAsync, StrictDecodeAsync))]
 #[strict_encoding(crate = crate, by_value)]
 #[non_exhaustive]
 pub enum Transport {
@@ -315,6 +366,9 @@ impl TryFrom<RawUniformAddr> for UniformAddr {
             a if a == AddrFormat::OnionV2 as u8 => AddrFormat::OnionV2,
             a if a == AddrFormat::OnionV3 as u8 => AddrFormat::OnionV3,
             a if a == AddrFormat::Lightning as u8 => AddrFormat::Lightning,
+            a if a == AddrFormat::Dns as u8 => AddrFormat::Dns,
+            a if a == AddrFormat::DnsHash as u8 => AddrFormat::DnsHash,
+            a if a == AddrFormat::I2p as u8 => AddrFormat::I2p,
             _ => return Err(DecodeError::UnknownAddrFormat),
         };
         let mut addr = [0u8; ADDR_LEN];
@@ -325,6 +379,12 @@ impl TryFrom<RawUniformAddr> for UniformAddr {
             AddrFormat::OnionV2 => &addr[..23],
             AddrFormat::OnionV3 => &addr[..1],
             AddrFormat::Lightning => &[][..],
+            AddrFormat::Dns => {
+                check_inline_dns(&addr)?;
+                &[][..]
+            }
+            AddrFormat::DnsHash => &addr[..1],
+            AddrFormat::I2p => &addr[..1],
         }
         .iter()
         .filter(|byte| **byte != 0)
@@ -353,6 +413,749 @@ impl TryFrom<RawUniformAddr> for UniformAddr {
     }
 }
 
+#[cfg(feature = "std")]
+impl FromStr for Transport {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "tcp" => Transport::Tcp,
+            "udp" => Transport::Udp,
+            "mtcp" => Transport::Mtcp,
+            "quic" => Transport::Quic,
+            _ => return Err(DecodeError::UnknownTransport),
+        })
+    }
+}
+
+/// Length of the Tor onion v2 address data
+#[cfg(feature = "std")]
+const ONION_V2_LEN: usize = 10;
+
+/// Length of the Tor onion v3 public key
+#[cfg(feature = "tor")]
+const ONION_V3_KEY_LEN: usize = 32;
+
+/// Length of the hashes used in DNS and I2P address representations
+const ADDR_HASH_LEN: usize = 32;
+
+/// Maximal length of a DNS name which can be stored inline
+const DNS_INLINE_LEN: usize = ADDR_LEN - 1;
+
+/// Maximal length of a DNS name
+const DNS_MAX_LEN: usize = 253;
+
+/// Maximal length of a single label in DNS name
+const DNS_LABEL_MAX_LEN: usize = 63;
+
+/// Version byte of the Tor onion v3 address
+#[cfg(feature = "tor")]
+const ONION_V3_VERSION: u8 = 3;
+
+#[cfg(feature = "std")]
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+/// Encodes data with RFC 4648 base32 alphabet in lower case without padding.
+#[cfg(feature = "std")]
+fn base32_encode(data: &[u8]) -> String {
+    let mut s = String::with_capacity((data.len() * 8 + 4) / 5);
+    let mut buffer = 0u16;
+    let mut bits = 0u8;
+    for byte in data {
+        buffer = (buffer << 8) | *byte as u16;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            s.push(BASE32_ALPHABET[(buffer >> bits) as usize & 0x1F] as char);
+        }
+    }
+    if bits > 0 {
+        s.push(BASE32_ALPHABET[(buffer << (5 - bits)) as usize & 0x1F] as char);
+    }
+    s
+}
+
+/// Decodes unpadded RFC 4648 base32 string in any case; fails on
+/// non-alphabet characters and non-zero trailing bits.
+#[cfg(feature = "std")]
+fn base32_decode(s: &str) -> Option<Vec<u8>> {
+    let mut data = Vec::with_capacity(s.len() * 5 / 8);
+    let mut buffer = 0u16;
+    let mut bits = 0u8;
+    for c in s.bytes() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|a| *a == c.to_ascii_lowercase())?;
+        buffer = (buffer << 5) | value as u16;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            data.push((buffer >> bits) as u8);
+        }
+    }
+    if buffer & ((1 << bits) - 1) != 0 {
+        return None;
+    }
+    Some(data)
+}
+
+/// Computes checksum of the Tor onion v3 address for a given public key.
+#[cfg(feature = "tor")]
+fn onion_v3_checksum(key: &[u8]) -> [u8; 2] {
+    use tiny_keccak::{Hasher, Sha3};
+
+    let mut hasher = Sha3::v256();
+    hasher.update(b".onion checksum");
+    hasher.update(key);
+    hasher.update(&[ONION_V3_VERSION]);
+    let mut hash = [0u8; 32];
+    hasher.finalize(&mut hash);
+    [hash[0], hash[1]]
+}
+
+/// Checks that the string is a valid DNS name in lower case: it must consist
+/// of non-empty labels up to 63 characters, containing only ASCII letters,
+/// digits and hyphens (not at the label start or end); the top-level label
+/// can't be numeric.
+fn check_dns_name(name: &str) -> Result<(), DecodeError> {
+    if name.is_empty() || name.len() > DNS_MAX_LEN {
+        return Err(DecodeError::InvalidAddr);
+    }
+    for label in name.split('.') {
+        if label.is_empty()
+            || label.len() > DNS_LABEL_MAX_LEN
+            || label.starts_with('-')
+            || label.ends_with('-')
+            || !label.bytes().all(|c| {
+                c.is_ascii_lowercase() || c.is_ascii_digit() || c == b'-'
+            })
+        {
+            return Err(DecodeError::InvalidAddr);
+        }
+    }
+    if name
+        .rsplit('.')
+        .next()
+        .map(|tld| tld.bytes().all(|c| c.is_ascii_digit()))
+        .unwrap_or_default()
+    {
+        return Err(DecodeError::InvalidAddr);
+    }
+    Ok(())
+}
+
+/// Checks inline DNS name representation, returning the name.
+fn check_inline_dns(addr: &RawAddr) -> Result<&str, DecodeError> {
+    let len = addr[0] as usize;
+    if len == 0 || len > DNS_INLINE_LEN {
+        return Err(DecodeError::InvalidAddr);
+    }
+    if addr[len + 1..].iter().any(|byte| *byte != 0) {
+        return Err(DecodeError::InvalidAddr);
+    }
+    let name = core::str::from_utf8(&addr[1..=len])
+        .map_err(|_| DecodeError::InvalidAddr)?;
+    check_dns_name(name)?;
+    Ok(name)
+}
+
+/// Checks that the data represent a compressed Secp256k1 public key.
+#[cfg(feature = "std")]
+fn check_pubkey(key: &[u8]) -> Result<(), DecodeError> {
+    if key.len() != 33 || (key[0] != 0x02 && key[0] != 0x03) {
+        return Err(DecodeError::InvalidPubkey);
+    }
+    #[cfg(feature = "bitcoin")]
+    bitcoin::secp256k1::PublicKey::from_slice(key)
+        .map_err(|_| DecodeError::InvalidPubkey)?;
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+impl UniformAddr {
+    /// Formats host and port parts of the address, without the transport.
+    ///
+    /// Address data which can't be rendered as a host name of its format (for
+    /// instance, a malformed inline DNS name, or an onion v3 key when the
+    /// `tor` feature is disabled and its checksum can't be computed) is
+    /// formatted as a raw hex string in the form of `<format:hex>`, so this
+    /// function never fails on the data it formats.
+    fn fmt_host_port(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.addr_format {
+            AddrFormat::IpV4 => {
+                let mut ip = [0u8; 4];
+                ip.copy_from_slice(&self.addr[ADDR_LEN - 4..]);
+                Display::fmt(&Ipv4Addr::from(ip), f)?
+            }
+            AddrFormat::IpV6 => {
+                let mut ip = [0u8; 16];
+                ip.copy_from_slice(&self.addr[ADDR_LEN - 16..]);
+                write!(f, "[{}]", Ipv6Addr::from(ip))?
+            }
+            AddrFormat::OnionV2 => write!(
+                f,
+                "{}.onion",
+                base32_encode(&self.addr[ADDR_LEN - ONION_V2_LEN..])
+            )?,
+            #[cfg(feature = "tor")]
+            AddrFormat::OnionV3 => {
+                match OnionAddrV3::from_uniform_addr_lossy(*self) {
+                    Ok(onion) => Display::fmt(&onion, f)?,
+                    Err(_) => self.fmt_raw(f)?,
+                }
+            }
+            // Onion v3 hostname can't be produced without its checksum
+            #[cfg(not(feature = "tor"))]
+            AddrFormat::OnionV3 => self.fmt_raw(f)?,
+            AddrFormat::Lightning => f.write_str(&self.addr.to_hex())?,
+            AddrFormat::Dns => match check_inline_dns(&self.addr) {
+                Ok(name) => f.write_str(name)?,
+                Err(_) => self.fmt_raw(f)?,
+            },
+            AddrFormat::DnsHash => {
+                f.write_str(&self.addr[ADDR_LEN - ADDR_HASH_LEN..].to_hex())?
+            }
+            AddrFormat::I2p => match I2pAddr::from_uniform_addr_lossy(*self) {
+                Ok(i2p) => Display::fmt(&i2p, f)?,
+                Err(_) => self.fmt_raw(f)?,
+            },
+        }
+        if let Some(port) = self.port {
+            write!(f, ":{}", port)?;
+        }
+        Ok(())
+    }
+
+    /// Formats address data which can't be represented as a host name as
+    /// `<format:hex>`.
+    fn fmt_raw(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "<{}:{}>", self.addr_format, self.addr.to_hex())
+    }
+
+    /// Parses host and optional port, assuming the transport is already
+    /// stripped from the string.
+    fn from_host_port(
+        s: &str,
+        transport: Option<Transport>,
+    ) -> Result<UniformAddr, DecodeError> {
+        let (host, port) = if let Some(rest) = s.strip_prefix('[') {
+            let (host, rest) =
+                rest.split_once(']').ok_or(DecodeError::InvalidAddr)?;
+            let port = match rest {
+                "" => None,
+                port => Some(
+                    port.strip_prefix(':').ok_or(DecodeError::InvalidAddr)?,
+                ),
+            };
+            let ip = Ipv6Addr::from_str(host)
+                .map_err(|_| DecodeError::InvalidAddr)?;
+            (ip.to_uniform_addr(), port)
+        } else {
+            let (host, port) = match s.rsplit_once(':') {
+                // IPv6 address without square brackets can't have a port
+                Some((host, _)) if host.contains(':') => (s, None),
+                Some((host, port)) => (host, Some(port)),
+                None => (s, None),
+            };
+            (UniformAddr::from_host(host)?, port)
+        };
+        let port = port
+            .map(|port| {
+                u16::from_str(port)
+                    .ok()
+                    .filter(|port| *port != 0)
+                    .ok_or(DecodeError::InvalidPort)
+            })
+            .transpose()?;
+        Ok(UniformAddr {
+            port,
+            transport,
+            ..host
+        })
+    }
+
+    /// Parses host part of the textual address representation.
+    fn from_host(host: &str) -> Result<UniformAddr, DecodeError> {
+        if host.is_empty() {
+            return Err(DecodeError::InsufficientData);
+        }
+        let mut addr = [0u8; ADDR_LEN];
+        let addr_format = if let Some(onion) = strip_suffix(host, ".onion") {
+            let data = base32_decode(onion).ok_or(DecodeError::InvalidAddr)?;
+            match data.len() {
+                ONION_V2_LEN => {
+                    addr[ADDR_LEN - ONION_V2_LEN..].copy_from_slice(&data);
+                    AddrFormat::OnionV2
+                }
+                #[cfg(feature = "tor")]
+                _ => {
+                    return OnionAddrV3::from_onion_data(&data)
+                        .map(|onion| onion.to_uniform_addr())
+                }
+                #[cfg(not(feature = "tor"))]
+                _ => return Err(DecodeError::UnsupportedAddrFormat),
+            }
+        } else if let Ok(ip) = Ipv4Addr::from_str(host) {
+            return Ok(ip.to_uniform_addr());
+        } else if let Ok(ip) = Ipv6Addr::from_str(host) {
+            return Ok(ip.to_uniform_addr());
+        } else if host.len() == ADDR_LEN * 2 {
+            let key = Vec::<u8>::from_hex(host)
+                .map_err(|_| DecodeError::InvalidPubkey)?;
+            check_pubkey(&key)?;
+            addr.copy_from_slice(&key);
+            AddrFormat::Lightning
+        } else if host.len() == ADDR_HASH_LEN * 2 {
+            let hash = Vec::<u8>::from_hex(host)
+                .map_err(|_| DecodeError::InvalidAddr)?;
+            addr[ADDR_LEN - ADDR_HASH_LEN..].copy_from_slice(&hash);
+            AddrFormat::DnsHash
+        } else if strip_suffix(host, I2P_SUFFIX).is_some() {
+            return I2pAddr::from_str(host).map(|i2p| i2p.to_uniform_addr());
+        } else if let Ok(name) = DnsName::from_str(host) {
+            return Ok(name.to_uniform_addr());
+        } else {
+            return Err(DecodeError::UnknownAddrFormat);
+        };
+        Ok(UniformAddr {
+            addr_format,
+            addr,
+            port: None,
+            transport: None,
+        })
+    }
+}
+
+/// Returns host name without the given suffix, if the host has that suffix
+/// (compared case-insensitively).
+#[cfg(feature = "std")]
+fn strip_suffix<'host>(host: &'host str, suffix: &str) -> Option<&'host str> {
+    host.len()
+        .checked_sub(suffix.len())
+        .filter(|pos| host.is_char_boundary(*pos))
+        .map(|pos| host.split_at(pos))
+        .filter(|(_, s)| s.eq_ignore_ascii_case(suffix))
+        .map(|(name, _)| name)
+}
+
+/// Splits optional transport scheme from the rest of the address string.
+#[cfg(feature = "std")]
+fn split_transport(s: &str) -> Result<(Option<Transport>, &str), DecodeError> {
+    match s.split_once("://") {
+        Some((scheme, rest)) => Ok((Some(Transport::from_str(scheme)?), rest)),
+        None => Ok((None, s)),
+    }
+}
+
+#[cfg(feature = "std")]
+impl Display for UniformAddr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(transport) = self.transport {
+            write!(f, "{}://", transport)?;
+        }
+        self.fmt_host_port(f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl FromStr for UniformAddr {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (transport, rest) = split_transport(s)?;
+        if rest.contains('@') {
+            // Use `NodeAddr` for parsing node public key with its address
+            return Err(DecodeError::ExcessiveData);
+        }
+        UniformAddr::from_host_port(rest, transport)
+    }
+}
+
+/// Address of a Lightning network peer, consisting of the node public key and
+/// the network address at which the node can be reached.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
+pub struct NodeAddr {
+    /// Node public key in compressed Secp256k1 form
+    pub node_id: [u8; 33],
+
+    /// Network address of the node
+    pub addr: UniformAddr,
+}
+
+#[cfg(feature = "std")]
+impl Display for NodeAddr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(transport) = self.addr.transport {
+            write!(f, "{}://", transport)?;
+        }
+        write!(f, "{}@", self.node_id.to_hex())?;
+        self.addr.fmt_host_port(f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl FromStr for NodeAddr {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (transport, rest) = split_transport(s)?;
+        let (node_id, addr) =
+            rest.split_once('@').ok_or(DecodeError::InsufficientData)?;
+        let key = Vec::<u8>::from_hex(node_id)
+            .map_err(|_| DecodeError::InvalidPubkey)?;
+        check_pubkey(&key)?;
+        let mut node_id = [0u8; 33];
+        node_id.copy_from_slice(&key);
+        let addr = UniformAddr::from_host_port(addr, transport)?;
+        if addr.addr_format == AddrFormat::Lightning {
+            return Err(DecodeError::UnsupportedAddrFormat);
+        }
+        Ok(NodeAddr { node_id, addr })
+    }
+}
+
+/// Tor onion service v3 address, identified by the ed25519 public key of the
+/// service.
+///
+/// In the uniform encoding the key is stored with a leading zero byte; the
+/// textual representation is `<base32(key | checksum | version)>.onion`, with
+/// checksum and version being verified during parsing. Deprecated onion v2
+/// addresses are rejected with [`DecodeError::DeprecatedAddrFormat`].
+#[cfg(feature = "tor")]
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, From)]
+pub struct OnionAddrV3([u8; ONION_V3_KEY_LEN]);
+
+#[cfg(feature = "tor")]
+impl OnionAddrV3 {
+    /// Constructs onion address from the ed25519 public key of the service.
+    #[inline]
+    pub fn from_public_key(key: [u8; ONION_V3_KEY_LEN]) -> Self {
+        OnionAddrV3(key)
+    }
+
+    /// Returns ed25519 public key of the onion service.
+    #[inline]
+    pub fn public_key(&self) -> [u8; ONION_V3_KEY_LEN] { self.0 }
+
+    /// Computes two-byte checksum used in the textual representation of the
+    /// address.
+    #[inline]
+    pub fn checksum(&self) -> [u8; 2] { onion_v3_checksum(&self.0) }
+
+    /// Constructs address from the data encoded in the onion hostname,
+    /// verifying its checksum and version byte.
+    #[cfg(feature = "std")]
+    fn from_onion_data(data: &[u8]) -> Result<Self, DecodeError> {
+        match data.len() {
+            ONION_V2_LEN => return Err(DecodeError::DeprecatedAddrFormat),
+            len if len == ONION_V3_KEY_LEN + 3 => {}
+            _ => return Err(DecodeError::InvalidAddr),
+        }
+        let mut key = [0u8; ONION_V3_KEY_LEN];
+        key.copy_from_slice(&data[..ONION_V3_KEY_LEN]);
+        let onion = OnionAddrV3(key);
+        if data[ONION_V3_KEY_LEN + 2] != ONION_V3_VERSION
+            || data[ONION_V3_KEY_LEN..ONION_V3_KEY_LEN + 2] != onion.checksum()
+        {
+            return Err(DecodeError::InvalidAddr);
+        }
+        Ok(onion)
+    }
+}
+
+#[cfg(all(feature = "std", feature = "tor"))]
+impl Display for OnionAddrV3 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut data = self.0.to_vec();
+        data.extend(self.checksum());
+        data.push(ONION_V3_VERSION);
+        write!(f, "{}.onion", base32_encode(&data))
+    }
+}
+
+#[cfg(all(feature = "std", feature = "tor"))]
+impl FromStr for OnionAddrV3 {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let onion =
+            strip_suffix(s, ".onion").ok_or(DecodeError::InvalidAddr)?;
+        let data = base32_decode(onion).ok_or(DecodeError::InvalidAddr)?;
+        OnionAddrV3::from_onion_data(&data)
+    }
+}
+
+#[cfg(feature = "tor")]
+impl Uniform for OnionAddrV3 {
+    #[inline]
+    fn addr_format(&self) -> AddrFormat { AddrFormat::OnionV3 }
+
+    #[inline]
+    fn addr(&self) -> RawAddr {
+        let mut addr = [0u8; ADDR_LEN];
+        addr[ADDR_LEN - ONION_V3_KEY_LEN..].copy_from_slice(&self.0);
+        addr
+    }
+
+    #[inline]
+    fn port(&self) -> Option<u16> { None }
+
+    #[inline]
+    fn transport(&self) -> Option<Transport> { None }
+
+    #[inline]
+    fn from_uniform_addr(addr: UniformAddr) -> Result<Self, DecodeError>
+    where
+        Self: Sized,
+    {
+        if addr.port.is_some() || addr.transport.is_some() {
+            return Err(DecodeError::ExcessiveData);
+        }
+        OnionAddrV3::from_uniform_addr_lossy(addr)
+    }
+
+    #[inline]
+    fn from_uniform_addr_lossy(addr: UniformAddr) -> Result<Self, DecodeError>
+    where
+        Self: Sized,
+    {
+        match addr.addr_format {
+            AddrFormat::OnionV3 => {}
+            AddrFormat::OnionV2 => {
+                return Err(DecodeError::DeprecatedAddrFormat)
+            }
+            _ => return Err(DecodeError::UnsupportedAddrFormat),
+        }
+        if addr.addr[..ADDR_LEN - ONION_V3_KEY_LEN]
+            .iter()
+            .any(|byte| *byte != 0)
+        {
+            return Err(DecodeError::InvalidAddr);
+        }
+        let mut key = [0u8; ONION_V3_KEY_LEN];
+        key.copy_from_slice(&addr.addr[ADDR_LEN - ONION_V3_KEY_LEN..]);
+        Ok(OnionAddrV3(key))
+    }
+}
+
+#[cfg(feature = "tor")]
+impl Strategy for OnionAddrV3 {
+    type Strategy = strategies::UsingUniformAddr;
+}
+
+/// Suffix of I2P hostnames containing base32-encoded destination hash
+#[cfg(feature = "std")]
+const I2P_SUFFIX: &str = ".b32.i2p";
+
+/// I2P destination address, represented by SHA256 hash of the destination.
+///
+/// The textual representation is `<base32(hash)>.b32.i2p`.
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, From)]
+pub struct I2pAddr([u8; ADDR_HASH_LEN]);
+
+impl I2pAddr {
+    /// Constructs I2P address from the hash of the destination.
+    #[inline]
+    pub fn from_hash(hash: [u8; ADDR_HASH_LEN]) -> Self { I2pAddr(hash) }
+
+    /// Returns hash of the I2P destination.
+    #[inline]
+    pub fn hash(&self) -> [u8; ADDR_HASH_LEN] { self.0 }
+}
+
+#[cfg(feature = "std")]
+impl Display for I2pAddr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", base32_encode(&self.0), I2P_SUFFIX)
+    }
+}
+
+#[cfg(feature = "std")]
+impl FromStr for I2pAddr {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let data = strip_suffix(s, I2P_SUFFIX)
+            .and_then(base32_decode)
+            .filter(|data| data.len() == ADDR_HASH_LEN)
+            .ok_or(DecodeError::InvalidAddr)?;
+        let mut hash = [0u8; ADDR_HASH_LEN];
+        hash.copy_from_slice(&data);
+        Ok(I2pAddr(hash))
+    }
+}
+
+impl Uniform for I2pAddr {
+    #[inline]
+    fn addr_format(&self) -> AddrFormat { AddrFormat::I2p }
+
+    #[inline]
+    fn addr(&self) -> RawAddr {
+        let mut addr = [0u8; ADDR_LEN];
+        addr[ADDR_LEN - ADDR_HASH_LEN..].copy_from_slice(&self.0);
+        addr
+    }
+
+    #[inline]
+    fn port(&self) -> Option<u16> { None }
+
+    #[inline]
+    fn transport(&self) -> Option<Transport> { None }
+
+    #[inline]
+    fn from_uniform_addr(addr: UniformAddr) -> Result<Self, DecodeError>
+    where
+        Self: Sized,
+    {
+        if addr.port.is_some() || addr.transport.is_some() {
+            return Err(DecodeError::ExcessiveData);
+        }
+        I2pAddr::from_uniform_addr_lossy(addr)
+    }
+
+    #[inline]
+    fn from_uniform_addr_lossy(addr: UniformAddr) -> Result<Self, DecodeError>
+    where
+        Self: Sized,
+    {
+        if addr.addr_format != AddrFormat::I2p {
+            return Err(DecodeError::UnsupportedAddrFormat);
+        }
+        if addr.addr[..ADDR_LEN - ADDR_HASH_LEN]
+            .iter()
+            .any(|byte| *byte != 0)
+        {
+            return Err(DecodeError::InvalidAddr);
+        }
+        let mut hash = [0u8; ADDR_HASH_LEN];
+        hash.copy_from_slice(&addr.addr[ADDR_LEN - ADDR_HASH_LEN..]);
+        Ok(I2pAddr(hash))
+    }
+}
+
+impl Strategy for I2pAddr {
+    type Strategy = strategies::UsingUniformAddr;
+}
+
+/// DNS host name, kept in lower case.
+///
+/// Names up to 32 bytes are uniformly encoded inline with
+/// [`AddrFormat::Dns`]; longer names are encoded as their SHA256 hash with
+/// [`AddrFormat::DnsHash`], so the name must be transferred out of band and
+/// recovered with [`DnsName::from_hashed_uniform_addr`].
+#[cfg(feature = "std")]
+#[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display)]
+#[display(inner)]
+pub struct DnsName(String);
+
+#[cfg(feature = "std")]
+impl DnsName {
+    /// Returns DNS name as a string slice.
+    #[inline]
+    pub fn as_str(&self) -> &str { &self.0 }
+
+    /// Detects whether the name fits the inline uniform representation
+    /// ([`AddrFormat::Dns`]).
+    #[inline]
+    pub fn is_inline(&self) -> bool { self.0.len() <= DNS_INLINE_LEN }
+
+    /// Computes SHA256 hash of the name used in [`AddrFormat::DnsHash`]
+    /// representation.
+    pub fn name_hash(&self) -> [u8; ADDR_HASH_LEN] {
+        use bitcoin_hashes::{sha256, Hash};
+        sha256::Hash::hash(self.0.as_bytes()).into_inner()
+    }
+
+    /// Reconstructs DNS name from the uniform address and the name provided
+    /// out of band, checking that the name matches the address. Works both
+    /// for inline and hashed names.
+    pub fn from_hashed_uniform_addr(
+        addr: UniformAddr,
+        name: &str,
+    ) -> Result<Self, DecodeError> {
+        let name = DnsName::from_str(name)?;
+        if name.to_uniform_addr().addr != addr.addr
+            || name.addr_format() != addr.addr_format
+        {
+            return Err(DecodeError::InvalidAddr);
+        }
+        Ok(name)
+    }
+}
+
+#[cfg(feature = "std")]
+impl FromStr for DnsName {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.to_ascii_lowercase();
+        check_dns_name(&name)?;
+        Ok(DnsName(name))
+    }
+}
+
+#[cfg(feature = "std")]
+impl Uniform for DnsName {
+    #[inline]
+    fn addr_format(&self) -> AddrFormat {
+        if self.is_inline() {
+            AddrFormat::Dns
+        } else {
+            AddrFormat::DnsHash
+        }
+    }
+
+    fn addr(&self) -> RawAddr {
+        let mut addr = [0u8; ADDR_LEN];
+        if self.is_inline() {
+            addr[0] = self.0.len() as u8;
+            addr[1..=self.0.len()].copy_from_slice(self.0.as_bytes());
+        } else {
+            addr[ADDR_LEN - ADDR_HASH_LEN..].copy_from_slice(&self.name_hash());
+        }
+        addr
+    }
+
+    #[inline]
+    fn port(&self) -> Option<u16> { None }
+
+    #[inline]
+    fn transport(&self) -> Option<Transport> { None }
+
+    #[inline]
+    fn from_uniform_addr(addr: UniformAddr) -> Result<Self, DecodeError>
+    where
+        Self: Sized,
+    {
+        if addr.port.is_some() || addr.transport.is_some() {
+            return Err(DecodeError::ExcessiveData);
+        }
+        DnsName::from_uniform_addr_lossy(addr)
+    }
+
+    /// Constructs DNS name from an inline uniform representation. Hashed
+    /// names fail with [`DecodeError::InsufficientData`]; use
+    /// [`DnsName::from_hashed_uniform_addr`] for them.
+    fn from_uniform_addr_lossy(addr: UniformAddr) -> Result<Self, DecodeError>
+    where
+        Self: Sized,
+    {
+        match addr.addr_format {
+            AddrFormat::Dns => check_inline_dns(&addr.addr)
+                .map(|name| DnsName(name.to_owned())),
+            AddrFormat::DnsHash => Err(DecodeError::InsufficientData),
+            _ => Err(DecodeError::UnsupportedAddrFormat),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Strategy for DnsName {
+    type Strategy = strategies::UsingUniformAddr;
+}
+
+#[cfg(feature = "std")]
 impl Uniform for IpAddr {
     #[inline]
     fn addr_format(&self) -> AddrFormat {
@@ -404,6 +1207,7 @@ impl Uniform for IpAddr {
     }
 }
 
+#[cfg(feature = "std")]
 impl Uniform for Ipv4Addr {
     #[inline]
     fn addr_format(&self) -> AddrFormat { AddrFormat::IpV4 }
@@ -446,6 +1250,7 @@ impl Uniform for Ipv4Addr {
     }
 }
 
+#[cfg(feature = "std")]
 impl Uniform for Ipv6Addr {
     #[inline]
     fn addr_format(&self) -> AddrFormat { AddrFormat::IpV6 }
@@ -488,6 +1293,7 @@ impl Uniform for Ipv6Addr {
     }
 }
 
+#[cfg(feature = "std")]
 impl Uniform for SocketAddr {
     #[inline]
     fn addr_format(&self) -> AddrFormat {
@@ -543,6 +1349,7 @@ impl Uniform for SocketAddr {
     }
 }
 
+#[cfg(feature = "std")]
 impl Uniform for SocketAddrV4 {
     #[inline]
     fn addr_format(&self) -> AddrFormat { AddrFormat::IpV4 }
@@ -586,6 +1393,7 @@ impl Uniform for SocketAddrV4 {
     }
 }
 
+#[cfg(feature = "std")]
 impl Uniform for SocketAddrV6 {
     #[inline]
     fn addr_format(&self) -> AddrFormat { AddrFormat::IpV6 }
@@ -633,26 +1441,32 @@ impl Strategy for UniformAddr {
     type Strategy = strategies::UsingUniformAddr;
 }
 
+#[cfg(feature = "std")]
 impl Strategy for IpAddr {
     type Strategy = strategies::UsingUniformAddr;
 }
 
+#[cfg(feature = "std")]
 impl Strategy for Ipv4Addr {
     type Strategy = strategies::UsingUniformAddr;
 }
 
+#[cfg(feature = "std")]
 impl Strategy for Ipv6Addr {
     type Strategy = strategies::UsingUniformAddr;
 }
 
+#[cfg(feature = "std")]
 impl Strategy for SocketAddr {
     type Strategy = strategies::UsingUniformAddr;
 }
 
+#[cfg(feature = "std")]
 impl Strategy for SocketAddrV4 {
     type Strategy = strategies::UsingUniformAddr;
 }
 
+#[cfg(feature = "std")]
 impl Strategy for SocketAddrV6 {
     type Strategy = strategies::UsingUniformAddr;
 }
@@ -660,7 +1474,7 @@ impl Strategy for SocketAddrV6 {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::strict_deserialize;
+    use crate::{strict_deserialize, strict_deserialize_canonical, Error};
 
     fn gen_ipv4_addrs() -> Vec<Ipv4Addr> {
         let vars = [0u8, 1, 32, 48, 64, 127, 168, 192, 254, 255];
@@ -728,10 +1542,14 @@ mod test {
         assert_eq!(OnionV2 as u8, 2);
         assert_eq!(OnionV3 as u8, 3);
         assert_eq!(Lightning as u8, 4);
+        assert_eq!(Dns as u8, 5);
+        assert_eq!(DnsHash as u8, 6);
+        assert_eq!(I2p as u8, 7);
 
         test_encoding_enum_u8_exhaustive!(
             crate => AddrFormat;
-            IpV4 => 0u8, IpV6 => 1u8, OnionV2 => 2u8, OnionV3 => 3u8, Lightning => 4u8
+            IpV4 => 0u8, IpV6 => 1u8, OnionV2 => 2u8, OnionV3 => 3u8, Lightning => 4u8,
+            Dns => 5u8, DnsHash => 6u8, I2p => 7u8
         ).unwrap();
         test_encoding_enum_u8_exhaustive!(
             crate => Transport;
@@ -755,13 +1573,413 @@ mod test {
         assert_eq!(OnionV2.to_string(), "onion(v2)");
         assert_eq!(OnionV3.to_string(), "onion(v3)");
         assert_eq!(Lightning.to_string(), "lightning");
+        assert_eq!(Dns.to_string(), "dns");
+        assert_eq!(DnsHash.to_string(), "dns(hash)");
+        assert_eq!(I2p.to_string(), "i2p");
 
-        /*
         assert_eq!(Transport::from_str("tcp").unwrap(), Tcp);
         assert_eq!(Transport::from_str("udp").unwrap(), Udp);
         assert_eq!(Transport::from_str("mtcp").unwrap(), Mtcp);
         assert_eq!(Transport::from_str("quic").unwrap(), Quic);
-         */
+        assert_eq!(
+            Transport::from_str("http"),
+            Err(DecodeError::UnknownTransport)
+        );
+    }
+
+    #[test]
+    fn text_roundtrip() {
+        for s in [
+            "127.0.0.1",
+            "tcp://127.0.0.1:9735",
+            "[::1]",
+            "udp://[2001:db8::ff00:42:8329]:8080",
+            "mtcp://expyuzz4wqqyqhjn.onion:9735",
+            "2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid.onion",
+            "quic://2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid.onion:443",
+            "02d1780dd0e08f4d873f94faf49d878d909a1174291d3fcac3e02a6c45e7eda744:9735",
+        ] {
+            let addr = UniformAddr::from_str(s).unwrap();
+            assert_eq!(addr.to_string(), s);
+            assert_eq!(UniformAddr::try_from(addr.to_raw_uniform()), Ok(addr));
+        }
+
+        let addr = UniformAddr::from_str("::1").unwrap();
+        assert_eq!(addr, Ipv6Addr::LOCALHOST.to_uniform_addr());
+        let addr = UniformAddr::from_str(
+            "TCP://2GZYXA5IHM7NSGGFXNU52RCK2VV4RVMDLKIU3ZZUI5DU4XYCLEN53WID.\
+             ONION",
+        );
+        let addr = addr.unwrap();
+        assert_eq!(addr.addr_format, AddrFormat::OnionV3);
+        assert_eq!(addr.transport, Some(Transport::Tcp));
+        assert_eq!(
+            addr.to_string(),
+            "tcp://2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid.\
+             onion"
+        );
+
+        let socket = SocketAddr::from_str("[2001:db8::1]:80").unwrap();
+        assert_eq!(
+            UniformAddr::from_str("[2001:db8::1]:80").unwrap(),
+            socket.to_uniform_addr()
+        );
+    }
+
+    #[test]
+    fn text_errors() {
+        use DecodeError::*;
+
+        let err = |s: &str| UniformAddr::from_str(s).unwrap_err();
+        assert_eq!(err(""), InsufficientData);
+        assert_eq!(err("999.0.0.1"), UnknownAddrFormat);
+        assert_eq!(err("-localhost"), UnknownAddrFormat);
+        assert_eq!(err("local_host"), UnknownAddrFormat);
+        assert_eq!(err("ftp://127.0.0.1"), UnknownTransport);
+        assert_eq!(err("127.0.0.1:"), InvalidPort);
+        assert_eq!(err("127.0.0.1:0"), InvalidPort);
+        assert_eq!(err("127.0.0.1:65536"), InvalidPort);
+        assert_eq!(err("[::1]9735"), InvalidAddr);
+        assert_eq!(err("[::1:9735"), InvalidAddr);
+        assert_eq!(err("expyuzz4wqqyqhj1.onion"), InvalidAddr);
+        // Last character is changed, breaking the checksum
+        assert_eq!(
+            err("2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wia.\
+                 onion"),
+            InvalidAddr
+        );
+        assert_eq!(
+            err("04d1780dd0e08f4d873f94faf49d878d909a1174291d3fcac3e02a6c45e7eda744"),
+            InvalidPubkey
+        );
+        assert_eq!(
+            err("02d1780dd0e08f4d873f94faf49d878d909a1174291d3fcac3e02a6c45e7eda744@127.0.0.1"),
+            ExcessiveData
+        );
+    }
+
+    #[test]
+    fn onion_v3() {
+        let s =
+            "2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid.onion";
+        let onion = OnionAddrV3::from_str(s).unwrap();
+        assert_eq!(onion.to_string(), s);
+        assert_eq!(OnionAddrV3::from_str(&s.to_uppercase()).unwrap(), onion);
+        assert_eq!(OnionAddrV3::from_public_key(onion.public_key()), onion);
+
+        let uniform = onion.to_uniform_addr();
+        assert_eq!(uniform.addr_format, AddrFormat::OnionV3);
+        assert_eq!(uniform.addr[0], 0);
+        assert_eq!(uniform.to_string(), s);
+        assert_eq!(UniformAddr::from_str(s).unwrap(), uniform);
+        assert_eq!(OnionAddrV3::from_uniform_addr(uniform), Ok(onion));
+        assert_eq!(
+            OnionAddrV3::from_raw_uniform_addr(onion.to_raw_uniform()),
+            Ok(onion)
+        );
+
+        let ser = onion.strict_serialize().unwrap();
+        assert_eq!(ser.len(), UNIFORM_LEN);
+        assert_eq!(ser, uniform.to_raw_uniform());
+        assert_eq!(OnionAddrV3::strict_deserialize(ser).unwrap(), onion);
+
+        let with_port = UniformAddr {
+            port: Some(9735),
+            ..uniform
+        };
+        assert_eq!(
+            OnionAddrV3::from_uniform_addr(with_port),
+            Err(DecodeError::ExcessiveData)
+        );
+        assert_eq!(OnionAddrV3::from_uniform_addr_lossy(with_port), Ok(onion));
+        let mut invalid = uniform;
+        invalid.addr[0] = 1;
+        assert_eq!(
+            OnionAddrV3::from_uniform_addr_lossy(invalid),
+            Err(DecodeError::InvalidAddr)
+        );
+        assert_eq!(
+            OnionAddrV3::from_uniform_addr(
+                Ipv4Addr::LOCALHOST.to_uniform_addr()
+            ),
+            Err(DecodeError::UnsupportedAddrFormat)
+        );
+    }
+
+    #[test]
+    fn onion_v3_invalid() {
+        // Wrong checksum
+        assert_eq!(
+            OnionAddrV3::from_str(
+                "2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wia.\
+                 onion"
+            ),
+            Err(DecodeError::InvalidAddr)
+        );
+        // Wrong version byte
+        let onion = OnionAddrV3::from_public_key([0xAB; 32]);
+        let mut data = onion.public_key().to_vec();
+        data.extend(onion.checksum());
+        data.push(2);
+        let s = format!("{}.onion", base32_encode(&data));
+        assert_eq!(OnionAddrV3::from_str(&s), Err(DecodeError::InvalidAddr));
+        assert_eq!(UniformAddr::from_str(&s), Err(DecodeError::InvalidAddr));
+        // Missing suffix and invalid characters
+        assert_eq!(
+            OnionAddrV3::from_str(
+                "2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid"
+            ),
+            Err(DecodeError::InvalidAddr)
+        );
+        assert_eq!(
+            OnionAddrV3::from_str("2gzyxa5ihm7nsggfxnu1.onion"),
+            Err(DecodeError::InvalidAddr)
+        );
+    }
+
+    #[test]
+    fn onion_v2_deprecation() {
+        let s = "expyuzz4wqqyqhjn.onion";
+        assert_eq!(
+            OnionAddrV3::from_str(s),
+            Err(DecodeError::DeprecatedAddrFormat)
+        );
+        let uniform = UniformAddr::from_str(s).unwrap();
+        assert_eq!(uniform.addr_format, AddrFormat::OnionV2);
+        assert_eq!(
+            OnionAddrV3::from_uniform_addr(uniform),
+            Err(DecodeError::DeprecatedAddrFormat)
+        );
+        assert_eq!(
+            OnionAddrV3::from_raw_uniform_addr_lossy(uniform.to_raw_uniform()),
+            Err(DecodeError::DeprecatedAddrFormat)
+        );
+    }
+
+    #[test]
+    fn dns_inline() {
+        let name = DnsName::from_str("Node.Example.COM").unwrap();
+        assert_eq!(name.as_str(), "node.example.com");
+        assert!(name.is_inline());
+
+        let uniform = name.to_uniform_addr();
+        assert_eq!(uniform.addr_format, AddrFormat::Dns);
+        assert_eq!(uniform.addr[0], 16);
+        assert_eq!(&uniform.addr[1..17], b"node.example.com");
+        assert_eq!(
+            UniformAddr::try_from(uniform.to_raw_uniform()),
+            Ok(uniform)
+        );
+        assert_eq!(DnsName::from_uniform_addr(uniform), Ok(name.clone()));
+
+        let ser = name.strict_serialize().unwrap();
+        assert_eq!(ser.len(), UNIFORM_LEN);
+        assert_eq!(DnsName::strict_deserialize(ser).unwrap(), name);
+
+        let s = "tcp://node.example.com:9735";
+        let addr = UniformAddr::from_str(s).unwrap();
+        assert_eq!(addr.to_string(), s);
+        assert_eq!(addr.addr, uniform.addr);
+        assert_eq!(
+            DnsName::from_uniform_addr(addr),
+            Err(DecodeError::ExcessiveData)
+        );
+        assert_eq!(DnsName::from_uniform_addr_lossy(addr), Ok(name));
+
+        let max = "a".repeat(28) + ".com";
+        let name = DnsName::from_str(&max).unwrap();
+        assert!(name.is_inline());
+        assert_eq!(name.addr_format(), AddrFormat::Dns);
+    }
+
+    #[test]
+    fn dns_hashed() {
+        let long = "a-very-long-host-name.subdomain.example.com";
+        let name = DnsName::from_str(long).unwrap();
+        assert!(!name.is_inline());
+
+        let uniform = name.to_uniform_addr();
+        assert_eq!(uniform.addr_format, AddrFormat::DnsHash);
+        assert_eq!(uniform.addr[0], 0);
+        assert_eq!(uniform.addr[1..], name.name_hash());
+        assert_eq!(
+            UniformAddr::try_from(uniform.to_raw_uniform()),
+            Ok(uniform)
+        );
+        assert_eq!(
+            DnsName::from_uniform_addr(uniform),
+            Err(DecodeError::InsufficientData)
+        );
+        assert_eq!(
+            DnsName::from_hashed_uniform_addr(uniform, long),
+            Ok(name.clone())
+        );
+        assert_eq!(
+            DnsName::from_hashed_uniform_addr(uniform, "other.example.com"),
+            Err(DecodeError::InvalidAddr)
+        );
+
+        assert_eq!(UniformAddr::from_str(long), Ok(uniform));
+        let text = uniform.to_string();
+        assert_eq!(text, name.name_hash().to_hex());
+        assert_eq!(UniformAddr::from_str(&text), Ok(uniform));
+
+        assert!(name.strict_serialize().is_ok());
+        assert!(
+            DnsName::strict_deserialize(name.strict_serialize().unwrap())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn dns_invalid() {
+        for name in [
+            "",
+            "example..com",
+            "-example.com",
+            "example-.com",
+            "exa_mple.com",
+            "127.0.0.1",
+            &"a".repeat(64),
+            &format!("{}.com", vec!["a".repeat(63); 4].join(".")),
+        ] {
+            assert_eq!(DnsName::from_str(name), Err(DecodeError::InvalidAddr));
+        }
+
+        let uniform =
+            DnsName::from_str("example.com").unwrap().to_uniform_addr();
+        for (pos, byte) in [(0, 0u8), (0, 33), (12, 1), (1, b'E')] {
+            let mut raw = uniform.to_raw_uniform();
+            raw[pos + 1] = byte;
+            assert_eq!(
+                UniformAddr::try_from(raw),
+                Err(DecodeError::InvalidAddr)
+            );
+        }
+    }
+
+    #[test]
+    fn display_malformed() {
+        let mut addr = [0u8; ADDR_LEN];
+        addr[1..].copy_from_slice(&[0xAB; 32]);
+        let uniform = UniformAddr {
+            addr_format: AddrFormat::Dns,
+            addr,
+            port: Some(80),
+            transport: None,
+        };
+        assert_eq!(uniform.to_string(), format!("<dns:{}>:80", addr.to_hex()));
+        assert!(UniformAddr::from_str(&uniform.to_string()).is_err());
+
+        addr[0] = 1;
+        for addr_format in [
+            AddrFormat::IpV4,
+            AddrFormat::IpV6,
+            AddrFormat::OnionV3,
+            AddrFormat::I2p,
+        ] {
+            let uniform = UniformAddr {
+                addr_format,
+                addr,
+                port: None,
+                transport: Some(Transport::Tcp),
+            };
+            let s = uniform.to_string();
+            assert!(s.starts_with("tcp://"));
+            let node = NodeAddr {
+                node_id: [2; 33],
+                addr: uniform,
+            };
+            assert!(node.to_string().ends_with(&s["tcp://".len()..]));
+        }
+        let uniform = UniformAddr {
+            addr_format: AddrFormat::OnionV3,
+            addr,
+            port: None,
+            transport: None,
+        };
+        assert_eq!(
+            uniform.to_string(),
+            format!("<onion(v3):{}>", addr.to_hex())
+        );
+    }
+
+    #[test]
+    fn i2p() {
+        let s = "udhdrtrcetjm5sxzskjyr5ztpeszydbh4dpl3pl4utgqqw2v4jna.b32.i2p";
+        let i2p = I2pAddr::from_str(s).unwrap();
+        assert_eq!(i2p.to_string(), s);
+        assert_eq!(I2pAddr::from_hash(i2p.hash()), i2p);
+
+        let uniform = i2p.to_uniform_addr();
+        assert_eq!(uniform.addr_format, AddrFormat::I2p);
+        assert_eq!(uniform.addr[0], 0);
+        assert_eq!(
+            UniformAddr::try_from(uniform.to_raw_uniform()),
+            Ok(uniform)
+        );
+        assert_eq!(I2pAddr::from_uniform_addr(uniform), Ok(i2p));
+
+        let ser = i2p.strict_serialize().unwrap();
+        assert_eq!(ser.len(), UNIFORM_LEN);
+        assert_eq!(I2pAddr::strict_deserialize(ser).unwrap(), i2p);
+
+        let s = format!("quic://{}:4567", s);
+        let addr = UniformAddr::from_str(&s).unwrap();
+        assert_eq!(addr.to_string(), s);
+        assert_eq!(I2pAddr::from_uniform_addr_lossy(addr), Ok(i2p));
+
+        let mut raw = uniform.to_raw_uniform();
+        raw[1] = 1;
+        assert_eq!(UniformAddr::try_from(raw), Err(DecodeError::InvalidAddr));
+        assert_eq!(
+            I2pAddr::from_str(
+                "udhdrtrcetjm5sxzskjyr5ztpeszydbh4dpl3pl4.b32.i2p"
+            ),
+            Err(DecodeError::InvalidAddr)
+        );
+        assert_eq!(
+            I2pAddr::from_uniform_addr(
+                OnionAddrV3::from_public_key([1; 32]).to_uniform_addr()
+            ),
+            Err(DecodeError::UnsupportedAddrFormat)
+        );
+    }
+
+    #[test]
+    fn unknown_addr_format() {
+        let mut raw = Ipv4Addr::LOCALHOST.to_raw_uniform();
+        for format in [8u8, 0x7F, 0xFF] {
+            raw[0] = format;
+            assert_eq!(
+                UniformAddr::try_from(raw),
+                Err(DecodeError::UnknownAddrFormat)
+            );
+            assert!(UniformAddr::strict_deserialize(raw).is_err());
+        }
+    }
+
+    #[test]
+    fn node_addr_text() {
+        let s = "tcp://02d1780dd0e08f4d873f94faf49d878d909a1174291d3fcac3e02a6c45e7eda744@127.0.0.1:9735";
+        let node = NodeAddr::from_str(s).unwrap();
+        assert_eq!(node.node_id[0], 0x02);
+        assert_eq!(node.addr.addr_format, AddrFormat::IpV4);
+        assert_eq!(node.addr.port, Some(9735));
+        assert_eq!(node.addr.transport, Some(Transport::Tcp));
+        assert_eq!(node.to_string(), s);
+
+        let s = "02d1780dd0e08f4d873f94faf49d878d909a1174291d3fcac3e02a6c45e7eda744@2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid.onion";
+        assert_eq!(NodeAddr::from_str(s).unwrap().to_string(), s);
+
+        assert_eq!(
+            NodeAddr::from_str("127.0.0.1:9735"),
+            Err(DecodeError::InsufficientData)
+        );
+        assert_eq!(
+            NodeAddr::from_str("0202@127.0.0.1:9735"),
+            Err(DecodeError::InvalidPubkey)
+        );
     }
 
     #[test]
@@ -944,6 +2162,24 @@ mod test {
         );
     }
 
+    #[test]
+    fn uniform_canonical() {
+        let ipv4 = Ipv4Addr::new(127, 0, 0, 1);
+        let raw = ipv4.to_raw_uniform();
+
+        // IPv4 addresses are decoded as IPv6 ones
+        let ipv6 = strict_deserialize::<Ipv6Addr>(raw).unwrap();
+        assert_eq!(ipv6, Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0x7F00, 1));
+        assert_eq!(
+            strict_deserialize_canonical::<Ipv6Addr>(raw).unwrap_err(),
+            Error::NonCanonical(core::any::type_name::<Ipv6Addr>())
+        );
+        assert_eq!(
+            strict_deserialize_canonical::<Ipv4Addr>(raw).unwrap(),
+            ipv4
+        );
+    }
+
     #[test]
     fn uniform_raw_roundtrip_ipv4() {
         for ip in gen_ipv4_addrs() {
@@ -1126,4 +2362,13 @@ mod test {
         assert_eq!(ser.len(), 37);
         assert_eq!(socket6, strict_deserialize(ser).unwrap());
     }
+
+    #[test]
+    fn encoded_len() {
+        assert_eq!(UniformAddr::FIXED_SIZE, Some(UNIFORM_LEN));
+        assert_eq!(SocketAddrV6::FIXED_SIZE, Some(37));
+        let addr = gen_ipv4_addrs()[1].to_uniform_addr();
+        assert_eq!(addr.strict_encoded_len(), Ok(37));
+        assert_eq!(addr.strict_serialize().unwrap().len(), 37);
+    }
 }
@@ -12,13 +12,17 @@
 // You should have received a copy of the Apache 2.0 License along with this
 // software. If not, see <https://opensource.org/licenses/Apache-2.0>.
 
-use std::borrow::Borrow;
-use std::cell::RefCell;
-use std::io;
-use std::rc::Rc;
-use std::sync::Arc;
+use alloc::borrow::Borrow;
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
 
-use crate::{Error, StrictDecode, StrictEncode};
+use crate::limits::{check_items, DepthGuard};
+use crate::schema::{LenPrefix, Primitive, StrictType, TypeDescr};
+use crate::{io, Error, StrictDecode, StrictEncode};
 
 impl StrictEncode for &[u8] {
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
@@ -29,9 +33,19 @@ impl StrictEncode for &[u8] {
         e.write_all(self)?;
         Ok(len)
     }
+
+    fn strict_encoded_len(&self) -> Result<usize, Error> {
+        Ok(self.len().strict_encoded_len()? + self.len())
+    }
 }
 
 impl<const LEN: usize> StrictEncode for [u8; LEN] {
+    const FIXED_SIZE: Option<usize> = if LEN > u16::MAX as usize {
+        None
+    } else {
+        Some(LEN)
+    };
+
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         if LEN > u16::MAX as usize {
             return Err(Error::ExceedMaxItems(LEN));
@@ -41,6 +55,15 @@ impl<const LEN: usize> StrictEncode for [u8; LEN] {
     }
 }
 
+impl<const LEN: usize> StrictEncode for &[u8; LEN] {
+    const FIXED_SIZE: Option<usize> = <[u8; LEN]>::FIXED_SIZE;
+
+    #[inline]
+    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
+        (*self).strict_encode(e)
+    }
+}
+
 impl<const LEN: usize> StrictDecode for [u8; LEN] {
     fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
         if LEN > u16::MAX as usize {
@@ -56,11 +79,16 @@ impl StrictEncode for Box<[u8]> {
     fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
         <[u8]>::borrow(self).strict_encode(e)
     }
+
+    fn strict_encoded_len(&self) -> Result<usize, Error> {
+        <[u8]>::borrow(self).strict_encoded_len()
+    }
 }
 
 impl StrictDecode for Box<[u8]> {
     fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
         let len = usize::strict_decode(&mut d)?;
+        check_items(len)?;
         let mut ret = vec![0u8; len];
         d.read_exact(&mut ret)?;
         Ok(ret.into_boxed_slice())
@@ -71,6 +99,8 @@ impl<T> StrictEncode for Rc<T>
 where
     T: StrictEncode,
 {
+    const FIXED_SIZE: Option<usize> = T::FIXED_SIZE;
+
     fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
         T::borrow(self).strict_encode(e)
     }
@@ -81,6 +111,7 @@ where
     T: StrictDecode,
 {
     fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
+        let _depth = DepthGuard::enter()?;
         Ok(Rc::new(T::strict_decode(d)?))
     }
 }
@@ -89,6 +120,8 @@ impl<T> StrictEncode for RefCell<T>
 where
     T: StrictEncode,
 {
+    const FIXED_SIZE: Option<usize> = T::FIXED_SIZE;
+
     fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
         self.borrow().strict_encode(e)
     }
@@ -107,6 +140,8 @@ impl<T> StrictEncode for Arc<T>
 where
     T: StrictEncode,
 {
+    const FIXED_SIZE: Option<usize> = T::FIXED_SIZE;
+
     fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
         T::borrow(self).strict_encode(e)
     }
@@ -117,6 +152,7 @@ where
     T: StrictDecode,
 {
     fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
+        let _depth = DepthGuard::enter()?;
         Ok(Arc::new(T::strict_decode(d)?))
     }
 }
@@ -125,12 +161,20 @@ impl StrictEncode for &str {
     fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
         self.as_bytes().strict_encode(e)
     }
+
+    fn strict_encoded_len(&self) -> Result<usize, Error> {
+        self.as_bytes().strict_encoded_len()
+    }
 }
 
 impl StrictEncode for String {
     fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
         self.as_bytes().strict_encode(e)
     }
+
+    fn strict_encoded_len(&self) -> Result<usize, Error> {
+        self.as_bytes().strict_encoded_len()
+    }
 }
 
 impl StrictDecode for String {
@@ -139,6 +183,45 @@ impl StrictDecode for String {
     }
 }
 
+impl<const LEN: usize> StrictType for [u8; LEN] {
+    fn strict_type() -> TypeDescr { TypeDescr::Bytes(LEN as u16) }
+}
+
+impl StrictType for Box<[u8]> {
+    fn strict_type() -> TypeDescr {
+        TypeDescr::list(LenPrefix::U16, TypeDescr::Primitive(Primitive::U8))
+    }
+}
+
+impl<T> StrictType for Rc<T>
+where
+    T: StrictType,
+{
+    fn strict_type() -> TypeDescr { T::strict_type() }
+}
+
+impl<T> StrictType for RefCell<T>
+where
+    T: StrictType,
+{
+    fn strict_type() -> TypeDescr { T::strict_type() }
+}
+
+impl<T> StrictType for Arc<T>
+where
+    T: StrictType,
+{
+    fn strict_type() -> TypeDescr { T::strict_type() }
+}
+
+impl StrictType for &str {
+    fn strict_type() -> TypeDescr { TypeDescr::Unicode(LenPrefix::U16) }
+}
+
+impl StrictType for String {
+    fn strict_type() -> TypeDescr { TypeDescr::Unicode(LenPrefix::U16) }
+}
+
 #[cfg(test)]
 pub mod test {
     use crate::{strict_deserialize, strict_serialize};
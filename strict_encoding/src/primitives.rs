@@ -15,14 +15,17 @@
 //! Taking implementation of little-endian integer encoding
 
 use core::time::Duration;
-use std::io;
-use std::io::{Read, Write};
 
 use amplify::num::u24;
 
 use super::{Error, StrictDecode, StrictEncode};
+use crate::io;
+use crate::io::{Read, Write};
+use crate::schema::{Primitive, StrictType, TypeDescr};
 
 impl StrictEncode for () {
+    const FIXED_SIZE: Option<usize> = Some(0);
+
     fn strict_encode<E: Write>(&self, _: E) -> Result<usize, Error> { Ok(0) }
 }
 
@@ -31,6 +34,8 @@ impl StrictDecode for () {
 }
 
 impl StrictEncode for bool {
+    const FIXED_SIZE: Option<usize> = Some(1);
+
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         (*self as u8).strict_encode(&mut e)
     }
@@ -47,6 +52,8 @@ impl StrictDecode for bool {
 }
 
 impl StrictEncode for u8 {
+    const FIXED_SIZE: Option<usize> = Some(1);
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         e.write_all(&[*self][..])?;
@@ -64,6 +71,8 @@ impl StrictDecode for u8 {
 }
 
 impl StrictEncode for i8 {
+    const FIXED_SIZE: Option<usize> = Some(1);
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         e.write_all(&self.to_le_bytes())?;
@@ -81,6 +90,8 @@ impl StrictDecode for i8 {
 }
 
 impl StrictEncode for u16 {
+    const FIXED_SIZE: Option<usize> = Some(2);
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         e.write_all(&self.to_le_bytes())?;
@@ -98,6 +109,8 @@ impl StrictDecode for u16 {
 }
 
 impl StrictEncode for i16 {
+    const FIXED_SIZE: Option<usize> = Some(2);
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         e.write_all(&self.to_le_bytes())?;
@@ -115,6 +128,8 @@ impl StrictDecode for i16 {
 }
 
 impl StrictEncode for u24 {
+    const FIXED_SIZE: Option<usize> = Some(3);
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         e.write_all(&self.to_le_bytes())?;
@@ -132,6 +147,8 @@ impl StrictDecode for u24 {
 }
 
 impl StrictEncode for u32 {
+    const FIXED_SIZE: Option<usize> = Some(4);
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         e.write_all(&self.to_le_bytes())?;
@@ -149,6 +166,8 @@ impl StrictDecode for u32 {
 }
 
 impl StrictEncode for i32 {
+    const FIXED_SIZE: Option<usize> = Some(4);
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         e.write_all(&self.to_le_bytes())?;
@@ -166,6 +185,8 @@ impl StrictDecode for i32 {
 }
 
 impl StrictEncode for u64 {
+    const FIXED_SIZE: Option<usize> = Some(8);
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         e.write_all(&self.to_le_bytes())?;
@@ -183,6 +204,8 @@ impl StrictDecode for u64 {
 }
 
 impl StrictEncode for i64 {
+    const FIXED_SIZE: Option<usize> = Some(8);
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         e.write_all(&self.to_le_bytes())?;
@@ -200,6 +223,8 @@ impl StrictDecode for i64 {
 }
 
 impl StrictEncode for u128 {
+    const FIXED_SIZE: Option<usize> = Some(16);
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         e.write_all(&self.to_le_bytes())?;
@@ -215,6 +240,8 @@ impl StrictDecode for u128 {
 }
 
 impl StrictEncode for i128 {
+    const FIXED_SIZE: Option<usize> = Some(16);
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         e.write_all(&self.to_le_bytes())?;
@@ -237,6 +264,15 @@ impl StrictEncode for usize {
         let size = *self as u16;
         size.strict_encode(&mut e)
     }
+
+    // `FIXED_SIZE` is not defined since values above `u16::MAX` can't be
+    // encoded
+    fn strict_encoded_len(&self) -> Result<usize, Error> {
+        if *self > u16::MAX as usize {
+            return Err(Error::ExceedMaxItems(*self));
+        }
+        Ok(2)
+    }
 }
 
 impl StrictDecode for usize {
@@ -246,6 +282,8 @@ impl StrictDecode for usize {
 }
 
 impl StrictEncode for f32 {
+    const FIXED_SIZE: Option<usize> = Some(4);
+
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         e.write_all(&self.to_le_bytes())?;
         Ok(4)
@@ -261,6 +299,8 @@ impl StrictDecode for f32 {
 }
 
 impl StrictEncode for f64 {
+    const FIXED_SIZE: Option<usize> = Some(8);
+
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         e.write_all(&self.to_le_bytes())?;
         Ok(8)
@@ -276,6 +316,8 @@ impl StrictDecode for f64 {
 }
 
 impl StrictEncode for Duration {
+    const FIXED_SIZE: Option<usize> = Some(12);
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
         (self.as_secs(), self.subsec_nanos()).strict_encode(e)
@@ -285,13 +327,58 @@ impl StrictEncode for Duration {
 impl StrictDecode for Duration {
     #[inline]
     fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
-        Ok(Self::new(
-            u64::strict_decode(&mut d)?,
-            u32::strict_decode(&mut d)?,
-        ))
+        let secs = u64::strict_decode(&mut d)?;
+        let nanos = u32::strict_decode(&mut d)?;
+        // `Duration::new` carries nanoseconds exceeding a second into seconds,
+        // which may overflow; such encodings are also non-canonical
+        if nanos >= 1_000_000_000 {
+            return Err(Error::ValueOutOfRange(
+                "Duration nanoseconds",
+                0..1_000_000_000,
+                nanos as u128,
+            ));
+        }
+        Ok(Self::new(secs, nanos))
     }
 }
 
+macro_rules! impl_strict_type_primitive {
+    ($ty:ty, $primitive:ident) => {
+        impl StrictType for $ty {
+            #[inline]
+            fn strict_type() -> TypeDescr {
+                TypeDescr::Primitive(Primitive::$primitive)
+            }
+        }
+    };
+}
+
+impl_strict_type_primitive!(bool, Bool);
+impl_strict_type_primitive!(u8, U8);
+impl_strict_type_primitive!(i8, I8);
+impl_strict_type_primitive!(u16, U16);
+impl_strict_type_primitive!(i16, I16);
+impl_strict_type_primitive!(u24, U24);
+impl_strict_type_primitive!(u32, U32);
+impl_strict_type_primitive!(i32, I32);
+impl_strict_type_primitive!(u64, U64);
+impl_strict_type_primitive!(i64, I64);
+impl_strict_type_primitive!(u128, U128);
+impl_strict_type_primitive!(i128, I128);
+impl_strict_type_primitive!(f32, F32);
+impl_strict_type_primitive!(f64, F64);
+// `usize` is used for collection lengths and is always encoded as `u16`
+impl_strict_type_primitive!(usize, U16);
+
+impl StrictType for () {
+    #[inline]
+    fn strict_type() -> TypeDescr { TypeDescr::Unit }
+}
+
+impl StrictType for Duration {
+    fn strict_type() -> TypeDescr { <(u64, u32)>::strict_type() }
+}
+
 #[cfg(feature = "chrono")]
 mod _chrono {
     use chrono::{DateTime, NaiveDateTime, Utc};
@@ -331,6 +418,16 @@ mod _chrono {
             Ok(DateTime::from_utc(naive, Utc))
         }
     }
+
+    impl StrictType for NaiveDateTime {
+        #[inline]
+        fn strict_type() -> TypeDescr { TypeDescr::Primitive(Primitive::I64) }
+    }
+
+    impl StrictType for DateTime<Utc> {
+        #[inline]
+        fn strict_type() -> TypeDescr { NaiveDateTime::strict_type() }
+    }
 }
 
 #[cfg(test)]
@@ -470,6 +567,26 @@ pub mod test {
         .unwrap();
     }
 
+    #[test]
+    fn test_duration_nanos() {
+        let mut ser = Duration::new(u64::MAX, 999_999_999)
+            .strict_serialize()
+            .unwrap();
+        assert_eq!(
+            strict_deserialize::<Duration>(&ser),
+            Ok(Duration::new(u64::MAX, 999_999_999))
+        );
+        ser[8..].copy_from_slice(&1_000_000_000u32.to_le_bytes());
+        assert_eq!(
+            strict_deserialize::<Duration>(&ser),
+            Err(Error::ValueOutOfRange(
+                "Duration nanoseconds",
+                0..1_000_000_000,
+                1_000_000_000
+            ))
+        );
+    }
+
     #[test]
     #[cfg(feature = "chrono")]
     fn test_chrono_encoding() {
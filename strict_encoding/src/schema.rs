@@ -0,0 +1,761 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Machine-readable description of the strict encoding layout of data types.
+//!
+//! Types implementing [`StrictType`] provide a [`TypeDescr`] describing the
+//! exact wire layout produced by their [`StrictEncode`] implementation: field
+//! names and order, enum representation and variant values, TLV ids and
+//! widths of collection length prefixes. The description can be shared with
+//! non-rust implementations: it serializes to JSON (with `serde` feature) and
+//! has its own stable binary form defined by its strict encoding.
+//!
+//! `StrictType` can be derived with `#[derive(StrictType)]` (or
+//! `#[derive(NetworkType)]` for types using network encoding with TLVs),
+//! which takes into account the same `#[strict_encoding(...)]` attributes as
+//! the encoding derivation macros.
+//!
+//! NB: descriptions of recursive data types are not supported.
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::limits::DepthGuard;
+use crate::{io, Error, StrictDecode, StrictEncode};
+
+/// Types providing description of their strict encoding layout
+pub trait StrictType {
+    /// Returns description of the strict encoding layout of the type
+    fn strict_type() -> TypeDescr;
+}
+
+/// Width of the length prefix used in strict encoding of collections
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "lowercase")
+)]
+#[repr(u8)]
+pub enum LenPrefix {
+    /// Length encoded as `u16` value (default for `Vec`, `String` and other
+    /// standard collections)
+    #[display("u16")]
+    U16 = 2,
+
+    /// Length encoded as `u24` value (used by [`crate::MediumVec`])
+    #[display("u24")]
+    U24 = 3,
+
+    /// Length encoded as `u32` value (used by [`crate::LargeVec`])
+    #[display("u32")]
+    U32 = 4,
+}
+
+/// Primitive (non-composite) types supported by strict encoding
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "lowercase")
+)]
+#[display(lowercase)]
+#[repr(u8)]
+pub enum Primitive {
+    /// Unsigned 8-bit integer
+    U8 = 0x00,
+    /// Unsigned 16-bit little-endian integer
+    U16 = 0x01,
+    /// Unsigned 24-bit little-endian integer
+    U24 = 0x02,
+    /// Unsigned 32-bit little-endian integer
+    U32 = 0x03,
+    /// Unsigned 64-bit little-endian integer
+    U64 = 0x04,
+    /// Unsigned 128-bit little-endian integer
+    U128 = 0x05,
+    /// Unsigned 256-bit little-endian integer
+    U256 = 0x06,
+    /// Unsigned 512-bit little-endian integer
+    U512 = 0x07,
+    /// Unsigned 1024-bit little-endian integer
+    U1024 = 0x08,
+
+    /// Signed 8-bit integer
+    I8 = 0x10,
+    /// Signed 16-bit little-endian integer
+    I16 = 0x11,
+    /// Signed 32-bit little-endian integer
+    I32 = 0x13,
+    /// Signed 64-bit little-endian integer
+    I64 = 0x14,
+    /// Signed 128-bit little-endian integer
+    I128 = 0x15,
+    /// Signed 256-bit little-endian integer
+    I256 = 0x16,
+    /// Signed 512-bit little-endian integer
+    I512 = 0x17,
+    /// Signed 1024-bit little-endian integer
+    I1024 = 0x18,
+
+    /// IEEE 754 single-precision float
+    F32 = 0x20,
+    /// IEEE 754 double-precision float
+    F64 = 0x21,
+
+    /// Boolean value encoded as a single byte, which must be either 0 or 1
+    Bool = 0x30,
+}
+
+impl LenPrefix {
+    /// Returns the narrowest length prefix able to hold lengths up to `max`
+    /// (see [`crate::Confined`]); lengths above `u24::MAX` use `u32` prefix.
+    pub const fn with_max_len(max: usize) -> LenPrefix {
+        if max <= u16::MAX as usize {
+            LenPrefix::U16
+        } else if max < 1 << 24 {
+            LenPrefix::U24
+        } else {
+            LenPrefix::U32
+        }
+    }
+}
+
+impl Primitive {
+    /// Returns number of bytes taken by the primitive in strict encoding
+    pub fn byte_len(self) -> usize {
+        match self {
+            Primitive::U8 | Primitive::I8 | Primitive::Bool => 1,
+            Primitive::U16 | Primitive::I16 => 2,
+            Primitive::U24 => 3,
+            Primitive::U32 | Primitive::I32 | Primitive::F32 => 4,
+            Primitive::U64 | Primitive::I64 | Primitive::F64 => 8,
+            Primitive::U128 | Primitive::I128 => 16,
+            Primitive::U256 | Primitive::I256 => 32,
+            Primitive::U512 | Primitive::I512 => 64,
+            Primitive::U1024 | Primitive::I1024 => 128,
+        }
+    }
+}
+
+/// Description of the strict encoding layout of a data type
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub enum TypeDescr {
+    /// Unit type, which does not take any bytes
+    Unit,
+
+    /// Primitive type
+    Primitive(Primitive),
+
+    /// Fixed-size byte string of the given length
+    Bytes(u16),
+
+    /// UTF-8 encoded string prefixed with its length in bytes
+    Unicode(LenPrefix),
+
+    /// Length-prefixed list of items
+    List(LenPrefix, Box<TypeDescr>),
+
+    /// Length-prefixed set of unique items ordered by their value
+    Set(LenPrefix, Box<TypeDescr>),
+
+    /// Length-prefixed map of unique keys ordered by their value and
+    /// associated values
+    Map(LenPrefix, Box<TypeDescr>, Box<TypeDescr>),
+
+    /// Optional value prefixed with a byte which must be either 0 (no value)
+    /// or 1
+    Option(Box<TypeDescr>),
+
+    /// Sequence of values of different types
+    Tuple(Vec<TypeDescr>),
+
+    /// Structure with fields encoded one after another
+    Struct(StructDescr),
+
+    /// Enumeration with variants distinguished by the value of an integer
+    /// tag
+    Enum(EnumDescr),
+
+    /// Data structure prefixed with a byte holding its version, which must be
+    /// equal to the given one (see [`crate::version`])
+    Versioned(u8, Box<TypeDescr>),
+
+    /// Type using some other encoding (like bitcoin consensus encoding),
+    /// which layout is not covered by this description. Contains type name.
+    Opaque(String),
+}
+
+impl TypeDescr {
+    /// Constructs list description
+    pub fn list(prefix: LenPrefix, item: TypeDescr) -> TypeDescr {
+        TypeDescr::List(prefix, Box::new(item))
+    }
+
+    /// Constructs set description
+    pub fn set(prefix: LenPrefix, item: TypeDescr) -> TypeDescr {
+        TypeDescr::Set(prefix, Box::new(item))
+    }
+
+    /// Constructs map description
+    pub fn map(prefix: LenPrefix, key: TypeDescr, val: TypeDescr) -> TypeDescr {
+        TypeDescr::Map(prefix, Box::new(key), Box::new(val))
+    }
+
+    /// Constructs optional value description
+    pub fn option(inner: TypeDescr) -> TypeDescr {
+        TypeDescr::Option(Box::new(inner))
+    }
+
+    /// Constructs description of a data structure of the given `version`
+    pub fn versioned(version: u8, inner: TypeDescr) -> TypeDescr {
+        TypeDescr::Versioned(version, Box::new(inner))
+    }
+
+    /// Constructs description of opaque type with the given name
+    pub fn opaque(name: impl ToString) -> TypeDescr {
+        TypeDescr::Opaque(name.to_string())
+    }
+
+    /// Constructs description of a structure with named fields
+    pub fn named_struct(
+        name: impl ToString,
+        fields: impl IntoIterator<Item = (&'static str, TypeDescr)>,
+    ) -> TypeDescr {
+        TypeDescr::Struct(StructDescr {
+            name: name.to_string(),
+            fields: fields
+                .into_iter()
+                .map(|(name, ty)| FieldDescr::named(name, ty))
+                .collect(),
+            tlvs: vec![],
+            unknown_tlvs: false,
+        })
+    }
+
+    /// For strings and collections returns the same description using the
+    /// provided length prefix; other types are returned unchanged
+    pub fn with_len_prefix(self, prefix: LenPrefix) -> TypeDescr {
+        match self {
+            TypeDescr::Unicode(_) => TypeDescr::Unicode(prefix),
+            TypeDescr::List(_, item) => TypeDescr::List(prefix, item),
+            TypeDescr::Set(_, item) => TypeDescr::Set(prefix, item),
+            TypeDescr::Map(_, key, val) => TypeDescr::Map(prefix, key, val),
+            other => other,
+        }
+    }
+
+    /// For optional values returns description of the inner value; for all
+    /// other types returns the description itself
+    pub fn into_non_optional(self) -> TypeDescr {
+        match self {
+            TypeDescr::Option(inner) => *inner,
+            other => other,
+        }
+    }
+}
+
+/// Description of a structure field or enum variant associated value
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct FieldDescr {
+    /// Field name; `None` for unnamed (tuple) fields
+    pub name: Option<String>,
+
+    /// Field type
+    pub ty: TypeDescr,
+}
+
+impl FieldDescr {
+    /// Constructs description of a named field
+    pub fn named(name: impl ToString, ty: TypeDescr) -> FieldDescr {
+        FieldDescr {
+            name: Some(name.to_string()),
+            ty,
+        }
+    }
+
+    /// Constructs description of an unnamed (tuple) field
+    pub fn unnamed(ty: TypeDescr) -> FieldDescr {
+        FieldDescr { name: None, ty }
+    }
+}
+
+/// Description of a structure field encoded as a TLV record
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct TlvDescr {
+    /// TLV type id
+    pub id: u64,
+
+    /// Name of the field
+    pub name: Option<String>,
+
+    /// Type of the value stored in the TLV record
+    pub ty: TypeDescr,
+}
+
+/// Description of a structure
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct StructDescr {
+    /// Name of the structure
+    pub name: String,
+
+    /// Fields, in the order of their encoding
+    pub fields: Vec<FieldDescr>,
+
+    /// Fields encoded as TLV records following the fields, ordered by their
+    /// TLV type id
+    pub tlvs: Vec<TlvDescr>,
+
+    /// Whether the structure keeps unknown odd TLV records
+    pub unknown_tlvs: bool,
+}
+
+/// Description of an enum variant
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct VariantDescr {
+    /// Name of the variant
+    pub name: String,
+
+    /// Value of the enum tag encoding the variant
+    pub value: u64,
+
+    /// Values associated with the variant, in the order of their encoding
+    pub fields: Vec<FieldDescr>,
+}
+
+/// Description of an enum
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct EnumDescr {
+    /// Name of the enum
+    pub name: String,
+
+    /// Integer type used to encode the enum tag
+    pub repr: Primitive,
+
+    /// Whether variant tags are assigned according to the variant order
+    /// (`by_order`) or according to the variant values (`by_value`)
+    pub by_order: bool,
+
+    /// Encoded variants
+    pub variants: Vec<VariantDescr>,
+}
+
+impl StrictEncode for LenPrefix {
+    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
+        (*self as u8).strict_encode(e)
+    }
+}
+
+impl StrictDecode for LenPrefix {
+    fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
+        match u8::strict_decode(d)? {
+            2 => Ok(LenPrefix::U16),
+            3 => Ok(LenPrefix::U24),
+            4 => Ok(LenPrefix::U32),
+            wrong => Err(Error::EnumValueNotKnown("LenPrefix", wrong as usize)),
+        }
+    }
+}
+
+impl StrictEncode for Primitive {
+    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
+        (*self as u8).strict_encode(e)
+    }
+}
+
+impl StrictDecode for Primitive {
+    fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
+        Ok(match u8::strict_decode(d)? {
+            0x00 => Primitive::U8,
+            0x01 => Primitive::U16,
+            0x02 => Primitive::U24,
+            0x03 => Primitive::U32,
+            0x04 => Primitive::U64,
+            0x05 => Primitive::U128,
+            0x06 => Primitive::U256,
+            0x07 => Primitive::U512,
+            0x08 => Primitive::U1024,
+            0x10 => Primitive::I8,
+            0x11 => Primitive::I16,
+            0x13 => Primitive::I32,
+            0x14 => Primitive::I64,
+            0x15 => Primitive::I128,
+            0x16 => Primitive::I256,
+            0x17 => Primitive::I512,
+            0x18 => Primitive::I1024,
+            0x20 => Primitive::F32,
+            0x21 => Primitive::F64,
+            0x30 => Primitive::Bool,
+            wrong => {
+                return Err(Error::EnumValueNotKnown(
+                    "Primitive",
+                    wrong as usize,
+                ))
+            }
+        })
+    }
+}
+
+const TYPE_UNIT: u8 = 0x00;
+const TYPE_PRIMITIVE: u8 = 0x01;
+const TYPE_BYTES: u8 = 0x02;
+const TYPE_UNICODE: u8 = 0x03;
+const TYPE_LIST: u8 = 0x04;
+const TYPE_SET: u8 = 0x05;
+const TYPE_MAP: u8 = 0x06;
+const TYPE_OPTION: u8 = 0x07;
+const TYPE_TUPLE: u8 = 0x08;
+const TYPE_STRUCT: u8 = 0x10;
+const TYPE_ENUM: u8 = 0x11;
+const TYPE_VERSIONED: u8 = 0x12;
+const TYPE_OPAQUE: u8 = 0xFF;
+
+// Type descriptions are recursive, so their encoding and decoding is
+// performed with dynamic readers and writers to avoid infinite
+// monomorphization of the generic encoding methods.
+impl TypeDescr {
+    fn encode_dyn(&self, mut e: &mut dyn io::Write) -> Result<usize, Error> {
+        Ok(match self {
+            TypeDescr::Unit => strict_encode_list!(e; TYPE_UNIT),
+            TypeDescr::Primitive(primitive) => {
+                strict_encode_list!(e; TYPE_PRIMITIVE, primitive)
+            }
+            TypeDescr::Bytes(len) => strict_encode_list!(e; TYPE_BYTES, len),
+            TypeDescr::Unicode(prefix) => {
+                strict_encode_list!(e; TYPE_UNICODE, prefix)
+            }
+            TypeDescr::List(prefix, item) => {
+                strict_encode_list!(e; TYPE_LIST, prefix, item.as_ref())
+            }
+            TypeDescr::Set(prefix, item) => {
+                strict_encode_list!(e; TYPE_SET, prefix, item.as_ref())
+            }
+            TypeDescr::Map(prefix, key, val) => {
+                strict_encode_list!(e; TYPE_MAP, prefix, key.as_ref(), val.as_ref())
+            }
+            TypeDescr::Option(inner) => {
+                strict_encode_list!(e; TYPE_OPTION, inner.as_ref())
+            }
+            TypeDescr::Tuple(items) => {
+                strict_encode_list!(e; TYPE_TUPLE, items)
+            }
+            TypeDescr::Struct(descr) => {
+                strict_encode_list!(e; TYPE_STRUCT, descr)
+            }
+            TypeDescr::Enum(descr) => strict_encode_list!(e; TYPE_ENUM, descr),
+            TypeDescr::Versioned(version, inner) => {
+                strict_encode_list!(e; TYPE_VERSIONED, version, inner.as_ref())
+            }
+            TypeDescr::Opaque(name) => {
+                strict_encode_list!(e; TYPE_OPAQUE, name)
+            }
+        })
+    }
+
+    fn decode_dyn(mut d: &mut dyn io::Read) -> Result<Self, Error> {
+        let _depth = DepthGuard::enter()?;
+        Ok(match u8::strict_decode(&mut d)? {
+            TYPE_UNIT => TypeDescr::Unit,
+            TYPE_PRIMITIVE => {
+                TypeDescr::Primitive(Primitive::strict_decode(&mut d)?)
+            }
+            TYPE_BYTES => TypeDescr::Bytes(u16::strict_decode(&mut d)?),
+            TYPE_UNICODE => {
+                TypeDescr::Unicode(LenPrefix::strict_decode(&mut d)?)
+            }
+            TYPE_LIST => TypeDescr::list(
+                LenPrefix::strict_decode(&mut d)?,
+                TypeDescr::strict_decode(&mut d)?,
+            ),
+            TYPE_SET => TypeDescr::set(
+                LenPrefix::strict_decode(&mut d)?,
+                TypeDescr::strict_decode(&mut d)?,
+            ),
+            TYPE_MAP => TypeDescr::map(
+                LenPrefix::strict_decode(&mut d)?,
+                TypeDescr::strict_decode(&mut d)?,
+                TypeDescr::strict_decode(&mut d)?,
+            ),
+            TYPE_OPTION => TypeDescr::option(TypeDescr::strict_decode(&mut d)?),
+            TYPE_TUPLE => {
+                TypeDescr::Tuple(StrictDecode::strict_decode(&mut d)?)
+            }
+            TYPE_STRUCT => {
+                TypeDescr::Struct(StructDescr::strict_decode(&mut d)?)
+            }
+            TYPE_ENUM => TypeDescr::Enum(EnumDescr::strict_decode(&mut d)?),
+            TYPE_VERSIONED => TypeDescr::versioned(
+                u8::strict_decode(&mut d)?,
+                TypeDescr::strict_decode(&mut d)?,
+            ),
+            TYPE_OPAQUE => TypeDescr::Opaque(String::strict_decode(&mut d)?),
+            wrong => {
+                return Err(Error::EnumValueNotKnown(
+                    "TypeDescr",
+                    wrong as usize,
+                ))
+            }
+        })
+    }
+}
+
+impl StrictEncode for TypeDescr {
+    #[inline]
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        self.encode_dyn(&mut e)
+    }
+}
+
+impl StrictDecode for TypeDescr {
+    #[inline]
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        TypeDescr::decode_dyn(&mut d)
+    }
+}
+
+impl StrictEncode for FieldDescr {
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        Ok(strict_encode_list!(e; self.name, self.ty))
+    }
+}
+
+impl StrictDecode for FieldDescr {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        Ok(strict_decode_self!(d; name, ty; crate))
+    }
+}
+
+impl StrictEncode for TlvDescr {
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        Ok(strict_encode_list!(e; self.id, self.name, self.ty))
+    }
+}
+
+impl StrictDecode for TlvDescr {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        Ok(strict_decode_self!(d; id, name, ty; crate))
+    }
+}
+
+impl StrictEncode for StructDescr {
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        Ok(
+            strict_encode_list!(e; self.name, self.fields, self.tlvs, self.unknown_tlvs),
+        )
+    }
+}
+
+impl StrictDecode for StructDescr {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        Ok(strict_decode_self!(d; name, fields, tlvs, unknown_tlvs; crate))
+    }
+}
+
+impl StrictEncode for VariantDescr {
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        Ok(strict_encode_list!(e; self.name, self.value, self.fields))
+    }
+}
+
+impl StrictDecode for VariantDescr {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        Ok(strict_decode_self!(d; name, value, fields; crate))
+    }
+}
+
+impl StrictEncode for EnumDescr {
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        Ok(
+            strict_encode_list!(e; self.name, self.repr, self.by_order, self.variants),
+        )
+    }
+}
+
+impl StrictDecode for EnumDescr {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        Ok(strict_decode_self!(d; name, repr, by_order, variants; crate))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use super::*;
+    use crate::{LargeVec, MediumVec};
+
+    #[test]
+    fn collections() {
+        assert_eq!(
+            Vec::<u8>::strict_type(),
+            TypeDescr::list(
+                LenPrefix::U16,
+                TypeDescr::Primitive(Primitive::U8)
+            )
+        );
+        assert_eq!(
+            MediumVec::<u16>::strict_type(),
+            TypeDescr::list(
+                LenPrefix::U24,
+                TypeDescr::Primitive(Primitive::U16)
+            )
+        );
+        assert_eq!(
+            LargeVec::<String>::strict_type(),
+            TypeDescr::list(LenPrefix::U32, TypeDescr::Unicode(LenPrefix::U16))
+        );
+        assert_eq!(
+            BTreeSet::<bool>::strict_type(),
+            TypeDescr::set(
+                LenPrefix::U16,
+                TypeDescr::Primitive(Primitive::Bool)
+            )
+        );
+        assert_eq!(
+            BTreeMap::<u8, Option<[u8; 32]>>::strict_type(),
+            TypeDescr::map(
+                LenPrefix::U16,
+                TypeDescr::Primitive(Primitive::U8),
+                TypeDescr::option(TypeDescr::Bytes(32))
+            )
+        );
+        assert_eq!(
+            <(u8, ())>::strict_type(),
+            TypeDescr::Tuple(vec![
+                TypeDescr::Primitive(Primitive::U8),
+                TypeDescr::Unit
+            ])
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "bitcoin")]
+    fn bitcoin_types() {
+        use bitcoin::{Address, Amount, OutPoint, Script, Transaction, Txid};
+
+        assert_eq!(Txid::strict_type(), TypeDescr::Bytes(32));
+        assert_eq!(Amount::strict_type(), TypeDescr::Primitive(Primitive::U64));
+        assert_eq!(OutPoint::strict_type(), TypeDescr::opaque("OutPoint"));
+        assert_eq!(
+            Transaction::strict_type(),
+            TypeDescr::opaque("Transaction")
+        );
+        match Address::strict_type() {
+            TypeDescr::Struct(descr) => {
+                assert_eq!(
+                    descr.fields[0].ty,
+                    TypeDescr::Primitive(Primitive::U32)
+                );
+                assert!(matches!(descr.fields[1].ty, TypeDescr::Enum(_)));
+            }
+            _ => panic!("address must be described as a structure"),
+        }
+        assert_eq!(Script::strict_type(), Vec::<u8>::strict_type());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn json() {
+        let descr = TypeDescr::named_struct("Data", [
+            ("id", u16::strict_type()),
+            ("items", MediumVec::<Option<String>>::strict_type()),
+        ]);
+        let json = serde_json::to_string(&descr).unwrap();
+        assert_eq!(
+            json,
+            r#"{"struct":{"name":"Data","fields":[{"name":"id","ty":{"primitive":"u16"}},{"name":"items","ty":{"list":["u24",{"option":{"unicode":"u16"}}]}}],"tlvs":[],"unknownTlvs":false}}"#
+        );
+        assert_eq!(serde_json::from_str::<TypeDescr>(&json).unwrap(), descr);
+    }
+
+    #[test]
+    fn binary_roundtrip() {
+        let descr = TypeDescr::Enum(EnumDescr {
+            name: s!("Enum"),
+            repr: Primitive::U16,
+            by_order: false,
+            variants: vec![
+                VariantDescr {
+                    name: s!("First"),
+                    value: 1,
+                    fields: vec![],
+                },
+                VariantDescr {
+                    name: s!("Second"),
+                    value: 0x10,
+                    fields: vec![
+                        FieldDescr::unnamed(
+                            BTreeMap::<u8, String>::strict_type(),
+                        ),
+                        FieldDescr::named(
+                            "data",
+                            TypeDescr::Struct(StructDescr {
+                                name: s!("Data"),
+                                fields: vec![FieldDescr::named(
+                                    "bytes",
+                                    LargeVec::<u8>::strict_type(),
+                                )],
+                                tlvs: vec![TlvDescr {
+                                    id: 1,
+                                    name: Some(s!("tlv")),
+                                    ty: TypeDescr::opaque("Transaction"),
+                                }],
+                                unknown_tlvs: true,
+                            }),
+                        ),
+                    ],
+                },
+            ],
+        });
+        let ser = descr.strict_serialize().unwrap();
+        assert_eq!(TypeDescr::strict_deserialize(ser).unwrap(), descr);
+        assert_eq!(
+            TypeDescr::list(LenPrefix::U24, TypeDescr::Bytes(32))
+                .strict_serialize()
+                .unwrap(),
+            vec![TYPE_LIST, 3, TYPE_BYTES, 32, 0]
+        );
+    }
+}
@@ -0,0 +1,1190 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Bridge between serde data model and strict encoding.
+//!
+//! [`Serializer`] and [`Deserializer`] allow types implementing serde
+//! `Serialize`/`Deserialize` traits to be encoded in exactly the same layout
+//! as produced by [`StrictEncode`] derivation:
+//! - integers and floats are little-endian, `bool` is a single `0`/`1` byte;
+//! - strings, byte strings, sequences and maps are prefixed with `u16` number
+//!   of bytes or items;
+//! - options are prefixed with `0`/`1` tag byte;
+//! - structures, tuples and fixed-size arrays are encoded as a concatenation of
+//!   their fields;
+//! - enum variants are prefixed with `u8` variant index, matching `by_order`
+//!   strict encoding of enums (the default one).
+//!
+//! Strict encoding is not self-describing, so types relying on
+//! `deserialize_any` (untagged enums, flattened structures etc) can't be
+//! decoded; `char` values are not supported either. Maps are encoded in their
+//! iteration order, so only ordered maps (like `BTreeMap`) produce
+//! deterministic encoding.
+//!
+//! Serde data model has no distinct `usize` type: serde implementations for
+//! `usize` values go through `u64`, so they are encoded as 8 bytes, while
+//! [`StrictEncode`] for `usize` produces 2-byte `u16` value. Thus, structures
+//! with `usize` fields have different encoding when serialized with serde and
+//! with strict encoding derivation; such fields should use `u16` (or
+//! [`as_strict`] adapter) if the same layout is required.
+//!
+//! Types implementing [`StrictEncode`] and [`StrictDecode`] can be embedded
+//! into serde structures with `#[serde(with =
+//! "strict_encoding::serde::as_strict")]` attribute, see [`as_strict`].
+
+use std::fmt::Display;
+
+use serde::de::{self, DeserializeOwned, IntoDeserializer};
+use serde::ser::{self, Serialize};
+
+use crate::limits::{check_items, DepthGuard};
+use crate::{io, Error, StrictDecode, StrictEncode};
+
+/// Name of a newtype struct used by [`as_strict`] adapter to mark data which
+/// are already strict-encoded.
+const RAW_NAME: &str = "$strict_encoding::Raw";
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self { Error::Serde(msg.to_string()) }
+}
+
+impl de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self { Error::Serde(msg.to_string()) }
+}
+
+/// Serializes `value` using strict encoding rules into a byte vector.
+pub fn to_vec<T>(value: &T) -> Result<Vec<u8>, Error>
+where
+    T: Serialize + ?Sized,
+{
+    let mut data = vec![];
+    to_writer(&mut data, value)?;
+    Ok(data)
+}
+
+/// Serializes `value` using strict encoding rules into the `writer`, returning
+/// number of bytes written.
+pub fn to_writer<W, T>(writer: W, value: &T) -> Result<usize, Error>
+where
+    W: io::Write,
+    T: Serialize + ?Sized,
+{
+    let mut serializer = Serializer::new(writer);
+    value.serialize(&mut serializer)?;
+    Ok(serializer.len)
+}
+
+/// Deserializes value from strict-encoded data, failing with
+/// [`Error::DataNotEntirelyConsumed`] if not all of the data were used.
+pub fn from_slice<T>(data: impl AsRef<[u8]>) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let data = data.as_ref();
+    let mut decoder = data;
+    let rv = T::deserialize(&mut Deserializer::new(&mut decoder))
+        .map_err(|err| err.at_offset(data.len() - decoder.len()))?;
+
+    // Fail if data are not consumed entirely.
+    if decoder.is_empty() {
+        Ok(rv)
+    } else {
+        Err(Error::DataNotEntirelyConsumed)
+    }
+}
+
+/// Deserializes value from strict-encoded data provided by the `reader`.
+pub fn from_reader<R, T>(reader: R) -> Result<T, Error>
+where
+    R: io::Read,
+    T: DeserializeOwned,
+{
+    T::deserialize(&mut Deserializer::new(reader))
+}
+
+/// Serde serializer producing strict-encoded data
+#[derive(Debug)]
+pub struct Serializer<W: io::Write> {
+    writer: W,
+    len: usize,
+    raw: bool,
+}
+
+impl<W: io::Write> Serializer<W> {
+    /// Constructs serializer writing strict-encoded data into `writer`.
+    pub fn new(writer: W) -> Self {
+        Serializer {
+            writer,
+            len: 0,
+            raw: false,
+        }
+    }
+
+    /// Returns number of bytes written by the serializer.
+    #[inline]
+    pub fn len(&self) -> usize { self.len }
+
+    /// Detects whether serializer has not written any data yet.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Returns the inner writer.
+    #[inline]
+    pub fn into_inner(self) -> W { self.writer }
+
+    fn encode(&mut self, value: impl StrictEncode) -> Result<(), Error> {
+        self.len += value.strict_encode(&mut self.writer)?;
+        Ok(())
+    }
+
+    fn encode_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+    ) -> Result<(), Error> {
+        if variant_index > u8::MAX as u32 {
+            return Err(Error::EnumValueOverflow(name));
+        }
+        self.encode(variant_index as u8)
+    }
+
+    fn encode_len(&mut self, len: Option<usize>) -> Result<(), Error> {
+        let len = len.ok_or_else(|| {
+            Error::Serde(s!(
+                "collections of unknown length can't be strictly encoded"
+            ))
+        })?;
+        self.encode(len)
+    }
+}
+
+impl<W: io::Write> ser::Serializer for &mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> { self.encode(v) }
+
+    fn serialize_i8(self, v: i8) -> Result<(), Error> { self.encode(v) }
+
+    fn serialize_i16(self, v: i16) -> Result<(), Error> { self.encode(v) }
+
+    fn serialize_i32(self, v: i32) -> Result<(), Error> { self.encode(v) }
+
+    fn serialize_i64(self, v: i64) -> Result<(), Error> { self.encode(v) }
+
+    fn serialize_i128(self, v: i128) -> Result<(), Error> { self.encode(v) }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Error> { self.encode(v) }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Error> { self.encode(v) }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Error> { self.encode(v) }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Error> { self.encode(v) }
+
+    fn serialize_u128(self, v: u128) -> Result<(), Error> { self.encode(v) }
+
+    fn serialize_f32(self, v: f32) -> Result<(), Error> { self.encode(v) }
+
+    fn serialize_f64(self, v: f64) -> Result<(), Error> { self.encode(v) }
+
+    fn serialize_char(self, _: char) -> Result<(), Error> {
+        Err(Error::Serde(s!(
+            "`char` type is not supported by strict encoding"
+        )))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> { self.encode(v) }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        if self.raw {
+            self.raw = false;
+            self.writer.write_all(v)?;
+            self.len += v.len();
+            Ok(())
+        } else {
+            self.encode(v)
+        }
+    }
+
+    fn serialize_none(self) -> Result<(), Error> { self.encode(0u8) }
+
+    fn serialize_some<T>(self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.encode(1u8)?;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> { Ok(()) }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        _: &'static str,
+    ) -> Result<(), Error> {
+        self.encode_variant(name, variant_index)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.raw = name == RAW_NAME;
+        let res = value.serialize(&mut *self);
+        self.raw = false;
+        res
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.encode_variant(name, variant_index)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, Error> {
+        self.encode_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self, Error> { Ok(self) }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self, Error> {
+        self.encode_variant(name, variant_index)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, Error> {
+        self.encode_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self, Error> {
+        self.encode_variant(name, variant_index)?;
+        Ok(self)
+    }
+
+    fn is_human_readable(&self) -> bool { false }
+}
+
+impl<W: io::Write> ser::SerializeSeq for &mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> { Ok(()) }
+}
+
+impl<W: io::Write> ser::SerializeTuple for &mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> { Ok(()) }
+}
+
+impl<W: io::Write> ser::SerializeTupleStruct for &mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> { Ok(()) }
+}
+
+impl<W: io::Write> ser::SerializeTupleVariant for &mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> { Ok(()) }
+}
+
+impl<W: io::Write> ser::SerializeMap for &mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> { Ok(()) }
+}
+
+impl<W: io::Write> ser::SerializeStruct for &mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(
+        &mut self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> { Ok(()) }
+}
+
+impl<W: io::Write> ser::SerializeStructVariant for &mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(
+        &mut self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> { Ok(()) }
+}
+
+/// Serde deserializer reading strict-encoded data
+#[derive(Debug)]
+pub struct Deserializer<R: io::Read> {
+    reader: R,
+}
+
+impl<R: io::Read> Deserializer<R> {
+    /// Constructs deserializer reading strict-encoded data from `reader`.
+    pub fn new(reader: R) -> Self { Deserializer { reader } }
+
+    /// Returns the inner reader.
+    #[inline]
+    pub fn into_inner(self) -> R { self.reader }
+
+    fn decode<T: StrictDecode>(&mut self) -> Result<T, Error> {
+        T::strict_decode(&mut self.reader)
+    }
+
+    fn visit_items<'de, V>(
+        &mut self,
+        len: usize,
+        path: ItemPath,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let _depth = DepthGuard::enter()?;
+        visitor.visit_seq(Items {
+            de: self,
+            len,
+            index: 0,
+            path,
+        })
+    }
+}
+
+impl<'de, R: io::Read> de::Deserializer<'de> for &mut Deserializer<R> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, _: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(Error::Serde(s!("strict encoding is not self-describing \
+                             and requires data type to be known")))
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_bool(self.decode()?)
+    }
+
+    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_i8(self.decode()?)
+    }
+
+    fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_i16(self.decode()?)
+    }
+
+    fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_i32(self.decode()?)
+    }
+
+    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_i64(self.decode()?)
+    }
+
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_i128(self.decode()?)
+    }
+
+    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_u8(self.decode()?)
+    }
+
+    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_u16(self.decode()?)
+    }
+
+    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_u32(self.decode()?)
+    }
+
+    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_u64(self.decode()?)
+    }
+
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_u128(self.decode()?)
+    }
+
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_f32(self.decode()?)
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_f64(self.decode()?)
+    }
+
+    fn deserialize_char<V>(self, _: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(Error::Serde(s!(
+            "`char` type is not supported by strict encoding"
+        )))
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_string(self.decode()?)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_string(self.decode()?)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_byte_buf(self.decode()?)
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_byte_buf(self.decode()?)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.decode::<u8>()? {
+            0 => visitor.visit_none(),
+            1 => visitor.visit_some(self),
+            invalid => Err(Error::WrongOptionalEncoding(invalid)),
+        }
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if name == RAW_NAME {
+            visitor.visit_seq(RawBytes { de: self })
+        } else {
+            visitor.visit_newtype_struct(self)
+        }
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let len = self.decode::<usize>()?;
+        check_items(len)?;
+        self.visit_items(len, ItemPath::Index, visitor)
+    }
+
+    fn deserialize_tuple<V>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.visit_items(len, ItemPath::None, visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.visit_items(len, ItemPath::None, visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let len = self.decode::<usize>()?;
+        check_items(len)?;
+        let _depth = DepthGuard::enter()?;
+        visitor.visit_map(Items {
+            de: self,
+            len,
+            index: 0,
+            path: ItemPath::Index,
+        })
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.visit_items(fields.len(), ItemPath::Field(fields), visitor)
+            .map_err(|err| err.in_type(name))
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_enum(Variant {
+            de: self,
+            name,
+            variants,
+        })
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_any(visitor)
+    }
+
+    fn is_human_readable(&self) -> bool { false }
+}
+
+/// Access to enum, reading variant index
+struct Variant<'a, R: io::Read> {
+    de: &'a mut Deserializer<R>,
+    name: &'static str,
+    variants: &'static [&'static str],
+}
+
+impl<'de, 'a, R: io::Read> de::EnumAccess<'de> for Variant<'a, R> {
+    type Error = Error;
+    type Variant = VariantData<'a, R>;
+
+    fn variant_seed<V>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, VariantData<'a, R>), Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let index = self.de.decode::<u8>()? as usize;
+        let variant = *self
+            .variants
+            .get(index)
+            .ok_or(Error::EnumValueNotKnown(self.name, index))?;
+        let value = seed.deserialize(
+            IntoDeserializer::<Error>::into_deserializer(index as u32),
+        )?;
+        Ok((value, VariantData {
+            de: self.de,
+            name: self.name,
+            variant,
+        }))
+    }
+}
+
+/// Access to enum variant data, which adds variant and type name to the
+/// errors happening during the data decoding
+struct VariantData<'a, R: io::Read> {
+    de: &'a mut Deserializer<R>,
+    name: &'static str,
+    variant: &'static str,
+}
+
+impl<'de, 'a, R: io::Read> de::VariantAccess<'de> for VariantData<'a, R> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> { Ok(()) }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(&mut *self.de)
+            .map_err(|err| err.in_variant(self.variant).in_type(self.name))
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.de
+            .visit_items(len, ItemPath::None, visitor)
+            .map_err(|err| err.in_variant(self.variant).in_type(self.name))
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.de
+            .visit_items(fields.len(), ItemPath::Field(fields), visitor)
+            .map_err(|err| err.in_variant(self.variant).in_type(self.name))
+    }
+}
+
+/// Describes how the position of sequence item is reflected in the error path
+#[derive(Clone, Copy)]
+enum ItemPath {
+    /// Position is not reflected in the error path
+    None,
+    /// Items are named structure fields
+    Field(&'static [&'static str]),
+    /// Items are collection elements
+    Index,
+}
+
+/// Access to a known number of sequence items or map entries
+struct Items<'a, R: io::Read> {
+    de: &'a mut Deserializer<R>,
+    len: usize,
+    index: usize,
+    path: ItemPath,
+}
+
+impl<'a, R: io::Read> Items<'a, R> {
+    fn next<'de, T>(&mut self, seed: T) -> Result<T::Value, Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        let index = self.index;
+        seed.deserialize(&mut *self.de)
+            .map_err(|err| match self.path {
+                ItemPath::None => err,
+                ItemPath::Field(fields) => err.in_field(fields[index]),
+                ItemPath::Index => err.within_item(index),
+            })
+    }
+}
+
+impl<'de, 'a, R: io::Read> de::SeqAccess<'de> for Items<'a, R> {
+    type Error = Error;
+
+    fn next_element_seed<T>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        let item = self.next(seed)?;
+        self.index += 1;
+        Ok(Some(item))
+    }
+
+    fn size_hint(&self) -> Option<usize> { Some(self.len) }
+}
+
+impl<'de, 'a, R: io::Read> de::MapAccess<'de> for Items<'a, R> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        self.next(seed).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let value = self.next(seed)?;
+        self.index += 1;
+        Ok(value)
+    }
+
+    fn size_hint(&self) -> Option<usize> { Some(self.len) }
+}
+
+/// Access to the raw strict-encoded bytes of unknown length, provided byte by
+/// byte to [`as_strict`] adapter.
+struct RawBytes<'a, R: io::Read> {
+    de: &'a mut Deserializer<R>,
+}
+
+impl<'de, 'a, R: io::Read> de::SeqAccess<'de> for RawBytes<'a, R> {
+    type Error = Error;
+
+    fn next_element_seed<T>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+}
+
+/// Adapter for embedding types implementing [`StrictEncode`] and
+/// [`StrictDecode`] into serde data structures with
+/// `#[serde(with = "strict_encoding::serde::as_strict")]` field attribute.
+///
+/// With strict encoding [`Serializer`] the field is encoded exactly as its
+/// [`StrictEncode`] implementation does; other serde formats receive the
+/// strict-encoded data as a byte string.
+pub mod as_strict {
+    use std::fmt::{self, Formatter};
+    use std::io;
+    use std::marker::PhantomData;
+
+    use serde::de::{self, Error as _};
+    use serde::ser::{self, Error as _};
+
+    use super::RAW_NAME;
+    use crate::{StrictDecode, StrictEncode};
+
+    struct Bytes<'a>(&'a [u8]);
+
+    impl<'a> ser::Serialize for Bytes<'a> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: ser::Serializer,
+        {
+            serializer.serialize_bytes(self.0)
+        }
+    }
+
+    /// Serializes value with its [`StrictEncode`] implementation.
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: StrictEncode,
+        S: ser::Serializer,
+    {
+        let data = value.strict_serialize().map_err(S::Error::custom)?;
+        serializer.serialize_newtype_struct(RAW_NAME, &Bytes(&data))
+    }
+
+    /// Deserializes value with its [`StrictDecode`] implementation.
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: StrictDecode,
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(RAW_NAME, Visitor(PhantomData))
+    }
+
+    struct Visitor<T>(PhantomData<T>);
+
+    impl<'de, T> de::Visitor<'de> for Visitor<T>
+    where
+        T: StrictDecode,
+    {
+        type Value = T;
+
+        fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+            f.write_str("strict-encoded data")
+        }
+
+        fn visit_newtype_struct<D>(self, d: D) -> Result<T, D::Error>
+        where
+            D: de::Deserializer<'de>,
+        {
+            d.deserialize_bytes(self)
+        }
+
+        fn visit_bytes<E>(self, v: &[u8]) -> Result<T, E>
+        where
+            E: de::Error,
+        {
+            crate::strict_deserialize(v).map_err(E::custom)
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<T, A::Error>
+        where
+            A: de::SeqAccess<'de>,
+        {
+            let mut reader = SeqReader {
+                seq: &mut seq,
+                err: None,
+            };
+            let res = T::strict_decode(&mut reader);
+            if let Some(err) = reader.err {
+                return Err(err);
+            }
+            let value = res.map_err(A::Error::custom)?;
+            match seq.size_hint() {
+                Some(len) if len > 0 => Err(A::Error::custom(
+                    "strict-encoded data contain extra bytes",
+                )),
+                _ => Ok(value),
+            }
+        }
+    }
+
+    /// Reader pulling bytes from serde sequence one by one
+    struct SeqReader<'de, A: de::SeqAccess<'de>> {
+        seq: A,
+        err: Option<A::Error>,
+    }
+
+    impl<'de, A> io::Read for SeqReader<'de, A>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if buf.is_empty() {
+                return Ok(0);
+            }
+            match self.seq.next_element::<u8>() {
+                Ok(Some(byte)) => {
+                    buf[0] = byte;
+                    Ok(1)
+                }
+                Ok(None) => Ok(0),
+                Err(err) => {
+                    self.err = Some(err);
+                    Err(io::ErrorKind::InvalidData.into())
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::fmt::Debug;
+
+    use super::*;
+    use crate::strict_serialize;
+
+    #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+    #[serde(crate = "serde_crate")]
+    struct Embedded {
+        id: u16,
+        #[serde(with = "as_strict")]
+        tlvs: crate::TlvStream,
+        #[serde(with = "as_strict")]
+        items: Vec<Option<u8>>,
+    }
+
+    fn embedded() -> Embedded {
+        let mut tlvs = crate::TlvStream::new();
+        tlvs.insert(1, [0xAA, 0xBB]);
+        Embedded {
+            id: 0x0201,
+            tlvs,
+            items: vec![None, Some(7)],
+        }
+    }
+
+    fn check_parity<T>(value: T)
+    where
+        T: Serialize + DeserializeOwned + StrictEncode + PartialEq + Debug,
+    {
+        let data = to_vec(&value).unwrap();
+        assert_eq!(data, strict_serialize(&value).unwrap());
+        assert_eq!(from_slice::<T>(&data).unwrap(), value);
+        assert_eq!(from_reader::<_, T>(&data[..]).unwrap(), value);
+    }
+
+    #[test]
+    fn primitives() {
+        check_parity(true);
+        check_parity(0xA1u8);
+        check_parity(-2i8);
+        check_parity(0xA1B2u16);
+        check_parity(-0x1234i16);
+        check_parity(0xA1B2C3D4u32);
+        check_parity(-1i32);
+        check_parity(u64::MAX);
+        check_parity(i64::MIN);
+        check_parity(u128::MAX - 1);
+        check_parity(i128::MIN + 1);
+        check_parity(0.5f32);
+        check_parity(-1.25f64);
+        check_parity(());
+        check_parity(s!("some text"));
+    }
+
+    #[test]
+    fn collections() {
+        check_parity(Some(5u16));
+        check_parity(None::<u16>);
+        check_parity(vec![1u32, 2, 3]);
+        check_parity(Vec::<String>::new());
+        check_parity([0xAAu8; 32]);
+        check_parity(bmap! { 1u8 => s!("a"), 2u8 => s!("b") });
+        check_parity(BTreeMap::<u16, Vec<u8>>::new());
+    }
+
+    #[test]
+    fn as_strict_adapter() {
+        let value = embedded();
+        let data = to_vec(&value).unwrap();
+        let mut expected = strict_serialize(&value.id).unwrap();
+        expected.extend(strict_serialize(&value.tlvs).unwrap());
+        expected.extend(strict_serialize(&value.items).unwrap());
+        assert_eq!(data, expected);
+        assert_eq!(from_slice::<Embedded>(&data).unwrap(), value);
+
+        let mut data = data;
+        data.push(0);
+        assert_eq!(
+            from_slice::<Embedded>(&data),
+            Err(Error::DataNotEntirelyConsumed)
+        );
+    }
+
+    #[test]
+    fn as_strict_json() {
+        let value = embedded();
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(
+            json,
+            r#"{"id":513,"tlvs":[1,0,1,0,0,0,0,0,0,0,2,0,170,187],"items":[2,0,0,1,7]}"#
+        );
+        assert_eq!(serde_json::from_str::<Embedded>(&json).unwrap(), value);
+
+        let json = r#"{"id":513,"tlvs":[1,0,1,0,0,0,0,0,0,0,2,0,170,187],"items":[2,0,0,1,7,0]}"#;
+        assert!(serde_json::from_str::<Embedded>(json).is_err());
+        let json = r#"{"id":513,"tlvs":[1,0,1,0,0,0,0,0,0,0,2,0,170,187],"items":[2,0,0,1]}"#;
+        assert!(serde_json::from_str::<Embedded>(json).is_err());
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(to_vec(&'a'), Err(Error::Serde(_))));
+        assert!(matches!(from_slice::<char>([0x61]), Err(Error::Serde(_))));
+        assert_eq!(
+            to_vec(&vec![0u8; u16::MAX as usize + 1]),
+            Err(Error::ExceedMaxItems(u16::MAX as usize + 1))
+        );
+        assert_eq!(
+            from_slice::<Option<u8>>([2, 0]),
+            Err(Error::WrongOptionalEncoding(2).at_offset(1))
+        );
+        assert_eq!(
+            from_slice::<u16>([1, 2, 3]),
+            Err(Error::DataNotEntirelyConsumed)
+        );
+        assert!(matches!(
+            from_slice::<serde_json::Value>([0])
+                .unwrap_err()
+                .root_cause(),
+            Error::Serde(_)
+        ));
+    }
+}
@@ -12,14 +12,15 @@
 // You should have received a copy of the Apache 2.0 License along with this
 // software. If not, see <https://opensource.org/licenses/Apache-2.0>.
 
-use std::io;
-
 use amplify::{Slice32, Wrapper};
 use bitcoin_hashes::{sha256, Hash};
 
-use crate::{StrictDecode, StrictEncode};
+use crate::schema::{StrictType, TypeDescr};
+use crate::{io, StrictDecode, StrictEncode};
 
 impl StrictEncode for Slice32 {
+    const FIXED_SIZE: Option<usize> = Some(32);
+
     fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, crate::Error> {
         // We use the same encoding as used by hashes - and ensure this by
         // cross-converting with hash
@@ -34,6 +35,11 @@ impl StrictDecode for Slice32 {
     }
 }
 
+impl StrictType for Slice32 {
+    #[inline]
+    fn strict_type() -> TypeDescr { TypeDescr::Bytes(32) }
+}
+
 #[cfg(test)]
 mod test {
     use amplify::hex::FromHex;
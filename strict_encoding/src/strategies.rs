@@ -16,11 +16,16 @@
 //!
 //! Implemented after concept by Martin Habovštiak <martin.habovstiak@gmail.com>
 
-use std::io;
+use alloc::string::ToString;
 
 use amplify::Wrapper;
 
+use super::schema::{StrictType, TypeDescr};
 use super::{net, Error, StrictDecode, StrictEncode};
+use crate::io;
+#[cfg(feature = "bitcoin")]
+use crate::limits::decode_canonical;
+use crate::limits::is_canonical;
 
 /// Encodes/decodes data as a [`bitcoin_hashes::Hash`]-based (wrapper) type,
 /// i.e. as a fixed-size byte string of [`bitcoin_hashes::Hash::LEN`] length.
@@ -62,6 +67,9 @@ where
     T: Strategy + Clone,
     amplify::Holder<T, <T as Strategy>::Strategy>: StrictEncode,
 {
+    const FIXED_SIZE: Option<usize> =
+        amplify::Holder::<T, <T as Strategy>::Strategy>::FIXED_SIZE;
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
         amplify::Holder::new(self.clone()).strict_encode(e)
@@ -84,6 +92,8 @@ where
     W: Wrapper,
     W::Inner: StrictEncode,
 {
+    const FIXED_SIZE: Option<usize> = W::Inner::FIXED_SIZE;
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
         self.as_inner().as_inner().strict_encode(e)
@@ -105,6 +115,8 @@ impl<H> StrictEncode for amplify::Holder<H, HashFixedBytes>
 where
     H: bitcoin_hashes::Hash,
 {
+    const FIXED_SIZE: Option<usize> = Some(H::LEN);
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         e.write_all(&self.as_inner()[..])?;
@@ -143,11 +155,20 @@ where
 #[cfg(feature = "bitcoin")]
 impl<B> StrictDecode for amplify::Holder<B, BitcoinConsensus>
 where
-    B: bitcoin::consensus::Decodable,
+    B: bitcoin::consensus::Decodable + bitcoin::consensus::Encodable,
 {
-    #[inline]
-    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
-        Ok(Self::new(B::consensus_decode(&mut d).map_err(Error::from)?))
+    fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
+        // Consensus decoders may accept several encodings of the same value,
+        // like PSBT key-value pairs going in arbitrary order
+        decode_canonical(d, |mut d| {
+            Ok(Self::new(B::consensus_decode(&mut d).map_err(Error::from)?))
+        })
+        .map_err(|err| match err.root_cause() {
+            Error::NonCanonical(_) => {
+                Error::NonCanonical(core::any::type_name::<B>())
+            }
+            _ => err,
+        })
     }
 }
 
@@ -179,6 +200,8 @@ impl<A> StrictEncode for amplify::Holder<A, UsingUniformAddr>
 where
     A: net::Uniform,
 {
+    const FIXED_SIZE: Option<usize> = Some(net::UNIFORM_LEN);
+
     #[inline]
     fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
         self.as_inner().to_raw_uniform().strict_encode(e)
@@ -189,15 +212,81 @@ impl<A> StrictDecode for amplify::Holder<A, UsingUniformAddr>
 where
     A: net::Uniform,
 {
-    #[inline]
     fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
-        Ok(Self::new(
-            A::from_raw_uniform_addr(net::RawUniformAddr::strict_decode(d)?)
-                .map_err(|err| Error::DataIntegrityError(err.to_string()))?,
-        ))
+        let raw = net::RawUniformAddr::strict_decode(d)?;
+        let addr = A::from_raw_uniform_addr(raw)
+            .map_err(|err| Error::DataIntegrityError(err.to_string()))?;
+        // Addresses may be converted from the uniform addresses of other
+        // formats, like IPv6 address from IPv4 one
+        if is_canonical() && addr.to_raw_uniform() != raw {
+            return Err(Error::NonCanonical(core::any::type_name::<A>()));
+        }
+        Ok(Self::new(addr))
+    }
+}
+
+impl<T> StrictType for T
+where
+    T: Strategy,
+    amplify::Holder<T, <T as Strategy>::Strategy>: StrictType,
+{
+    #[inline]
+    fn strict_type() -> TypeDescr {
+        amplify::Holder::<T, <T as Strategy>::Strategy>::strict_type()
     }
 }
 
+impl<W> StrictType for amplify::Holder<W, Wrapped>
+where
+    W: Wrapper,
+    W::Inner: StrictType,
+{
+    #[inline]
+    fn strict_type() -> TypeDescr { W::Inner::strict_type() }
+}
+
+impl<H> StrictType for amplify::Holder<H, HashFixedBytes>
+where
+    H: bitcoin_hashes::Hash,
+{
+    #[inline]
+    fn strict_type() -> TypeDescr { TypeDescr::Bytes(H::LEN as u16) }
+}
+
+/// Types encoded with consensus encoding are described as opaque types named
+/// after the last segment of their rust type path.
+#[cfg(any(feature = "bitcoin", feature = "monero"))]
+fn consensus_type_name<T>() -> TypeDescr {
+    let name = core::any::type_name::<T>();
+    TypeDescr::opaque(name.rsplit("::").next().unwrap_or(name))
+}
+
+#[cfg(feature = "bitcoin")]
+impl<B> StrictType for amplify::Holder<B, BitcoinConsensus>
+where
+    B: bitcoin::consensus::Encodable,
+{
+    #[inline]
+    fn strict_type() -> TypeDescr { consensus_type_name::<B>() }
+}
+
+#[cfg(feature = "monero")]
+impl<B> StrictType for amplify::Holder<B, MoneroConsensus>
+where
+    B: monero::consensus::Encodable,
+{
+    #[inline]
+    fn strict_type() -> TypeDescr { consensus_type_name::<B>() }
+}
+
+impl<A> StrictType for amplify::Holder<A, UsingUniformAddr>
+where
+    A: net::Uniform,
+{
+    #[inline]
+    fn strict_type() -> TypeDescr { TypeDescr::Bytes(net::UNIFORM_LEN as u16) }
+}
+
 #[cfg(feature = "bitcoin")]
 impl From<bitcoin::hashes::Error> for Error {
     #[inline]
@@ -0,0 +1,324 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Streaming encoding and decoding of large collections.
+//!
+//! [`StreamWriter`] writes the length prefix of a collection followed by its
+//! items provided one by one, and [`StreamReader`] reads the length prefix
+//! and decodes the items lazily on iteration. Neither of them keeps the
+//! items in memory, so collections of any size (like multi-gigabyte
+//! [`LargeVec`](crate::LargeVec)s) can be processed in constant memory. The
+//! produced and consumed data are identical to the encoding of the
+//! corresponding collection type.
+//!
+//! ```
+//! use strict_encoding::stream::{StreamReader, StreamWriter};
+//! use strict_encoding::{LargeVec, StrictDecode};
+//!
+//! let mut data = vec![];
+//! let mut writer = StreamWriter::large(&mut data, 3).unwrap();
+//! for item in 1u64..=3 {
+//!     writer.write(&item).unwrap();
+//! }
+//! writer.finish().unwrap();
+//!
+//! let vec = LargeVec::<u64>::strict_decode(&data[..]).unwrap();
+//! assert_eq!(vec.as_slice(), &[1, 2, 3]);
+//!
+//! let reader = StreamReader::<_, u64>::large(&data[..]).unwrap();
+//! assert_eq!(reader.sum::<Result<u64, _>>().unwrap(), 6);
+//! ```
+
+use core::fmt::{self, Debug, Formatter};
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+
+use crate::schema::LenPrefix;
+use crate::value::{decode_len, encode_len};
+use crate::{io, Error, StrictDecode, StrictEncode};
+
+/// Writer encoding a collection item by item.
+///
+/// The number of the items must be known in advance since it is written
+/// first, as the collection length prefix. Writing more items fails with
+/// [`Error::ItemsCountMismatch`]; [`StreamWriter::finish`] must be called
+/// once all items are written to check that none of them are missed.
+pub struct StreamWriter<E, T>
+where
+    E: io::Write,
+    T: StrictEncode,
+{
+    writer: E,
+    count: usize,
+    written: usize,
+    len: usize,
+    _phantom: PhantomData<T>,
+}
+
+impl<E, T> StreamWriter<E, T>
+where
+    E: io::Write,
+    T: StrictEncode,
+{
+    /// Starts writing a collection of `count` items with the given length
+    /// prefix. Fails with [`Error::ExceedMaxItems`] if the prefix can't hold
+    /// `count`.
+    pub fn with_prefix(
+        mut writer: E,
+        prefix: LenPrefix,
+        count: usize,
+    ) -> Result<Self, Error> {
+        let len = encode_len(prefix, count, &mut writer)?;
+        Ok(StreamWriter {
+            writer,
+            count,
+            written: 0,
+            len,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Starts writing [`LargeVec`](crate::LargeVec) of `count` items, i.e. a
+    /// collection with `u32` length prefix.
+    #[inline]
+    pub fn large(writer: E, count: usize) -> Result<Self, Error> {
+        Self::with_prefix(writer, LenPrefix::U32, count)
+    }
+
+    /// Starts writing [`MediumVec`](crate::MediumVec) of `count` items, i.e.
+    /// a collection with `u24` length prefix.
+    #[inline]
+    pub fn medium(writer: E, count: usize) -> Result<Self, Error> {
+        Self::with_prefix(writer, LenPrefix::U24, count)
+    }
+
+    /// Encodes the next item, returning the number of bytes written.
+    pub fn write(&mut self, item: &T) -> Result<usize, Error> {
+        if self.written >= self.count {
+            return Err(Error::ItemsCountMismatch(
+                self.count,
+                self.written + 1,
+            ));
+        }
+        let len = item
+            .strict_encode(&mut self.writer)
+            .map_err(|err| err.within_item(self.written))?;
+        self.written += 1;
+        self.len += len;
+        Ok(len)
+    }
+
+    /// Returns number of the items which are still to be written.
+    #[inline]
+    pub fn remaining(&self) -> usize { self.count - self.written }
+
+    /// Completes writing the collection, returning the total number of
+    /// written bytes, including the length prefix. Fails with
+    /// [`Error::ItemsCountMismatch`] if less items than declared were
+    /// written.
+    pub fn finish(self) -> Result<usize, Error> {
+        if self.written != self.count {
+            return Err(Error::ItemsCountMismatch(self.count, self.written));
+        }
+        Ok(self.len)
+    }
+}
+
+impl<E, T> Debug for StreamWriter<E, T>
+where
+    E: io::Write,
+    T: StrictEncode,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamWriter")
+            .field("count", &self.count)
+            .field("written", &self.written)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+/// Reader lazily decoding items of a collection.
+///
+/// The reader reads the collection length prefix on construction and then
+/// decodes one item per iteration. Once decoding of an item fails, the error
+/// is returned and the iteration stops.
+pub struct StreamReader<D, T>
+where
+    D: io::Read,
+    T: StrictDecode,
+{
+    reader: D,
+    count: usize,
+    read: usize,
+    failed: bool,
+    _phantom: PhantomData<T>,
+}
+
+impl<D, T> StreamReader<D, T>
+where
+    D: io::Read,
+    T: StrictDecode,
+{
+    /// Starts reading a collection with the given length prefix.
+    pub fn with_prefix(
+        mut reader: D,
+        prefix: LenPrefix,
+    ) -> Result<Self, Error> {
+        let count = decode_len(prefix, &mut reader)?;
+        Ok(StreamReader {
+            reader,
+            count,
+            read: 0,
+            failed: false,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Starts reading [`LargeVec`](crate::LargeVec), i.e. a collection with
+    /// `u32` length prefix.
+    #[inline]
+    pub fn large(reader: D) -> Result<Self, Error> {
+        Self::with_prefix(reader, LenPrefix::U32)
+    }
+
+    /// Starts reading [`MediumVec`](crate::MediumVec), i.e. a collection with
+    /// `u24` length prefix.
+    #[inline]
+    pub fn medium(reader: D) -> Result<Self, Error> {
+        Self::with_prefix(reader, LenPrefix::U24)
+    }
+
+    /// Returns number of the collection items, as declared by its length
+    /// prefix.
+    #[inline]
+    pub fn items_count(&self) -> usize { self.count }
+
+    /// Returns number of the items which are not read yet.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        if self.failed {
+            0
+        } else {
+            self.count - self.read
+        }
+    }
+
+    /// Returns the underlying reader. If all items were read, the reader is
+    /// positioned right after the collection data.
+    #[inline]
+    pub fn into_inner(self) -> D { self.reader }
+}
+
+impl<D, T> Debug for StreamReader<D, T>
+where
+    D: io::Read,
+    T: StrictDecode,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamReader")
+            .field("count", &self.count)
+            .field("read", &self.read)
+            .field("failed", &self.failed)
+            .finish()
+    }
+}
+
+impl<D, T> Iterator for StreamReader<D, T>
+where
+    D: io::Read,
+    T: StrictDecode,
+{
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining() == 0 {
+            return None;
+        }
+        let index = self.read;
+        self.read += 1;
+        let item = T::strict_decode(&mut self.reader)
+            .map_err(|err| err.within_item(index));
+        self.failed = item.is_err();
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining()))
+    }
+}
+
+impl<D, T> FusedIterator for StreamReader<D, T>
+where
+    D: io::Read,
+    T: StrictDecode,
+{
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{strict_serialize, MediumVec};
+
+    #[test]
+    fn write_read() {
+        let mut data = vec![];
+        let mut writer = StreamWriter::medium(&mut data, 2).unwrap();
+        assert_eq!(writer.write(&s!("a")), Ok(3));
+        assert_eq!(writer.remaining(), 1);
+        assert_eq!(writer.write(&s!("bc")), Ok(4));
+        assert_eq!(writer.finish(), Ok(10));
+
+        let vec = MediumVec::try_from(vec![s!("a"), s!("bc")]).unwrap();
+        assert_eq!(data, strict_serialize(&vec).unwrap());
+
+        data.push(0xFF);
+        let mut reader = StreamReader::<_, String>::medium(&data[..]).unwrap();
+        assert_eq!(reader.items_count(), 2);
+        assert_eq!(reader.next(), Some(Ok(s!("a"))));
+        assert_eq!(reader.remaining(), 1);
+        assert_eq!(reader.next(), Some(Ok(s!("bc"))));
+        assert_eq!(reader.next(), None);
+        assert_eq!(reader.into_inner(), &[0xFF]);
+    }
+
+    #[test]
+    fn count_mismatch() {
+        let mut data = vec![];
+        let mut writer = StreamWriter::large(&mut data, 1).unwrap();
+        writer.write(&1u8).unwrap();
+        assert_eq!(writer.write(&2u8), Err(Error::ItemsCountMismatch(1, 2)));
+        assert_eq!(writer.finish(), Ok(5));
+
+        let writer = StreamWriter::<_, u8>::large(vec![], 2).unwrap();
+        assert_eq!(writer.finish(), Err(Error::ItemsCountMismatch(2, 0)));
+
+        assert_eq!(
+            StreamWriter::<_, u8>::medium(vec![], 0x1000000).unwrap_err(),
+            Error::ExceedMaxItems(0x1000000)
+        );
+    }
+
+    #[test]
+    fn read_failure() {
+        let data = [3u8, 0, 0, 0, 1, 2];
+        let mut reader = StreamReader::<_, bool>::large(&data[..]).unwrap();
+        assert_eq!(reader.next(), Some(Ok(true)));
+        assert!(reader.next().unwrap().is_err());
+        assert_eq!(reader.remaining(), 0);
+        assert_eq!(reader.next(), None);
+
+        assert!(StreamReader::<_, u8>::large(&data[..2]).is_err());
+    }
+}
@@ -0,0 +1,628 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! TLV (type-length-value) extensions to the strict encoding.
+//!
+//! Structures deriving strict encoding with `#[network_encoding(use_tlv)]`
+//! attribute encode their TLV fields as a [`TlvStream`]: an ordered set of
+//! records, each consisting of a type id and raw value bytes. An empty stream
+//! is not encoded at all; non-empty stream is encoded as a collection of
+//! records with `u16` count prefix, where each record is represented by
+//! `u64` type id followed by the value bytes prefixed with `u16` length.
+//!
+//! With `#[network_encoding(use_tlv, bolt)]` attribute the stream is encoded
+//! according to [BOLT-1] instead: records are not prefixed with their count
+//! and continue up to the end of the data, while both type ids and value
+//! lengths are encoded as [`BigSize`] integers. The record values are still
+//! encoded with the strict encoding of the field types.
+//!
+//! Derived structures can be converted into TLV streams with
+//! [`ToTlvStream`] and updated from them with [`ApplyTlvStream`], allowing to
+//! inspect or forward TLV records without re-encoding the whole structure.
+//!
+//! [BOLT-1]: https://github.com/lightning/bolts/blob/master/01-messaging.md
+
+use alloc::boxed::Box;
+use alloc::collections::{btree_map, BTreeMap};
+use alloc::vec::Vec;
+
+use crate::limits::{check_items, decode_canonical, DepthGuard};
+use crate::{io, Error, StrictDecode, StrictEncode};
+
+/// Possible errors during TLV extension encoding and decoding process
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
+#[cfg_attr(feature = "std", derive(Error))]
+#[display(doc_comments)]
+pub enum TlvError {
+    /// deterministic order of TLV records is broken: type {read} follows after
+    /// type {max}
+    Order {
+        /// TLV type id read at the current position
+        read: u64,
+        /// maximum value of TLV type id read previously
+        max: u64,
+    },
+
+    /// incorrect length of TLV record value: expected {expected}, but only
+    /// {actual} bytes read
+    Len {
+        /// TLV value length encoded in the TLV record
+        expected: u64,
+        /// Actual remaining length of the TLV stream
+        actual: u64,
+    },
+
+    /// repeated TLV record with id {0}
+    Repeated(u64),
+
+    /// an unknown even TLV type {0}
+    UnknownEvenType(u64),
+}
+
+/// Variable-length unsigned integer defined by [BOLT-1], which is used in
+/// TLV streams for type ids and value lengths.
+///
+/// Values below `0xFD` are encoded as a single byte; larger values are
+/// encoded as `0xFD`, `0xFE` or `0xFF` marker followed by big-endian `u16`,
+/// `u32` or `u64`. Decoding fails on non-minimal encodings.
+///
+/// [BOLT-1]: https://github.com/lightning/bolts/blob/master/01-messaging.md#appendix-a-bigsize-test-vectors
+#[derive(
+    Wrapper, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default,
+    Display, From
+)]
+#[display(inner)]
+pub struct BigSize(#[from] u64);
+
+impl StrictEncode for BigSize {
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        match self.0 {
+            0..=0xFC => e.write_all(&[self.0 as u8])?,
+            0xFD..=0xFFFF => {
+                e.write_all(&[0xFD])?;
+                e.write_all(&(self.0 as u16).to_be_bytes())?;
+            }
+            0x1_0000..=0xFFFF_FFFF => {
+                e.write_all(&[0xFE])?;
+                e.write_all(&(self.0 as u32).to_be_bytes())?;
+            }
+            _ => {
+                e.write_all(&[0xFF])?;
+                e.write_all(&self.0.to_be_bytes())?;
+            }
+        }
+        self.strict_encoded_len()
+    }
+
+    fn strict_encoded_len(&self) -> Result<usize, Error> {
+        Ok(match self.0 {
+            0..=0xFC => 1,
+            0xFD..=0xFFFF => 3,
+            0x1_0000..=0xFFFF_FFFF => 5,
+            _ => 9,
+        })
+    }
+}
+
+impl StrictDecode for BigSize {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        let marker = u8::strict_decode(&mut d)?;
+        BigSize::decode_after(marker, d)
+    }
+}
+
+impl BigSize {
+    /// Decodes the rest of the value once its first byte is already read.
+    pub(crate) fn decode_after(marker: u8, mut d: impl io::Read) -> Result<Self, Error> {
+        let (value, min) = match marker {
+            0xFD => {
+                let mut buf = [0u8; 2];
+                d.read_exact(&mut buf)?;
+                (u16::from_be_bytes(buf) as u64, 0xFD)
+            }
+            0xFE => {
+                let mut buf = [0u8; 4];
+                d.read_exact(&mut buf)?;
+                (u32::from_be_bytes(buf) as u64, 0x1_0000)
+            }
+            0xFF => {
+                let mut buf = [0u8; 8];
+                d.read_exact(&mut buf)?;
+                (u64::from_be_bytes(buf), 0x1_0000_0000)
+            }
+            value => return Ok(BigSize(value as u64)),
+        };
+        if value < min {
+            return Err(Error::DataIntegrityError(format!(
+                "non-minimal BigSize encoding of value {}",
+                value
+            )));
+        }
+        Ok(BigSize(value))
+    }
+}
+
+/// Stream of TLV records ordered by their type ids.
+///
+/// Each type id may be present in the stream only once; the uniqueness and
+/// the order of records are checked during decoding.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default, From)]
+pub struct TlvStream(#[from] BTreeMap<u64, Box<[u8]>>);
+
+impl TlvStream {
+    /// Constructs empty TLV stream.
+    #[inline]
+    pub fn new() -> TlvStream { TlvStream::default() }
+
+    /// Returns number of TLV records in the stream.
+    #[inline]
+    pub fn len(&self) -> usize { self.0.len() }
+
+    /// Detects whether the stream has no TLV records.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+
+    /// Detects whether the stream contains a record with the given type id.
+    #[inline]
+    pub fn contains(&self, type_no: u64) -> bool {
+        self.0.contains_key(&type_no)
+    }
+
+    /// Returns value of the record with the given type id, if present.
+    #[inline]
+    pub fn get(&self, type_no: u64) -> Option<&[u8]> {
+        self.0.get(&type_no).map(Box::as_ref)
+    }
+
+    /// Inserts record into the stream, returning the previous value of the
+    /// record with the same type id, if there was any.
+    #[inline]
+    pub fn insert(
+        &mut self,
+        type_no: u64,
+        value: impl AsRef<[u8]>,
+    ) -> Option<Box<[u8]>> {
+        self.0.insert(type_no, Box::from(value.as_ref()))
+    }
+
+    /// Removes record with the given type id from the stream, returning its
+    /// value.
+    #[inline]
+    pub fn remove(&mut self, type_no: u64) -> Option<Box<[u8]>> {
+        self.0.remove(&type_no)
+    }
+
+    /// Returns iterator over the records in the order of their type ids.
+    #[inline]
+    pub fn iter(&self) -> btree_map::Iter<'_, u64, Box<[u8]>> { self.0.iter() }
+
+    /// Checks that all even (i.e. mandatory) records in the stream are known,
+    /// as reported by `is_known` function. Unknown odd records are allowed.
+    pub fn check_unknown_even(
+        &self,
+        is_known: impl Fn(u64) -> bool,
+    ) -> Result<(), TlvError> {
+        match self
+            .0
+            .keys()
+            .find(|type_no| *type_no % 2 == 0 && !is_known(**type_no))
+        {
+            Some(type_no) => Err(TlvError::UnknownEvenType(*type_no)),
+            None => Ok(()),
+        }
+    }
+
+    /// Releases inner map of the records.
+    #[inline]
+    pub fn into_inner(self) -> BTreeMap<u64, Box<[u8]>> { self.0 }
+
+    /// Encodes the stream according to BOLT-1 rules, with [`BigSize`] type
+    /// ids and value lengths and without the count of records. Returns
+    /// number of bytes written.
+    pub fn bolt_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        let mut len = 0;
+        for (type_no, value) in &self.0 {
+            len += BigSize(*type_no).strict_encode(&mut e)?;
+            len += BigSize(value.len() as u64).strict_encode(&mut e)?;
+            e.write_all(value)?;
+            len += value.len();
+        }
+        Ok(len)
+    }
+
+    /// Decodes BOLT-1 encoded stream, reading records up to the end of the
+    /// data. Record type ids must be strictly increasing.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`TlvError::Order`] or [`TlvError::Repeated`] if the
+    /// records are not ordered or repeated, and with [`TlvError::Len`] if
+    /// the data end before the end of a record value.
+    pub fn bolt_decode<D: io::Read>(mut d: D) -> Result<TlvStream, Error> {
+        let mut stream = BTreeMap::new();
+        let mut max = None;
+        loop {
+            let mut marker = [0u8; 1];
+            let type_no = match d.read(&mut marker)? {
+                0 => break,
+                _ => BigSize::decode_after(marker[0], &mut d)?.0,
+            };
+            match max {
+                Some(max) if max == type_no => {
+                    return Err(TlvError::Repeated(type_no).into())
+                }
+                Some(max) if max > type_no => {
+                    return Err(TlvError::Order { read: type_no, max }.into())
+                }
+                _ => {}
+            }
+            max = Some(type_no);
+            let expected = BigSize::strict_decode(&mut d)?.0;
+            stream.insert(type_no, read_value(&mut d, expected)?);
+        }
+        Ok(TlvStream(stream))
+    }
+}
+
+/// Reads TLV record value of the given length, failing with
+/// [`TlvError::Len`] if there is not enough data.
+fn read_value(mut d: impl io::Read, expected: u64) -> Result<Box<[u8]>, Error> {
+    let mut value = Vec::new();
+    let mut buf = [0u8; 1024];
+    while (value.len() as u64) < expected {
+        let remaining = expected - value.len() as u64;
+        let chunk = &mut buf[..remaining.min(1024) as usize];
+        match d.read(chunk)? {
+            0 => {
+                return Err(TlvError::Len {
+                    expected,
+                    actual: value.len() as u64,
+                }
+                .into())
+            }
+            read => value.extend_from_slice(&chunk[..read]),
+        }
+    }
+    Ok(value.into_boxed_slice())
+}
+
+impl IntoIterator for TlvStream {
+    type Item = (u64, Box<[u8]>);
+    type IntoIter = btree_map::IntoIter<u64, Box<[u8]>>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.0.into_iter() }
+}
+
+impl<'a> IntoIterator for &'a TlvStream {
+    type Item = (&'a u64, &'a Box<[u8]>);
+    type IntoIter = btree_map::Iter<'a, u64, Box<[u8]>>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.0.iter() }
+}
+
+impl StrictEncode for TlvStream {
+    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
+        if self.0.is_empty() {
+            return Ok(0);
+        }
+        self.0.strict_encode(e)
+    }
+}
+
+impl StrictDecode for TlvStream {
+    fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
+        // Since empty TLV stream is not encoded at all, zero length prefix and
+        // truncated length prefix are not canonical encodings of the empty
+        // stream
+        decode_canonical(d, |mut d| {
+            // Empty TLV stream is not encoded at all, so the end of data here
+            // means absence of TLV records
+            let len = match usize::strict_decode(&mut d) {
+                Err(err)
+                    if err.root_cause()
+                        == &io::Error::from(io::ErrorKind::UnexpectedEof)
+                            .into() =>
+                {
+                    return Ok(TlvStream::default())
+                }
+                res => res?,
+            };
+            check_items(len)?;
+            let _depth = DepthGuard::enter()?;
+            let mut stream = BTreeMap::new();
+            let mut max = None;
+            for _ in 0..len {
+                let type_no = u64::strict_decode(&mut d)?;
+                let value = Box::<[u8]>::strict_decode(&mut d)?;
+                match max {
+                    Some(max) if max == type_no => {
+                        return Err(TlvError::Repeated(type_no).into())
+                    }
+                    Some(max) if max > type_no => {
+                        return Err(
+                            TlvError::Order { read: type_no, max }.into()
+                        )
+                    }
+                    _ => {}
+                }
+                max = Some(type_no);
+                stream.insert(type_no, value);
+            }
+            Ok(TlvStream(stream))
+        })
+    }
+}
+
+/// Conversion of a data structure with TLV extensions into a [`TlvStream`].
+///
+/// Derived for structures with `#[network_encoding(use_tlv)]` attribute by
+/// `NetworkEncode` derive macro.
+pub trait ToTlvStream {
+    /// Collects all TLV fields of the structure, including unknown TLV
+    /// records, into a stream.
+    fn to_tlv_stream(&self) -> Result<TlvStream, Error>;
+}
+
+/// Update of a data structure with TLV extensions from a [`TlvStream`].
+///
+/// Derived for structures with `#[network_encoding(use_tlv)]` attribute by
+/// `NetworkDecode` derive macro.
+pub trait ApplyTlvStream {
+    /// Decodes TLV fields of the structure from the records in the stream;
+    /// fields which do not have a record in the stream are left intact.
+    /// Unknown odd records are put into the field marked with
+    /// `unknown_tlvs`, if there is any.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`TlvError::UnknownEvenType`] if the stream contains an
+    /// even record not known to the structure, or if some of the known
+    /// records can't be decoded.
+    fn apply_tlv_stream(&mut self, stream: TlvStream) -> Result<(), Error>;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        strict_deserialize, strict_deserialize_canonical, strict_serialize,
+    };
+
+    #[test]
+    fn stream_encoding() {
+        let mut stream = TlvStream::new();
+        assert_eq!(strict_serialize(&stream).unwrap(), Vec::<u8>::new());
+        assert_eq!(strict_deserialize::<TlvStream>([]).unwrap(), stream);
+
+        assert_eq!(stream.insert(0xCAFE, [0xA2]), None);
+        assert_eq!(stream.insert(0x0BAD, [0xA1, 0xA1]), None);
+        assert_eq!(
+            stream.insert(0x0BAD, [0xA1]),
+            Some(Box::from([0xA1, 0xA1].as_ref()))
+        );
+        assert_eq!(stream.len(), 2);
+        assert_eq!(stream.get(0x0BAD), Some([0xA1].as_ref()));
+        assert!(!stream.contains(0xBEEF));
+
+        let data = [
+            0x02, 0x00, // count
+            0xAD, 0x0B, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0xA1,
+            0xFE, 0xCA, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0xA2,
+        ];
+        assert_eq!(strict_serialize(&stream).unwrap(), data);
+        assert_eq!(strict_deserialize::<TlvStream>(data).unwrap(), stream);
+        assert_eq!(stream.iter().map(|(t, _)| *t).collect::<Vec<_>>(), [
+            0x0BAD, 0xCAFE
+        ]);
+    }
+
+    #[test]
+    fn stream_rules() {
+        let record = |type_no: u8| [type_no, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+
+        let mut data = vec![0x02, 0x00];
+        data.extend(record(3));
+        data.extend(record(1));
+        assert_eq!(
+            strict_deserialize::<TlvStream>(&data).unwrap_err(),
+            Error::Tlv(TlvError::Order { read: 1, max: 3 })
+        );
+
+        let mut data = vec![0x02, 0x00];
+        data.extend(record(3));
+        data.extend(record(3));
+        assert_eq!(
+            strict_deserialize::<TlvStream>(&data).unwrap_err(),
+            Error::Tlv(TlvError::Repeated(3))
+        );
+
+        let mut data = vec![0x03, 0x00];
+        data.extend(record(1));
+        data.extend(record(2));
+        data.extend(record(4));
+        let stream = strict_deserialize::<TlvStream>(&data).unwrap();
+        assert_eq!(stream.check_unknown_even(|t| t == 2 || t == 4), Ok(()));
+        assert_eq!(
+            stream.check_unknown_even(|t| t == 2),
+            Err(TlvError::UnknownEvenType(4))
+        );
+    }
+
+    #[test]
+    fn stream_canonical() {
+        // Empty stream is encoded as no data at all, so zero or truncated
+        // length prefixes are its non-canonical encodings
+        for data in [&[0x00, 0x00][..], &[0x00]] {
+            assert_eq!(
+                strict_deserialize::<TlvStream>(data).unwrap(),
+                TlvStream::new()
+            );
+            assert_eq!(
+                strict_deserialize_canonical::<TlvStream>(data).unwrap_err(),
+                Error::NonCanonical(core::any::type_name::<TlvStream>())
+            );
+        }
+        assert_eq!(
+            strict_deserialize_canonical::<TlvStream>([]).unwrap(),
+            TlvStream::new()
+        );
+    }
+
+    // Test vectors from BOLT-1 Appendix A
+    #[test]
+    fn bigsize_vectors() {
+        let valid: [(u64, &[u8]); 8] = [
+            (0, &[0x00]),
+            (252, &[0xfc]),
+            (253, &[0xfd, 0x00, 0xfd]),
+            (65535, &[0xfd, 0xff, 0xff]),
+            (65536, &[0xfe, 0x00, 0x01, 0x00, 0x00]),
+            (4294967295, &[0xfe, 0xff, 0xff, 0xff, 0xff]),
+            (4294967296, &[
+                0xff, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+            ]),
+            (u64::MAX, &[0xff; 9]),
+        ];
+        for (value, data) in valid {
+            let size = BigSize::from(value);
+            assert_eq!(strict_serialize(&size).unwrap(), data);
+            assert_eq!(size.strict_encoded_len().unwrap(), data.len());
+            assert_eq!(strict_deserialize::<BigSize>(data).unwrap(), size);
+        }
+
+        let non_canonical: [&[u8]; 3] =
+            [&[0xfd, 0x00, 0xfc], &[0xfe, 0x00, 0x00, 0xff, 0xff], &[
+                0xff, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff,
+            ]];
+        for data in non_canonical {
+            assert!(matches!(
+                strict_deserialize::<BigSize>(data).unwrap_err(),
+                Error::DataIntegrityError(_)
+            ));
+        }
+
+        let eof = Error::from(io::Error::from(io::ErrorKind::UnexpectedEof));
+        let short: [&[u8]; 7] = [
+            &[0xfd, 0x00],
+            &[0xfe, 0xff, 0xff],
+            &[0xff, 0xff, 0xff, 0xff, 0xff],
+            &[],
+            &[0xfd],
+            &[0xfe],
+            &[0xff],
+        ];
+        for data in short {
+            assert_eq!(strict_deserialize::<BigSize>(data).unwrap_err(), eof);
+        }
+    }
+
+    // TLV stream decoding vectors from BOLT-1 Appendix B
+    #[test]
+    fn bolt_stream_vectors() {
+        let valid: [&[u8]; 7] = [
+            &[],
+            &[0x21, 0x00],
+            &[0xfd, 0x02, 0x01, 0x00],
+            &[0xfd, 0x00, 0xfd, 0x00],
+            &[0xfd, 0x00, 0xff, 0x00],
+            &[0xfe, 0x02, 0x00, 0x00, 0x01, 0x00],
+            &[0xff, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00],
+        ];
+        for data in valid {
+            let stream = TlvStream::bolt_decode(data).unwrap();
+            assert!(stream.iter().all(|(_, value)| value.is_empty()));
+            let mut encoded = vec![];
+            assert_eq!(stream.bolt_encode(&mut encoded).unwrap(), data.len());
+            assert_eq!(encoded, data);
+        }
+
+        let stream = TlvStream::bolt_decode(
+            &[
+                0x02, 0x08, 0, 0, 0, 0, 0, 0, 0x02, 0x26, 0xfd, 0x00, 0xfe,
+                0x02, 0x02, 0x26,
+            ][..],
+        )
+        .unwrap();
+        assert_eq!(stream.len(), 2);
+        assert_eq!(stream.get(2), Some(&[0, 0, 0, 0, 0, 0, 0x02, 0x26][..]));
+        assert_eq!(stream.get(254), Some(&[0x02, 0x26][..]));
+
+        let eof = Error::from(io::Error::from(io::ErrorKind::UnexpectedEof));
+        let truncated: [&[u8]; 4] =
+            [&[0xfd], &[0xfd, 0x01], &[0x0f, 0xfd], &[0x0f, 0xfd, 0x26]];
+        for data in truncated {
+            assert_eq!(TlvStream::bolt_decode(data).unwrap_err(), eof);
+        }
+        assert_eq!(
+            TlvStream::bolt_decode(&[0x0f, 0xfd, 0x26, 0x02][..]).unwrap_err(),
+            Error::Tlv(TlvError::Len {
+                expected: 0x2602,
+                actual: 0
+            })
+        );
+        let mut data = vec![0x0f, 0xfd, 0x02, 0x01];
+        data.extend([0u8; 0x200]);
+        assert_eq!(
+            TlvStream::bolt_decode(&data[..]).unwrap_err(),
+            Error::Tlv(TlvError::Len {
+                expected: 0x201,
+                actual: 0x200
+            })
+        );
+        assert!(matches!(
+            TlvStream::bolt_decode(&[0xfd, 0x00, 0x01, 0x00][..]).unwrap_err(),
+            Error::DataIntegrityError(_)
+        ));
+
+        assert_eq!(
+            TlvStream::bolt_decode(
+                &[0x02, 0x08, 0, 0, 0, 0, 0, 0, 0x02, 0x26, 0x01, 0x01, 0x2a][..]
+            )
+            .unwrap_err(),
+            Error::Tlv(TlvError::Order { read: 1, max: 2 })
+        );
+        assert_eq!(
+            TlvStream::bolt_decode(&[0x1f, 0x00, 0x0f, 0x01, 0x2a][..])
+                .unwrap_err(),
+            Error::Tlv(TlvError::Order {
+                read: 0x0f,
+                max: 0x1f
+            })
+        );
+        assert_eq!(
+            TlvStream::bolt_decode(&[0x1f, 0x00, 0x1f, 0x01, 0x2a][..])
+                .unwrap_err(),
+            Error::Tlv(TlvError::Repeated(0x1f))
+        );
+
+        let even: [(&[u8], u64); 4] = [
+            (&[0x12, 0x00], 0x12),
+            (&[0xfd, 0x01, 0x02, 0x00], 0x102),
+            (&[0xfe, 0x01, 0x00, 0x00, 0x02, 0x00], 0x0100_0002),
+            (
+                &[0xff, 0x01, 0, 0, 0, 0, 0, 0, 0x02, 0x00],
+                0x0100_0000_0000_0002,
+            ),
+        ];
+        for (data, type_no) in even {
+            let stream = TlvStream::bolt_decode(data).unwrap();
+            assert_eq!(
+                stream.check_unknown_even(|_| false),
+                Err(TlvError::UnknownEvenType(type_no))
+            );
+        }
+    }
+}
@@ -0,0 +1,1193 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Dynamic decoding of strict-encoded data driven by a runtime type
+//! description.
+//!
+//! [`StrictValue::decode`] reads data according to a [`TypeDescr`] (exported
+//! by [`crate::StrictType`] implementations or loaded from a file) following
+//! the same rules as [`StrictDecode`] implementations do, producing a generic
+//! tree of [`StrictValue`]s. The tree can be printed, serialized into JSON
+//! (with `serde` feature) and encoded back into the same bytes with its
+//! [`StrictEncode`] implementation.
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt::{self, Display, Formatter};
+
+use amplify::num::u24;
+
+use crate::limits::{check_items, prealloc_capacity, DepthGuard};
+use crate::schema::{
+    EnumDescr, FieldDescr, LenPrefix, Primitive, StructDescr, TypeDescr,
+};
+use crate::{io, Error, StrictDecode, StrictEncode, TlvError, TlvStream};
+
+/// Generic value of some strict-encoded data type
+#[derive(Clone, PartialEq, Debug)]
+pub enum StrictValue {
+    /// Unit value, which does not take any bytes
+    Unit,
+
+    /// Boolean value
+    Bool(bool),
+
+    /// Unsigned integer of up to 128 bits
+    Uint(Primitive, u128),
+
+    /// Signed integer of up to 128 bits
+    Int(Primitive, i128),
+
+    /// Integer of more than 128 bits, represented by its little-endian bytes
+    BigInt(Primitive, Vec<u8>),
+
+    /// Single-precision float
+    F32(f32),
+
+    /// Double-precision float
+    F64(f64),
+
+    /// Fixed-size byte string
+    Bytes(Vec<u8>),
+
+    /// Unicode string
+    Unicode(LenPrefix, String),
+
+    /// List of items
+    List(LenPrefix, Vec<StrictValue>),
+
+    /// Set of unique items, ordered by their value
+    Set(LenPrefix, Vec<StrictValue>),
+
+    /// Map of unique keys, ordered by their value, to the values
+    Map(LenPrefix, Vec<(StrictValue, StrictValue)>),
+
+    /// Optional value
+    Option(Option<Box<StrictValue>>),
+
+    /// Tuple of values
+    Tuple(Vec<StrictValue>),
+
+    /// Structure
+    Struct(StructValue),
+
+    /// Enum variant
+    Enum(EnumValue),
+
+    /// Value of a versioned data structure together with its version
+    Versioned(u8, Box<StrictValue>),
+}
+
+/// Value of a structure field or enum variant associated data
+#[derive(Clone, PartialEq, Debug)]
+pub struct FieldValue {
+    /// Field name; `None` for unnamed (tuple) fields
+    pub name: Option<String>,
+
+    /// Field value
+    pub value: StrictValue,
+}
+
+/// Value of a TLV record
+#[derive(Clone, PartialEq, Debug)]
+pub struct TlvValue {
+    /// TLV type id
+    pub id: u64,
+
+    /// Name of the field the TLV record corresponds to; `None` for unknown
+    /// TLV records
+    pub name: Option<String>,
+
+    /// Value of the record. Unknown records are represented by
+    /// [`StrictValue::Bytes`] with the raw record data.
+    pub value: StrictValue,
+}
+
+/// Value of a structure
+#[derive(Clone, PartialEq, Debug)]
+pub struct StructValue {
+    /// Name of the structure
+    pub name: String,
+
+    /// Fields, in the order of their encoding
+    pub fields: Vec<FieldValue>,
+
+    /// TLV records present in the data, ordered by their type id
+    pub tlvs: Vec<TlvValue>,
+}
+
+/// Value of an enum
+#[derive(Clone, PartialEq, Debug)]
+pub struct EnumValue {
+    /// Name of the enum
+    pub name: String,
+
+    /// Name of the enum variant
+    pub variant: String,
+
+    /// Integer type used to encode the enum tag
+    pub repr: Primitive,
+
+    /// Tag encoding the variant
+    pub tag: u64,
+
+    /// Data associated with the variant
+    pub fields: Vec<FieldValue>,
+}
+
+impl StrictValue {
+    /// Decodes value of the type described by `ty` from the reader.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`StrictDecode`] implementation of the
+    /// described type would return on the same data.
+    /// [`Error::UnsupportedDataStructure`] is returned for types described as
+    /// [`TypeDescr::Opaque`].
+    pub fn decode(
+        ty: &TypeDescr,
+        mut d: impl io::Read,
+    ) -> Result<StrictValue, Error> {
+        StrictValue::decode_dyn(ty, &mut d)
+    }
+
+    /// Decodes value of the type described by `ty` from the byte slice,
+    /// failing with [`Error::DataNotEntirelyConsumed`] if some data remain in
+    /// the slice after the value is decoded.
+    pub fn deserialize(
+        ty: &TypeDescr,
+        data: impl AsRef<[u8]>,
+    ) -> Result<StrictValue, Error> {
+        let mut decoder = data.as_ref();
+        let value = StrictValue::decode(ty, &mut decoder)?;
+        if decoder.is_empty() {
+            Ok(value)
+        } else {
+            Err(Error::DataNotEntirelyConsumed)
+        }
+    }
+
+    // Type descriptions are recursive, so we use dynamic reader to avoid
+    // infinite monomorphization of the decoding procedure.
+    fn decode_dyn(
+        ty: &TypeDescr,
+        mut d: &mut dyn io::Read,
+    ) -> Result<StrictValue, Error> {
+        Ok(match ty {
+            TypeDescr::Unit => StrictValue::Unit,
+            TypeDescr::Primitive(primitive) => {
+                decode_primitive(*primitive, &mut d)?
+            }
+            TypeDescr::Bytes(len) => {
+                let mut buf = vec![0u8; *len as usize];
+                d.read_exact(&mut buf)?;
+                StrictValue::Bytes(buf)
+            }
+            TypeDescr::Unicode(prefix) => {
+                let len = decode_len(*prefix, &mut d)?;
+                check_items(len)?;
+                let mut buf = Vec::with_capacity(prealloc_capacity::<u8>(len));
+                let mut chunk = [0u8; 0x400];
+                while buf.len() < len {
+                    let chunk = &mut chunk[..(len - buf.len()).min(0x400)];
+                    d.read_exact(chunk)?;
+                    buf.extend_from_slice(chunk);
+                }
+                StrictValue::Unicode(*prefix, String::from_utf8(buf)?)
+            }
+            TypeDescr::List(prefix, item) => {
+                let len = decode_len(*prefix, &mut d)?;
+                check_items(len)?;
+                let _depth = DepthGuard::enter()?;
+                let mut items =
+                    Vec::with_capacity(prealloc_capacity::<StrictValue>(len));
+                for _ in 0..len {
+                    let mut reader = CountingReader {
+                        inner: &mut *d,
+                        count: 0,
+                    };
+                    items.push(StrictValue::decode_dyn(item, &mut reader)?);
+                    if reader.count == 0 {
+                        return Err(Error::UnsupportedDataStructure(
+                            "list items taking no bytes can't be decoded \
+                             dynamically",
+                        ));
+                    }
+                }
+                StrictValue::List(*prefix, items)
+            }
+            TypeDescr::Set(prefix, item) => {
+                let len = decode_len(*prefix, &mut d)?;
+                check_items(len)?;
+                let _depth = DepthGuard::enter()?;
+                let mut items =
+                    Vec::<StrictValue>::with_capacity(prealloc_capacity::<
+                        StrictValue,
+                    >(
+                        len
+                    ));
+                for _ in 0..len {
+                    let val = StrictValue::decode_dyn(item, d)?;
+                    if let Some(last) = items.last() {
+                        check_order(last, &val)?;
+                    }
+                    items.push(val);
+                }
+                StrictValue::Set(*prefix, items)
+            }
+            TypeDescr::Map(prefix, key, val) => {
+                let len = decode_len(*prefix, &mut d)?;
+                check_items(len)?;
+                let _depth = DepthGuard::enter()?;
+                let mut items =
+                    Vec::<(StrictValue, StrictValue)>::with_capacity(
+                        prealloc_capacity::<(StrictValue, StrictValue)>(len),
+                    );
+                for _ in 0..len {
+                    let k = StrictValue::decode_dyn(key, d)?;
+                    if let Some((last, _)) = items.last() {
+                        check_order(last, &k)?;
+                    }
+                    let v = StrictValue::decode_dyn(val, d)?;
+                    items.push((k, v));
+                }
+                StrictValue::Map(*prefix, items)
+            }
+            TypeDescr::Option(inner) => match u8::strict_decode(&mut d)? {
+                0 => StrictValue::Option(None),
+                1 => StrictValue::Option(Some(Box::new(
+                    StrictValue::decode_dyn(inner, d)?,
+                ))),
+                invalid => return Err(Error::WrongOptionalEncoding(invalid)),
+            },
+            TypeDescr::Tuple(items) => {
+                let _depth = DepthGuard::enter()?;
+                StrictValue::Tuple(
+                    items
+                        .iter()
+                        .map(|item| StrictValue::decode_dyn(item, d))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            TypeDescr::Struct(descr) => {
+                let _depth = DepthGuard::enter()?;
+                StrictValue::Struct(decode_struct(descr, d)?)
+            }
+            TypeDescr::Enum(descr) => {
+                let _depth = DepthGuard::enter()?;
+                StrictValue::Enum(decode_enum(descr, d)?)
+            }
+            TypeDescr::Versioned(version, inner) => {
+                match u8::strict_decode(&mut d)? {
+                    ver if ver == *version => {}
+                    ver if ver > *version => {
+                        return Err(Error::UnsupportedDataStructure(
+                            "data structure version is newer than the \
+                             described one",
+                        ))
+                    }
+                    _ => {
+                        return Err(Error::UnsupportedDataStructure(
+                            "data of older data structure versions can't be \
+                             decoded dynamically",
+                        ))
+                    }
+                }
+                StrictValue::Versioned(
+                    *version,
+                    Box::new(StrictValue::decode_dyn(inner, d)?),
+                )
+            }
+            TypeDescr::Opaque(_) => {
+                return Err(Error::UnsupportedDataStructure(
+                    "opaque types can't be decoded dynamically",
+                ))
+            }
+        })
+    }
+
+    /// Compares two values of the same type in the same way as their rust
+    /// types do. Returns `None` if the values are of different types or can't
+    /// be compared (like `NaN` floats).
+    pub fn compare(&self, other: &StrictValue) -> Option<Ordering> {
+        match (self, other) {
+            (StrictValue::Unit, StrictValue::Unit) => Some(Ordering::Equal),
+            (StrictValue::Bool(a), StrictValue::Bool(b)) => a.partial_cmp(b),
+            (StrictValue::Uint(_, a), StrictValue::Uint(_, b)) => {
+                a.partial_cmp(b)
+            }
+            (StrictValue::Int(_, a), StrictValue::Int(_, b)) => {
+                a.partial_cmp(b)
+            }
+            (StrictValue::BigInt(p1, a), StrictValue::BigInt(p2, b))
+                if p1 == p2 && a.len() == b.len() =>
+            {
+                let signed = !matches!(
+                    p1,
+                    Primitive::U256 | Primitive::U512 | Primitive::U1024
+                );
+                let key = |bytes: &[u8]| {
+                    let mut be =
+                        bytes.iter().rev().copied().collect::<Vec<_>>();
+                    if signed {
+                        if let Some(msb) = be.first_mut() {
+                            *msb ^= 0x80;
+                        }
+                    }
+                    be
+                };
+                key(a).partial_cmp(&key(b))
+            }
+            (StrictValue::F32(a), StrictValue::F32(b)) => a.partial_cmp(b),
+            (StrictValue::F64(a), StrictValue::F64(b)) => a.partial_cmp(b),
+            (StrictValue::Bytes(a), StrictValue::Bytes(b)) => a.partial_cmp(b),
+            (StrictValue::Unicode(_, a), StrictValue::Unicode(_, b)) => {
+                a.partial_cmp(b)
+            }
+            (StrictValue::List(_, a), StrictValue::List(_, b))
+            | (StrictValue::Set(_, a), StrictValue::Set(_, b))
+            | (StrictValue::Tuple(a), StrictValue::Tuple(b)) => {
+                compare_iter(a.iter().zip(b), a.len(), b.len())
+            }
+            (StrictValue::Map(_, a), StrictValue::Map(_, b)) => {
+                for ((k1, v1), (k2, v2)) in a.iter().zip(b) {
+                    match k1.compare(k2)?.then(v1.compare(v2)?) {
+                        Ordering::Equal => continue,
+                        ord => return Some(ord),
+                    }
+                }
+                a.len().partial_cmp(&b.len())
+            }
+            (StrictValue::Option(a), StrictValue::Option(b)) => match (a, b) {
+                (None, None) => Some(Ordering::Equal),
+                (None, Some(_)) => Some(Ordering::Less),
+                (Some(_), None) => Some(Ordering::Greater),
+                (Some(a), Some(b)) => a.compare(b),
+            },
+            (StrictValue::Struct(a), StrictValue::Struct(b)) => compare_iter(
+                a.fields
+                    .iter()
+                    .map(|f| &f.value)
+                    .zip(b.fields.iter().map(|f| &f.value)),
+                a.fields.len(),
+                b.fields.len(),
+            ),
+            (StrictValue::Enum(a), StrictValue::Enum(b)) => {
+                match a.tag.cmp(&b.tag) {
+                    Ordering::Equal => compare_iter(
+                        a.fields
+                            .iter()
+                            .map(|f| &f.value)
+                            .zip(b.fields.iter().map(|f| &f.value)),
+                        a.fields.len(),
+                        b.fields.len(),
+                    ),
+                    ord => Some(ord),
+                }
+            }
+            (StrictValue::Versioned(v1, a), StrictValue::Versioned(v2, b))
+                if v1 == v2 =>
+            {
+                a.compare(b)
+            }
+            _ => None,
+        }
+    }
+
+    fn encode_dyn(&self, mut e: &mut dyn io::Write) -> Result<usize, Error> {
+        Ok(match self {
+            StrictValue::Unit => 0,
+            StrictValue::Bool(val) => val.strict_encode(&mut e)?,
+            StrictValue::Uint(primitive, val) => {
+                let len = primitive.byte_len();
+                if len < 16 && *val >> (len * 8) != 0 {
+                    return Err(Error::ValueOutOfRange(
+                        "unsigned integer",
+                        0..(1u128 << (len * 8)),
+                        *val,
+                    ));
+                }
+                e.write_all(&val.to_le_bytes()[..len])?;
+                len
+            }
+            StrictValue::Int(primitive, val) => {
+                let len = primitive.byte_len();
+                let shift = 128 - len * 8;
+                if (*val << shift) >> shift != *val {
+                    return Err(Error::DataIntegrityError(format!(
+                        "value {} does not fit into {}",
+                        val, primitive
+                    )));
+                }
+                e.write_all(&val.to_le_bytes()[..len])?;
+                len
+            }
+            StrictValue::BigInt(primitive, bytes) => {
+                if bytes.len() != primitive.byte_len() {
+                    return Err(Error::DataIntegrityError(format!(
+                        "{} must be represented by {} bytes",
+                        primitive,
+                        primitive.byte_len()
+                    )));
+                }
+                e.write_all(bytes)?;
+                bytes.len()
+            }
+            StrictValue::F32(val) => val.strict_encode(&mut e)?,
+            StrictValue::F64(val) => val.strict_encode(&mut e)?,
+            StrictValue::Bytes(bytes) => {
+                e.write_all(bytes)?;
+                bytes.len()
+            }
+            StrictValue::Unicode(prefix, s) => {
+                encode_len(*prefix, s.len(), &mut e)? + {
+                    e.write_all(s.as_bytes())?;
+                    s.len()
+                }
+            }
+            StrictValue::List(prefix, items)
+            | StrictValue::Set(prefix, items) => {
+                let mut len = encode_len(*prefix, items.len(), &mut e)?;
+                for item in items {
+                    len += item.encode_dyn(e)?;
+                }
+                len
+            }
+            StrictValue::Map(prefix, items) => {
+                let mut len = encode_len(*prefix, items.len(), &mut e)?;
+                for (key, val) in items {
+                    len += key.encode_dyn(e)?;
+                    len += val.encode_dyn(e)?;
+                }
+                len
+            }
+            StrictValue::Option(None) => 0u8.strict_encode(&mut e)?,
+            StrictValue::Option(Some(val)) => {
+                1u8.strict_encode(&mut e)? + val.encode_dyn(e)?
+            }
+            StrictValue::Tuple(items) => {
+                let mut len = 0;
+                for item in items {
+                    len += item.encode_dyn(e)?;
+                }
+                len
+            }
+            StrictValue::Struct(val) => {
+                let mut len = 0;
+                for field in &val.fields {
+                    len += field.value.encode_dyn(e)?;
+                }
+                if !val.tlvs.is_empty() {
+                    len += val.tlvs.len().strict_encode(&mut e)?;
+                    for tlv in &val.tlvs {
+                        len += tlv.id.strict_encode(&mut e)?;
+                        len += tlv
+                            .value
+                            .strict_serialize()?
+                            .strict_encode(&mut e)?;
+                    }
+                }
+                len
+            }
+            StrictValue::Enum(val) => {
+                let mut len = match val.repr {
+                    Primitive::U8 => u8::try_from(val.tag)
+                        .map_err(|_| Error::EnumValueOverflow("enum"))?
+                        .strict_encode(&mut e)?,
+                    Primitive::U16 => u16::try_from(val.tag)
+                        .map_err(|_| Error::EnumValueOverflow("enum"))?
+                        .strict_encode(&mut e)?,
+                    Primitive::U32 => u32::try_from(val.tag)
+                        .map_err(|_| Error::EnumValueOverflow("enum"))?
+                        .strict_encode(&mut e)?,
+                    Primitive::U64 => val.tag.strict_encode(&mut e)?,
+                    _ => {
+                        return Err(Error::UnsupportedDataStructure(
+                            "enum tags must be represented by u8, u16, u32 or \
+                             u64 value",
+                        ))
+                    }
+                };
+                for field in &val.fields {
+                    len += field.value.encode_dyn(e)?;
+                }
+                len
+            }
+            StrictValue::Versioned(version, val) => {
+                version.strict_encode(&mut e)? + val.encode_dyn(e)?
+            }
+        })
+    }
+}
+
+impl StrictEncode for StrictValue {
+    #[inline]
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        self.encode_dyn(&mut e)
+    }
+}
+
+fn compare_iter<'a>(
+    iter: impl Iterator<Item = (&'a StrictValue, &'a StrictValue)>,
+    len1: usize,
+    len2: usize,
+) -> Option<Ordering> {
+    for (a, b) in iter {
+        match a.compare(b)? {
+            Ordering::Equal => continue,
+            ord => return Some(ord),
+        }
+    }
+    len1.partial_cmp(&len2)
+}
+
+fn check_order(prev: &StrictValue, next: &StrictValue) -> Result<(), Error> {
+    match prev.compare(next) {
+        Some(Ordering::Greater) => Err(Error::DataIntegrityError(format!(
+            "encoded values are not deterministically ordered: value `{}` \
+             should go before `{}`",
+            next, prev
+        ))),
+        Some(Ordering::Equal) => Err(Error::RepeatedValue(next.to_string())),
+        _ if prev == next => Err(Error::RepeatedValue(next.to_string())),
+        _ => Ok(()),
+    }
+}
+
+// Lists of items taking no bytes (like unit values or structures ending with
+// an empty TLV stream at the end of data) may declare any number of items
+// without providing any data for them. Each of the items still takes memory
+// once decoded as a `StrictValue`, so the list decoder counts bytes consumed by
+// each item and rejects such lists.
+struct CountingReader<'a> {
+    inner: &'a mut dyn io::Read,
+    count: usize,
+}
+
+impl io::Read for CountingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.count += read;
+        Ok(read)
+    }
+}
+
+pub(crate) fn decode_len(
+    prefix: LenPrefix,
+    mut d: impl io::Read,
+) -> Result<usize, Error> {
+    Ok(match prefix {
+        LenPrefix::U16 => usize::strict_decode(&mut d)?,
+        LenPrefix::U24 => u24::strict_decode(&mut d)?.as_u32() as usize,
+        LenPrefix::U32 => u32::strict_decode(&mut d)? as usize,
+    })
+}
+
+pub(crate) fn encode_len(
+    prefix: LenPrefix,
+    len: usize,
+    mut e: impl io::Write,
+) -> Result<usize, Error> {
+    match prefix {
+        LenPrefix::U16 => len.strict_encode(&mut e),
+        LenPrefix::U24 => u24::try_from(len as u32)
+            .ok()
+            .filter(|_| len <= u32::MAX as usize)
+            .ok_or(Error::ExceedMaxItems(len))?
+            .strict_encode(&mut e),
+        LenPrefix::U32 => u32::try_from(len)
+            .map_err(|_| Error::ExceedMaxItems(len))?
+            .strict_encode(&mut e),
+    }
+}
+
+fn decode_primitive(
+    primitive: Primitive,
+    mut d: impl io::Read,
+) -> Result<StrictValue, Error> {
+    Ok(match primitive {
+        Primitive::Bool => StrictValue::Bool(bool::strict_decode(&mut d)?),
+        Primitive::F32 => StrictValue::F32(f32::strict_decode(&mut d)?),
+        Primitive::F64 => StrictValue::F64(f64::strict_decode(&mut d)?),
+        Primitive::U256
+        | Primitive::U512
+        | Primitive::U1024
+        | Primitive::I256
+        | Primitive::I512
+        | Primitive::I1024 => {
+            let mut buf = vec![0u8; primitive.byte_len()];
+            d.read_exact(&mut buf)?;
+            StrictValue::BigInt(primitive, buf)
+        }
+        Primitive::U8
+        | Primitive::U16
+        | Primitive::U24
+        | Primitive::U32
+        | Primitive::U64
+        | Primitive::U128 => {
+            let mut buf = [0u8; 16];
+            d.read_exact(&mut buf[..primitive.byte_len()])?;
+            StrictValue::Uint(primitive, u128::from_le_bytes(buf))
+        }
+        Primitive::I8
+        | Primitive::I16
+        | Primitive::I32
+        | Primitive::I64
+        | Primitive::I128 => {
+            let len = primitive.byte_len();
+            let mut buf = [0u8; 16];
+            d.read_exact(&mut buf[..len])?;
+            let shift = 128 - len * 8;
+            // Sign-extending the value
+            let val = (i128::from_le_bytes(buf) << shift) >> shift;
+            StrictValue::Int(primitive, val)
+        }
+    })
+}
+
+fn decode_fields(
+    fields: &[FieldDescr],
+    d: &mut dyn io::Read,
+) -> Result<Vec<FieldValue>, Error> {
+    fields
+        .iter()
+        .map(|field| {
+            Ok(FieldValue {
+                name: field.name.clone(),
+                value: StrictValue::decode_dyn(&field.ty, d)?,
+            })
+        })
+        .collect()
+}
+
+fn decode_struct(
+    descr: &StructDescr,
+    mut d: &mut dyn io::Read,
+) -> Result<StructValue, Error> {
+    let fields = decode_fields(&descr.fields, d)?;
+
+    let mut tlvs = Vec::<TlvValue>::new();
+    if !descr.tlvs.is_empty() || descr.unknown_tlvs {
+        for (id, data) in TlvStream::strict_decode(&mut d)? {
+            let tlv = match descr.tlvs.iter().find(|tlv| tlv.id == id) {
+                Some(tlv) => TlvValue {
+                    id,
+                    name: tlv.name.clone(),
+                    value: StrictValue::deserialize(&tlv.ty, data)?,
+                },
+                None if id % 2 == 0 => {
+                    return Err(TlvError::UnknownEvenType(id).into())
+                }
+                None => TlvValue {
+                    id,
+                    name: None,
+                    value: StrictValue::Bytes(data.into_vec()),
+                },
+            };
+            tlvs.push(tlv);
+        }
+    }
+
+    Ok(StructValue {
+        name: descr.name.clone(),
+        fields,
+        tlvs,
+    })
+}
+
+fn decode_enum(
+    descr: &EnumDescr,
+    mut d: &mut dyn io::Read,
+) -> Result<EnumValue, Error> {
+    let tag = match descr.repr {
+        Primitive::U8 => u8::strict_decode(&mut d)? as u64,
+        Primitive::U16 => u16::strict_decode(&mut d)? as u64,
+        Primitive::U32 => u32::strict_decode(&mut d)? as u64,
+        Primitive::U64 => u64::strict_decode(&mut d)?,
+        _ => {
+            return Err(Error::UnsupportedDataStructure(
+                "enum tags must be represented by u8, u16, u32 or u64 value",
+            ))
+        }
+    };
+    let variant = descr
+        .variants
+        .iter()
+        .find(|variant| variant.value == tag)
+        .ok_or(Error::EnumValueNotKnown("enum", tag as usize))?;
+    Ok(EnumValue {
+        name: descr.name.clone(),
+        variant: variant.name.clone(),
+        repr: descr.repr,
+        tag,
+        fields: decode_fields(&variant.fields, d)?,
+    })
+}
+
+fn fmt_hex(
+    f: &mut Formatter<'_>,
+    bytes: impl Iterator<Item = u8>,
+) -> fmt::Result {
+    f.write_str("0x")?;
+    for byte in bytes {
+        write!(f, "{:02x}", byte)?;
+    }
+    Ok(())
+}
+
+fn fmt_fields(f: &mut Formatter<'_>, fields: &[FieldValue]) -> fmt::Result {
+    if fields.is_empty() {
+        return Ok(());
+    }
+    let named = fields.iter().all(|field| field.name.is_some());
+    f.write_str(if named { " { " } else { "(" })?;
+    for (index, field) in fields.iter().enumerate() {
+        if index > 0 {
+            f.write_str(", ")?;
+        }
+        match field.name {
+            Some(ref name) if named => write!(f, "{}: {}", name, field.value)?,
+            _ => Display::fmt(&field.value, f)?,
+        }
+    }
+    f.write_str(if named { " }" } else { ")" })
+}
+
+impl Display for StrictValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            StrictValue::Unit => f.write_str("()"),
+            StrictValue::Bool(val) => Display::fmt(val, f),
+            StrictValue::Uint(_, val) => Display::fmt(val, f),
+            StrictValue::Int(_, val) => Display::fmt(val, f),
+            // Big integers are printed as big-endian hex numbers
+            StrictValue::BigInt(_, bytes) => {
+                fmt_hex(f, bytes.iter().rev().copied())
+            }
+            StrictValue::F32(val) => Display::fmt(val, f),
+            StrictValue::F64(val) => Display::fmt(val, f),
+            StrictValue::Bytes(bytes) => fmt_hex(f, bytes.iter().copied()),
+            StrictValue::Unicode(_, s) => write!(f, "{:?}", s),
+            StrictValue::List(_, items) | StrictValue::Set(_, items) => {
+                f.write_str("[")?;
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    Display::fmt(item, f)?;
+                }
+                f.write_str("]")
+            }
+            StrictValue::Map(_, items) => {
+                f.write_str("{")?;
+                for (index, (key, val)) in items.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}: {}", key, val)?;
+                }
+                f.write_str("}")
+            }
+            StrictValue::Option(None) => f.write_str("None"),
+            StrictValue::Option(Some(val)) => write!(f, "Some({})", val),
+            StrictValue::Tuple(items) => {
+                f.write_str("(")?;
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    Display::fmt(item, f)?;
+                }
+                f.write_str(")")
+            }
+            StrictValue::Struct(val) => {
+                f.write_str(&val.name)?;
+                fmt_fields(f, &val.fields)?;
+                for tlv in &val.tlvs {
+                    match tlv.name {
+                        Some(ref name) => {
+                            write!(f, " <{}#{}: {}>", name, tlv.id, tlv.value)?
+                        }
+                        None => write!(f, " <#{}: {}>", tlv.id, tlv.value)?,
+                    }
+                }
+                Ok(())
+            }
+            StrictValue::Enum(val) => {
+                write!(f, "{}::{}", val.name, val.variant)?;
+                fmt_fields(f, &val.fields)
+            }
+            StrictValue::Versioned(version, val) => {
+                write!(f, "v{} {}", version, val)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+mod _serde {
+    use serde::ser::{SerializeMap, SerializeSeq};
+    use serde::{Serialize, Serializer};
+
+    use super::*;
+
+    struct Hex<'a>(&'a [u8], bool);
+
+    impl<'a> Display for Hex<'a> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            if self.1 {
+                fmt_hex(f, self.0.iter().rev().copied())
+            } else {
+                fmt_hex(f, self.0.iter().copied())
+            }
+        }
+    }
+
+    struct Fields<'a>(&'a [FieldValue], &'a [TlvValue]);
+
+    impl<'a> Serialize for Fields<'a> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let Fields(fields, tlvs) = *self;
+            if fields.iter().all(|field| field.name.is_some()) {
+                let mut map = serializer
+                    .serialize_map(Some(fields.len() + tlvs.len()))?;
+                for field in fields {
+                    let name = field.name.as_deref().unwrap_or_default();
+                    map.serialize_entry(name, &field.value)?;
+                }
+                for tlv in tlvs {
+                    match tlv.name {
+                        Some(ref name) => {
+                            map.serialize_entry(name, &tlv.value)?
+                        }
+                        None => map
+                            .serialize_entry(&tlv.id.to_string(), &tlv.value)?,
+                    }
+                }
+                map.end()
+            } else if fields.len() == 1 && tlvs.is_empty() {
+                fields[0].value.serialize(serializer)
+            } else {
+                let mut seq = serializer
+                    .serialize_seq(Some(fields.len() + tlvs.len()))?;
+                for field in fields {
+                    seq.serialize_element(&field.value)?;
+                }
+                for tlv in tlvs {
+                    seq.serialize_element(&(tlv.id, &tlv.value))?;
+                }
+                seq.end()
+            }
+        }
+    }
+
+    /// Values are serialized into their natural JSON representation: numbers,
+    /// strings, arrays and objects for structures. Byte strings and integers
+    /// larger than 128 bits are serialized as hex strings; maps are
+    /// serialized as arrays of key-value pairs; enum variants are serialized
+    /// as variant names (if they have no associated data) or as single-key
+    /// objects.
+    impl Serialize for StrictValue {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            match self {
+                StrictValue::Unit => serializer.serialize_unit(),
+                StrictValue::Bool(val) => serializer.serialize_bool(*val),
+                StrictValue::Uint(_, val) => serializer.serialize_u128(*val),
+                StrictValue::Int(_, val) => serializer.serialize_i128(*val),
+                StrictValue::BigInt(_, bytes) => {
+                    serializer.collect_str(&Hex(bytes, true))
+                }
+                StrictValue::F32(val) => serializer.serialize_f32(*val),
+                StrictValue::F64(val) => serializer.serialize_f64(*val),
+                StrictValue::Bytes(bytes) => {
+                    serializer.collect_str(&Hex(bytes, false))
+                }
+                StrictValue::Unicode(_, s) => serializer.serialize_str(s),
+                StrictValue::List(_, items)
+                | StrictValue::Set(_, items)
+                | StrictValue::Tuple(items) => serializer.collect_seq(items),
+                StrictValue::Map(_, items) => {
+                    serializer.collect_seq(items.iter().map(|(k, v)| (k, v)))
+                }
+                StrictValue::Option(val) => match val {
+                    None => serializer.serialize_none(),
+                    Some(val) => serializer.serialize_some(val.as_ref()),
+                },
+                StrictValue::Struct(val) => {
+                    Fields(&val.fields, &val.tlvs).serialize(serializer)
+                }
+                StrictValue::Enum(val) if val.fields.is_empty() => {
+                    serializer.serialize_str(&val.variant)
+                }
+                StrictValue::Enum(val) => {
+                    let mut map = serializer.serialize_map(Some(1))?;
+                    map.serialize_entry(
+                        &val.variant,
+                        &Fields(&val.fields, &[]),
+                    )?;
+                    map.end()
+                }
+                StrictValue::Versioned(_, val) => val.serialize(serializer),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use super::*;
+    use crate::{LargeVec, MediumVec, StrictType};
+
+    #[derive(Clone, PartialEq, Debug)]
+    #[derive(StrictEncode, StrictDecode, StrictType)]
+    #[strict_encoding(crate = crate)]
+    enum Kind {
+        Empty,
+        Value(i16),
+        #[strict_encoding(value = 0x10)]
+        Named {
+            key: Option<String>,
+            flag: bool,
+        },
+    }
+
+    #[derive(Clone, PartialEq, Debug)]
+    #[derive(StrictEncode, StrictDecode, StrictType)]
+    #[strict_encoding(crate = crate)]
+    struct Data {
+        id: u32,
+        name: String,
+        kinds: MediumVec<Kind>,
+        set: BTreeSet<i64>,
+        map: BTreeMap<u8, LargeVec<u8>>,
+        digest: [u8; 4],
+        pair: (u8, i8),
+    }
+
+    fn data() -> Data {
+        Data {
+            id: 0xDEADCAFE,
+            name: s!("data"),
+            kinds: MediumVec::try_from(vec![
+                Kind::Empty,
+                Kind::Value(-2),
+                Kind::Named {
+                    key: Some(s!("k")),
+                    flag: true,
+                },
+            ])
+            .unwrap(),
+            set: bset! { -5, 1, 7 },
+            map: bmap! { 1u8 => LargeVec::try_from(vec![0xAA]).unwrap() },
+            digest: [1, 2, 3, 4],
+            pair: (0xFF, -1),
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let data = data();
+        let ser = data.strict_serialize().unwrap();
+        let value =
+            StrictValue::deserialize(&Data::strict_type(), &ser).unwrap();
+        assert_eq!(value.strict_serialize().unwrap(), ser);
+
+        let fields = match value {
+            StrictValue::Struct(ref val) => &val.fields,
+            _ => panic!("structure must be decoded as a structure value"),
+        };
+        assert_eq!(fields[0].name.as_deref(), Some("id"));
+        assert_eq!(
+            fields[0].value,
+            StrictValue::Uint(Primitive::U32, 0xDEADCAFE)
+        );
+        assert_eq!(
+            fields[3].value,
+            StrictValue::Set(LenPrefix::U16, vec![
+                StrictValue::Int(Primitive::I64, -5),
+                StrictValue::Int(Primitive::I64, 1),
+                StrictValue::Int(Primitive::I64, 7),
+            ])
+        );
+    }
+
+    #[test]
+    fn display() {
+        let ser = data().strict_serialize().unwrap();
+        let value =
+            StrictValue::deserialize(&Data::strict_type(), ser).unwrap();
+        assert_eq!(
+            value.to_string(),
+            "Data { id: 3735931646, name: \"data\", kinds: [Kind::Empty, \
+             Kind::Value(-2), Kind::Named { key: Some(\"k\"), flag: true }], \
+             set: [-5, 1, 7], map: {1: [170]}, digest: 0x01020304, pair: \
+             (255, -1) }"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn json() {
+        let ser = data().strict_serialize().unwrap();
+        let value =
+            StrictValue::deserialize(&Data::strict_type(), ser).unwrap();
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"{"id":3735931646,"name":"data","kinds":["Empty",{"Value":-2},{"Named":{"key":"k","flag":true}}],"set":[-5,1,7],"map":[[1,[170]]],"digest":"0x01020304","pair":[255,-1]}"#
+        );
+    }
+
+    #[test]
+    fn decoding_rules() {
+        let ty = Option::<bool>::strict_type();
+        assert_eq!(
+            StrictValue::deserialize(&ty, [0x02]).unwrap_err(),
+            Error::WrongOptionalEncoding(2)
+        );
+        assert_eq!(
+            StrictValue::deserialize(&ty, [0x01, 0x02]).unwrap_err(),
+            Error::ValueOutOfRange("boolean", 0..1, 2)
+        );
+        assert_eq!(
+            StrictValue::deserialize(&ty, [0x00, 0x00]).unwrap_err(),
+            Error::DataNotEntirelyConsumed
+        );
+        assert_eq!(
+            StrictValue::deserialize(&Kind::strict_type(), [0x03]).unwrap_err(),
+            Error::EnumValueNotKnown("enum", 3)
+        );
+
+        let ty = BTreeSet::<u16>::strict_type();
+        assert_eq!(
+            StrictValue::deserialize(&ty, [0x02, 0x00, 0x01, 0x00, 0x01, 0x00])
+                .unwrap_err(),
+            Error::RepeatedValue(s!("1"))
+        );
+        assert!(StrictValue::deserialize(&ty, [
+            0x02, 0x00, 0x02, 0x00, 0x01, 0x00
+        ])
+        .is_err());
+
+        assert_eq!(
+            StrictValue::deserialize(&TypeDescr::opaque("Transaction"), [])
+                .unwrap_err(),
+            Error::UnsupportedDataStructure(
+                "opaque types can't be decoded dynamically"
+            )
+        );
+
+        let ty = TypeDescr::list(LenPrefix::U32, TypeDescr::Tuple(vec![]));
+        assert_eq!(
+            StrictValue::deserialize(&ty, [0xFF, 0xFF, 0xFF, 0xFF])
+                .unwrap_err(),
+            Error::UnsupportedDataStructure(
+                "list items taking no bytes can't be decoded dynamically"
+            )
+        );
+        assert_eq!(
+            StrictValue::deserialize(&ty, [0x00, 0x00, 0x00, 0x00]).unwrap(),
+            StrictValue::List(LenPrefix::U32, vec![])
+        );
+
+        // Empty TLV stream at the end of data takes no bytes
+        let ty = TypeDescr::list(
+            LenPrefix::U32,
+            TypeDescr::Struct(StructDescr {
+                name: s!("Tlv"),
+                fields: vec![],
+                tlvs: vec![],
+                unknown_tlvs: true,
+            }),
+        );
+        assert_eq!(
+            StrictValue::deserialize(&ty, [0x00, 0x00, 0x00, 0x01])
+                .unwrap_err(),
+            Error::UnsupportedDataStructure(
+                "list items taking no bytes can't be decoded dynamically"
+            )
+        );
+    }
+
+    #[test]
+    fn tlv_stream() {
+        let ty = TypeDescr::Struct(StructDescr {
+            name: s!("Tlv"),
+            fields: vec![FieldDescr::named(
+                "fixed",
+                TypeDescr::Primitive(Primitive::U8),
+            )],
+            tlvs: vec![crate::schema::TlvDescr {
+                id: 1,
+                name: Some(s!("known")),
+                ty: TypeDescr::Primitive(Primitive::U16),
+            }],
+            unknown_tlvs: true,
+        });
+
+        let value = StrictValue::deserialize(&ty, [0x05]).unwrap();
+        assert_eq!(value.to_string(), "Tlv { fixed: 5 }");
+        assert_eq!(value.strict_serialize().unwrap(), vec![0x05]);
+
+        let data = [
+            0x05, 0x02, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x02, 0x00, 0x34, 0x12, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x01, 0x00, 0xFF,
+        ];
+        let value = StrictValue::deserialize(&ty, data).unwrap();
+        assert_eq!(
+            value.to_string(),
+            "Tlv { fixed: 5 } <known#1: 4660> <#3: 0xff>"
+        );
+        assert_eq!(value.strict_serialize().unwrap(), data);
+
+        let mut data = data;
+        data[15] = 0x04;
+        assert_eq!(
+            StrictValue::deserialize(&ty, data).unwrap_err(),
+            TlvError::UnknownEvenType(4).into()
+        );
+    }
+
+    #[test]
+    fn integers() {
+        let ty = <(i8, i128)>::strict_type();
+        let ser = (-1i8, i128::MIN).strict_serialize().unwrap();
+        let value = StrictValue::deserialize(&ty, &ser).unwrap();
+        assert_eq!(
+            value,
+            StrictValue::Tuple(vec![
+                StrictValue::Int(Primitive::I8, -1),
+                StrictValue::Int(Primitive::I128, i128::MIN)
+            ])
+        );
+        assert_eq!(value.strict_serialize().unwrap(), ser);
+
+        assert!(StrictValue::Uint(Primitive::U8, 0x100)
+            .strict_serialize()
+            .is_err());
+        assert!(StrictValue::Int(Primitive::I8, -129)
+            .strict_serialize()
+            .is_err());
+    }
+}
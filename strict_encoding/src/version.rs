@@ -0,0 +1,105 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Versioned strict encoding of persisted data structures.
+//!
+//! Types deriving strict encoding with `#[strict_encoding(version = N)]`
+//! attribute are encoded with a single byte holding version number `N`
+//! followed by the data of the type. Decoders read the version byte first
+//! and
+//! - decode the data of the type if the version is `N`;
+//! - fail with [`Error::UnsupportedDataStructure`] if the version is greater
+//!   than `N`, i.e. the data were created by a newer software;
+//! - for the older versions either fail with the same error, or, if the type is
+//!   marked with `migrate` argument, call [`StrictMigrate`] implementation of
+//!   the type, which decodes the data of the older version and upgrades them
+//!   into the current one.
+//!
+//! Older versions of a type are usually kept as separate types decoding
+//! the layout of the corresponding version, so per-version decode functions
+//! are just their `StrictDecode` implementations:
+//!
+//! ```
+//! use strict_encoding::version::StrictMigrate;
+//! use strict_encoding::{io, Error, StrictDecode, StrictEncode};
+//!
+//! // Layout of the version 1, which had no `timeout` field
+//! #[derive(StrictDecode)]
+//! struct ConfigV1 {
+//!     name: String,
+//! }
+//!
+//! #[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+//! #[strict_encoding(version = 2, migrate)]
+//! struct Config {
+//!     name: String,
+//!     timeout: u16,
+//! }
+//!
+//! impl StrictMigrate for Config {
+//!     fn strict_migrate<D: io::Read>(
+//!         version: u8,
+//!         d: D,
+//!     ) -> Result<Self, Error> {
+//!         match version {
+//!             1 => {
+//!                 let v1 = ConfigV1::strict_decode(d)?;
+//!                 Ok(Config {
+//!                     name: v1.name,
+//!                     timeout: 60,
+//!                 })
+//!             }
+//!             _ => Err(Error::UnsupportedDataStructure(
+//!                 "configurations of version 0 are not supported",
+//!             )),
+//!         }
+//!     }
+//! }
+//!
+//! let config = Config::strict_deserialize([1, 1, 0, b'a']).unwrap();
+//! assert_eq!(config, Config {
+//!     name: "a".to_string(),
+//!     timeout: 60
+//! });
+//! assert_eq!(config.strict_serialize().unwrap(), [2, 1, 0, b'a', 60, 0]);
+//! assert!(matches!(
+//!     Config::strict_deserialize([3, 1, 0, b'a', 60, 0]),
+//!     Err(Error::UnsupportedDataStructure(_))
+//! ));
+//! ```
+//!
+//! Layout of the versioned types is described by
+//! [`TypeDescr::Versioned`](crate::schema::TypeDescr::Versioned).
+
+use crate::{io, Error};
+
+/// Decoding of the strictly encoded data of the older versions of a data
+/// structure, upgrading them into its current version.
+///
+/// Implementation of the trait is required for the types deriving strict
+/// decoding with `#[strict_encoding(version = N, migrate)]` attribute. See
+/// [module-level documentation](self) for the details.
+pub trait StrictMigrate: Sized {
+    /// Decodes data of the given `version`, which is always less than the
+    /// current version of the type, and converts them into the current
+    /// version of the type. The version byte is already consumed from the
+    /// reader.
+    ///
+    /// # Errors
+    ///
+    /// Implementations should return [`Error::UnsupportedDataStructure`] for
+    /// the versions which can't be upgraded, and any other decoding error
+    /// otherwise.
+    fn strict_migrate<D: io::Read>(version: u8, d: D) -> Result<Self, Error>;
+}
@@ -0,0 +1,1383 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Property-based testing of strict encoding with [`proptest`].
+//!
+//! Strategies generating values which are guaranteed to be strict-encodable
+//! are provided by [`StrictArbitrary`] trait. The trait is implemented for
+//! strict-encodable types of the standard library, `amplify` and
+//! `bitcoin_hashes` crates, and for collection and networking types of
+//! [`strict_encoding`]. With `bitcoin` and `chrono` features it is also
+//! implemented for the types of the corresponding crates, except
+//! `PartiallySignedTransaction`, `psbt::raw::Pair`, `TapTree` and
+//! `ScriptLeaf`. Types enabled by `float`, `crypto`, `miniscript` and `monero`
+//! features of [`strict_encoding`] and schema description types are not
+//! covered. User structures and enums get the trait implementation with
+//! [`strict_arbitrary`](crate::strict_arbitrary) macro:
+//!
+//! ```
+//! # #[macro_use] extern crate strict_encoding;
+//! # #[macro_use] extern crate strict_encoding_test;
+//! #[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+//! struct Transfer {
+//!     amount: u64,
+//!     memo: Option<String>,
+//! }
+//! strict_arbitrary!(struct Transfer { amount, memo });
+//!
+//! #[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+//! enum Payment {
+//!     Cancel,
+//!     Single(Transfer),
+//!     Batch { transfers: Vec<Transfer>, fee: u32 },
+//! }
+//! strict_arbitrary!(enum Payment {
+//!     Cancel,
+//!     Single(transfer),
+//!     Batch { transfers, fee },
+//! });
+//!
+//! check_strict_roundtrip!(Payment, cases = 64).unwrap();
+//! ```
+//!
+//! Generated collections are limited to [`MAX_GENERATED_ITEMS`] items, so
+//! nested collections still fit into strict encoding limits and tests run
+//! fast.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::net::{
+    IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6,
+};
+use std::ops::{Range, RangeFrom, RangeInclusive, RangeTo, RangeToInclusive};
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use amplify::flags::FlagVec;
+use amplify::num::{i1024, i256, i512, u1024, u24, u256, u512};
+use amplify::Slice32;
+use bitcoin_hashes::{
+    hash160, hmac, ripemd160, sha256, sha256d, sha256t, sha512,
+};
+use proptest::collection::{btree_map, btree_set, vec, SizeRange};
+use proptest::prelude::*;
+use proptest::strategy::BoxedStrategy;
+use proptest::test_runner::{Config, TestCaseError, TestError, TestRunner};
+use strict_encoding::net::{
+    DnsName, I2pAddr, OnionAddrV3, Transport, Uniform, UniformAddr,
+};
+use strict_encoding::{
+    BigSize, Confined, LargeVec, MediumVec, StrictDecode, StrictEncode,
+    TlvStream,
+};
+
+use crate::{test_object_encoding_roundtrip, test_truncated_decoding};
+
+/// Maximal number of items in generated collections
+pub const MAX_GENERATED_ITEMS: usize = 16;
+
+/// Types which can be randomly generated for property-based tests of strict
+/// encoding.
+///
+/// The trait is used instead of [`proptest::arbitrary::Arbitrary`] since the
+/// latter can't be implemented for strict encoding collection types outside
+/// of [`proptest`] and [`strict_encoding`] crates; it also ensures that
+/// generated values do not exceed strict encoding limits.
+pub trait StrictArbitrary: StrictEncode + StrictDecode + Clone + Debug {
+    /// Returns strategy generating strict-encodable values of the type.
+    fn strict_strategy() -> BoxedStrategy<Self>;
+}
+
+macro_rules! impl_strict_arbitrary_any {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl StrictArbitrary for $ty {
+                #[inline]
+                fn strict_strategy() -> BoxedStrategy<Self> {
+                    any::<$ty>().boxed()
+                }
+            }
+        )+
+    };
+}
+
+impl_strict_arbitrary_any!(
+    (),
+    bool,
+    u8,
+    i8,
+    u16,
+    i16,
+    u32,
+    i32,
+    u64,
+    i64,
+    u128,
+    i128,
+    Ipv4Addr,
+    Ipv6Addr,
+);
+
+impl StrictArbitrary for usize {
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        (0..=u16::MAX as usize).boxed()
+    }
+}
+
+impl StrictArbitrary for u24 {
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        any::<[u8; 3]>().prop_map(u24::from_le_bytes).boxed()
+    }
+}
+
+macro_rules! impl_strict_arbitrary_float {
+    ($($ty:ident),+) => {
+        $(
+            /// NaN values are not generated since they are not equal to
+            /// themselves and can't be checked in roundtrip tests.
+            impl StrictArbitrary for $ty {
+                fn strict_strategy() -> BoxedStrategy<Self> {
+                    use proptest::num::$ty;
+                    ($ty::POSITIVE
+                        | $ty::NEGATIVE
+                        | $ty::NORMAL
+                        | $ty::SUBNORMAL
+                        | $ty::ZERO
+                        | $ty::INFINITE)
+                        .boxed()
+                }
+            }
+        )+
+    };
+}
+
+impl_strict_arbitrary_float!(f32, f64);
+
+macro_rules! impl_strict_arbitrary_bigint {
+    ($($ty:ident: $len:literal),+ $(,)?) => {
+        $(
+            impl StrictArbitrary for $ty {
+                fn strict_strategy() -> BoxedStrategy<Self> {
+                    <[u8; $len]>::strict_strategy()
+                        .prop_map($ty::from_le_bytes)
+                        .boxed()
+                }
+            }
+        )+
+    };
+}
+
+impl_strict_arbitrary_bigint!(
+    u256: 32,
+    u512: 64,
+    u1024: 128,
+    i256: 32,
+    i512: 64,
+    i1024: 128,
+);
+
+impl StrictArbitrary for Duration {
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        (any::<u64>(), 0..1_000_000_000u32)
+            .prop_map(|(secs, nanos)| Duration::new(secs, nanos))
+            .boxed()
+    }
+}
+
+impl<const LEN: usize> StrictArbitrary for [u8; LEN] {
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        vec(any::<u8>(), LEN)
+            .prop_map(|bytes| {
+                bytes.try_into().expect("vector of the array length")
+            })
+            .boxed()
+    }
+}
+
+impl StrictArbitrary for Slice32 {
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        <[u8; 32]>::strict_strategy()
+            .prop_map(Slice32::from)
+            .boxed()
+    }
+}
+
+impl StrictArbitrary for Box<[u8]> {
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        vec(any::<u8>(), 0..=MAX_GENERATED_ITEMS)
+            .prop_map(Vec::into_boxed_slice)
+            .boxed()
+    }
+}
+
+impl StrictArbitrary for String {
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        vec(any::<char>(), 0..=MAX_GENERATED_ITEMS)
+            .prop_map(String::from_iter)
+            .boxed()
+    }
+}
+
+impl<T> StrictArbitrary for Option<T>
+where
+    T: StrictArbitrary + 'static,
+{
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        proptest::option::of(T::strict_strategy()).boxed()
+    }
+}
+
+impl<T> StrictArbitrary for Rc<T>
+where
+    T: StrictArbitrary + 'static,
+{
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        T::strict_strategy().prop_map(Rc::new).boxed()
+    }
+}
+
+impl<T> StrictArbitrary for Arc<T>
+where
+    T: StrictArbitrary + Send + Sync + 'static,
+{
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        T::strict_strategy().prop_map(Arc::new).boxed()
+    }
+}
+
+impl<A, B> StrictArbitrary for (A, B)
+where
+    A: StrictArbitrary + 'static,
+    B: StrictArbitrary + 'static,
+{
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        (A::strict_strategy(), B::strict_strategy()).boxed()
+    }
+}
+
+impl<T> StrictArbitrary for Vec<T>
+where
+    T: StrictArbitrary + 'static,
+{
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        vec(T::strict_strategy(), 0..=MAX_GENERATED_ITEMS).boxed()
+    }
+}
+
+impl<T> StrictArbitrary for MediumVec<T>
+where
+    T: StrictArbitrary + 'static,
+{
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        Vec::<T>::strict_strategy()
+            .prop_map(|vec| {
+                MediumVec::try_from(vec).expect("small vector length")
+            })
+            .boxed()
+    }
+}
+
+impl<T> StrictArbitrary for LargeVec<T>
+where
+    T: StrictArbitrary + 'static,
+{
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        Vec::<T>::strict_strategy()
+            .prop_map(|vec| {
+                LargeVec::try_from(vec).expect("small vector length")
+            })
+            .boxed()
+    }
+}
+
+impl<T> StrictArbitrary for BTreeSet<T>
+where
+    T: StrictArbitrary + Ord + 'static,
+{
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        btree_set(T::strict_strategy(), 0..=MAX_GENERATED_ITEMS).boxed()
+    }
+}
+
+impl<K, V> StrictArbitrary for BTreeMap<K, V>
+where
+    K: StrictArbitrary + Ord + 'static,
+    V: StrictArbitrary + 'static,
+{
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        btree_map(
+            K::strict_strategy(),
+            V::strict_strategy(),
+            0..=MAX_GENERATED_ITEMS,
+        )
+        .boxed()
+    }
+}
+
+impl StrictArbitrary for BigSize {
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        any::<u64>().prop_map(BigSize::from).boxed()
+    }
+}
+
+/// NB: Truncated TLV streams are decoded as empty streams by design, so
+/// generated values can't be checked with
+/// [`check_strict_roundtrip`](crate::check_strict_roundtrip) macro.
+impl StrictArbitrary for TlvStream {
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        BTreeMap::<u64, Box<[u8]>>::strict_strategy()
+            .prop_map(TlvStream::from)
+            .boxed()
+    }
+}
+
+impl StrictArbitrary for IpAddr {
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        prop_oneof![
+            Ipv4Addr::strict_strategy().prop_map(IpAddr::V4),
+            Ipv6Addr::strict_strategy().prop_map(IpAddr::V6),
+        ]
+        .boxed()
+    }
+}
+
+impl StrictArbitrary for SocketAddrV4 {
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        (Ipv4Addr::strict_strategy(), any::<u16>())
+            .prop_map(|(ip, port)| SocketAddrV4::new(ip, port))
+            .boxed()
+    }
+}
+
+/// Uniform encoding does not preserve IPv6 flow information and scope id, so
+/// they are always zero in generated values.
+impl StrictArbitrary for SocketAddrV6 {
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        (Ipv6Addr::strict_strategy(), any::<u16>())
+            .prop_map(|(ip, port)| SocketAddrV6::new(ip, port, 0, 0))
+            .boxed()
+    }
+}
+
+impl StrictArbitrary for SocketAddr {
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        prop_oneof![
+            SocketAddrV4::strict_strategy().prop_map(SocketAddr::V4),
+            SocketAddrV6::strict_strategy().prop_map(SocketAddr::V6),
+        ]
+        .boxed()
+    }
+}
+
+impl StrictArbitrary for OnionAddrV3 {
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        <[u8; 32]>::strict_strategy()
+            .prop_map(OnionAddrV3::from_public_key)
+            .boxed()
+    }
+}
+
+impl StrictArbitrary for I2pAddr {
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        <[u8; 32]>::strict_strategy()
+            .prop_map(I2pAddr::from_hash)
+            .boxed()
+    }
+}
+
+/// Only names fitting the inline uniform representation are generated, since
+/// longer names are encoded as their hashes and can't be decoded.
+impl StrictArbitrary for DnsName {
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        let label = (
+            proptest::char::range('a', 'z'),
+            vec(
+                prop_oneof![
+                    proptest::char::range('a', 'z'),
+                    proptest::char::range('0', '9'),
+                    Just('-'),
+                ],
+                0..=6,
+            ),
+        )
+            .prop_map(|(first, rest)| {
+                let mut label = String::from(first);
+                label.extend(rest);
+                // Labels can't end with a hyphen
+                label.truncate(label.trim_end_matches('-').len());
+                label
+            });
+        vec(label, 1..=3)
+            .prop_map(|labels| {
+                DnsName::from_str(&labels.join(".")).expect("valid DNS name")
+            })
+            .boxed()
+    }
+}
+
+impl StrictArbitrary for Transport {
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        prop_oneof![
+            Just(Transport::Tcp),
+            Just(Transport::Udp),
+            Just(Transport::Mtcp),
+            Just(Transport::Quic),
+        ]
+        .boxed()
+    }
+}
+
+impl StrictArbitrary for UniformAddr {
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        let host = prop_oneof![
+            IpAddr::strict_strategy().prop_map(|addr| addr.to_uniform_addr()),
+            OnionAddrV3::strict_strategy()
+                .prop_map(|addr| addr.to_uniform_addr()),
+            I2pAddr::strict_strategy().prop_map(|addr| addr.to_uniform_addr()),
+        ];
+        (
+            host,
+            Option::<u16>::strict_strategy(),
+            Option::<Transport>::strict_strategy(),
+        )
+            .prop_map(|(addr, port, transport)| UniformAddr {
+                port,
+                transport,
+                ..addr
+            })
+            .boxed()
+    }
+}
+
+macro_rules! impl_strict_arbitrary_hash {
+    ($($ty:ty: $len:literal),+ $(,)?) => {
+        $(
+            impl StrictArbitrary for $ty {
+                fn strict_strategy() -> BoxedStrategy<Self> {
+                    <[u8; $len]>::strict_strategy()
+                        .prop_map(<$ty as bitcoin_hashes::Hash>::from_inner)
+                        .boxed()
+                }
+            }
+        )+
+    };
+}
+
+impl_strict_arbitrary_hash!(
+    sha256::Hash: 32,
+    sha256d::Hash: 32,
+    sha512::Hash: 64,
+    ripemd160::Hash: 20,
+    hash160::Hash: 20,
+);
+
+impl<T> StrictArbitrary for sha256t::Hash<T>
+where
+    T: sha256t::Tag + 'static,
+{
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        <[u8; 32]>::strict_strategy()
+            .prop_map(<Self as bitcoin_hashes::Hash>::from_inner)
+            .boxed()
+    }
+}
+
+impl<T> StrictArbitrary for hmac::Hmac<T>
+where
+    T: bitcoin_hashes::Hash + 'static,
+{
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        vec(any::<u8>(), T::LEN)
+            .prop_map(|bytes| {
+                <Self as bitcoin_hashes::Hash>::from_slice(&bytes)
+                    .expect("slice of the hash length")
+            })
+            .boxed()
+    }
+}
+
+impl StrictArbitrary for FlagVec {
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        // Flags are encoded without trailing zero bytes
+        vec(any::<u8>(), 0..=MAX_GENERATED_ITEMS)
+            .prop_map(|bytes| FlagVec::from_inner(bytes).shrunk())
+            .boxed()
+    }
+}
+
+impl<T> StrictArbitrary for RefCell<T>
+where
+    T: StrictArbitrary + 'static,
+{
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        T::strict_strategy().prop_map(RefCell::new).boxed()
+    }
+}
+
+impl<T> StrictArbitrary for Range<T>
+where
+    T: StrictArbitrary + 'static,
+{
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        (T::strict_strategy(), T::strict_strategy())
+            .prop_map(|(start, end)| start..end)
+            .boxed()
+    }
+}
+
+impl<T> StrictArbitrary for RangeInclusive<T>
+where
+    T: StrictArbitrary + 'static,
+{
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        (T::strict_strategy(), T::strict_strategy())
+            .prop_map(|(start, end)| start..=end)
+            .boxed()
+    }
+}
+
+impl<T> StrictArbitrary for RangeFrom<T>
+where
+    T: StrictArbitrary + 'static,
+{
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        T::strict_strategy().prop_map(|start| start..).boxed()
+    }
+}
+
+impl<T> StrictArbitrary for RangeTo<T>
+where
+    T: StrictArbitrary + 'static,
+{
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        T::strict_strategy().prop_map(|end| ..end).boxed()
+    }
+}
+
+impl<T> StrictArbitrary for RangeToInclusive<T>
+where
+    T: StrictArbitrary + 'static,
+{
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        T::strict_strategy().prop_map(|end| ..=end).boxed()
+    }
+}
+
+impl<T> StrictArbitrary for HashSet<T>
+where
+    T: StrictArbitrary + Ord + Hash + 'static,
+{
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        BTreeSet::<T>::strict_strategy()
+            .prop_map(HashSet::from_iter)
+            .boxed()
+    }
+}
+
+impl<T> StrictArbitrary for HashMap<usize, T>
+where
+    T: StrictArbitrary + 'static,
+{
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        BTreeMap::<usize, T>::strict_strategy()
+            .prop_map(HashMap::from_iter)
+            .boxed()
+    }
+}
+
+/// Range of the number of items in a generated confined collection: from
+/// `MIN` up to `MAX`, but no more than [`MAX_GENERATED_ITEMS`] above `MIN`.
+fn confined_len(min: usize, max: usize) -> SizeRange {
+    (min..=max.min(min.saturating_add(MAX_GENERATED_ITEMS))).into()
+}
+
+/// NB: Generated sets must have at least `MIN` distinct items, so `MIN` must
+/// not exceed the number of distinct values of the item type.
+impl<T, const MIN: usize, const MAX: usize> StrictArbitrary
+    for Confined<Vec<T>, MIN, MAX>
+where
+    T: StrictArbitrary + 'static,
+{
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        vec(T::strict_strategy(), confined_len(MIN, MAX))
+            .prop_map(|vec| {
+                Confined::try_from(vec).expect("length within the bounds")
+            })
+            .boxed()
+    }
+}
+
+/// Confined strings are bounded by the number of bytes, so the generated
+/// strings are truncated to `MAX` bytes and padded with ASCII zeros up to
+/// `MIN` bytes.
+impl<const MIN: usize, const MAX: usize> StrictArbitrary
+    for Confined<String, MIN, MAX>
+{
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        vec(any::<char>(), confined_len(MIN, MAX))
+            .prop_map(|chars| {
+                let mut s = String::from_iter(chars);
+                while s.len() > MAX {
+                    s.pop();
+                }
+                while s.len() < MIN {
+                    s.push('0');
+                }
+                Confined::try_from(s).expect("length within the bounds")
+            })
+            .boxed()
+    }
+}
+
+impl<T, const MIN: usize, const MAX: usize> StrictArbitrary
+    for Confined<BTreeSet<T>, MIN, MAX>
+where
+    T: StrictArbitrary + Ord + 'static,
+{
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        btree_set(T::strict_strategy(), confined_len(MIN, MAX))
+            .prop_map(|set| {
+                Confined::try_from(set).expect("length within the bounds")
+            })
+            .boxed()
+    }
+}
+
+impl<K, V, const MIN: usize, const MAX: usize> StrictArbitrary
+    for Confined<BTreeMap<K, V>, MIN, MAX>
+where
+    K: StrictArbitrary + Ord + 'static,
+    V: StrictArbitrary + 'static,
+{
+    fn strict_strategy() -> BoxedStrategy<Self> {
+        btree_map(
+            K::strict_strategy(),
+            V::strict_strategy(),
+            confined_len(MIN, MAX),
+        )
+        .prop_map(|map| {
+            Confined::try_from(map).expect("length within the bounds")
+        })
+        .boxed()
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod _chrono {
+    use chrono::{DateTime, NaiveDateTime, Utc};
+
+    use super::*;
+
+    /// Timestamps are strict-encoded as a number of seconds, so generated
+    /// values do not have fractional seconds.
+    impl StrictArbitrary for NaiveDateTime {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            (-(1i64 << 40)..(1i64 << 40))
+                .prop_map(|secs| {
+                    NaiveDateTime::from_timestamp_opt(secs, 0)
+                        .expect("timestamp within chrono limits")
+                })
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for DateTime<Utc> {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            NaiveDateTime::strict_strategy()
+                .prop_map(|naive| DateTime::from_utc(naive, Utc))
+                .boxed()
+        }
+    }
+}
+
+#[cfg(feature = "bitcoin")]
+mod _bitcoin {
+    use bitcoin::psbt::{self, PsbtSighashType};
+    use bitcoin::schnorr::{TweakedKeyPair, TweakedPublicKey};
+    use bitcoin::secp256k1::{
+        self, ecdsa, schnorr, KeyPair, Message, Parity, Secp256k1,
+        XOnlyPublicKey,
+    };
+    use bitcoin::util::bip32;
+    use bitcoin::util::taproot::{
+        ControlBlock, FutureLeafVersion, LeafVersion, TapBranchHash,
+        TapLeafHash, TapSighashHash, TapTweakHash, TaprootMerkleBranch,
+    };
+    use bitcoin::{
+        Address, Amount, BlockHash, EcdsaSig, EcdsaSighashType, LockTime,
+        Network, OutPoint, PackedLockTime, PubkeyHash, SchnorrSig,
+        SchnorrSighashType, Script, ScriptHash, Sequence, Sighash, Transaction,
+        TxIn, TxOut, Txid, WPubkeyHash, WScriptHash, Witness, Wtxid,
+        XpubIdentifier,
+    };
+
+    use super::*;
+
+    impl_strict_arbitrary_hash!(
+        Txid: 32,
+        Wtxid: 32,
+        BlockHash: 32,
+        XpubIdentifier: 20,
+        PubkeyHash: 20,
+        WPubkeyHash: 20,
+        ScriptHash: 20,
+        WScriptHash: 32,
+        Sighash: 32,
+        TapBranchHash: 32,
+        TapLeafHash: 32,
+        TapTweakHash: 32,
+        TapSighashHash: 32,
+    );
+
+    impl StrictArbitrary for Amount {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            any::<u64>().prop_map(Amount::from_sat).boxed()
+        }
+    }
+
+    impl StrictArbitrary for Network {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            prop_oneof![
+                Just(Network::Bitcoin),
+                Just(Network::Testnet),
+                Just(Network::Signet),
+                Just(Network::Regtest),
+            ]
+            .boxed()
+        }
+    }
+
+    impl StrictArbitrary for Script {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            Vec::<u8>::strict_strategy().prop_map(Script::from).boxed()
+        }
+    }
+
+    impl StrictArbitrary for Witness {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            vec(Vec::<u8>::strict_strategy(), 0..=4)
+                .prop_map(Witness::from_vec)
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for Sequence {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            any::<u32>().prop_map(Sequence).boxed()
+        }
+    }
+
+    impl StrictArbitrary for PackedLockTime {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            any::<u32>().prop_map(PackedLockTime).boxed()
+        }
+    }
+
+    impl StrictArbitrary for LockTime {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            any::<u32>().prop_map(LockTime::from_consensus).boxed()
+        }
+    }
+
+    impl StrictArbitrary for OutPoint {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            (Txid::strict_strategy(), any::<u32>())
+                .prop_map(|(txid, vout)| OutPoint::new(txid, vout))
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for TxOut {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            (any::<u64>(), Script::strict_strategy())
+                .prop_map(|(value, script_pubkey)| TxOut {
+                    value,
+                    script_pubkey,
+                })
+                .boxed()
+        }
+    }
+
+    fn txin_strategy(witness: BoxedStrategy<Witness>) -> BoxedStrategy<TxIn> {
+        (
+            OutPoint::strict_strategy(),
+            Script::strict_strategy(),
+            Sequence::strict_strategy(),
+            witness,
+        )
+            .prop_map(|(previous_output, script_sig, sequence, witness)| TxIn {
+                previous_output,
+                script_sig,
+                sequence,
+                witness,
+            })
+            .boxed()
+    }
+
+    /// Witness is not a part of transaction input consensus encoding, so it
+    /// is always empty in generated inputs.
+    impl StrictArbitrary for TxIn {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            txin_strategy(Just(Witness::new()).boxed())
+        }
+    }
+
+    /// Transactions without inputs can't be unambiguously decoded, so
+    /// generated transactions always have at least one input.
+    impl StrictArbitrary for Transaction {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            (
+                any::<i32>(),
+                PackedLockTime::strict_strategy(),
+                vec(txin_strategy(Witness::strict_strategy()), 1..=4),
+                vec(TxOut::strict_strategy(), 0..=4),
+            )
+                .prop_map(|(version, lock_time, input, output)| Transaction {
+                    version,
+                    lock_time,
+                    input,
+                    output,
+                })
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for secp256k1::SecretKey {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            <[u8; 32]>::strict_strategy()
+                .prop_filter_map("invalid secret key", |bytes| {
+                    secp256k1::SecretKey::from_slice(&bytes).ok()
+                })
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for KeyPair {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            secp256k1::SecretKey::strict_strategy()
+                .prop_map(|sk| {
+                    KeyPair::from_secret_key(&Secp256k1::signing_only(), &sk)
+                })
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for TweakedKeyPair {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            KeyPair::strict_strategy()
+                .prop_map(TweakedKeyPair::dangerous_assume_tweaked)
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for secp256k1::PublicKey {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            KeyPair::strict_strategy()
+                .prop_map(|pair| pair.public_key())
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for bitcoin::PublicKey {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            (secp256k1::PublicKey::strict_strategy(), any::<bool>())
+                .prop_map(|(inner, compressed)| bitcoin::PublicKey {
+                    compressed,
+                    inner,
+                })
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for XOnlyPublicKey {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            KeyPair::strict_strategy()
+                .prop_map(|pair| XOnlyPublicKey::from_keypair(&pair).0)
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for TweakedPublicKey {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            XOnlyPublicKey::strict_strategy()
+                .prop_map(TweakedPublicKey::dangerous_assume_tweaked)
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for ecdsa::Signature {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            (secp256k1::SecretKey::strict_strategy(), any::<[u8; 32]>())
+                .prop_map(|(sk, msg)| {
+                    let msg = Message::from_slice(&msg).expect("32-byte hash");
+                    Secp256k1::signing_only().sign_ecdsa(&msg, &sk)
+                })
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for schnorr::Signature {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            (KeyPair::strict_strategy(), any::<[u8; 32]>())
+                .prop_map(|(pair, msg)| {
+                    let msg = Message::from_slice(&msg).expect("32-byte hash");
+                    Secp256k1::signing_only()
+                        .sign_schnorr_no_aux_rand(&msg, &pair)
+                })
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for EcdsaSighashType {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            prop_oneof![
+                Just(EcdsaSighashType::All),
+                Just(EcdsaSighashType::None),
+                Just(EcdsaSighashType::Single),
+                Just(EcdsaSighashType::AllPlusAnyoneCanPay),
+                Just(EcdsaSighashType::NonePlusAnyoneCanPay),
+                Just(EcdsaSighashType::SinglePlusAnyoneCanPay),
+            ]
+            .boxed()
+        }
+    }
+
+    impl StrictArbitrary for SchnorrSighashType {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            prop_oneof![
+                Just(SchnorrSighashType::Default),
+                Just(SchnorrSighashType::All),
+                Just(SchnorrSighashType::None),
+                Just(SchnorrSighashType::Single),
+                Just(SchnorrSighashType::AllPlusAnyoneCanPay),
+                Just(SchnorrSighashType::NonePlusAnyoneCanPay),
+                Just(SchnorrSighashType::SinglePlusAnyoneCanPay),
+            ]
+            .boxed()
+        }
+    }
+
+    impl StrictArbitrary for PsbtSighashType {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            any::<u32>().prop_map(PsbtSighashType::from_u32).boxed()
+        }
+    }
+
+    impl StrictArbitrary for EcdsaSig {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            (
+                ecdsa::Signature::strict_strategy(),
+                EcdsaSighashType::strict_strategy(),
+            )
+                .prop_map(|(sig, hash_ty)| EcdsaSig { sig, hash_ty })
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for SchnorrSig {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            (
+                schnorr::Signature::strict_strategy(),
+                SchnorrSighashType::strict_strategy(),
+            )
+                .prop_map(|(sig, hash_ty)| SchnorrSig { sig, hash_ty })
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for Address {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            let script = vec(any::<u8>(), 0..=MAX_GENERATED_ITEMS)
+                .prop_map(Script::from);
+            let pk = bitcoin::PublicKey::strict_strategy()
+                .prop_map(|pk| bitcoin::PublicKey::new(pk.inner));
+            let payload = prop_oneof![
+                pk.clone()
+                    .prop_map(|pk| bitcoin::util::address::Payload::p2pkh(&pk)),
+                script.clone().prop_map(|script| {
+                    bitcoin::util::address::Payload::p2sh(&script)
+                        .expect("small script")
+                }),
+                pk.prop_map(|pk| {
+                    bitcoin::util::address::Payload::p2wpkh(&pk)
+                        .expect("compressed key")
+                }),
+                script.prop_map(|script| {
+                    bitcoin::util::address::Payload::p2wsh(&script)
+                }),
+                TweakedPublicKey::strict_strategy()
+                    .prop_map(bitcoin::util::address::Payload::p2tr_tweaked),
+            ];
+            (payload, Network::strict_strategy())
+                .prop_map(|(payload, network)| Address { payload, network })
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for LeafVersion {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            any::<u8>()
+                .prop_filter_map("invalid leaf version", |version| {
+                    LeafVersion::from_consensus(version & 0xFE).ok()
+                })
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for FutureLeafVersion {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            LeafVersion::strict_strategy()
+                .prop_filter_map(
+                    "known leaf version",
+                    |version| match version {
+                        LeafVersion::Future(version) => Some(version),
+                        LeafVersion::TapScript => None,
+                    },
+                )
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for TaprootMerkleBranch {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            Vec::<sha256::Hash>::strict_strategy()
+                .prop_map(|hashes| {
+                    TaprootMerkleBranch::try_from(hashes)
+                        .expect("merkle branch within consensus limit")
+                })
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for ControlBlock {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            (
+                LeafVersion::strict_strategy(),
+                prop_oneof![Just(Parity::Even), Just(Parity::Odd)],
+                XOnlyPublicKey::strict_strategy(),
+                TaprootMerkleBranch::strict_strategy(),
+            )
+                .prop_map(
+                    |(
+                        leaf_version,
+                        output_key_parity,
+                        internal_key,
+                        merkle_branch,
+                    )| ControlBlock {
+                        leaf_version,
+                        output_key_parity,
+                        internal_key,
+                        merkle_branch,
+                    },
+                )
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for bip32::ChildNumber {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            (0..(1u32 << 31), any::<bool>())
+                .prop_map(|(index, hardened)| {
+                    if hardened {
+                        bip32::ChildNumber::Hardened { index }
+                    } else {
+                        bip32::ChildNumber::Normal { index }
+                    }
+                })
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for bip32::DerivationPath {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            vec(bip32::ChildNumber::strict_strategy(), 0..=4)
+                .prop_map(bip32::DerivationPath::from)
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for bip32::ChainCode {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            <[u8; 32]>::strict_strategy()
+                .prop_map(|bytes| bip32::ChainCode::from(&bytes[..]))
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for bip32::Fingerprint {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            <[u8; 4]>::strict_strategy()
+                .prop_map(|bytes| bip32::Fingerprint::from(&bytes[..]))
+                .boxed()
+        }
+    }
+
+    /// Extended keys encode only mainnet and testnet versions, so generated
+    /// keys belong to one of these two networks.
+    impl StrictArbitrary for bip32::ExtendedPrivKey {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            (
+                any::<bool>(),
+                vec(any::<u8>(), 16..=64),
+                vec(bip32::ChildNumber::strict_strategy(), 0..=2),
+            )
+                .prop_map(|(testnet, seed, path)| {
+                    let network = if testnet {
+                        Network::Testnet
+                    } else {
+                        Network::Bitcoin
+                    };
+                    bip32::ExtendedPrivKey::new_master(network, &seed)
+                        .expect("valid seed")
+                        .derive_priv(
+                            &Secp256k1::signing_only(),
+                            &bip32::DerivationPath::from(path),
+                        )
+                        .expect("valid derivation")
+                })
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for bip32::ExtendedPubKey {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            bip32::ExtendedPrivKey::strict_strategy()
+                .prop_map(|xpriv| {
+                    bip32::ExtendedPubKey::from_priv(
+                        &Secp256k1::signing_only(),
+                        &xpriv,
+                    )
+                })
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for psbt::raw::Key {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            (any::<u8>(), Vec::<u8>::strict_strategy())
+                .prop_map(|(type_value, key)| psbt::raw::Key {
+                    type_value,
+                    key,
+                })
+                .boxed()
+        }
+    }
+
+    impl StrictArbitrary for psbt::raw::ProprietaryKey {
+        fn strict_strategy() -> BoxedStrategy<Self> {
+            (
+                Vec::<u8>::strict_strategy(),
+                any::<u8>(),
+                Vec::<u8>::strict_strategy(),
+            )
+                .prop_map(|(prefix, subtype, key)| psbt::raw::ProprietaryKey {
+                    prefix,
+                    subtype,
+                    key,
+                })
+                .boxed()
+        }
+    }
+}
+
+/// Runs property-based test checking strict encoding of the values generated
+/// by the `strategy`. For each generated value the test performs all checks
+/// of [`test_object_encoding_roundtrip`] and [`test_truncated_decoding`].
+///
+/// Used by [`check_strict_roundtrip`](crate::check_strict_roundtrip) macro,
+/// which should be preferred in tests.
+///
+/// # Error
+///
+/// Returns [`TestError::Fail`] with the minimal (shrunk) failing value and
+/// the description of the failure, or [`TestError::Abort`] if the strategy
+/// rejected too many values.
+pub fn check_strict_roundtrip_with<S>(
+    strategy: S,
+    config: Config,
+) -> Result<(), TestError<S::Value>>
+where
+    S: Strategy,
+    S::Value: StrictEncode + StrictDecode + PartialEq + Clone + Debug,
+{
+    TestRunner::new(config).run(&strategy, |object| {
+        test_object_encoding_roundtrip(&object)
+            .and_then(|_| test_truncated_decoding(&object))
+            .map_err(|err| TestCaseError::fail(err.to_string()))
+    })
+}
+
+/// Implements [`StrictArbitrary`] for a user-defined structure or enum, which
+/// fields must implement [`StrictArbitrary`] themselves.
+///
+/// The macro repeats structure or enum definition without field types, since
+/// the types are inferred from the type definition; tuple fields must be
+/// given arbitrary distinct names. Generic types are not supported and
+/// structures and enum variants can't have more than 12 fields.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate strict_encoding;
+/// # #[macro_use] extern crate strict_encoding_test;
+/// #[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+/// struct Unit;
+/// strict_arbitrary!(struct Unit);
+///
+/// #[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+/// struct Tuple(u8, Vec<u16>);
+/// strict_arbitrary!(struct Tuple(first, second));
+///
+/// #[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+/// enum Sum {
+///     One,
+///     Two(u8, u8),
+///     Three { unit: Unit, tuple: Tuple },
+/// }
+/// strict_arbitrary!(enum Sum { One, Two(a, b), Three { unit, tuple } });
+/// ```
+#[macro_export]
+macro_rules! strict_arbitrary {
+    (struct $name:ident $(;)?) => {
+        $crate::strict_arbitrary!(@impl $name, $crate::proptest::strategy::Strategy::boxed(
+            $crate::proptest::strategy::Just($name)
+        ));
+    };
+
+    (struct $name:ident ( $($field:ident),+ $(,)? ) $(;)?) => {
+        $crate::strict_arbitrary!(@impl $name, $crate::strict_arbitrary!(
+            @fields ($($field),+) => $name($($field),+)
+        ));
+    };
+
+    (struct $name:ident { $($field:ident),+ $(,)? }) => {
+        $crate::strict_arbitrary!(@impl $name, $crate::strict_arbitrary!(
+            @fields ($($field),+) => $name { $($field),+ }
+        ));
+    };
+
+    (enum $name:ident {
+        $( $variant:ident
+            $( ( $($tfield:ident),+ $(,)? ) )?
+            $( { $($nfield:ident),+ $(,)? } )?
+        ),+ $(,)?
+    }) => {
+        $crate::strict_arbitrary!(@impl $name, $crate::proptest::strategy::Strategy::boxed(
+            $crate::proptest::strategy::Union::new(vec![$(
+                $crate::strict_arbitrary!(@variant $name :: $variant
+                    $( ( $($tfield),+ ) )?
+                    $( { $($nfield),+ } )?
+                )
+            ),+])
+        ));
+    };
+
+    (@variant $name:ident :: $variant:ident) => {
+        $crate::proptest::strategy::Strategy::boxed(
+            $crate::proptest::strategy::Just($name::$variant)
+        )
+    };
+
+    (@variant $name:ident :: $variant:ident ( $($field:ident),+ )) => {
+        $crate::strict_arbitrary!(@fields ($($field),+) => $name::$variant($($field),+))
+    };
+
+    (@variant $name:ident :: $variant:ident { $($field:ident),+ }) => {
+        $crate::strict_arbitrary!(@fields ($($field),+) => $name::$variant { $($field),+ })
+    };
+
+    (@fields ($($field:ident),+) => $ctor:expr) => {
+        $crate::proptest::strategy::Strategy::boxed(
+            $crate::proptest::strategy::Strategy::prop_map(
+                ($( $crate::strict_arbitrary!(@strategy $field), )+),
+                |($($field,)+)| $ctor,
+            )
+        )
+    };
+
+    (@strategy $field:ident) => {
+        <_ as $crate::arbitrary::StrictArbitrary>::strict_strategy()
+    };
+
+    (@impl $name:ident, $strategy:expr) => {
+        impl $crate::arbitrary::StrictArbitrary for $name {
+            fn strict_strategy() -> $crate::proptest::strategy::BoxedStrategy<Self> {
+                $strategy
+            }
+        }
+    };
+}
+
+/// Runs property-based test checking strict encoding of randomly generated
+/// values, returning [`proptest::test_runner::TestError`] with the minimal
+/// failing value if any of the checks fails.
+///
+/// For each generated value the macro checks that
+/// - the value survives encode-decode roundtrip;
+/// - encoder reports correct length of the encoded data;
+/// - decoding of any truncated prefix of the encoded data fails with an error
+///   and does not panic.
+///
+/// Values are generated with [`arbitrary::StrictArbitrary`] implementation of
+/// the type, or by a custom strategy given with `strategy = ...` argument. The
+/// number of test cases defaults to proptest configuration (256 cases, which
+/// may be overridden with `PROPTEST_CASES` environment variable) and may be set
+/// with `cases = ...` argument. Failing cases are persisted in
+/// `proptest-regressions` directory next to the source file.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate strict_encoding;
+/// # #[macro_use] extern crate strict_encoding_test;
+/// # use strict_encoding_test::proptest::prelude::*;
+/// use std::collections::BTreeMap;
+///
+/// check_strict_roundtrip!(BTreeMap<u8, Vec<String>>).unwrap();
+/// check_strict_roundtrip!(Option<u16>, cases = 16).unwrap();
+/// check_strict_roundtrip!(strategy = (1..10u8).prop_map(|n| vec![n; n as usize]))
+///     .unwrap();
+/// ```
+///
+/// [`arbitrary::StrictArbitrary`]: crate::arbitrary::StrictArbitrary
+#[macro_export]
+macro_rules! check_strict_roundtrip {
+    (@config $($cases:expr)?) => {{
+        #[allow(unused_mut)]
+        let mut config = $crate::proptest::test_runner::Config {
+            source_file: Some(file!()),
+            ..$crate::proptest::test_runner::Config::default()
+        };
+        $( config.cases = $cases; )?
+        config
+    }};
+
+    (strategy = $strategy:expr $(, cases = $cases:expr)? $(,)?) => {
+        $crate::arbitrary::check_strict_roundtrip_with(
+            $strategy,
+            $crate::check_strict_roundtrip!(@config $($cases)?),
+        )
+    };
+
+    ($ty:ty $(, cases = $cases:expr)? $(,)?) => {
+        $crate::arbitrary::check_strict_roundtrip_with(
+            <$ty as $crate::arbitrary::StrictArbitrary>::strict_strategy(),
+            $crate::check_strict_roundtrip!(@config $($cases)?),
+        )
+    };
+}
@@ -47,6 +47,27 @@
 //! serialization (which should not be obtained by just encoding the object),
 //! use [`test_encoding_roundtrip`] method.
 //!
+//! With `async` feature, [`test_async_object_encoding_roundtrip`] and
+//! [`test_async_encoding_roundtrip`] additionally check that asynchronous
+//! encoding produces exactly the same data as the blocking one.
+//!
+//! [`test_truncated_decoding`] checks that decoding of any truncated prefix of
+//! the object encoding fails with an error and does not panic.
+//!
+//! If [`CORPUS_DIR_ENV`] environment variable is set, encodings of all objects
+//! passing the roundtrip tests are saved into the directory it points to,
+//! grouped by their type name. This is used to build seed corpus for fuzzing.
+//!
+//! # Property-based testing
+//!
+//! With `proptest` feature, [`check_strict_roundtrip`] macro performs all the
+//! roundtrip checks listed above against randomly generated values. Values are
+//! produced by [`arbitrary::StrictArbitrary`] strategies, which are provided
+//! for the strict-encodable types of the standard library, `amplify`,
+//! `bitcoin_hashes` and `strict_encoding` crates (and for `bitcoin` and
+//! `chrono` types with the features of the same name) and may be generated for
+//! user structures and enums with [`strict_arbitrary`] macro.
+//!
 //! # General guidelines
 //!
 //! Proper testing should not exercise `asset`s and instead propagate errors
@@ -54,30 +75,63 @@
 //! with `?` operator:
 //!
 //! ```
+//! # #[macro_use] extern crate strict_encoding;
 //! # #[macro_use] extern crate strict_encoding_test;
 //! use strict_encoding_test::*;
 //!
 //! #[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
 //! struct Data(pub Vec<u8>);
 //!
-//! #[test]
 //! fn test_data_encoding() -> Result<(), DataEncodingTestFailure<Data>> {
 //!     let data1 = Data(vec![0x01, 0x02]);
 //!     test_encoding_roundtrip(&data1, &[0x02, 0x00, 0x01, 0x02])?;
 //!
 //!     let data2 = Data(vec![0xff]);
-//!     test_encoding_roundtrip(&data2, &[0x02, 0x00, 0xff])?;
+//!     test_encoding_roundtrip(&data2, &[0x01, 0x00, 0xff])?;
 //!
 //!     Ok(())
 //! }
+//! # test_data_encoding().unwrap();
 //! ```
 
 #[macro_use]
 extern crate amplify;
+#[cfg(feature = "bitcoin")]
+extern crate bitcoin_crate as bitcoin;
+#[cfg(feature = "chrono")]
+extern crate chrono_crate as chrono;
+
+#[cfg(feature = "proptest")]
+pub mod arbitrary;
 
+use std::collections::hash_map::DefaultHasher;
 use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::{any, env, fs, panic};
 
+#[cfg(feature = "proptest")]
+pub use proptest;
 use strict_encoding::{Error, StrictDecode, StrictEncode};
+#[cfg(feature = "async")]
+use strict_encoding::{StrictDecodeAsync, StrictEncodeAsync};
+
+/// Environment variable with a path to the directory where the test helpers
+/// save encodings of all successfully tested objects.
+pub const CORPUS_DIR_ENV: &str = "STRICT_ENCODING_CORPUS";
+
+/// Saves test vector into `<CORPUS_DIR_ENV>/<type name>/<data hash>` file, if
+/// [`CORPUS_DIR_ENV`] is set. I/O errors are ignored since they must not
+/// affect test results.
+fn save_test_vector<T>(data: &[u8]) {
+    let dir = match env::var_os(CORPUS_DIR_ENV) {
+        Some(dir) => std::path::PathBuf::from(dir).join(any::type_name::<T>()),
+        None => return,
+    };
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    let path = dir.join(format!("{:016x}", hasher.finish()));
+    let _ = fs::create_dir_all(&dir).and_then(|_| fs::write(path, data));
+}
 
 /// Failures happening during strict encoding tests of enum encodings.
 ///
@@ -231,6 +285,7 @@ where
 /// # Example
 ///
 /// ```
+/// # #[macro_use] extern crate strict_encoding;
 /// # #[macro_use] extern crate strict_encoding_test;
 ///
 /// #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
@@ -317,6 +372,7 @@ macro_rules! test_encoding_enum {
 /// # Example
 ///
 /// ```
+/// # #[macro_use] extern crate strict_encoding;
 /// # #[macro_use] extern crate strict_encoding_test;
 ///
 /// #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
@@ -406,6 +462,7 @@ macro_rules! test_encoding_enum_by_values {
 /// # Example
 ///
 /// ```
+/// # #[macro_use] extern crate strict_encoding;
 /// # #[macro_use] extern crate strict_encoding_test;
 ///
 /// #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
@@ -440,7 +497,7 @@ macro_rules! test_encoding_enum_u8_exhaustive {
             $( set.insert($val); )+
             for x in 0..=u8::MAX {
                 if !set.contains(&x) {
-                    match $se::strict_deserialize(&[x]) {
+                    match $se::strict_deserialize(&[x]).map_err($se::Error::into_root_cause) {
                         Err($se::Error::EnumValueNotKnown(stringify!($enum), a)) if a == x as usize => {},
                         Err(err) => return Err(
                             EnumEncodingTestFailure::DecoderWrongErrorOnUnknownValue(x, err.to_string())
@@ -493,6 +550,16 @@ where
         returned: usize,
     },
 
+    /// Failure of the strict encode implementation: computed length of the
+    /// encoded data differs from the length of the serialized data
+    #[display("Encoded length computed as `{computed}` instead of `{actual}`")]
+    EncodedLenMismatch {
+        /// Actual length of the serialized data
+        actual: usize,
+        /// Length returned by `strict_encoded_len`
+        computed: usize,
+    },
+
     /// Test case failure representing mismatch between object produced
     /// by decoding from the originally encoded object
     #[display(
@@ -524,6 +591,42 @@ where
         /// Object decoded from the test vector
         object: T,
     },
+
+    /// Test case failure representing mismatch between data produced by
+    /// blocking and asynchronous encoders for the same object
+    #[display(
+        "Asynchronous encoding of `{object:02x?}` differs from the blocking \
+         one:
+        \tBlocking: {blocking:02x?}
+        \tAsynchronous: {asynchronous:02x?}
+        "
+    )]
+    AsyncEncodingMismatch {
+        /// Data produced by the blocking encoder
+        blocking: Vec<u8>,
+        /// Data produced by the asynchronous encoder
+        asynchronous: Vec<u8>,
+        /// Object which was encoded
+        object: T,
+    },
+
+    /// Test case failure representing successful decoding of a truncated
+    /// object encoding
+    #[display(
+        "Decoding of truncated data succeeded with `{decoded:02x?}`:
+        \tTruncated data: {truncated:02x?}
+        "
+    )]
+    TruncatedDataDecoded {
+        /// Truncated object encoding
+        truncated: Vec<u8>,
+        /// Object decoded from the truncated data
+        decoded: T,
+    },
+
+    /// Test case failure representing panic of the decoder
+    #[display("Decoder panicked on data {0:02x?}")]
+    DecoderPanicked(#[doc = "Data which caused the panic"] Vec<u8>),
 }
 
 /// Test helper performing encode-decode roundtrip for a provided object. Object
@@ -540,7 +643,8 @@ where
 /// - encoding or decoding failures;
 /// - if the original object is not equivalent to its decoded version;
 /// - if encoder returns number of bytes that does not match the length of the
-///   encoded data.
+///   encoded data;
+/// - if `strict_encoded_len` does not match the length of the encoded data.
 ///
 /// # Panics
 ///
@@ -577,6 +681,15 @@ where
             returned: written,
         });
     }
+    let computed = object
+        .strict_encoded_len()
+        .map_err(DataEncodingTestFailure::EncoderFailure)?;
+    if computed != len {
+        return Err(DataEncodingTestFailure::EncodedLenMismatch {
+            actual: len,
+            computed,
+        });
+    }
     let decoded_object =
         T::strict_decode(&encoded_object[..]).map_err(|e| {
             DataEncodingTestFailure::DecoderFailure(e, encoded_object.clone())
@@ -589,6 +702,7 @@ where
             },
         );
     }
+    save_test_vector::<T>(&encoded_object);
     Ok(encoded_object)
 }
 
@@ -606,7 +720,8 @@ where
 /// - encoding or decoding failures;
 /// - if the original test vector is not equivalent to its transcoded version;
 /// - if encoder returns number of bytes that does not match the length of the
-///   test vector.
+///   test vector;
+/// - if `strict_encoded_len` does not match the length of the test vector.
 ///
 /// # Panics
 ///
@@ -662,7 +777,8 @@ where
 /// - if the original object is not equivalent to its decoded version;
 /// - if the original test vector is not equivalent to its transcoded version;
 /// - if encoder returns number of bytes that does not match the length of the
-///   test vector.
+///   test vector;
+/// - if `strict_encoded_len` does not match the length of the test vector.
 ///
 /// # Panics
 ///
@@ -699,3 +815,186 @@ where
     }
     Ok(())
 }
+
+/// Test helper performing encode-decode roundtrip for a provided object with
+/// both blocking and asynchronous encoders, checking that they produce and
+/// consume the same data. Object type must be `PartialEq + Clone + Debug`.
+///
+/// # Returns
+///
+/// If suceeds, encoded byte string representing the object. Otheriwse,
+/// [`DataEncodingTestFailure`] (see description below)
+///
+/// # Error
+///
+/// Errors on:
+/// - all cases when [`test_object_encoding_roundtrip`] fails;
+/// - asynchronous encoding or decoding failures;
+/// - if asynchronous encoding differs from the blocking one;
+/// - if the original object is not equivalent to its asynchronously decoded
+///   version.
+///
+/// # Panics
+///
+/// Panics if tokio runtime can't be created; all other test failures are
+/// returned as [`DataEncodingTestFailure`].
+#[cfg(feature = "async")]
+pub fn test_async_object_encoding_roundtrip<T>(
+    object: &T,
+) -> Result<Vec<u8>, DataEncodingTestFailure<T>>
+where
+    T: StrictEncode
+        + StrictDecode
+        + StrictEncodeAsync
+        + StrictDecodeAsync
+        + PartialEq
+        + Clone
+        + Debug,
+{
+    let encoded_object = test_object_encoding_roundtrip(object)?;
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("unable to create tokio runtime");
+    runtime.block_on(async {
+        let mut async_encoded: Vec<u8> = vec![];
+        let written = object
+            .strict_encode_async(&mut async_encoded)
+            .await
+            .map_err(DataEncodingTestFailure::EncoderFailure)?;
+        if written != async_encoded.len() {
+            return Err(DataEncodingTestFailure::EncoderReturnedWrongLength {
+                actual: async_encoded.len(),
+                returned: written,
+            });
+        }
+        if async_encoded != encoded_object {
+            return Err(DataEncodingTestFailure::AsyncEncodingMismatch {
+                blocking: encoded_object,
+                asynchronous: async_encoded,
+                object: object.clone(),
+            });
+        }
+        let decoded_object = T::strict_deserialize_async(&encoded_object)
+            .await
+            .map_err(|e| {
+                DataEncodingTestFailure::DecoderFailure(
+                    e,
+                    encoded_object.clone(),
+                )
+            })?;
+        if &decoded_object != object {
+            return Err(
+                DataEncodingTestFailure::TranscodedObjectDiffersFromOriginal {
+                    original: object.clone(),
+                    transcoded: decoded_object,
+                },
+            );
+        }
+        Ok(encoded_object)
+    })
+}
+
+/// Test helper performing double encode-decode roundtrip for an object and a
+/// matching binary encoding test vector with both blocking and asynchronous
+/// encoders. Object type must be `PartialEq + Clone + Debug`.
+///
+/// # Error
+///
+/// Errors on:
+/// - all cases when [`test_encoding_roundtrip`] or
+///   [`test_async_object_encoding_roundtrip`] fail;
+/// - if the original test vector is not equivalent to the asynchronous encoding
+///   of the object.
+///
+/// # Panics
+///
+/// Panics if tokio runtime can't be created; all other test failures are
+/// returned as [`DataEncodingTestFailure`].
+#[cfg(feature = "async")]
+pub fn test_async_encoding_roundtrip<T>(
+    object: &T,
+    test_vec: impl AsRef<[u8]>,
+) -> Result<(), DataEncodingTestFailure<T>>
+where
+    T: StrictEncode
+        + StrictDecode
+        + StrictEncodeAsync
+        + StrictDecodeAsync
+        + PartialEq
+        + Clone
+        + Debug,
+{
+    let test_vec = test_vec.as_ref();
+    test_encoding_roundtrip(object, test_vec)?;
+    let encoded_object = test_async_object_encoding_roundtrip(object)?;
+    if test_vec != encoded_object {
+        return Err(
+            DataEncodingTestFailure::TranscodedVecDiffersFromOriginal {
+                original: test_vec.to_vec(),
+                transcoded: encoded_object,
+                object: object.clone(),
+            },
+        );
+    }
+    Ok(())
+}
+
+/// Test helper checking that decoding of any truncated prefix of the object
+/// encoding (including an empty one) fails with an error.
+///
+/// NB: Types ending with [`strict_encoding::TlvStream`], including structures
+/// with TLV extensions, successfully decode data truncated at the beginning of
+/// the TLV stream by design, and can't be checked with this function.
+///
+/// # Error
+///
+/// Errors on:
+/// - encoding failure;
+/// - if decoding of some truncated data succeeds;
+/// - if the decoder panics on some truncated data.
+///
+/// # Panics
+///
+/// Function does not panics and instead returns [`DataEncodingTestFailure`] for
+/// each type of test failures; panics of the decoder are caught and reported
+/// as [`DataEncodingTestFailure::DecoderPanicked`].
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate strict_encoding;
+/// # use strict_encoding_test::test_truncated_decoding;
+///
+/// #[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+/// struct Data(pub Vec<u8>);
+///
+/// test_truncated_decoding(&Data(vec![0x01, 0x02])).unwrap();
+/// ```
+pub fn test_truncated_decoding<T>(
+    object: &T,
+) -> Result<(), DataEncodingTestFailure<T>>
+where
+    T: StrictEncode + StrictDecode + PartialEq + Clone + Debug,
+{
+    let encoded_object = object
+        .strict_serialize()
+        .map_err(DataEncodingTestFailure::EncoderFailure)?;
+    for len in 0..encoded_object.len() {
+        let truncated = &encoded_object[..len];
+        match panic::catch_unwind(|| T::strict_decode(truncated)) {
+            Ok(Err(_)) => {}
+            Ok(Ok(decoded)) => {
+                return Err(DataEncodingTestFailure::TruncatedDataDecoded {
+                    truncated: truncated.to_vec(),
+                    decoded,
+                })
+            }
+            Err(_) => {
+                return Err(DataEncodingTestFailure::DecoderPanicked(
+                    truncated.to_vec(),
+                ))
+            }
+        }
+    }
+    Ok(())
+}